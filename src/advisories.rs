@@ -0,0 +1,124 @@
+//! Loading a [RustSec advisory database](https://github.com/rustsec/advisory-db),
+//! so that `cargo vet` can warn about vetted packages with known advisories.
+//!
+//! This only understands as much of the database as is needed to tell which
+//! versions of a package an advisory affects, and leaves everything else to
+//! `cargo audit`.
+
+use std::path::Path;
+
+use cargo_metadata::{Version, VersionReq};
+use serde::Deserialize;
+
+use crate::errors::AdvisoryDbError;
+use crate::format::{PackageName, PackageStr, SortedMap};
+
+/// The advisories in an advisory database, by the package they're for.
+#[derive(Debug, Default)]
+pub struct AdvisoryDb {
+    advisories: SortedMap<PackageName, Vec<Advisory>>,
+}
+
+/// An advisory, from the toml block at the start of its markdown file.
+#[derive(Debug, Deserialize)]
+pub struct Advisory {
+    pub advisory: AdvisoryMetadata,
+    #[serde(default)]
+    pub versions: AdvisoryVersions,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AdvisoryMetadata {
+    pub id: String,
+    pub package: PackageName,
+    /// The date the advisory was withdrawn, if it turned out to be wrong.
+    pub withdrawn: Option<toml::Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AdvisoryVersions {
+    #[serde(default)]
+    pub patched: Vec<VersionReq>,
+    #[serde(default)]
+    pub unaffected: Vec<VersionReq>,
+}
+
+impl Advisory {
+    /// Parse an advisory from the contents of its markdown file.
+    fn parse(path: &Path, contents: &str) -> Result<Self, AdvisoryDbError> {
+        let missing_front_matter = || AdvisoryDbError::MissingFrontMatter {
+            path: path.to_owned(),
+        };
+        let front_matter = contents
+            .trim_start()
+            .strip_prefix("```toml")
+            .ok_or_else(missing_front_matter)?;
+        let end = front_matter
+            .find("\n```")
+            .ok_or_else(missing_front_matter)?;
+        toml::from_str(&front_matter[..end]).map_err(|error| AdvisoryDbError::Parse {
+            path: path.to_owned(),
+            error,
+        })
+    }
+
+    /// Whether this advisory applies to the given version of its package.
+    pub fn affects(&self, version: &Version) -> bool {
+        self.advisory.withdrawn.is_none()
+            && !self
+                .versions
+                .patched
+                .iter()
+                .chain(&self.versions.unaffected)
+                .any(|req| req.matches(version))
+    }
+}
+
+impl AdvisoryDb {
+    /// Load the advisories from a checkout of the advisory database, which
+    /// keeps them in `crates/<package>/<id>.md`.
+    pub fn load(path: &Path) -> Result<Self, AdvisoryDbError> {
+        let read_error = |path: &Path| {
+            let path = path.to_owned();
+            move |error| AdvisoryDbError::Read { path, error }
+        };
+        let crates = path.join("crates");
+        let mut db = AdvisoryDb::default();
+        for package_dir in std::fs::read_dir(&crates).map_err(read_error(&crates))? {
+            let package_dir = package_dir.map_err(read_error(&crates))?.path();
+            if !package_dir.is_dir() {
+                continue;
+            }
+            for file in std::fs::read_dir(&package_dir).map_err(read_error(&package_dir))? {
+                let file = file.map_err(read_error(&package_dir))?.path();
+                if file.extension() != Some("md".as_ref()) {
+                    continue;
+                }
+                let contents = std::fs::read_to_string(&file).map_err(read_error(&file))?;
+                db.insert(Advisory::parse(&file, &contents)?);
+            }
+        }
+        for advisories in db.advisories.values_mut() {
+            advisories.sort_by(|a, b| a.advisory.id.cmp(&b.advisory.id));
+        }
+        Ok(db)
+    }
+
+    fn insert(&mut self, advisory: Advisory) {
+        self.advisories
+            .entry(advisory.advisory.package.clone())
+            .or_default()
+            .push(advisory);
+    }
+
+    /// The ids of the advisories which affect this version of a package.
+    pub fn ids_for(&self, package: PackageStr, version: &Version) -> Vec<String> {
+        self.advisories
+            .get(package)
+            .into_iter()
+            .flatten()
+            .filter(|advisory| advisory.affects(version))
+            .map(|advisory| advisory.advisory.id.clone())
+            .collect()
+    }
+}
@@ -4,7 +4,8 @@ use cargo_metadata::Version;
 use clap::{Parser, Subcommand, ValueEnum};
 use tracing::level_filters::LevelFilter;
 
-use crate::format::{CriteriaName, PackageName, VersionReq};
+use crate::format::{CriteriaName, ImportName, OwnerName, PackageName, VersionReq};
+use crate::storage::{DEFAULT_MAX_FETCH_FILES, DEFAULT_MAX_FETCH_SIZE};
 
 #[derive(Parser)]
 #[clap(version, about, long_about = None)]
@@ -29,7 +30,7 @@ pub struct Cli {
     pub command: Option<Commands>,
 
     // Top-level flags
-    /// Path to Cargo.toml
+    /// Path to Cargo.toml, or the directory containing it
     #[clap(long, name = "PATH", parse(from_os_str))]
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub manifest_path: Option<PathBuf>,
@@ -53,6 +54,10 @@ pub struct Cli {
     pub features: Vec<String>,
 
     /// Do not fetch new imported audits.
+    ///
+    /// cargo vet never updates Cargo.lock, so regardless of this flag it must
+    /// exist and be up to date. When this flag is set, such an error is assumed
+    /// to mean that an up-to-date Cargo.lock wasn't committed.
     #[clap(long, action)]
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub locked: bool,
@@ -76,17 +81,74 @@ pub struct Cli {
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub output_file: Option<PathBuf>,
 
+    /// Append to --output-file instead of replacing it
+    #[clap(long, action)]
+    #[clap(requires = "output-file")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub output_file_append: bool,
+
     /// Instead of stderr, write logs to this file (only used after successful CLI parsing)
     #[clap(long, action)]
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub log_file: Option<PathBuf>,
 
+    /// Append to --log-file instead of replacing it
+    #[clap(long, action)]
+    #[clap(requires = "log-file")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub log_file_append: bool,
+
+    /// Rotate --log-file once it reaches this size (in bytes)
+    ///
+    /// Before logging, a log file of at least this size is moved aside to
+    /// `<log-file>.1` (replacing any previous one), and a fresh log is started.
+    #[clap(long, action)]
+    #[clap(requires = "log-file-append")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub log_file_max_size: Option<u64>,
+
+    /// How long to wait (in seconds) for another cargo-vet process to release
+    /// the store's lock before giving up
+    #[clap(long, action, default_value = "300")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub lock_timeout: u64,
+
+    /// How long to wait (in seconds) for a network request before giving up
+    ///
+    /// Requests which time out, lose their connection, or get a server error
+    /// are retried a couple of times (with increasing delays) before failing.
+    /// Defaults to the store's `network.timeout`, or 60 seconds.
+    #[clap(long, action, value_name = "SECS")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub network_timeout: Option<u64>,
+
     /// The format of the output
     #[clap(long, value_enum, action)]
     #[clap(default_value_t = OutputFormat::Human)]
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub output_format: OutputFormat,
 
+    /// The maximum total size (in bytes) of a fetched package once unpacked
+    ///
+    /// Fetching a package which would unpack to more than this is aborted, to
+    /// guard against hostile packages exhausting your disk. Raise it if you
+    /// need to review a legitimately enormous package. Packages which cargo
+    /// has already unpacked in its own registry cache are used in place, and
+    /// aren't subject to this limit.
+    #[clap(long, action)]
+    #[clap(default_value_t = DEFAULT_MAX_FETCH_SIZE)]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub max_fetch_size: u64,
+
+    /// The maximum number of files in a fetched package
+    ///
+    /// Fetching a package with more files than this is aborted, for the same
+    /// reasons as `--max-fetch-size`.
+    #[clap(long, action)]
+    #[clap(default_value_t = DEFAULT_MAX_FETCH_FILES)]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub max_fetch_files: u64,
+
     /// Use the following path as the diff-cache
     ///
     /// The diff-cache stores the summary results used by vet's suggestion machinery.
@@ -98,6 +160,24 @@ pub struct Cli {
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub diff_cache: Option<PathBuf>,
 
+    /// Use the store (supply-chain) at this path
+    ///
+    /// This overrides `store.path` in `[metadata.vet]`, e.g. to vet against a
+    /// store which is checked out somewhere other than the project.
+    #[clap(long, action, value_name = "DIR")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub store_path: Option<PathBuf>,
+
+    /// A directory of unpacked packages to use before cargo's registry
+    ///
+    /// This is for sandboxes without a usual CARGO_HOME. Packages may be laid
+    /// out like cargo's registry (`foo-1.2.3`) or like `cargo vendor`'s (`foo`).
+    /// Combine this with --frozen to never hit the network. Defaults to
+    /// $CARGO_VET_REGISTRY_SRC if that's set.
+    #[clap(long, action, value_name = "DIR")]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub registry_src: Option<PathBuf>,
+
     /// Filter out different parts of the build graph and pretend that's the true graph
     ///
     /// Example: `--filter-graph="exclude(any(eq(is_dev_only(true)),eq(name(serde_derive))))"`
@@ -144,6 +224,17 @@ pub struct Cli {
     #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
     pub filter_graph: Option<Vec<GraphFilter>>,
 
+    /// Don't require dev-dependencies to be vetted
+    ///
+    /// Packages which are only used by tests, benches, and examples are pruned
+    /// from the graph, so they need neither audits nor exemptions. Packages
+    /// which are also normal dependencies are still required as usual. This is
+    /// shorthand for `--filter-graph="exclude(is_dev_only(true))"`, and can't
+    /// be used with `prune` or `regenerate exemptions`.
+    #[clap(long, action)]
+    #[clap(help_heading = "GLOBAL OPTIONS", global = true)]
+    pub exclude_dev: bool,
+
     // Args for `Check` when the subcommand is not explicitly specified.
     //
     // These are exclusive with specifying a subcommand due to
@@ -196,7 +287,8 @@ pub enum Commands {
     /// information.
     ///
     /// If you don't consider an exemption to be "backlog", add `suggest = false` to its
-    /// entry and we won't remove it while suggesting.
+    /// entry and we won't remove it while suggesting (unless `--include-exemptions`
+    /// is passed).
     ///
     /// See also `regenerate exemptions`, which can be used to "garbage collect"
     /// your backlog (if you run it while `check` is passing).
@@ -211,6 +303,9 @@ pub enum Commands {
     ///
     /// At this point you can either configure your project further or start working on your
     /// review backlog with `suggest`.
+    ///
+    /// If you pass `--import name=url`, those audits are imported right away, and only packages
+    /// which they don't already cover get `exemptions`.
     #[clap(disable_version_flag = true)]
     Init(InitArgs),
 
@@ -240,6 +335,11 @@ pub enum Commands {
     /// with `inspect` or `diff`. Otherwise you must either supply the package name
     /// and one version (for a full audit) or two versions (for a delta audit).
     ///
+    /// A first-party package with `audit-as-crates-io = true` may be certified
+    /// by name alone. This shows the diff from the newest version of it you've
+    /// audited to your local source, and records a delta audit to the local
+    /// version (or a full audit, if no earlier version has been audited).
+    ///
     /// Once the package+version(s) have been selected, we will try to guess what
     /// criteria to certify it for. First we will `check`, and if the check fails
     /// and your audit would seemingly fix this package, we will use the criteria
@@ -262,6 +362,18 @@ pub enum Commands {
     #[clap(subcommand)]
     Regenerate(RegenerateSubcommands),
 
+    /// Remove exemptions which are no longer needed
+    ///
+    /// This drops exemptions which are fully covered by audits, and weakens the
+    /// criteria of overbroad ones (i.e. safe-to-deploy may be reduced to
+    /// safe-to-run). It's the same minimization `regenerate exemptions` does,
+    /// except that it refuses to run if `check` is failing, so it never adds
+    /// new exemptions.
+    ///
+    /// Each change is printed. With `--dry-run`, config.toml isn't modified.
+    #[clap(disable_version_flag = true)]
+    Prune(PruneArgs),
+
     /// Mark a package as exempted from review
     ///
     /// Exemptions are *usually* just "backlog" and the expectation is that you will review
@@ -301,6 +413,21 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     RecordViolation(RecordViolationArgs),
 
+    /// Import the reviews from cargo-crev proofs as audits
+    ///
+    /// This reads the `.crev` proofs in `--proofs` (by default, the proofs
+    /// cargo-crev has fetched) and records a full audit in audits.toml for
+    /// every positive review of a package in your dependency tree, by one of
+    /// the `--trusted-id`s or an id they trust at `medium` or above.
+    ///
+    /// Reviews with at least medium thoroughness and understanding are
+    /// imported as safe-to-deploy, and those with at least low as
+    /// safe-to-run. Other reviews, and packages which aren't in your tree,
+    /// are skipped, as are proofs which aren't validly signed by the id they
+    /// claim to be from.
+    #[clap(disable_version_flag = true)]
+    ImportCrev(ImportCrevArgs),
+
     // Plumbing/Debug Commands
     /// Reformat all of vet's files (in case you hand-edited them)
     ///
@@ -309,6 +436,17 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     Fmt(FmtArgs),
 
+    /// Download the source of every package, ahead of time
+    ///
+    /// This fetches your imports (unless `--locked` is passed) and the source
+    /// of every crates.io package in your dependency tree into the cache,
+    /// along with whatever `suggest` would diff them against, so that later
+    /// commands run with `--frozen` don't need the network. This
+    /// is useful in CI to separate the network-heavy phase from the rest, and
+    /// to fail early if the network is unavailable.
+    #[clap(disable_version_flag = true)]
+    Fetch(FetchArgs),
+
     /// Explicitly fetch the imports (foreign audit files)
     ///
     /// `cargo vet check` will implicitly do this, so this mostly exists as "plumbing"
@@ -316,6 +454,107 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     FetchImports(FetchImportsArgs),
 
+    /// Check that imports.lock matches what fetching the imports now produces
+    ///
+    /// This re-fetches every import in config.toml and compares the result with
+    /// the committed imports.lock, without modifying it. Any difference, whether
+    /// from local edits to imports.lock or from changes upstream, is summarized
+    /// and makes the command fail. Nothing is vetted, so this is a quick way to
+    /// check in CI that your imports are reproducible.
+    #[clap(disable_version_flag = true)]
+    VerifyImportsLock(VerifyImportsLockArgs),
+
+    /// Check that a store is internally consistent, without a project
+    ///
+    /// This validates the criteria, audits, exemptions, and policies in the store
+    /// against each other, but doesn't run `cargo metadata` or check that anything
+    /// has been vetted. This is useful for repositories which only exist to hold
+    /// audits for others to import, and so have no Rust project to check.
+    #[clap(disable_version_flag = true)]
+    CheckSelf(CheckSelfArgs),
+
+    /// Sign a store's audits.toml and config.toml, for others to import
+    ///
+    /// Imports with a `public-key` in config.toml only accept an audits.toml
+    /// which has been signed by that key, and only follow the imports of a
+    /// config.toml signed by it. This signs both files of the store with the
+    /// given ssh or minisign secret key, writing each signature next to its
+    /// file as `.sig` (ssh) or `.minisig` (minisign), which should be published
+    /// alongside them.
+    ///
+    /// Signing is done by `ssh-keygen -Y sign` or `minisign -S`, which must be
+    /// installed and may prompt for the key's passphrase. Sign again whenever
+    /// audits.toml or config.toml changes.
+    #[clap(disable_version_flag = true)]
+    Sign(SignArgs),
+
+    /// Check that the sources of your dependencies match what was audited
+    ///
+    /// Full audits recorded after `inspect` include a `source-hash` of the exact
+    /// source that was reviewed. This fetches every foreign package in your tree
+    /// which has such an audit, recomputes the hash, and reports any mismatches.
+    /// Audits without a `source-hash` are skipped.
+    #[clap(disable_version_flag = true)]
+    Verify(VerifyArgs),
+
+    /// List the audits recorded by a given person
+    ///
+    /// This prints every entry in audits.toml whose `who` contains the given
+    /// text (case-insensitively), so a name or email address is enough. This is
+    /// useful for spot-checking a reviewer's work, or reviewing the audits of
+    /// someone who has left the project.
+    #[clap(disable_version_flag = true)]
+    AuditsOf(AuditsOfArgs),
+
+    /// Print how much of the dependency tree each criteria covers
+    ///
+    /// This prints a compact one-line summary of how many of your third-party
+    /// packages satisfy each criteria, e.g. `safe-to-deploy: 380/412 (92%)`,
+    /// which is handy for badges and for tracking coverage over time.
+    /// Packages which are only vetted thanks to exemptions are included in the
+    /// counts; `--output-format=json` additionally reports how many are
+    /// covered by audits alone.
+    ///
+    /// Like `cargo vet --locked`, this uses the imports already in imports.lock
+    /// and never modifies the store.
+    #[clap(disable_version_flag = true)]
+    Coverage(CoverageArgs),
+
+    /// Export the review backlog for bulk issue creation
+    ///
+    /// This prints one entry per exemption in config.toml, and one per package
+    /// which is currently failing to vet, with the package, version, criteria,
+    /// and any notes. The CSV and JSON formats are meant to be fed into issue
+    /// tracker importers, so teams can track burning down their backlog as
+    /// tickets. Use `--output-file` to write it to a file.
+    ///
+    /// Like `cargo vet --locked`, this uses the imports already in imports.lock
+    /// and never modifies the store.
+    #[clap(disable_version_flag = true)]
+    ExportBacklog(ExportBacklogArgs),
+
+    /// Print the effective `[metadata.vet]` configuration
+    ///
+    /// cargo vet's own configuration (such as where the store is) is layered
+    /// from its defaults, then `[workspace.metadata.vet]`, then
+    /// `[package.metadata.vet]`. This prints each resolved setting, along with
+    /// which of those layers supplied it. It works even if `cargo vet init`
+    /// hasn't been run yet.
+    #[clap(disable_version_flag = true)]
+    PrintConfig(PrintConfigArgs),
+
+    /// Check that the environment is set up for cargo vet to work
+    ///
+    /// This checks for a usable `git`, that `$CARGO`, `CARGO_HOME` and cargo's
+    /// registry can be found, that the temporary directory is writable, and
+    /// that the imports in your config.toml (if any) can be fetched. Each check
+    /// is reported as passing or failing, with a hint on how to fix failures.
+    ///
+    /// This never modifies anything, so it's a good thing to run first when
+    /// cargo vet is failing in confusing ways.
+    #[clap(disable_version_flag = true)]
+    Doctor(DoctorArgs),
+
     /// Print the cargo build graph as understood by `cargo vet`
     ///
     /// This is a debugging command, the output's format is not guaranteed.
@@ -323,7 +562,8 @@ pub enum Commands {
     /// build graph is. Our graph is based on that result.
     ///
     /// With `--output-format=human` (the default) this will print out mermaid-js
-    /// diagrams, which things like github natively support rendering of.
+    /// diagrams, which things like github natively support rendering of, or
+    /// graphviz DOT graphs with `--graph-format=dot`.
     ///
     /// With `--output-format=json` we will print out more raw statistics for you
     /// to search/analyze.
@@ -343,6 +583,31 @@ pub enum Commands {
     #[clap(hide = true)]
     HelpMarkdown(HelpMarkdownArgs),
 
+    /// Print the contents of the diff-cache (for debugging suggestions)
+    ///
+    /// This lists every cached diffstat, and can optionally recompute them to
+    /// detect stale entries. Use the global `--diff-cache` argument to inspect
+    /// a diff-cache other than the default one.
+    #[clap(disable_version_flag = true)]
+    #[clap(hide = true)]
+    ExplainDiffCache(ExplainDiffCacheArgs),
+
+    /// Write the diff-cache to a file, to share with others
+    ///
+    /// Computing the diffstats that `suggest` uses to rank audits means
+    /// fetching and diffing packages, which is slow. A diff-cache exported with
+    /// this command can be merged into anyone else's with `import-diff-cache`,
+    /// so that they can skip that work for the same packages.
+    #[clap(disable_version_flag = true)]
+    ExportDiffCache(ExportDiffCacheArgs),
+
+    /// Merge a diff-cache exported elsewhere into your own
+    ///
+    /// Diffstats which are already in your diff-cache are kept, and any
+    /// imported ones which disagree with them are reported.
+    #[clap(disable_version_flag = true)]
+    ImportDiffCache(ImportDiffCacheArgs),
+
     /// Clean up old packages from the vet cache
     ///
     /// Removes packages which haven't been accessed in a while, and deletes
@@ -364,6 +629,10 @@ pub enum RegenerateSubcommands {
     /// We will try our best to preserve existing exemptions, removing only those that
     /// aren't needed, and adding only those that are needed. Exemptions that are overbroad
     /// may also be weakened (i.e. safe-to-deploy may be reduced to safe-to-run).
+    /// Exemptions with `suggest = false` are assumed to be hand-written and are left
+    /// untouched. Added exemptions get a note saying where they came from.
+    ///
+    /// Running this again without changing anything else is a no-op.
     #[clap(disable_version_flag = true)]
     Exemptions(RegenerateExemptionsArgs),
 
@@ -391,10 +660,72 @@ pub struct CheckArgs {
     /// certain of the requirements for to be emitted.
     #[clap(long, action)]
     pub shallow: bool,
+
+    /// Treat dependencies missing this criteria as errors
+    ///
+    /// If any criteria are denied, dependencies which are only missing other
+    /// criteria are reported as warnings, and don't fail the check. Can be
+    /// passed multiple times.
+    #[clap(long, action)]
+    pub deny: Vec<CriteriaName>,
+
+    /// Treat dependencies missing only this criteria as warnings
+    ///
+    /// Such dependencies are still reported, but don't fail the check. If a
+    /// criteria is both denied and warned, it's denied. Can be passed multiple
+    /// times.
+    #[clap(long, action)]
+    pub warn: Vec<CriteriaName>,
+
+    /// Explain which audits satisfied this package
+    ///
+    /// For each version of the package in the graph, prints the chain of
+    /// audits, deltas, and exemptions which satisfied each of its criteria.
+    /// `--output-format=json` reports these for every vetted package.
+    #[clap(long, action)]
+    pub explain: Option<PackageName>,
+
+    /// Fail if any exemptions aren't needed
+    ///
+    /// Exemptions for packages which aren't in the graph, or which audits
+    /// already cover, are warned about by default. This makes them an error
+    /// instead, so that CI notices when `cargo vet prune` should be run.
+    #[clap(long, action)]
+    pub deny_unused_exemptions: bool,
+
+    /// Warn about dependencies with advisories in this RustSec advisory database
+    ///
+    /// The path should be a checkout of https://github.com/rustsec/advisory-db.
+    /// Known advisories are listed next to the dependencies they affect, even
+    /// if those have been audited, but never fail the check.
+    #[cfg(feature = "advisories")]
+    #[clap(long, action)]
+    pub advisory_db: Option<PathBuf>,
 }
 
 #[derive(clap::Args)]
-pub struct InitArgs {}
+pub struct InitArgs {
+    /// Import audits from this source, in the form `name=url`
+    ///
+    /// The import is added to config.toml and fetched immediately, and
+    /// exemptions are only added for packages it doesn't already cover.
+    /// Can be passed multiple times.
+    #[clap(long, action)]
+    pub import: Vec<ImportArg>,
+    /// Only require `safe-to-run` for packages that are only used by dev-dependencies
+    ///
+    /// This sets `dev-safe-to-run = true` in config.toml, so that no policies
+    /// need to be written for dev-only packages.
+    #[clap(long, action)]
+    pub dev_safe_to_run: bool,
+    /// A note to add to every exemption added by init
+    ///
+    /// `{package}`, `{version}` and `{date}` (today, as YYYY-MM-DD) are
+    /// replaced with the details of each exemption, e.g.
+    /// `--exemption-note "{package} {version} exempted on {date}, see TICKET-123"`.
+    #[clap(long, action, value_name = "TEMPLATE")]
+    pub exemption_note: Option<String>,
+}
 
 /// Fetches the crate to a temp location and pushd's to it
 #[derive(clap::Args)]
@@ -403,11 +734,27 @@ pub struct InspectArgs {
     #[clap(action)]
     pub package: PackageName,
     /// The version to inspect
-    #[clap(action)]
+    #[clap(action, value_parser = parse_version)]
     pub version: Version,
     /// How to inspect the source
     #[clap(long, action, default_value = "sourcegraph")]
     pub mode: FetchMode,
+    /// The URL to open with `--mode=web`
+    ///
+    /// `{package}` and `{version}` are replaced with the package and version
+    /// being inspected.
+    #[clap(
+        long,
+        action,
+        value_name = "TEMPLATE",
+        default_value = "https://docs.rs/crate/{package}/{version}/source/"
+    )]
+    pub url_template: String,
+    /// Print the path the source was fetched to instead of opening a shell
+    ///
+    /// Only applies to `--mode=local`.
+    #[clap(long, action)]
+    pub no_shell: bool,
 }
 
 /// Emits a diff of the two versions
@@ -417,14 +764,34 @@ pub struct DiffArgs {
     #[clap(action)]
     pub package: PackageName,
     /// The base version to diff
-    #[clap(action)]
+    #[clap(action, value_parser = parse_version)]
     pub version1: Version,
     /// The target version to diff
-    #[clap(action)]
+    #[clap(action, value_parser = parse_version)]
     pub version2: Version,
     /// How to inspect the source
     #[clap(long, action, default_value = "sourcegraph")]
-    pub mode: FetchMode,
+    pub mode: DiffMode,
+    /// Show changed words rather than changed lines (`git diff --word-diff`)
+    ///
+    /// Only applies to `--mode=local`.
+    #[clap(long, action)]
+    pub word_diff: bool,
+    /// Show this many lines of context around each change (`git diff --unified`)
+    ///
+    /// Only applies to `--mode=local`. This doesn't affect the diffstats used
+    /// to recommend audits.
+    #[clap(long, action, value_name = "N")]
+    pub context: Option<u32>,
+    /// Display the diff with this command instead of `git diff`
+    ///
+    /// The command is run by the shell with the directories of the two
+    /// versions appended, e.g. `--tool difft` or `--tool 'delta --side-by-side'`.
+    /// Defaults to `diff.tool` in config.toml. Without either, `git diff` is
+    /// used, which respects git's own `diff.external`. Only applies to
+    /// `--mode=local`.
+    #[clap(long, action, value_name = "CMD")]
+    pub tool: Option<String>,
 }
 
 /// Certifies a package as audited
@@ -434,10 +801,10 @@ pub struct CertifyArgs {
     #[clap(action)]
     pub package: Option<PackageName>,
     /// The version to certify as audited
-    #[clap(action)]
+    #[clap(action, value_parser = parse_version)]
     pub version1: Option<Version>,
     /// If present, instead certify a diff from version1->version2
-    #[clap(action)]
+    #[clap(action, value_parser = parse_version)]
     pub version2: Option<Version>,
     /// The criteria to certify for this audit
     ///
@@ -451,7 +818,9 @@ pub struct CertifyArgs {
     pub dependency_criteria: Vec<DependencyCriteriaArg>,
     /// Who to name as the auditor
     ///
-    /// If not provided, we will collect this information from the local git.
+    /// If not provided, we will collect this information from the local git
+    /// (`$GIT_AUTHOR_NAME`/`$GIT_AUTHOR_EMAIL`, or `user.name`/`user.email`),
+    /// falling back to `$USER`.
     #[clap(long, action)]
     pub who: Option<String>,
     /// A free-form string to include with the new audit entry
@@ -466,8 +835,50 @@ pub struct CertifyArgs {
     ///
     /// To catch typos/mistakes, we check if the thing you're trying to
     /// talk about is part of your current build, but this flag disables that.
+    /// This is useful for certifying a crate ahead of adding it to your project.
+    /// Criteria are still checked.
     #[clap(long, action)]
     pub force: bool,
+    /// Certify a full audit of version2 because version1 can't be fetched
+    ///
+    /// If the version you'd take a delta from has been yanked, you may not be
+    /// able to fetch it to diff against. With this flag, a delta from version1
+    /// to version2 is instead recorded as a full audit of version2, with a note
+    /// explaining why.
+    #[clap(long, action)]
+    pub base_unavailable: bool,
+    /// Copy the audits of this package from an import into your own audits
+    ///
+    /// The value is the name of the import in config.toml. Its audits are
+    /// taken from imports.lock, so run `cargo vet fetch-imports` first if it
+    /// may be out of date. If versions are given, only audits of exactly those
+    /// versions are copied. Criteria are translated with the import's
+    /// `criteria-map`.
+    #[clap(
+        long,
+        action,
+        value_name = "IMPORT",
+        conflicts_with_all = &["criteria", "dependency-criteria", "base-unavailable"]
+    )]
+    pub import_from: Option<ImportName>,
+    /// Certify a delta to the given version from the closest version which is
+    /// already audited or exempted
+    ///
+    /// The base is picked the same way `cargo vet suggest` picks one. If there
+    /// is no audited version to certify a delta from, nothing is certified.
+    #[clap(long, action, requires = "version1", conflicts_with = "version2")]
+    pub from_audited: bool,
+    /// Certify every audit that `cargo vet suggest` recommends
+    ///
+    /// Each suggested audit is recorded with the criteria it was suggested
+    /// for (or `--criteria`, if given), after confirming it at a prompt.
+    /// With `--accept-all`, they're all certified without prompting.
+    #[clap(
+        long,
+        action,
+        conflicts_with_all = &["package", "version1", "version2", "dependency-criteria", "base-unavailable", "import-from", "from-audited", "force"]
+    )]
+    pub all: bool,
 }
 
 /// Forbids the given version
@@ -486,7 +897,9 @@ pub struct RecordViolationArgs {
     pub criteria: Vec<CriteriaName>,
     /// Who to name as the auditor
     ///
-    /// If not provided, we will collect this information from the local git.
+    /// If not provided, we will collect this information from the local git
+    /// (`$GIT_AUTHOR_NAME`/`$GIT_AUTHOR_EMAIL`, or `user.name`/`user.email`),
+    /// falling back to `$USER`.
     #[clap(long, action)]
     pub who: Option<String>,
     /// A free-form string to include with the new forbid entry
@@ -502,6 +915,22 @@ pub struct RecordViolationArgs {
     pub force: bool,
 }
 
+/// Imports audits from cargo-crev reviews
+#[derive(clap::Args)]
+pub struct ImportCrevArgs {
+    /// The crev ids whose reviews should be imported
+    ///
+    /// Reviews by ids which these trust at `medium` or above are imported too.
+    #[clap(long, action, required = true)]
+    pub trusted_id: Vec<String>,
+    /// The directory to read crev proofs from
+    ///
+    /// This can be a checkout of a crev proof repository. If not provided,
+    /// the proofs cargo-crev has fetched, and your own, are used.
+    #[clap(long, action)]
+    pub proofs: Option<PathBuf>,
+}
+
 /// Certifies the given version
 #[derive(clap::Args)]
 pub struct AddExemptionArgs {
@@ -547,16 +976,148 @@ pub struct SuggestArgs {
     /// certain of the requirements for to be emitted.
     #[clap(long, action)]
     pub shallow: bool,
+    /// Only suggest audits for crates owned by the given owner.
+    ///
+    /// Owners are assigned to crates by the `owners` table in config.toml.
+    #[clap(long, action)]
+    pub assignee: Option<OwnerName>,
+    /// Also suggest audits to replace exemptions marked `suggest = false`.
+    ///
+    /// Those exemptions are normally left alone, as they aren't considered
+    /// part of your review backlog. With this flag every exemption is treated
+    /// as a candidate for review, so the suggestions show what it would take
+    /// to retire all of them.
+    #[clap(long, action)]
+    pub include_exemptions: bool,
+    /// Only suggest a batch of audits which fits within this many lines of review.
+    ///
+    /// The batch is chosen to close as many audit gaps as possible, favouring
+    /// audits of packages with many dependents and small diffs. Suggestions
+    /// which don't fit are left for a later batch.
+    #[clap(long, action, value_name = "LINES")]
+    pub budget: Option<u64>,
+    /// Only suggest audits whose diffs have at most this many lines.
+    ///
+    /// Suggestions are already listed smallest diff first, so this just cuts
+    /// off the reviews which are too big to pick up right now.
+    #[clap(long, action, value_name = "LINES")]
+    pub max_lines: Option<u64>,
+    /// Only suggest audits which would help satisfy this criteria.
+    ///
+    /// Audits for criteria which imply it are included, as they satisfy it
+    /// too.
+    #[clap(long, action)]
+    pub criteria: Option<CriteriaName>,
+    /// Fail as soon as a package can't be fetched or diffed.
+    ///
+    /// By default a package which can't be analyzed (e.g. because its version
+    /// was yanked) is skipped, and listed after the suggestions for the other
+    /// packages. With this flag it is an error instead.
+    #[clap(long, action)]
+    pub fail_fast: bool,
+    /// Which previously audited version to suggest diffing from.
+    ///
+    /// The smallest diff is the least review work right now, but can suggest
+    /// an awkward delta from an old version (or backwards from a newer one).
+    /// Always diffing from the highest audited version keeps the audit chain
+    /// moving forward, at the cost of sometimes reviewing more code. Diffing
+    /// from the lowest audited version does the opposite, and is mostly
+    /// useful to get a single delta which covers everything since a package
+    /// was first audited.
+    #[clap(long, value_enum, action, value_name = "STRATEGY")]
+    #[clap(default_value_t = PreferAuditedVersion::SmallestDiff)]
+    pub prefer_audited_version: PreferAuditedVersion,
 }
 
 #[derive(clap::Args)]
 pub struct FmtArgs {}
 
+#[derive(clap::Args)]
+pub struct FetchArgs {}
+
 #[derive(clap::Args)]
 pub struct FetchImportsArgs {}
 
 #[derive(clap::Args)]
-pub struct RegenerateExemptionsArgs {}
+pub struct VerifyImportsLockArgs {}
+
+#[derive(clap::Args)]
+pub struct CheckSelfArgs {
+    /// The path to the store (supply-chain) to check
+    ///
+    /// Defaults to `--store-path`, or `supply-chain` in the current directory.
+    #[clap(action, name = "store", value_name = "STORE_PATH")]
+    pub store_path: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct SignArgs {
+    /// The ssh or minisign secret key to sign with
+    #[clap(long, action)]
+    pub key: PathBuf,
+    /// The path to the store (supply-chain) to sign the audits of
+    ///
+    /// Defaults to `--store-path`, or `supply-chain` in the current directory.
+    #[clap(action, name = "store", value_name = "STORE_PATH")]
+    pub store_path: Option<PathBuf>,
+}
+
+#[derive(clap::Args)]
+pub struct VerifyArgs {}
+
+#[derive(clap::Args)]
+pub struct PruneArgs {
+    /// Print what would be pruned without modifying config.toml
+    #[clap(long, action)]
+    pub dry_run: bool,
+}
+
+#[derive(clap::Args)]
+pub struct AuditsOfArgs {
+    /// The name or email (or part of one) to search for
+    #[clap(action)]
+    pub who: String,
+}
+
+#[derive(clap::Args)]
+pub struct CoverageArgs {}
+
+#[derive(clap::Args)]
+pub struct ExportBacklogArgs {
+    /// The format to export the backlog in
+    #[clap(long, value_enum, action)]
+    #[clap(default_value_t = BacklogFormat::Csv)]
+    pub format: BacklogFormat,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum BacklogFormat {
+    /// Comma-separated values, with a header row
+    Csv,
+    /// A JSON array of objects
+    Json,
+}
+
+#[derive(clap::Args)]
+pub struct PrintConfigArgs {}
+
+#[derive(clap::Args)]
+pub struct DoctorArgs {}
+
+#[derive(clap::Args)]
+pub struct RegenerateExemptionsArgs {
+    /// The note to add to every exemption this adds
+    ///
+    /// `{package}`, `{version}` and `{date}` (today, as YYYY-MM-DD) are
+    /// replaced with the details of each exemption, like `init --exemption-note`.
+    /// Pass an empty string to not add notes.
+    #[clap(long, action, value_name = "TEMPLATE")]
+    #[clap(default_value = DEFAULT_REGENERATED_EXEMPTION_NOTE)]
+    pub exemption_note: String,
+}
+
+pub const DEFAULT_REGENERATED_EXEMPTION_NOTE: &str =
+    "automatically added by `cargo vet regenerate exemptions`";
 
 #[derive(clap::Args)]
 pub struct RegenerateImportsArgs {}
@@ -567,6 +1128,35 @@ pub struct RegenerateAuditAsCratesIoArgs {}
 #[derive(clap::Args)]
 pub struct HelpMarkdownArgs {}
 
+#[derive(clap::Args)]
+pub struct ExplainDiffCacheArgs {
+    /// Recompute each cached diffstat and report any which differ
+    ///
+    /// This requires fetching every version mentioned in the cache.
+    #[clap(long, action)]
+    pub validate: bool,
+}
+
+#[derive(clap::Args)]
+pub struct ExportDiffCacheArgs {
+    /// The file to write the diff-cache to
+    #[clap(action)]
+    pub path: PathBuf,
+}
+
+#[derive(clap::Args)]
+pub struct ImportDiffCacheArgs {
+    /// The diff-cache file to merge in
+    #[clap(action)]
+    pub path: PathBuf,
+    /// Recompute each imported diffstat, and only merge the ones which match
+    ///
+    /// This requires fetching every version mentioned in the imported file,
+    /// but means it doesn't need to be trusted.
+    #[clap(long, action)]
+    pub validate: bool,
+}
+
 #[derive(clap::Args)]
 pub struct GcArgs {
     /// Packages in the vet cache which haven't been used for this many days
@@ -579,6 +1169,13 @@ pub struct GcArgs {
     /// time you use cargo vet.
     #[clap(long, action)]
     pub clean: bool,
+
+    /// Clear the diff-cache, forcing every diffstat to be recomputed the next
+    /// time it's needed.
+    ///
+    /// This respects the global `--diff-cache` argument.
+    #[clap(long, action)]
+    pub clear_diff_cache: bool,
 }
 
 #[derive(clap::Args)]
@@ -587,6 +1184,34 @@ pub struct DumpGraphArgs {
     #[clap(long, value_enum, action)]
     #[clap(default_value_t = DumpGraphDepth::FirstParty)]
     pub depth: DumpGraphDepth,
+    /// Also run the resolver, and include what it considered and concluded
+    /// for each third-party package (only with `--output-format=json`).
+    ///
+    /// This includes the criteria validated for the package, the result of
+    /// searching for an audit chain for each criteria, the audits and
+    /// exemptions which were available, and the final verdict.
+    #[clap(long, action)]
+    pub resolution: bool,
+    /// The kind of graph to print with `--output-format=human`
+    #[clap(long, value_enum, action)]
+    #[clap(default_value_t = DumpGraphFormat::Mermaid)]
+    pub graph_format: DumpGraphFormat,
+    /// Include the criteria each package is required to satisfy
+    ///
+    /// These are computed by pushing the policies in config.toml down through
+    /// the graph, the way `check` does, which is useful for understanding why
+    /// a package is required to be e.g. `safe-to-deploy`. This requires
+    /// `cargo vet init` to have been run.
+    #[clap(long, action)]
+    pub criteria: bool,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+pub enum DumpGraphFormat {
+    /// A mermaid-js diagram
+    Mermaid,
+    /// A graphviz DOT graph
+    Dot,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -598,6 +1223,17 @@ pub enum DumpGraphDepth {
     Full,
 }
 
+/// How `suggest` picks the audited version to diff from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum PreferAuditedVersion {
+    /// The audited version with the smallest diff to the current one
+    SmallestDiff,
+    /// The highest audited version
+    Highest,
+    /// The lowest audited version
+    Lowest,
+}
+
 /// Logging verbosity levels
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum Verbose {
@@ -609,6 +1245,11 @@ pub enum Verbose {
     Trace,
 }
 
+/// Parse a version argument, naming the bad input if it isn't valid semver.
+fn parse_version(s: &str) -> Result<Version, String> {
+    Version::parse(s).map_err(|e| format!("'{s}' is not a valid semver version ({e})"))
+}
+
 #[derive(Clone, Debug)]
 pub struct DependencyCriteriaArg {
     pub dependency: PackageName,
@@ -648,16 +1289,67 @@ impl FromStr for DependencyCriteriaArg {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct ImportArg {
+    pub name: ImportName,
+    pub url: String,
+}
+
+impl FromStr for ImportArg {
+    // the error must be owned as well
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use nom::{
+            bytes::complete::{is_not, tag},
+            combinator::{all_consuming, rest},
+            error::{convert_error, VerboseError},
+            sequence::tuple,
+            Finish, IResult,
+        };
+        type ParseResult<I, O> = IResult<I, O, VerboseError<I>>;
+
+        fn parse(input: &str) -> ParseResult<&str, ImportArg> {
+            let (rest, (name, _, url)) =
+                all_consuming(tuple((is_not("="), tag("="), rest)))(input)?;
+            Ok((
+                rest,
+                ImportArg {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                },
+            ))
+        }
+
+        match parse(s).finish() {
+            Ok((_remaining, val)) => Ok(val),
+            Err(e) => Err(convert_error(s, e)),
+        }
+    }
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum FetchMode {
     Local,
     Sourcegraph,
+    /// Open `--url-template` in the browser
+    Web,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum DiffMode {
+    Local,
+    Sourcegraph,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
 pub enum OutputFormat {
     Human,
     Json,
+    /// A self-contained HTML page (only supported by `check` and `suggest`)
+    Html,
+    /// A SARIF 2.1.0 log for code scanning tools (only supported by `check`)
+    Sarif,
 }
 
 #[derive(Clone, Debug)]
@@ -666,6 +1358,13 @@ pub enum GraphFilter {
     Exclude(GraphFilterQuery),
 }
 
+impl GraphFilter {
+    /// The filter `--exclude-dev` is shorthand for.
+    pub fn exclude_dev_only() -> Self {
+        GraphFilter::Exclude(GraphFilterQuery::Prop(GraphFilterProperty::IsDevOnly(true)))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum GraphFilterQuery {
     Any(Vec<GraphFilterQuery>),
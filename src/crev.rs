@@ -0,0 +1,336 @@
+//! Reading [cargo-crev](https://github.com/crev-dev/cargo-crev) proofs, so
+//! that existing crev reviews can be imported as audits with
+//! `cargo vet import-crev`.
+//!
+//! Only package reviews and trust proofs are understood. A crev id is an
+//! ed25519 public key, so every proof's signature is checked against the id
+//! it claims to be from, and proofs which fail that are skipped.
+
+use std::path::Path;
+
+use cargo_metadata::Version;
+use chrono::{DateTime, FixedOffset};
+use ring::signature::{UnparsedPublicKey, ED25519};
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::errors::CrevError;
+use crate::format::{
+    AuditEntry, AuditKind, CriteriaName, DependencyCriteria, FastSet, PackageName, PackageStr,
+    SortedMap, SAFE_TO_DEPLOY, SAFE_TO_RUN,
+};
+
+/// The `source` crev records for packages from crates.io.
+const CRATES_IO_SOURCE: &str = "https://crates.io";
+
+/// All the proofs found under a directory, such as a crev proof repository
+/// or crev's local cache of them.
+#[derive(Debug, Default)]
+pub struct CrevProofs {
+    pub reviews: Vec<PackageReview>,
+    pub trusts: Vec<Trust>,
+    /// How many proofs were skipped because they couldn't be parsed, or
+    /// their signature didn't match.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CrevId {
+    #[serde(rename = "id-type")]
+    pub id_type: String,
+    pub id: String,
+    pub url: Option<String>,
+}
+
+impl CrevId {
+    /// Whether `signature` is this id's signature of `body`.
+    fn signed(&self, body: &str, signature: &str) -> bool {
+        let decode = |data: &str| base64::decode_config(data.trim(), base64::URL_SAFE_NO_PAD);
+        match (&self.id_type[..], decode(&self.id), decode(signature)) {
+            ("crev", Ok(key), Ok(signature)) => UnparsedPublicKey::new(&ED25519, key)
+                .verify(body.as_bytes(), &signature)
+                .is_ok(),
+            _ => false,
+        }
+    }
+}
+
+/// Just enough of any proof to check its signature.
+#[derive(Deserialize)]
+struct ProofHeader {
+    /// Only present in the newer `CREV PROOF` format.
+    kind: Option<String>,
+    from: CrevId,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PackageReview {
+    /// When the review was made, as an RFC 3339 timestamp. A newer review by
+    /// the same id of the same package supersedes older ones.
+    pub date: String,
+    pub from: CrevId,
+    pub package: ReviewedPackage,
+    pub review: Option<Review>,
+    #[serde(default)]
+    pub comment: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReviewedPackage {
+    pub source: String,
+    pub name: PackageName,
+    pub version: Version,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Review {
+    pub thoroughness: Level,
+    pub understanding: Level,
+    pub rating: Rating,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Trust {
+    pub from: CrevId,
+    pub ids: Vec<CrevId>,
+    pub trust: TrustLevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Level {
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rating {
+    #[serde(alias = "dangerous")]
+    Negative,
+    Neutral,
+    Positive,
+    #[serde(alias = "superb")]
+    Strong,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrustLevel {
+    Distrust,
+    None,
+    Low,
+    Medium,
+    High,
+}
+
+impl PackageReview {
+    /// The review's date, or `None` if it can't be parsed, which sorts before
+    /// any parsed date.
+    fn timestamp(&self) -> Option<DateTime<FixedOffset>> {
+        DateTime::parse_from_rfc3339(&self.date).ok()
+    }
+}
+
+impl Review {
+    /// The criteria a review vouches for, if any. Careful reviews by someone
+    /// who understood the code are taken to mean it's safe to deploy, and
+    /// quicker ones only that it's safe to run.
+    pub fn criteria(&self) -> Option<&'static str> {
+        if self.rating < Rating::Positive {
+            return None;
+        }
+        match self.thoroughness.min(self.understanding) {
+            Level::Medium | Level::High => Some(SAFE_TO_DEPLOY),
+            Level::Low => Some(SAFE_TO_RUN),
+            Level::None => None,
+        }
+    }
+}
+
+impl CrevProofs {
+    /// Load every `.crev` file under the given directory.
+    pub fn load(path: &Path) -> Result<Self, CrevError> {
+        let mut proofs = CrevProofs::default();
+        proofs.load_dir(path)?;
+        Ok(proofs)
+    }
+
+    fn load_dir(&mut self, dir: &Path) -> Result<(), CrevError> {
+        let read_error = |path: &Path| {
+            let path = path.to_owned();
+            move |error| CrevError::Read { path, error }
+        };
+        for entry in std::fs::read_dir(dir).map_err(read_error(dir))? {
+            let path = entry.map_err(read_error(dir))?.path();
+            if path.is_dir() {
+                // Skip `.git` and the like
+                if !path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .starts_with('.')
+                {
+                    self.load_dir(&path)?;
+                }
+            } else if path.extension() == Some("crev".as_ref()) {
+                let contents = std::fs::read_to_string(&path).map_err(read_error(&path))?;
+                self.parse(&path, &contents);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parse the proofs in a `.crev` file, which may hold any number of them.
+    /// Each looks like:
+    ///
+    /// ```text
+    /// -----BEGIN CREV PACKAGE REVIEW-----
+    /// <yaml>
+    /// -----BEGIN CREV PACKAGE REVIEW SIGNATURE-----
+    /// <signature>
+    /// -----END CREV PACKAGE REVIEW-----
+    /// ```
+    ///
+    /// Newer versions of crev write `CREV PROOF` instead, with the kind of
+    /// proof in the yaml's `kind` field. Proofs which can't be parsed or
+    /// verified are skipped with a warning, so one bad proof in a cache full
+    /// of other people's proof repositories doesn't stop the import.
+    fn parse(&mut self, path: &Path, contents: &str) {
+        let mut lines = contents.lines();
+        while let Some(line) = lines.next() {
+            let header = match line
+                .trim()
+                .strip_prefix("-----BEGIN CREV ")
+                .and_then(|rest| rest.strip_suffix("-----"))
+            {
+                Some(header) => header,
+                None => continue,
+            };
+            // The signature is of the body exactly as written, newlines and all.
+            let body = lines
+                .by_ref()
+                .take_while(|line| !line.trim().starts_with("-----BEGIN CREV "))
+                .map(|line| format!("{line}\n"))
+                .collect::<String>();
+            let signature = lines
+                .by_ref()
+                .take_while(|line| !line.trim().starts_with("-----END CREV "))
+                .collect::<String>();
+
+            if let Err(error) = self.parse_proof(path, header, &body, &signature) {
+                warn!("skipping a crev proof: {error}");
+                self.skipped += 1;
+            }
+        }
+    }
+
+    fn parse_proof(
+        &mut self,
+        path: &Path,
+        header: &str,
+        body: &str,
+        signature: &str,
+    ) -> Result<(), CrevError> {
+        let parse_error = |error| CrevError::Parse {
+            path: path.to_owned(),
+            error,
+        };
+        let proof = serde_yaml::from_str::<ProofHeader>(body).map_err(parse_error)?;
+        let kind = match header {
+            "PROOF" => proof.kind.unwrap_or_default(),
+            header => header.to_lowercase(),
+        };
+        if kind != "package review" && kind != "trust" {
+            // Code reviews, advisories, etc. have nothing to import
+            return Ok(());
+        }
+        if !proof.from.signed(body, signature) {
+            return Err(CrevError::InvalidSignature {
+                path: path.to_owned(),
+                id: proof.from.id,
+            });
+        }
+        if kind == "package review" {
+            self.reviews
+                .push(serde_yaml::from_str(body).map_err(parse_error)?);
+        } else {
+            self.trusts
+                .push(serde_yaml::from_str(body).map_err(parse_error)?);
+        }
+        Ok(())
+    }
+
+    /// The given ids, plus the ids they trust at `medium` or above, minus any
+    /// ids they distrust.
+    pub fn trust_set(&self, ids: &[String]) -> FastSet<String> {
+        let mut trusted = ids.iter().cloned().collect::<FastSet<_>>();
+        let mut distrusted = FastSet::new();
+        for trust in &self.trusts {
+            if !ids.contains(&trust.from.id) {
+                continue;
+            }
+            let trust_ids = trust.ids.iter().map(|id| id.id.clone());
+            match trust.trust {
+                TrustLevel::Distrust => distrusted.extend(trust_ids),
+                level if level >= TrustLevel::Medium => trusted.extend(trust_ids),
+                _ => {}
+            }
+        }
+        trusted.retain(|id| !distrusted.contains(id));
+        trusted
+    }
+
+    /// Translate the positive reviews by the trusted ids of the given
+    /// packages into full audits.
+    pub fn audits_for<'a>(
+        &self,
+        trusted: &FastSet<String>,
+        packages: impl IntoIterator<Item = (PackageStr<'a>, &'a Version)>,
+    ) -> Vec<(PackageName, AuditEntry)> {
+        let packages = packages.into_iter().collect::<FastSet<_>>();
+        // Only the newest review by each id of each package counts, as it may
+        // have withdrawn or changed an older one.
+        let mut latest = SortedMap::<_, &PackageReview>::new();
+        for proof in &self.reviews {
+            if proof.package.source != CRATES_IO_SOURCE
+                || !trusted.contains(&proof.from.id)
+                || !packages.contains(&(&proof.package.name[..], &proof.package.version))
+            {
+                continue;
+            }
+            let key = (&proof.from.id, &proof.package.name, &proof.package.version);
+            match latest.get(&key) {
+                Some(newest) if newest.timestamp() >= proof.timestamp() => {}
+                _ => {
+                    latest.insert(key, proof);
+                }
+            }
+        }
+        latest
+            .into_values()
+            .filter_map(|proof| {
+                let criteria = proof.review.as_ref()?.criteria()?;
+                let mut notes = format!("Imported from the cargo-crev review by {}", proof.from.id);
+                let comment = proof.comment.trim();
+                if !comment.is_empty() {
+                    notes = format!("{comment}\n\n{notes}");
+                }
+                let who = proof.from.url.as_ref().unwrap_or(&proof.from.id);
+                let entry = AuditEntry {
+                    who: Some(who.clone()),
+                    criteria: vec![CriteriaName::from(criteria).into()],
+                    kind: AuditKind::Full {
+                        version: proof.package.version.clone(),
+                        dependency_criteria: DependencyCriteria::new(),
+                    },
+                    notes: Some(notes),
+                    source_hash: None,
+                };
+                Some((proof.package.name.clone(), entry))
+            })
+            .collect()
+    }
+}
@@ -4,7 +4,7 @@ use cargo_metadata::Version;
 use miette::{Diagnostic, NamedSource, SourceOffset, SourceSpan};
 use thiserror::Error;
 
-use crate::format::{ForeignCriteriaName, ImportName, PackageName};
+use crate::format::{CriteriaName, ForeignCriteriaName, ImportName, PackageName};
 
 pub type SourceFile = Arc<NamedSource>;
 
@@ -29,6 +29,9 @@ pub enum AuditAsError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     ShouldntBeAuditAs(ShouldntBeAuditAsErrors),
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    SharesCratesIoVersion(SharesCratesIoVersionErrors),
     // FIXME: we should probably just make the caller pass this in?
     #[error(transparent)]
     #[diagnostic(transparent)]
@@ -67,6 +70,24 @@ impl Display for ShouldntBeAuditAsErrors {
     }
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(help(
+    "Make their audit-as-crates-io entries `false` to vet them as first-party code instead"
+))]
+pub struct SharesCratesIoVersionErrors {
+    pub errors: Vec<SharesCratesIoVersionError>,
+}
+
+impl Display for SharesCratesIoVersionErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("some audit-as-crates-io packages are also used from crates.io, and can't share its audits")?;
+        for e in &self.errors {
+            f.write_fmt(format_args!("\n  {}", e))?
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("{package}:{version}")]
 pub struct NeedsAuditAsError {
@@ -81,6 +102,14 @@ pub struct ShouldntBeAuditAsError {
     pub version: Version,
 }
 
+#[derive(Debug, Error)]
+#[error("{package}:{version} from {source_repr}")]
+pub struct SharesCratesIoVersionError {
+    pub package: PackageName,
+    pub version: Version,
+    pub source_repr: String,
+}
+
 ///////////////////////////////////////////////////////////
 // CertifyError
 ///////////////////////////////////////////////////////////
@@ -99,12 +128,52 @@ pub enum CertifyError {
     #[error("'{0}' isn't one of your foreign packages")]
     #[diagnostic(help("use --force to ignore this error"))]
     NotAPackage(PackageName),
+    #[error("'{criteria}' isn't one of your criteria")]
+    #[diagnostic(help("the possible criteria are {:?}", valid_names))]
+    UnknownCriteria {
+        criteria: CriteriaName,
+        valid_names: Vec<CriteriaName>,
+    },
+    #[error("--base-unavailable can only be used when certifying a delta")]
+    BaseUnavailableWithoutDelta,
+    #[error("no criteria were given, and there's no terminal to prompt for them")]
+    #[diagnostic(help("pass --criteria to certify non-interactively"))]
+    CriteriaNotGiven,
+    #[error("the certify statement can't be confirmed without a terminal")]
+    #[diagnostic(help(
+        "pass --accept-all (and --notes for any notes) to certify non-interactively"
+    ))]
+    StatementNotAccepted,
+    #[error("'{0}' isn't one of your imports")]
+    #[diagnostic(help(
+        "imports are listed in config.toml, you may need to run `cargo vet fetch-imports`"
+    ))]
+    UnknownImport(ImportName),
+    #[error("there's no audited version of {package} to certify a delta to {version} from")]
+    #[diagnostic(help(
+        "pass both versions to certify a delta, or drop --from-audited to certify a full audit"
+    ))]
+    NoDeltaBase {
+        package: PackageName,
+        version: Version,
+    },
+    #[error("'{0}' has no criteria-map, so its audits can't be copied")]
+    #[diagnostic(help(
+        "add a criteria-map to the import in config.toml, to say what their criteria mean to you"
+    ))]
+    NoCriteriaMap(ImportName),
+    #[error("'{import}' has no audits of {package} which map to your criteria")]
+    NoImportedAudits {
+        import: ImportName,
+        package: PackageName,
+    },
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Suggest(#[from] SuggestError),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
     #[error(transparent)]
     EditError(#[from] EditError),
-    #[error(transparent)]
-    UserInfoError(#[from] UserInfoError),
 }
 
 ///////////////////////////////////////////////////////////
@@ -134,6 +203,19 @@ pub enum InitError {
     StoreCommit(#[source] StoreCommitError),
 }
 
+//////////////////////////////////////////////////////////
+// LockfileError
+//////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("Cargo.lock is missing, refusing to vet an unpinned dependency graph")]
+pub struct LockfileError {
+    #[source]
+    pub error: cargo_metadata::Error,
+    #[help]
+    pub help: Option<String>,
+}
+
 //////////////////////////////////////////////////////////
 // MinimizeUnauditedError
 //////////////////////////////////////////////////////////
@@ -148,6 +230,42 @@ pub enum MinimizeUnauditedError {
     Unknown,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum PruneError {
+    #[error("`cargo vet check` is failing, so exemptions can't be safely pruned")]
+    #[diagnostic(help(
+        "run `cargo vet regenerate exemptions` to also add the exemptions needed to pass"
+    ))]
+    CheckFailing,
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Minimize(#[from] MinimizeUnauditedError),
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[diagnostic(help("run `cargo vet prune` to remove them"))]
+pub struct UnusedExemptionsErrors {
+    pub errors: Vec<UnusedExemptionError>,
+}
+
+impl Display for UnusedExemptionsErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("some exemptions aren't needed to pass `cargo vet check`")?;
+        for e in &self.errors {
+            f.write_fmt(format_args!("\n  {}", e))?
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{package}:{version}")]
+pub struct UnusedExemptionError {
+    pub package: PackageName,
+    pub version: Version,
+}
+
 ///////////////////////////////////////////////////////////
 // StoreErrors
 ///////////////////////////////////////////////////////////
@@ -187,6 +305,14 @@ pub enum StoreAcquireError {
     Validate(#[from] StoreValidateErrors),
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[error("imports.lock doesn't match the imports in config.toml:{}", differences.iter().map(|d| format!("\n  {d}")).collect::<String>())]
+#[diagnostic(help("run `cargo vet fetch-imports` to update it"))]
+pub struct ImportsLockOutdatedError {
+    /// One line per difference.
+    pub differences: Vec<String>,
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[non_exhaustive]
 #[error("Failed to commit store")]
@@ -203,6 +329,25 @@ pub enum StoreCommitError {
     ),
 }
 
+////////////////////////////////////////////////////////////
+// StoreWarnings
+////////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("'{old}' is deprecated, it has been renamed to '{new}'")]
+#[diagnostic(
+    severity(Warning),
+    help("run `cargo vet fmt` to migrate your store to the new names")
+)]
+pub struct DeprecatedFieldWarning {
+    #[source_code]
+    pub source_code: SourceFile,
+    #[label]
+    pub span: SourceSpan,
+    pub old: &'static str,
+    pub new: &'static str,
+}
+
 ////////////////////////////////////////////////////////////
 // StoreValidateErrors
 ////////////////////////////////////////////////////////////
@@ -220,6 +365,18 @@ pub enum StoreValidateError {
     #[diagnostic(transparent)]
     #[error(transparent)]
     InvalidCriteria(InvalidCriteriaError),
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    ImpliesCycle(ImpliesCycleError),
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    InvalidImportUrl(InvalidImportUrlError),
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    InvalidPublicKey(InvalidPublicKeyError),
+    #[diagnostic(transparent)]
+    #[error(transparent)]
+    InvalidProxy(InvalidProxyError),
 }
 
 #[derive(Debug, Error, Diagnostic)]
@@ -228,12 +385,55 @@ pub enum StoreValidateError {
 pub struct InvalidCriteriaError {
     #[source_code]
     pub source_code: SourceFile,
-    #[label]
+    #[label("in {entry}")]
     pub span: SourceSpan,
     pub invalid: String,
+    /// The entry which referenced the criteria, e.g. `policy.foo`.
+    pub entry: String,
     pub valid_names: Arc<Vec<String>>,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[error("criteria implies itself: {}", cycle.join(" -> "))]
+#[diagnostic(help("remove one of the `implies` in the cycle"))]
+pub struct ImpliesCycleError {
+    #[source_code]
+    pub source_code: SourceFile,
+    #[label("this closes the cycle")]
+    pub span: SourceSpan,
+    /// The criteria in the cycle, starting and ending with the same one.
+    pub cycle: Vec<CriteriaName>,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("invalid URL for foreign import {import_name} @ {import_url:?}: {reason}")]
+#[diagnostic(help(
+    "import urls must be http, https, or file urls (git repositories may also be ssh or git urls)"
+))]
+pub struct InvalidImportUrlError {
+    pub import_name: ImportName,
+    pub import_url: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("invalid network.proxy {proxy:?}: {reason}")]
+#[diagnostic(help("proxies are given as urls, like `http://proxy.example.com:8080`"))]
+pub struct InvalidProxyError {
+    pub proxy: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Error, Diagnostic)]
+#[error("invalid public-key for foreign import {import_name}: {reason}")]
+#[diagnostic(help(
+    "public keys must be an ssh public key (`ssh-ed25519 AAAA...`) or a minisign public key (`RW...`)"
+))]
+pub struct InvalidPublicKeyError {
+    pub import_name: ImportName,
+    pub reason: String,
+}
+
 //////////////////////////////////////////////////////////
 // CacheErrors
 /////////////////////////////////////////////////////////
@@ -337,11 +537,17 @@ pub enum FetchAndDiffError {
 #[derive(Debug, Error, Diagnostic)]
 #[non_exhaustive]
 pub enum DiffError {
+    #[error("Failed to read package contents")]
+    Io(
+        #[from]
+        #[source]
+        std::io::Error,
+    ),
     #[error("Failed to diff package")]
-    CommandError(
+    Git(
         #[from]
         #[source]
-        CommandError,
+        git2::Error,
     ),
 }
 
@@ -376,6 +582,15 @@ pub enum FetchError {
         package: PackageName,
         version: Version,
     },
+    #[error("{package}:{version} has been yanked, and couldn't be fetched")]
+    #[diagnostic(help(
+        "if this is the base of a delta you're auditing, audit the newer version in full \
+         instead, and record that with `cargo vet certify {package} {version} <newer-version> --base-unavailable`"
+    ))]
+    Yanked {
+        package: PackageName,
+        version: Version,
+    },
     #[error("Failed to unpack .crate at {}", src.display())]
     Unpack {
         src: PathBuf,
@@ -388,6 +603,19 @@ pub enum FetchError {
         #[source]
         error: std::io::Error,
     },
+    #[error(
+        "{package}:{version} doesn't match the registry's checksum (expected sha256 {expected}, got {actual})"
+    )]
+    #[diagnostic(help(
+        "the .crate at {} may have been tampered with or corrupted; delete it to download it again", target.display()
+    ))]
+    ChecksumMismatch {
+        package: PackageName,
+        version: Version,
+        target: std::path::PathBuf,
+        expected: String,
+        actual: String,
+    },
     #[error(transparent)]
     #[diagnostic(transparent)]
     Download(#[from] DownloadError),
@@ -411,6 +639,10 @@ pub enum UnpackError {
         #[source]
         error: std::io::Error,
     },
+    #[error("Archive unpacks to more than {limit} bytes (see --max-fetch-size)")]
+    TooLarge { limit: u64 },
+    #[error("Archive contains more than {limit} files (see --max-fetch-files)")]
+    TooManyFiles { limit: u64 },
     #[error("Failed to finalize unpack to {}", target.display())]
     LockCreate {
         target: std::path::PathBuf,
@@ -436,6 +668,33 @@ pub enum FetchAuditError {
         #[source]
         error: url::ParseError,
     },
+    #[error("foreign import {import_name} @ {import_url} isn't from an allowed source")]
+    #[diagnostic(help(
+        "if you trust this source, add it to `allowed-import-sources` in config.toml"
+    ))]
+    UntrustedUrl {
+        import_name: ImportName,
+        import_url: String,
+    },
+    #[error("failed to fetch foreign import {import_name}")]
+    ImportDownload {
+        import_name: ImportName,
+        #[source]
+        error: DownloadError,
+    },
+    #[error("failed to fetch foreign import {import_name} from git")]
+    ImportGit {
+        import_name: ImportName,
+        #[source]
+        error: GitFetchError,
+    },
+    #[error("foreign import {import_name}'s audits.toml isn't signed by its public-key")]
+    #[diagnostic(help("if the import's key has changed, update its `public-key` in config.toml"))]
+    BadSignature {
+        import_name: ImportName,
+        #[source]
+        error: SignatureError,
+    },
     #[diagnostic(transparent)]
     #[error(transparent)]
     Download(#[from] DownloadError),
@@ -451,7 +710,7 @@ pub enum FetchAuditError {
 }
 
 #[derive(Debug, Error, Diagnostic)]
-#[error("Some of your imported audits changed their criteria descriptions")]
+#[error("Some of your imported audits changed their criteria definitions")]
 #[diagnostic()]
 pub struct CriteriaChangeErrors {
     #[related]
@@ -460,14 +719,101 @@ pub struct CriteriaChangeErrors {
 #[derive(Debug, Error, Diagnostic)]
 // FIXME: it would be rad if this was a diff!
 #[error(
-    "{import_name}'s '{criteria_name}' criteria changed from\n\n{old_desc}\n\nto\n\n{new_desc}\n"
+    "{import_name}'s '{criteria_name}' criteria changed its {what} from\n\n{old}\n\nto\n\n{new}\n"
 )]
 #[diagnostic(help("Run `cargo vet regenerate imports` to accept this new definition"))]
 pub struct CriteriaChangeError {
     pub import_name: ImportName,
     pub criteria_name: ForeignCriteriaName,
-    pub old_desc: String,
-    pub new_desc: String,
+    /// Which part of the definition changed, e.g. "description".
+    pub what: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+//////////////////////////////////////////////////////////
+// GitFetchError
+//////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum GitFetchError {
+    #[error("failed to run git")]
+    Command(
+        #[from]
+        #[source]
+        CommandError,
+    ),
+    #[error("`git {args}` failed:\n{stderr}")]
+    GitFailed { args: String, stderr: String },
+    #[error("couldn't create a git checkout in {}", target.display())]
+    CreateCheckout {
+        target: std::path::PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+}
+
+//////////////////////////////////////////////////////////
+// SignatureError
+//////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum SignatureError {
+    #[error("failed to run ssh-keygen")]
+    Command(
+        #[from]
+        #[source]
+        CommandError,
+    ),
+    #[error("ssh-keygen rejected the signature:\n{0}")]
+    SshRejected(String),
+    #[error("bad minisign signature")]
+    Minisign(#[source] minisign_verify::Error),
+    #[error("couldn't write a temporary file for ssh-keygen")]
+    TempFile(#[source] std::io::Error),
+}
+
+//////////////////////////////////////////////////////////
+// SignError
+//////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum SignError {
+    #[error("couldn't read the key at {}", path.display())]
+    ReadKey {
+        path: std::path::PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("couldn't read {}", path.display())]
+    ReadData {
+        path: std::path::PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("couldn't write the signature to {}", path.display())]
+    WriteSignature {
+        path: std::path::PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("failed to run the signing tool")]
+    #[diagnostic(help(
+        "ssh keys are signed with `ssh-keygen`, and minisign keys with `minisign`"
+    ))]
+    Command(
+        #[from]
+        #[source]
+        CommandError,
+    ),
+    #[error("{tool} failed to sign ({status})")]
+    Failed {
+        tool: &'static str,
+        status: std::process::ExitStatus,
+    },
 }
 
 //////////////////////////////////////////////////////////
@@ -507,6 +853,12 @@ pub enum DownloadError {
         #[source]
         error: std::io::Error,
     },
+    #[error("failed to read {url}")]
+    FailedToReadFile {
+        url: reqwest::Url,
+        #[source]
+        error: std::io::Error,
+    },
     #[error("Download wasn't valid utf8: {url}")]
     InvalidText {
         url: reqwest::Url,
@@ -515,6 +867,22 @@ pub enum DownloadError {
     },
 }
 
+impl DownloadError {
+    /// Whether the server said there's nothing at the url (a 404 or 410).
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            DownloadError::FailedToStartDownload { error, .. } => matches!(
+                error.status(),
+                Some(reqwest::StatusCode::NOT_FOUND | reqwest::StatusCode::GONE)
+            ),
+            DownloadError::FailedToReadFile { error, .. } => {
+                error.kind() == std::io::ErrorKind::NotFound
+            }
+            _ => false,
+        }
+    }
+}
+
 //////////////////////////////////////////////////////////
 // SuggestError
 //////////////////////////////////////////////////////////
@@ -525,6 +893,13 @@ pub enum SuggestError {
     #[error(transparent)]
     #[diagnostic(transparent)]
     CacheAcquire(#[from] CacheAcquireError),
+    #[error("couldn't suggest audits for {package}:{version}: {reason}")]
+    #[diagnostic(help("drop --fail-fast to suggest audits for the other packages anyway"))]
+    PackageFailed {
+        package: PackageName,
+        version: Version,
+        reason: String,
+    },
 }
 
 //////////////////////////////////////////////////////////
@@ -540,6 +915,12 @@ pub enum FlockError {
         #[source]
         std::io::Error,
     ),
+    #[error("the {what} is locked by another cargo-vet process (gave up after {}s)", timeout.as_secs())]
+    #[diagnostic(help("wait for the other process to finish, or pass a larger --lock-timeout"))]
+    Timeout {
+        what: String,
+        timeout: std::time::Duration,
+    },
 }
 
 //////////////////////////////////////////////////////////
@@ -547,16 +928,44 @@ pub enum FlockError {
 //////////////////////////////////////////////////////////
 
 #[derive(Debug, Error, Diagnostic)]
-#[error("Failed to parse toml file")]
+#[error("Failed to parse {location}")]
 pub struct TomlParseError {
     #[source_code]
     pub source_code: SourceFile,
     #[label("here")]
     pub span: SourceOffset,
+    /// The name of the file, followed by the line and column of the error if
+    /// toml knows them (e.g. `audits.toml:42:5`).
+    pub location: String,
     #[source]
     pub error: toml::de::Error,
 }
 
+impl TomlParseError {
+    pub fn new(file_name: &str, source: String, error: toml::de::Error) -> Self {
+        let (span, location) = match error.line_col() {
+            Some((line, col)) => {
+                let line_start = source
+                    .split_inclusive('\n')
+                    .take(line)
+                    .map(str::len)
+                    .sum::<usize>();
+                (
+                    SourceOffset::from(line_start + col),
+                    format!("{file_name}:{}:{}", line + 1, col + 1),
+                )
+            }
+            None => (SourceOffset::from(0), file_name.to_owned()),
+        };
+        TomlParseError {
+            source_code: Arc::new(NamedSource::new(file_name, source)),
+            span,
+            location,
+            error,
+        }
+    }
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[error("Failed to parse json file")]
 pub struct JsonParseError {
@@ -568,6 +977,18 @@ pub struct JsonParseError {
     pub error: serde_json::Error,
 }
 
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum LoadDiffCacheError {
+    #[error(transparent)]
+    #[diagnostic(transparent)]
+    Toml(#[from] LoadTomlError),
+
+    #[error("diff-cache is format version {found}, but this cargo-vet uses version {expected}")]
+    #[diagnostic(help("it needs to be regenerated with this version of cargo-vet"))]
+    VersionMismatch { found: u64, expected: u64 },
+}
+
 #[derive(Debug, Error, Diagnostic)]
 #[non_exhaustive]
 pub enum LoadTomlError {
@@ -629,3 +1050,54 @@ pub enum StoreTomlError {
         std::io::Error,
     ),
 }
+
+//////////////////////////////////////////////////////////
+// AdvisoryDbError
+//////////////////////////////////////////////////////////
+
+#[cfg(feature = "advisories")]
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum AdvisoryDbError {
+    #[error("couldn't read the advisory database at {}", path.display())]
+    #[diagnostic(help(
+        "--advisory-db should be a checkout of https://github.com/rustsec/advisory-db"
+    ))]
+    Read {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("{} doesn't start with a ```toml block", path.display())]
+    MissingFrontMatter { path: PathBuf },
+    #[error("couldn't parse the advisory at {}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        error: toml::de::Error,
+    },
+}
+
+//////////////////////////////////////////////////////////
+// CrevError
+//////////////////////////////////////////////////////////
+
+#[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
+pub enum CrevError {
+    #[error("couldn't read the crev proofs at {}", path.display())]
+    #[diagnostic(help("--proofs should be a crev proof repository, or crev's cache of them"))]
+    Read {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+    #[error("couldn't parse the crev proof in {}: {error}", path.display())]
+    Parse {
+        path: PathBuf,
+        #[source]
+        error: serde_yaml::Error,
+    },
+    #[error("the crev proof in {} isn't validly signed by {id}", path.display())]
+    InvalidSignature { path: PathBuf, id: String },
+}
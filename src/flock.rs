@@ -9,11 +9,15 @@ use std::fs::{self, File, OpenOptions};
 use std::io;
 use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Display, Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use sys::*;
 
 use crate::errors::FlockError;
 
+/// How often to retry a contended lock when waiting with a timeout.
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub struct FileLock {
     f: Option<File>,
@@ -158,6 +162,27 @@ impl Filesystem {
             OpenOptions::new().read(true).write(true).create(true),
             State::Exclusive,
             msg,
+            None,
+        )
+    }
+
+    /// Like `open_rw`, but gives up with [`FlockError::Timeout`] if the lock
+    /// can't be acquired within `timeout`.
+    pub fn open_rw_with_timeout<P>(
+        &self,
+        path: P,
+        msg: &str,
+        timeout: Duration,
+    ) -> Result<FileLock, FlockError>
+    where
+        P: AsRef<Path>,
+    {
+        self.open(
+            path.as_ref(),
+            OpenOptions::new().read(true).write(true).create(true),
+            State::Exclusive,
+            msg,
+            Some(timeout),
         )
     }
 
@@ -179,6 +204,7 @@ impl Filesystem {
             OpenOptions::new().read(true),
             State::Shared,
             msg,
+            None,
         )
     }
 
@@ -188,6 +214,7 @@ impl Filesystem {
         opts: &OpenOptions,
         state: State,
         msg: &str,
+        timeout: Option<Duration>,
     ) -> Result<FileLock, FlockError> {
         let path = self.root.join(path);
 
@@ -205,12 +232,14 @@ impl Filesystem {
         })?;
         match state {
             State::Exclusive => {
-                acquire(msg, &path, &|| try_lock_exclusive(&f), &|| {
+                acquire(msg, &path, timeout, &|| try_lock_exclusive(&f), &|| {
                     lock_exclusive(&f)
                 })?;
             }
             State::Shared => {
-                acquire(msg, &path, &|| try_lock_shared(&f), &|| lock_shared(&f))?;
+                acquire(msg, &path, timeout, &|| try_lock_shared(&f), &|| {
+                    lock_shared(&f)
+                })?;
             }
             State::Unlocked => {}
         }
@@ -242,11 +271,15 @@ impl PartialEq<Filesystem> for Path {
 /// status message based on `msg` to `config`'s shell, and then use `block` to
 /// block waiting to acquire a lock.
 ///
+/// If `timeout` is given, instead of blocking we keep retrying `try` until it
+/// elapses, and then give up with [`FlockError::Timeout`].
+///
 /// Returns an error if the lock could not be acquired or if any error other
 /// than a contention error happens.
 fn acquire(
     msg: &str,
     path: &Path,
+    timeout: Option<Duration>,
     lock_try: &dyn Fn() -> io::Result<()>,
     lock_block: &dyn Fn() -> io::Result<()>,
 ) -> Result<(), FlockError> {
@@ -280,6 +313,24 @@ fn acquire(
     }
     eprintln!("Blocking: waiting for file lock on {}", msg);
 
+    if let Some(timeout) = timeout {
+        let start = Instant::now();
+        loop {
+            std::thread::sleep(LOCK_RETRY_INTERVAL.min(timeout));
+            match lock_try() {
+                Ok(()) => return Ok(()),
+                Err(e) if !error_contended(&e) => Err(e)?,
+                Err(_) => {}
+            }
+            if start.elapsed() >= timeout {
+                return Err(FlockError::Timeout {
+                    what: msg.to_owned(),
+                    timeout,
+                });
+            }
+        }
+    }
+
     lock_block()?;
     return Ok(());
 
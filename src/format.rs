@@ -4,7 +4,7 @@ use crate::serialization::spanned::Spanned;
 use crate::{flock::Filesystem, serialization};
 use core::{cmp, fmt};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use cargo_metadata::Version;
@@ -22,6 +22,8 @@ pub type SortedSet<T> = BTreeSet<T>;
 pub type CriteriaName = String;
 pub type CriteriaStr<'a> = &'a str;
 pub type ForeignCriteriaName = String;
+pub type OwnerName = String;
+pub type FeatureSetName = String;
 pub type PackageName = String;
 pub type PackageStr<'a> = &'a str;
 pub type ImportName = String;
@@ -75,12 +77,54 @@ pub struct MetaConfigInstance {
     // for now assuming this species global semantics of some kind.
     pub version: Option<u64>,
     pub store: Option<StoreInfo>,
+    /// Where this instance came from (not part of the format, filled in after parsing)
+    #[serde(skip)]
+    pub source: MetaConfigSource,
 }
 #[derive(serde::Deserialize)]
 pub struct StoreInfo {
     pub path: Option<PathBuf>,
 }
 
+impl MetaConfigInstance {
+    /// Make a relative `store.path` relative to `root` (the directory of the
+    /// Cargo.toml it was declared in), rather than to whatever directory we
+    /// happened to be run from.
+    pub fn anchor_store_path(mut self, root: &Path) -> Self {
+        if let Some(path) = self.store.as_mut().and_then(|store| store.path.as_mut()) {
+            if path.is_relative() {
+                *path = root.join(&*path);
+            }
+        }
+        self
+    }
+}
+
+/// The place a [`MetaConfigInstance`] was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetaConfigSource {
+    /// Our builtin defaults
+    #[default]
+    Default,
+    /// `[workspace.metadata.vet]` in the workspace's Cargo.toml
+    Workspace,
+    /// `[package.metadata.vet]` in the root package's Cargo.toml
+    Package,
+    /// `--store-path` on the command line
+    Cli,
+}
+
+impl fmt::Display for MetaConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MetaConfigSource::Default => "defaults",
+            MetaConfigSource::Workspace => "[workspace.metadata.vet]",
+            MetaConfigSource::Package => "[package.metadata.vet]",
+            MetaConfigSource::Cli => "--store-path",
+        })
+    }
+}
+
 // FIXME: It's *possible* for someone to have a workspace but not have a
 // global `vet` instance for the whole workspace. In this case they *could*
 // have individual `vet` instances for each subcrate they care about.
@@ -93,21 +137,29 @@ pub struct MetaConfig(pub Vec<MetaConfigInstance>);
 
 impl MetaConfig {
     pub fn store_path(&self) -> Filesystem {
+        Filesystem::new(self.store_path_with_source().0.into())
+    }
+    pub fn version(&self) -> u64 {
+        self.version_with_source().0
+    }
+    /// The effective `store.path`, and the config which supplied it.
+    pub fn store_path_with_source(&self) -> (&Path, MetaConfigSource) {
         // Last config gets priority to set this
         for config in self.0.iter().rev() {
             if let Some(store) = &config.store {
                 if let Some(path) = &store.path {
-                    return Filesystem::new(path.into());
+                    return (path, config.source);
                 }
             }
         }
         unreachable!("Default config didn't define store.path???");
     }
-    pub fn version(&self) -> u64 {
+    /// The effective `version`, and the config which supplied it.
+    pub fn version_with_source(&self) -> (u64, MetaConfigSource) {
         // Last config gets priority to set this
         for config in self.0.iter().rev() {
             if let Some(ver) = config.version {
-                return ver;
+                return (ver, config.source);
             }
         }
         unreachable!("Default config didn't define version???");
@@ -162,6 +214,9 @@ pub struct AuditEntry {
     pub criteria: Vec<Spanned<CriteriaName>>,
     pub kind: AuditKind,
     pub notes: Option<String>,
+    /// A hash of the exact source that was reviewed (only for full audits),
+    /// as computed by [`crate::storage::hash_package_source`].
+    pub source_hash: Option<String>,
 }
 
 /// Implement PartialOrd manually because the order we want for sorting is
@@ -269,7 +324,53 @@ pub struct ConfigFile {
     #[serde(rename = "default-criteria")]
     #[serde(default = "get_default_criteria")]
     #[serde(skip_serializing_if = "is_default_criteria")]
-    pub default_criteria: CriteriaName,
+    pub default_criteria: Spanned<CriteriaName>,
+
+    /// Whether an audit's criteria may be validated individually, requiring only
+    /// that dependencies satisfy the criteria being checked. If false, a
+    /// dependency must satisfy *all* of the audit's criteria for the audit to be
+    /// usable for any of them (e.g. a safe-to-deploy audit can't be used to
+    /// validate safe-to-run if its dependencies are only safe-to-run).
+    /// Defaults to true.
+    #[serde(rename = "decompose-criteria")]
+    #[serde(default = "get_default_decompose_criteria")]
+    #[serde(skip_serializing_if = "is_default_decompose_criteria")]
+    pub decompose_criteria: bool,
+
+    /// Whether packages which are only reachable through dev-dependencies should
+    /// only need to be `safe-to-run`, no matter what their dependents require.
+    /// Packages with an explicit policy are still held to that policy.
+    /// Defaults to false.
+    #[serde(rename = "dev-safe-to-run")]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub dev_safe_to_run: bool,
+
+    /// Criteria that third-party proc-macro crates must always satisfy, on top
+    /// of whatever their dependents require of them, since they run arbitrary
+    /// code at build time no matter how they're used. Packages with an explicit
+    /// policy are still held to that policy instead.
+    #[serde(rename = "proc-macro-criteria")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(with = "serialization::string_or_vec")]
+    #[serde(default)]
+    pub proc_macro_criteria: Vec<Spanned<CriteriaName>>,
+
+    /// If non-empty, imports may only be fetched from URLs matching one of these
+    /// entries. An entry is either a bare host (`example.com`) or a URL prefix
+    /// (`https://example.com/audits/`).
+    #[serde(rename = "allowed-import-sources")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub allowed_import_sources: Vec<String>,
+
+    /// Registries other than crates.io whose packages are third-party, and so
+    /// need to be audited, given by the url of their index. Packages from any
+    /// other registry, like those from git or a path, are first-party.
+    #[serde(rename = "third-party-registries")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub third_party_registries: Vec<String>,
 
     /// Remote audits.toml's that we trust and want to import.
     #[serde(skip_serializing_if = "SortedMap::is_empty")]
@@ -277,10 +378,45 @@ pub struct ConfigFile {
     pub imports: SortedMap<ImportName, RemoteImport>,
 
     /// A table of policies for first-party crates.
+    ///
+    /// Keys may also be patterns (where `*` matches any sequence of characters),
+    /// which apply to every matching crate without a more specific entry.
+    /// Use [`policy_for`] to look up the policy for a crate.
     #[serde(skip_serializing_if = "SortedMap::is_empty")]
     #[serde(default)]
     pub policy: SortedMap<PackageName, PolicyEntry>,
 
+    /// Who is responsible for auditing which crates, keyed by a crate name pattern
+    /// (where `*` matches any sequence of characters). This is purely informational,
+    /// and is used to group and filter the output of `cargo vet suggest`.
+    #[serde(skip_serializing_if = "SortedMap::is_empty")]
+    #[serde(default)]
+    pub owners: SortedMap<String, OwnerName>,
+
+    /// Freeform notes about why a package is handled the way it is (e.g. why an
+    /// exemption is being kept around). These are shown alongside the package
+    /// whenever it comes up in `cargo vet check` or `cargo vet suggest`.
+    #[serde(skip_serializing_if = "SortedMap::is_empty")]
+    #[serde(default)]
+    pub notes: SortedMap<PackageName, String>,
+
+    /// Additional combinations of features to resolve the dependency graph with.
+    /// If any are specified, audits are required for the union of the graphs.
+    #[serde(rename = "feature-sets")]
+    #[serde(skip_serializing_if = "SortedMap::is_empty")]
+    #[serde(default)]
+    pub feature_sets: SortedMap<FeatureSetName, FeatureSet>,
+
+    /// Settings for `cargo vet diff`.
+    #[serde(skip_serializing_if = "DiffConfig::is_empty")]
+    #[serde(default)]
+    pub diff: DiffConfig,
+
+    /// Settings for fetching things over the network.
+    #[serde(skip_serializing_if = "NetworkConfig::is_empty")]
+    #[serde(default)]
+    pub network: NetworkConfig,
+
     /// All of the "foreign" dependencies that we rely on but haven't audited yet.
     /// Foreign dependencies are just "things on crates.io", everything else
     /// (paths, git, etc) is assumed to be "under your control" and therefore implicitly trusted.
@@ -294,11 +430,112 @@ pub static SAFE_TO_DEPLOY: CriteriaStr = "safe-to-deploy";
 pub static SAFE_TO_RUN: CriteriaStr = "safe-to-run";
 pub static DEFAULT_CRITERIA: CriteriaStr = SAFE_TO_DEPLOY;
 
-pub fn get_default_criteria() -> CriteriaName {
-    CriteriaName::from(DEFAULT_CRITERIA)
+pub fn get_default_criteria() -> Spanned<CriteriaName> {
+    Spanned::from(CriteriaName::from(DEFAULT_CRITERIA))
+}
+fn is_default_criteria(val: &Spanned<CriteriaName>) -> bool {
+    **val == DEFAULT_CRITERIA
+}
+pub fn get_default_decompose_criteria() -> bool {
+    true
+}
+fn is_default_decompose_criteria(val: &bool) -> bool {
+    *val
+}
+
+/// Check whether a package name matches a simple glob pattern, where `*` matches
+/// any (possibly empty) sequence of characters and everything else must match exactly.
+pub fn package_glob_matches(pattern: &str, name: PackageStr) -> bool {
+    let mut parts = pattern.split('*');
+    // There's always at least one part, which must be a prefix
+    let first = parts.next().unwrap();
+    let mut rest = match name.strip_prefix(first) {
+        Some(rest) => rest,
+        None => return false,
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    // If there was no `*` at all, we need an exact match
+    let last = match parts.pop() {
+        Some(last) => last,
+        None => return rest.is_empty(),
+    };
+    // Greedily match the middle parts in order, the last part must be a suffix
+    for part in parts {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+/// Look up the policy which applies to a package.
+///
+/// An exact match always wins. Otherwise the longest (most specific) pattern
+/// which matches the package is used.
+pub fn policy_for<'a>(
+    policy: &'a SortedMap<PackageName, PolicyEntry>,
+    name: PackageStr,
+) -> Option<&'a PolicyEntry> {
+    policy.get(name).or_else(|| {
+        policy
+            .iter()
+            .filter(|(pattern, _)| pattern.contains('*') && package_glob_matches(pattern, name))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, entry)| entry)
+    })
+}
+
+/// A combination of features to pass to `cargo metadata`, to catch dependencies
+/// which are only enabled by certain (possibly mutually exclusive) features.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct FeatureSet {
+    /// The features to enable.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub features: Vec<String>,
+    /// Whether to disable the default features.
+    #[serde(rename = "no-default-features")]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    #[serde(default)]
+    pub no_default_features: bool,
+}
+
+/// The `[diff]` table of config.toml.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct DiffConfig {
+    /// A command to display diffs with instead of `git diff`, which is passed
+    /// the two directories being compared (see `cargo vet diff --tool`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub tool: Option<String>,
+}
+
+impl DiffConfig {
+    fn is_empty(&self) -> bool {
+        self.tool.is_none()
+    }
+}
+
+/// The `[network]` table of config.toml.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+pub struct NetworkConfig {
+    /// The proxy to send all requests through. This overrides the usual
+    /// `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` environment variables.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// How long to wait (in seconds) for a network request before giving up.
+    /// `--network-timeout` takes precedence over this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub timeout: Option<u64>,
 }
-fn is_default_criteria(val: &CriteriaName) -> bool {
-    val == DEFAULT_CRITERIA
+
+impl NetworkConfig {
+    fn is_empty(&self) -> bool {
+        self.proxy.is_none() && self.timeout.is_none()
+    }
 }
 
 /// Policies that first-party (non-foreign) crates must pass.
@@ -365,6 +602,28 @@ pub struct PolicyEntry {
     #[serde(default)]
     pub dependency_criteria: DependencyCriteria,
 
+    /// Same as `dependency-criteria`, but for this crate's dev-dependencies.
+    ///
+    /// This lets a package which is both a normal and a dev-dependency have
+    /// different requirements in each role. Any dev-dependency edge that isn't
+    /// explicitly specified defaults to `dependency-criteria`.
+    #[serde(rename = "dev-dependency-criteria")]
+    #[serde(skip_serializing_if = "DependencyCriteria::is_empty")]
+    #[serde(with = "serialization::dependency_criteria")]
+    #[serde(default)]
+    pub dev_dependency_criteria: DependencyCriteria,
+
+    /// Same as `dependency-criteria`, but for this crate's build-dependencies.
+    ///
+    /// Any build-dependency edge that isn't explicitly specified defaults to
+    /// `dependency-criteria`. A package which is both a normal and a
+    /// build-dependency must satisfy the criteria for both edges.
+    #[serde(rename = "build-dependency-criteria")]
+    #[serde(skip_serializing_if = "DependencyCriteria::is_empty")]
+    #[serde(with = "serialization::dependency_criteria")]
+    #[serde(default)]
+    pub build_dependency_criteria: DependencyCriteria,
+
     /// Freeform notes
     pub notes: Option<String>,
 }
@@ -373,20 +632,74 @@ pub static DEFAULT_POLICY_CRITERIA: CriteriaStr = SAFE_TO_DEPLOY;
 pub static DEFAULT_POLICY_DEV_CRITERIA: CriteriaStr = SAFE_TO_RUN;
 
 /// A remote audits.toml that we trust the contents of (by virtue of trusting the maintainer).
-#[derive(serde::Serialize, serde::Deserialize, Clone)]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Default)]
 pub struct RemoteImport {
     /// URL of the foreign audits.toml
+    ///
+    /// Empty if the audits are imported from `git` instead.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub url: String,
+    /// URL of a git repository to import the foreign audits.toml from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The commit (or tag, or anything else `git rev-parse` understands) of
+    /// `git` to import from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// The branch of `git` to import from, instead of its default branch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// The path of the audits.toml within `git`. Defaults to
+    /// `supply-chain/audits.toml`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
     /// A list of criteria that are implied by foreign criteria
     #[serde(rename = "criteria-map")]
     pub criteria_map: Vec<CriteriaMapping>,
+    /// How many levels of the foreign store's own imports to follow. The default
+    /// of 0 only imports the audits.toml at `url`.
+    #[serde(default, skip_serializing_if = "is_default_import_depth")]
+    pub depth: u64,
+    /// If non-empty, imports followed because of `depth` must have a url allowed
+    /// by this list (in the same format as `allowed-import-sources`).
+    #[serde(
+        rename = "transitive-sources",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub transitive_sources: Vec<String>,
+    /// If set, the foreign audits.toml must be signed by this ssh or minisign
+    /// public key, with the signature in a file next to it (see
+    /// [`crate::signing`]).
+    #[serde(
+        rename = "public-key",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub public_key: Option<String>,
+}
+
+pub static DEFAULT_IMPORT_DEPTH: u64 = 0;
+
+pub static DEFAULT_GIT_IMPORT_PATH: &str = "supply-chain/audits.toml";
+
+impl RemoteImport {
+    /// Where the audits are imported from, for display and for checking
+    /// against `allowed-import-sources`.
+    pub fn source_url(&self) -> &str {
+        self.git.as_deref().unwrap_or(&self.url)
+    }
+}
+
+fn is_default_import_depth(val: &u64) -> bool {
+    val == &DEFAULT_IMPORT_DEPTH
 }
 
 /// Translations of foreign criteria to local criteria.
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct CriteriaMapping {
     /// This local criteria is implied...
-    pub ours: CriteriaName,
+    pub ours: Spanned<CriteriaName>,
     /// If all of these foreign criteria apply
     #[serde(with = "serialization::string_or_vec")]
     pub theirs: Vec<Spanned<ForeignCriteriaName>>,
@@ -442,6 +755,34 @@ fn is_default_exemptions_suggest(val: &bool) -> bool {
 #[derive(serde::Serialize, serde::Deserialize, Clone)]
 pub struct ImportsFile {
     pub audits: SortedMap<ImportName, AuditsFile>,
+    /// For imports with a non-zero `depth`, the foreign stores whose audits were
+    /// merged into that import, and the chain of imports that led to them.
+    #[serde(
+        rename = "transitive-imports",
+        default,
+        skip_serializing_if = "SortedMap::is_empty"
+    )]
+    pub transitive_imports: SortedMap<ImportName, Vec<TransitiveImport>>,
+    /// For imports from `git`, the commit their audits were read from.
+    #[serde(
+        rename = "git-commits",
+        default,
+        skip_serializing_if = "SortedMap::is_empty"
+    )]
+    pub git_commits: SortedMap<ImportName, String>,
+    /// The url (or git repository) each import was fetched from.
+    #[serde(default, skip_serializing_if = "SortedMap::is_empty")]
+    pub sources: SortedMap<ImportName, String>,
+}
+
+/// A foreign store which was reached by following another store's imports.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransitiveImport {
+    /// The names of the imports followed to get here, starting with the name
+    /// the directly imported store gives its own import.
+    pub path: Vec<ImportName>,
+    /// URL of the foreign audits.toml
+    pub url: String,
 }
 
 ////////////////////////////////////////////////////////////////////////////////////
@@ -456,6 +797,22 @@ pub struct ImportsFile {
 
 pub type DiffCache = SortedMap<PackageName, SortedMap<Delta, DiffStat>>;
 
+/// The current version of the diff-cache format. Bump this whenever the
+/// structure of the diff-cache, or the way its diffstats are computed, changes
+/// so that old caches get discarded rather than misread.
+///
+/// Caches written before the format was versioned are version 0.
+pub const DIFF_CACHE_VERSION: u64 = 1;
+
+/// The on-disk form of a [`DiffCache`].
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DiffCacheFile {
+    #[serde(default)]
+    pub version: u64,
+    #[serde(default)]
+    pub diffs: DiffCache,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct DiffStat {
     pub raw: String,
@@ -477,6 +834,10 @@ pub enum FetchCommand {
     Inspect {
         package: PackageName,
         version: Version,
+        /// The hash of the fetched source, if it was fetched
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_hash: Option<String>,
     },
     Diff {
         package: PackageName,
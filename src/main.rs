@@ -1,19 +1,25 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::ops::Deref;
 use std::panic::panic_any;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use std::{fs::File, io, mem, panic, path::PathBuf};
+use std::{
+    fs::File,
+    io, mem, panic,
+    path::{Path, PathBuf},
+};
 
-use cargo_metadata::{Metadata, Package, Version};
+use cargo_metadata::{Metadata, Package, PackageId, Version};
 use clap::{CommandFactory, Parser};
 use console::Term;
 use errors::{
-    AuditAsError, AuditAsErrors, CacheAcquireError, CertifyError, MinimizeUnauditedError,
-    NeedsAuditAsError, NeedsAuditAsErrors, ShouldntBeAuditAsError, ShouldntBeAuditAsErrors,
-    UserInfoError,
+    AuditAsError, AuditAsErrors, CacheAcquireError, CertifyError, LockfileError,
+    MinimizeUnauditedError, NeedsAuditAsError, NeedsAuditAsErrors, PruneError,
+    SharesCratesIoVersionError, SharesCratesIoVersionErrors, ShouldntBeAuditAsError,
+    ShouldntBeAuditAsErrors, UnusedExemptionError, UnusedExemptionsErrors, UserInfoError,
 };
-use format::{CriteriaName, CriteriaStr, PackageName, PolicyEntry};
+use format::{CriteriaName, CriteriaStr, FeatureSetName, PackageName};
 use futures_util::future::join_all;
 use lazy_static::lazy_static;
 use miette::{miette, Context, Diagnostic, IntoDiagnostic};
@@ -24,17 +30,24 @@ use thiserror::Error;
 use tracing::{error, info, trace, warn};
 
 use crate::cli::*;
-use crate::errors::{CommandError, DownloadError};
+use crate::errors::{CommandError, DownloadError, InvalidImportUrlError, SuggestError};
 use crate::format::{
-    AuditEntry, AuditKind, AuditsFile, ConfigFile, CriteriaEntry, Delta, DependencyCriteria,
-    ExemptedDependency, FetchCommand, ImportsFile, MetaConfig, MetaConfigInstance, PackageStr,
-    SortedMap, StoreInfo,
+    AuditEntry, AuditKind, AuditsFile, ConfigFile, CriteriaEntry, CriteriaMapping, Delta,
+    DependencyCriteria, ExemptedDependency, FastSet, FetchCommand, ForeignCriteriaName,
+    ImportsFile, MetaConfig, MetaConfigInstance, MetaConfigSource, PackageStr, RemoteImport,
+    SortedMap, SortedSet, StoreInfo,
+};
+use crate::out::{progress_bar, Out};
+use crate::resolver::{
+    Conclusion, CriteriaMapper, CriteriaSet, DepGraph, ResolveDepth, SuggestItem, ROOT_VERSION,
 };
-use crate::out::Out;
-use crate::resolver::{Conclusion, CriteriaMapper, DepGraph, ResolveDepth, SuggestItem};
-use crate::storage::{Cache, Store};
+use crate::serialization::spanned::Spanned;
+use crate::storage::{Cache, Store, CONFIG_TOML};
 
+#[cfg(feature = "advisories")]
+pub mod advisories;
 mod cli;
+pub mod crev;
 mod editor;
 pub mod errors;
 mod flock;
@@ -43,6 +56,7 @@ pub mod network;
 mod out;
 pub mod resolver;
 mod serialization;
+pub mod signing;
 pub mod storage;
 #[cfg(test)]
 mod tests;
@@ -53,6 +67,9 @@ pub struct Config {
     pub metacfg: MetaConfig,
     /// `cargo metadata`
     pub metadata: Metadata,
+    /// Packages which are only in `metadata` because of the `feature-sets` in
+    /// config.toml, and the feature sets which pulled them in.
+    pub feature_set_only_packages: SortedMap<PackageId, Vec<FeatureSetName>>,
     /// Freestanding configuration values
     _rest: PartialConfig,
 }
@@ -68,6 +85,13 @@ pub struct PartialConfig {
     pub mock_cache: bool,
 }
 
+impl PartialConfig {
+    /// How long to wait on another process holding the store's lock
+    pub fn lock_timeout(&self) -> Duration {
+        Duration::from_secs(self.cli.lock_timeout)
+    }
+}
+
 // Makes it a bit easier to have both a "partial" and "full" config
 impl Deref for Config {
     type Target = PartialConfig;
@@ -77,27 +101,83 @@ impl Deref for Config {
 }
 
 pub trait PackageExt {
-    fn is_third_party(&self, policy: &SortedMap<PackageName, PolicyEntry>) -> bool;
+    fn is_third_party(&self, config: &ConfigFile) -> bool;
+    fn is_from_third_party_registry(&self, config: &ConfigFile) -> bool;
+    fn is_crates_io(&self) -> bool;
+    fn is_from_other_registry(&self) -> bool;
 }
 
 impl PackageExt for Package {
-    fn is_third_party(&self, policy: &SortedMap<PackageName, PolicyEntry>) -> bool {
-        let forced_third_party = policy
-            .get(&self.name)
+    /// Whether this package needs to be audited, with the audit-as-crates-io
+    /// policy applied.
+    fn is_third_party(&self, config: &ConfigFile) -> bool {
+        let forced_third_party = format::policy_for(&config.policy, &self.name)
             .and_then(|policy| policy.audit_as_crates_io)
             .unwrap_or(false);
-        let is_crates_io = self
-            .source
+
+        forced_third_party || self.is_from_third_party_registry(config)
+    }
+
+    /// Whether this package is from crates.io, or one of the
+    /// `third-party-registries`.
+    fn is_from_third_party_registry(&self, config: &ConfigFile) -> bool {
+        if self.is_crates_io() {
+            return true;
+        }
+        let source = match &self.source {
+            Some(source) if is_registry_source(&source.repr) => &source.repr,
+            // Packages from git or a path are always first-party
+            _ => return false,
+        };
+        config
+            .third_party_registries
+            .iter()
+            .any(|registry| registry_index(registry) == registry_index(source))
+    }
+
+    fn is_crates_io(&self) -> bool {
+        self.source
             .as_ref()
-            .map(|s| s.is_crates_io())
-            .unwrap_or(false);
+            .map(|s| s.is_crates_io() || registry_index(&s.repr) == CRATES_IO_SPARSE_INDEX)
+            .unwrap_or(false)
+    }
 
-        forced_third_party || is_crates_io
+    /// Whether this package is from a registry other than crates.io, which we
+    /// can't fetch its source from.
+    fn is_from_other_registry(&self) -> bool {
+        !self.is_crates_io()
+            && self
+                .source
+                .as_ref()
+                .map(|source| is_registry_source(&source.repr))
+                .unwrap_or(false)
     }
 }
 
+/// The index of crates.io, when cargo accesses it with the sparse protocol.
+const CRATES_IO_SPARSE_INDEX: &str = "https://index.crates.io";
+
+fn is_registry_source(source: &str) -> bool {
+    source.starts_with("registry+") || source.starts_with("sparse+")
+}
+
+/// The url of a registry's index, with any `registry+` or `sparse+` prefix and
+/// trailing slash removed so that different spellings of it can be compared.
+fn registry_index(source: &str) -> &str {
+    source
+        .strip_prefix("registry+")
+        .or_else(|| source.strip_prefix("sparse+"))
+        .unwrap_or(source)
+        .trim_end_matches('/')
+}
+
 const CACHE_DIR_SUFFIX: &str = "cargo-vet";
 const CARGO_ENV: &str = "CARGO";
+const REGISTRY_SRC_ENV: &str = "CARGO_VET_REGISTRY_SRC";
+/// The `who` recorded for audits when we can't work out who the user is
+const WHO_PLACEHOLDER: &str = "?TODO?";
+const CARGO_TOML: &str = "Cargo.toml";
+const CARGO_LOCK: &str = "Cargo.lock";
 // package.metadata.vet
 const PACKAGE_VET_CONFIG: &str = "vet";
 // workspace.metadata.vet
@@ -144,6 +224,34 @@ fn report_error(error: &miette::Report) {
     error!("{:?}", error);
 }
 
+/// Open a file for --output-file or --log-file, either replacing it or
+/// appending to it.
+fn open_output_file(path: &Path, append: bool) -> io::Result<File> {
+    if append {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+    } else {
+        File::create(path)
+    }
+}
+
+/// If the log file at `path` has grown to at least `max_size` bytes, move it
+/// aside to `<path>.1` so that a fresh log is started.
+fn rotate_log_file(path: &Path, max_size: u64) -> io::Result<()> {
+    match std::fs::metadata(path) {
+        Ok(metadata) if metadata.len() >= max_size => {
+            let mut rotated = path.as_os_str().to_owned();
+            rotated.push(".1");
+            std::fs::rename(path, rotated)
+        }
+        Ok(_) => Ok(()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
 fn main() -> Result<(), ()> {
     // NOTE: Limit the maximum number of blocking threads to 128, rather than
     // the default of 512.
@@ -190,7 +298,7 @@ fn real_main() -> Result<(), miette::Report> {
     use cli::Commands::*;
 
     let fake_cli = cli::FakeCli::parse();
-    let cli::FakeCli::Vet(cli) = fake_cli;
+    let cli::FakeCli::Vet(mut cli) = fake_cli;
 
     //////////////////////////////////////////////////////
     // Setup logging / output
@@ -198,7 +306,10 @@ fn real_main() -> Result<(), miette::Report> {
 
     // Init the logger (and make trace logging less noisy)
     if let Some(log_path) = &cli.log_file {
-        let log_file = File::create(log_path).unwrap();
+        if let Some(max_size) = cli.log_file_max_size {
+            rotate_log_file(log_path, max_size).unwrap();
+        }
+        let log_file = open_output_file(log_path, cli.log_file_append).unwrap();
         tracing_subscriber::fmt::fmt()
             .with_max_level(cli.verbose)
             .with_target(false)
@@ -271,7 +382,7 @@ fn real_main() -> Result<(), miette::Report> {
 
     // Setup our output stream
     let out: Arc<dyn Out> = if let Some(output_path) = &cli.output_file {
-        Arc::new(File::create(output_path).unwrap())
+        Arc::new(open_output_file(output_path, cli.output_file_append).unwrap())
     } else {
         Arc::new(Term::stdout())
     };
@@ -282,6 +393,36 @@ fn real_main() -> Result<(), miette::Report> {
         set_report_errors_as_json(out.clone());
     }
 
+    if cli.registry_src.is_none() {
+        cli.registry_src = std::env::var_os(REGISTRY_SRC_ENV).map(PathBuf::from);
+    }
+
+    if cli.exclude_dev {
+        // These would treat the exemptions of every dev-only package as unused
+        if matches!(
+            cli.command,
+            Some(Commands::Prune(_))
+                | Some(Commands::Regenerate(RegenerateSubcommands::Exemptions(_)))
+        ) {
+            // ERRORS: immediate fatal diagnostic
+            return Err(miette!(
+                "--exclude-dev can't be used with prune or regenerate exemptions, \
+                 as it would remove the exemptions for dev-only packages"
+            ));
+        }
+        cli.filter_graph
+            .get_or_insert_with(Vec::new)
+            .push(GraphFilter::exclude_dev_only());
+    }
+
+    // Let --manifest-path name the directory containing the Cargo.toml, and
+    // check it up front so that a typo doesn't become a confusing cargo error.
+    cli.manifest_path = cli
+        .manifest_path
+        .take()
+        .map(resolve_manifest_path)
+        .transpose()?;
+
     ////////////////////////////////////////////////////
     // Potentially handle freestanding commands
     ////////////////////////////////////////////////////
@@ -299,6 +440,18 @@ fn real_main() -> Result<(), miette::Report> {
     match &partial_cfg.cli.command {
         Some(HelpMarkdown(sub_args)) => return cmd_help_md(&out, &partial_cfg, sub_args),
         Some(Gc(sub_args)) => return cmd_gc(&out, &partial_cfg, sub_args),
+        Some(ExplainDiffCache(sub_args)) => {
+            return cmd_explain_diff_cache(&out, &partial_cfg, sub_args)
+        }
+        Some(ExportDiffCache(sub_args)) => {
+            return cmd_export_diff_cache(&out, &partial_cfg, sub_args)
+        }
+        Some(ImportDiffCache(sub_args)) => {
+            return cmd_import_diff_cache(&out, &partial_cfg, sub_args)
+        }
+        Some(CheckSelf(sub_args)) => return cmd_check_self(&out, &partial_cfg, sub_args),
+        Some(Sign(sub_args)) => return cmd_sign(&out, &partial_cfg, sub_args),
+        Some(Doctor(sub_args)) => return cmd_doctor(&out, &partial_cfg, sub_args),
         _ => {
             // Not a freestanding command, time to do full parsing and setup
         }
@@ -309,13 +462,8 @@ fn real_main() -> Result<(), miette::Report> {
     ///////////////////////////////////////////////////
 
     let cli = &partial_cfg.cli;
-    let cargo_path = std::env::var_os(CARGO_ENV).expect("Cargo failed to set $CARGO, how?");
 
-    let mut cmd = cargo_metadata::MetadataCommand::new();
-    cmd.cargo_path(cargo_path);
-    if let Some(manifest_path) = &cli.manifest_path {
-        cmd.manifest_path(manifest_path);
-    }
+    let mut cmd = cargo_metadata_command(cli);
     if !cli.no_all_features {
         cmd.features(cargo_metadata::CargoOpt::AllFeatures);
     }
@@ -325,23 +473,32 @@ fn real_main() -> Result<(), miette::Report> {
     if !cli.features.is_empty() {
         cmd.features(cargo_metadata::CargoOpt::SomeFeatures(cli.features.clone()));
     }
-    // We never want cargo-vet to update the Cargo.lock.
-    // For frozen runs we also don't want to touch the network.
-    let mut other_options = Vec::new();
-    if cli.frozen {
-        other_options.push("--frozen".to_string());
-    } else {
-        other_options.push("--locked".to_string());
-    }
-    cmd.other_options(other_options);
 
     info!("Running: {:#?}", cmd.cargo_command());
 
+    // `inspect` and `diff` are also useful as general-purpose tools outside of
+    // a vetted project, so if there's no project (or no store) just run them
+    // on their own.
+    let run_standalone = || match &partial_cfg.cli.command {
+        Some(Inspect(sub_args)) => Some(cmd_inspect(&out, &partial_cfg, None, sub_args)),
+        Some(Diff(sub_args)) => Some(cmd_diff(&out, &partial_cfg, None, sub_args)),
+        _ => None,
+    };
+
     // ERRORS: immediate fatal diagnostic
-    let metadata = cmd
-        .exec()
-        .into_diagnostic()
-        .wrap_err("'cargo metadata' exited unsuccessfully")?;
+    let mut metadata = match exec_cargo_metadata(cli, &cmd) {
+        Ok(metadata) => metadata,
+        // Errors in a project's manifests are still errors, it's only the
+        // lack of a project which makes standalone runs reasonable.
+        Err(err) if !has_manifest(cli) => {
+            if let Some(result) = run_standalone() {
+                info!("no Cargo.toml found, running standalone: {err:?}");
+                return result;
+            }
+            return Err(err);
+        }
+        Err(err) => return Err(err),
+    };
 
     // trace!("Got Metadata! {:#?}", metadata);
     trace!("Got Metadata!");
@@ -350,7 +507,136 @@ fn real_main() -> Result<(), miette::Report> {
     // Parse out our own configuration
     //////////////////////////////////////////////////////
 
+    let metacfg = compute_metacfg(&metadata, cli.store_path.as_deref())?;
+
+    info!("Final Metadata Config: ");
+    let (version, version_source) = metacfg.version_with_source();
+    info!("  - version: {} (from {})", version, version_source);
+    let (store_path, store_path_source) = metacfg.store_path_with_source();
+    info!(
+        "  - store.path: {:#?} (from {})",
+        store_path, store_path_source
+    );
+
+    if let Some(PrintConfig(sub_args)) = &cli.command {
+        return cmd_print_config(&out, &partial_cfg, &metacfg, sub_args);
+    }
+
+    //////////////////////////////////////////////////////
+    // Run the actual command
+    //////////////////////////////////////////////////////
+
+    let init = Store::is_init(&metacfg);
+    if matches!(cli.command, Some(Commands::Init { .. })) {
+        if init {
+            // ERRORS: immediate fatal diagnostic
+            return Err(miette!(
+                "'cargo vet' already initialized (store found at {})",
+                metacfg.store_path().display()
+            ));
+        }
+    } else if !init {
+        if let Some(result) = run_standalone() {
+            return result;
+        }
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!(
+            "You must run 'cargo vet init' (store not found at {})",
+            metacfg.store_path().display()
+        ));
+    }
+
+    // If the config asks for extra feature sets, vet the union of all of their graphs
+    let mut feature_set_only_packages = SortedMap::new();
+    if init && !matches!(cli.command, Some(Commands::Init { .. })) {
+        let feature_sets = Store::peek_config(&metacfg)?.feature_sets;
+        for (name, feature_set) in &feature_sets {
+            // Feature sets add to the features passed on the command line,
+            // rather than replacing them.
+            let mut cmd = cargo_metadata_command(cli);
+            if feature_set.no_default_features || cli.no_default_features {
+                cmd.features(cargo_metadata::CargoOpt::NoDefaultFeatures);
+            }
+            let features = cli
+                .features
+                .iter()
+                .chain(&feature_set.features)
+                .cloned()
+                .collect::<Vec<_>>();
+            if !features.is_empty() {
+                cmd.features(cargo_metadata::CargoOpt::SomeFeatures(features));
+            }
+
+            info!("Running for feature set {name}: {:#?}", cmd.cargo_command());
+
+            // ERRORS: immediate fatal diagnostic
+            let feature_set_metadata = exec_cargo_metadata(cli, &cmd)
+                .wrap_err_with(|| format!("couldn't get metadata for feature set {name}"))?;
+            for package_id in merge_metadata(&mut metadata, feature_set_metadata) {
+                feature_set_only_packages
+                    .entry(package_id)
+                    .or_insert_with(Vec::new)
+                    .push(name.clone());
+            }
+        }
+    }
+
+    let cfg = Config {
+        metacfg,
+        metadata,
+        feature_set_only_packages,
+        _rest: partial_cfg,
+    };
+
+    use RegenerateSubcommands::*;
+    match &cfg.cli.command {
+        None => cmd_check(&out, &cfg, &cfg.cli.check_args),
+        Some(Check(sub_args)) => cmd_check(&out, &cfg, sub_args),
+        Some(Init(sub_args)) => cmd_init(&out, &cfg, sub_args),
+        Some(Certify(sub_args)) => cmd_certify(&out, &cfg, sub_args),
+        Some(AddExemption(sub_args)) => cmd_add_exemption(&out, &cfg, sub_args),
+        Some(RecordViolation(sub_args)) => cmd_record_violation(&out, &cfg, sub_args),
+        Some(ImportCrev(sub_args)) => cmd_import_crev(&out, &cfg, sub_args),
+        Some(Suggest(sub_args)) => cmd_suggest(&out, &cfg, sub_args),
+        Some(Fmt(sub_args)) => cmd_fmt(&out, &cfg, sub_args),
+        Some(Fetch(sub_args)) => cmd_fetch(&out, &cfg, sub_args),
+        Some(FetchImports(sub_args)) => cmd_fetch_imports(&out, &cfg, sub_args),
+        Some(VerifyImportsLock(sub_args)) => cmd_verify_imports_lock(&out, &cfg, sub_args),
+        Some(Verify(sub_args)) => cmd_verify(&out, &cfg, sub_args),
+        Some(AuditsOf(sub_args)) => cmd_audits_of(&out, &cfg, sub_args),
+        Some(ExportBacklog(sub_args)) => cmd_export_backlog(&out, &cfg, sub_args),
+        Some(Coverage(sub_args)) => cmd_coverage(&out, &cfg, sub_args),
+        Some(DumpGraph(sub_args)) => cmd_dump_graph(&out, &cfg, sub_args),
+        Some(Inspect(sub_args)) => cmd_inspect(&out, &cfg, Some(&cfg), sub_args),
+        Some(Diff(sub_args)) => cmd_diff(&out, &cfg, Some(&cfg), sub_args),
+        Some(Regenerate(Imports(sub_args))) => cmd_regenerate_imports(&out, &cfg, sub_args),
+        Some(Regenerate(Exemptions(sub_args))) => cmd_regenerate_exemptions(&out, &cfg, sub_args),
+        Some(Regenerate(AuditAsCratesIo(sub_args))) => {
+            cmd_regenerate_audit_as(&out, &cfg, sub_args)
+        }
+        Some(Prune(sub_args)) => cmd_prune(&out, &cfg, sub_args),
+        Some(HelpMarkdown(_))
+        | Some(Gc(_))
+        | Some(ExplainDiffCache(_))
+        | Some(ExportDiffCache(_))
+        | Some(ImportDiffCache(_))
+        | Some(CheckSelf(_))
+        | Some(Sign(_))
+        | Some(PrintConfig(_))
+        | Some(Doctor(_)) => {
+            unreachable!("handled earlier")
+        }
+    }
+}
+
+/// Layer our defaults with `[workspace.metadata.vet]`, `[package.metadata.vet]`,
+/// and `--store-path` to get the effective configuration for this project.
+fn compute_metacfg(
+    metadata: &Metadata,
+    store_path: Option<&Path>,
+) -> Result<MetaConfig, miette::Report> {
     let default_config = MetaConfigInstance {
+        source: MetaConfigSource::Default,
         version: Some(1),
         store: Some(StoreInfo {
             path: Some(
@@ -362,33 +648,65 @@ fn real_main() -> Result<(), miette::Report> {
         }),
     };
 
-    // FIXME: what is `store.path` relative to here?
+    // A relative `store.path` is relative to the directory of the Cargo.toml
+    // which declares it, so that we find the same store no matter where we're run.
+    let workspace_root = metadata.workspace_root.as_std_path();
     let workspace_metacfg = metadata
         .workspace_metadata
         .get(WORKSPACE_VET_CONFIG)
         .map(|cfg| {
             // ERRORS: immediate fatal diagnostic
             MetaConfigInstance::deserialize(cfg)
+                .map(|cfg| {
+                    MetaConfigInstance {
+                        source: MetaConfigSource::Workspace,
+                        ..cfg
+                    }
+                    .anchor_store_path(workspace_root)
+                })
                 .into_diagnostic()
                 .wrap_err("Workspace had [{WORKSPACE_VET_CONFIG}] but it was malformed")
         })
         .transpose()?;
 
-    // FIXME: what is `store.path` relative to here?
     let package_metacfg = metadata
         .root_package()
-        .and_then(|r| r.metadata.get(PACKAGE_VET_CONFIG))
-        .map(|cfg| {
+        .and_then(|r| Some((r, r.metadata.get(PACKAGE_VET_CONFIG)?)))
+        .map(|(root_package, cfg)| {
+            let package_dir = root_package
+                .manifest_path
+                .parent()
+                .map_or(workspace_root, |dir| dir.as_std_path());
             // ERRORS: immediate fatal diagnostic
             MetaConfigInstance::deserialize(cfg)
+                .map(|cfg| {
+                    MetaConfigInstance {
+                        source: MetaConfigSource::Package,
+                        ..cfg
+                    }
+                    .anchor_store_path(package_dir)
+                })
                 .into_diagnostic()
                 .wrap_err("Root package had [{PACKAGE_VET_CONFIG}] but it was malformed")
         })
         .transpose()?;
 
-    if workspace_metacfg.is_some() && package_metacfg.is_some() {
-        // ERRORS: immediate fatal diagnostic
-        return Err(miette!("Both a workspace and a package defined [metadata.vet]! We don't know what that means, if you do, let us know!"));
+    // The package's config is layered over the workspace's, so it wins for any
+    // keys they both set (e.g. a member which wants its own store). They have to
+    // agree on the version though, as that changes what the rest means.
+    if let (Some(workspace), Some(package)) = (&workspace_metacfg, &package_metacfg) {
+        if let (Some(workspace_version), Some(package_version)) =
+            (workspace.version, package.version)
+        {
+            if workspace_version != package_version {
+                // ERRORS: immediate fatal diagnostic
+                return Err(miette!(
+                    "{} has version = {workspace_version}, but {} has version = {package_version}",
+                    workspace.source,
+                    package.source,
+                ));
+            }
+        }
     }
 
     let mut metacfgs = vec![default_config];
@@ -398,80 +716,256 @@ fn real_main() -> Result<(), miette::Report> {
     if let Some(metacfg) = package_metacfg {
         metacfgs.push(metacfg);
     }
-    let metacfg = MetaConfig(metacfgs);
-
-    info!("Final Metadata Config: ");
-    info!("  - version: {}", metacfg.version());
-    info!("  - store.path: {:#?}", metacfg.store_path());
+    if let Some(path) = store_path {
+        metacfgs.push(MetaConfigInstance {
+            source: MetaConfigSource::Cli,
+            version: None,
+            store: Some(StoreInfo {
+                path: Some(path.to_owned()),
+            }),
+        });
+    }
+    Ok(MetaConfig(metacfgs))
+}
 
-    //////////////////////////////////////////////////////
-    // Run the actual command
-    //////////////////////////////////////////////////////
+/// Whether there's a Cargo.toml for `cargo metadata` to read, either given by
+/// `--manifest-path` or in the current directory or one of its parents.
+fn has_manifest(cli: &Cli) -> bool {
+    if cli.manifest_path.is_some() {
+        return true;
+    }
+    std::env::current_dir()
+        .map(|dir| dir.ancestors().any(|dir| dir.join(CARGO_TOML).is_file()))
+        .unwrap_or(false)
+}
 
-    let init = Store::is_init(&metacfg);
-    if matches!(cli.command, Some(Commands::Init { .. })) {
-        if init {
-            // ERRORS: immediate fatal diagnostic
-            return Err(miette!(
-                "'cargo vet' already initialized (store found at {})",
-                metacfg.store_path().display()
-            ));
+/// Find the Cargo.toml that `--manifest-path` refers to, which may be given
+/// as either the manifest itself or the directory containing it.
+fn resolve_manifest_path(path: PathBuf) -> Result<PathBuf, miette::Report> {
+    if path.is_dir() {
+        let manifest = path.join(CARGO_TOML);
+        if manifest.is_file() {
+            return Ok(manifest);
         }
-    } else if !init {
         // ERRORS: immediate fatal diagnostic
         return Err(miette!(
-            "You must run 'cargo vet init' (store not found at {})",
-            metacfg.store_path().display()
+            "--manifest-path {} is a directory without a {CARGO_TOML}",
+            path.display()
         ));
     }
+    if !path.is_file() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!("--manifest-path {} doesn't exist", path.display()));
+    }
+    Ok(path)
+}
 
-    let cfg = Config {
-        metacfg,
-        metadata,
-        _rest: partial_cfg,
+/// Create a `cargo metadata` invocation with all the options the cli implies,
+/// except for the choice of features.
+fn cargo_metadata_command(cli: &Cli) -> cargo_metadata::MetadataCommand {
+    let cargo_path = std::env::var_os(CARGO_ENV).expect("Cargo failed to set $CARGO, how?");
+
+    let mut cmd = cargo_metadata::MetadataCommand::new();
+    cmd.cargo_path(cargo_path);
+    if let Some(manifest_path) = &cli.manifest_path {
+        cmd.manifest_path(manifest_path);
+    }
+    // We never want cargo-vet to update the Cargo.lock.
+    // For frozen runs we also don't want to touch the network.
+    let mut other_options = Vec::new();
+    if cli.frozen {
+        other_options.push("--frozen".to_string());
+    } else {
+        other_options.push("--locked".to_string());
+    }
+    cmd.other_options(other_options);
+    cmd
+}
+
+/// Run `cargo metadata`, explaining failures caused by a missing Cargo.lock,
+/// which we always refuse to create.
+///
+/// Cargo's error messages aren't stable, so rather than parsing them, a
+/// failure is only put down to Cargo.lock if there isn't one next to the
+/// manifest or any of its parent directories (where the workspace root would
+/// be). Cargo already explains an out-of-date Cargo.lock itself, and any
+/// other failure is passed through as it is.
+fn exec_cargo_metadata(
+    cli: &Cli,
+    cmd: &cargo_metadata::MetadataCommand,
+) -> Result<Metadata, miette::Report> {
+    match cmd.exec() {
+        Ok(metadata) => Ok(metadata),
+        Err(error @ cargo_metadata::Error::CargoMetadata { .. }) if !has_lockfile(cli) => {
+            let help = if cli.locked || cli.frozen {
+                "commit a Cargo.lock, so that what's vetted matches what's built"
+            } else {
+                "run `cargo generate-lockfile` (or just build your project) to create Cargo.lock"
+            };
+            Err(LockfileError {
+                error,
+                help: Some(help.to_owned()),
+            }
+            .into())
+        }
+        Err(error) => Err(error)
+            .into_diagnostic()
+            .wrap_err("'cargo metadata' exited unsuccessfully"),
+    }
+}
+
+/// Whether there's a Cargo.lock in the directory of the manifest
+/// `cargo metadata` reads, or in one of its parents.
+fn has_lockfile(cli: &Cli) -> bool {
+    let dir = match &cli.manifest_path {
+        Some(manifest_path) => manifest_path.parent().map(Path::to_owned),
+        None => std::env::current_dir().ok(),
     };
+    dir.map(|dir| dir.ancestors().any(|dir| dir.join(CARGO_LOCK).is_file()))
+        .unwrap_or(false)
+}
 
-    use RegenerateSubcommands::*;
-    match &cfg.cli.command {
-        None => cmd_check(&out, &cfg, &cfg.cli.check_args),
-        Some(Check(sub_args)) => cmd_check(&out, &cfg, sub_args),
-        Some(Init(sub_args)) => cmd_init(&out, &cfg, sub_args),
-        Some(Certify(sub_args)) => cmd_certify(&out, &cfg, sub_args),
-        Some(AddExemption(sub_args)) => cmd_add_exemption(&out, &cfg, sub_args),
-        Some(RecordViolation(sub_args)) => cmd_record_violation(&out, &cfg, sub_args),
-        Some(Suggest(sub_args)) => cmd_suggest(&out, &cfg, sub_args),
-        Some(Fmt(sub_args)) => cmd_fmt(&out, &cfg, sub_args),
-        Some(FetchImports(sub_args)) => cmd_fetch_imports(&out, &cfg, sub_args),
-        Some(DumpGraph(sub_args)) => cmd_dump_graph(&out, &cfg, sub_args),
-        Some(Inspect(sub_args)) => cmd_inspect(&out, &cfg, sub_args),
-        Some(Diff(sub_args)) => cmd_diff(&out, &cfg, sub_args),
-        Some(Regenerate(Imports(sub_args))) => cmd_regenerate_imports(&out, &cfg, sub_args),
-        Some(Regenerate(Exemptions(sub_args))) => cmd_regenerate_exemptions(&out, &cfg, sub_args),
-        Some(Regenerate(AuditAsCratesIo(sub_args))) => {
-            cmd_regenerate_audit_as(&out, &cfg, sub_args)
+/// Merge the packages and dependency edges of `other` into `metadata`, so that
+/// the result describes the union of both dependency graphs.
+///
+/// Returns the ids of the packages which weren't already in `metadata`.
+fn merge_metadata(metadata: &mut Metadata, other: Metadata) -> Vec<PackageId> {
+    let known = metadata
+        .packages
+        .iter()
+        .map(|package| package.id.clone())
+        .collect::<FastSet<_>>();
+    let mut new_packages = Vec::new();
+    for package in other.packages {
+        if !known.contains(&package.id) {
+            new_packages.push(package.id.clone());
+            metadata.packages.push(package);
+        }
+    }
+
+    if let (Some(resolve), Some(other_resolve)) = (&mut metadata.resolve, other.resolve) {
+        for other_node in other_resolve.nodes {
+            let node = match resolve.nodes.iter_mut().find(|n| n.id == other_node.id) {
+                Some(node) => node,
+                None => {
+                    resolve.nodes.push(other_node);
+                    continue;
+                }
+            };
+            for dep in other_node.dependencies {
+                if !node.dependencies.contains(&dep) {
+                    node.dependencies.push(dep);
+                }
+            }
+            for feature in other_node.features {
+                if !node.features.contains(&feature) {
+                    node.features.push(feature);
+                }
+            }
+            for other_dep in other_node.deps {
+                let dep = match node.deps.iter_mut().find(|d| d.pkg == other_dep.pkg) {
+                    Some(dep) => dep,
+                    None => {
+                        node.deps.push(other_dep);
+                        continue;
+                    }
+                };
+                // DepKindInfo isn't comparable, so compare it by its parts
+                for kind in other_dep.dep_kinds {
+                    let target = kind.target.as_ref().map(|t| t.to_string());
+                    if !dep.dep_kinds.iter().any(|k| {
+                        k.kind == kind.kind && k.target.as_ref().map(|t| t.to_string()) == target
+                    }) {
+                        dep.dep_kinds.push(kind);
+                    }
+                }
+            }
         }
-        Some(HelpMarkdown(_)) | Some(Gc(_)) => unreachable!("handled earlier"),
     }
+
+    new_packages
 }
 
-fn cmd_init(_out: &Arc<dyn Out>, cfg: &Config, _sub_args: &InitArgs) -> Result<(), miette::Report> {
+fn cmd_init(_out: &Arc<dyn Out>, cfg: &Config, sub_args: &InitArgs) -> Result<(), miette::Report> {
     // Initialize vet
     trace!("initializing...");
 
+    // Check the imports up front, so we don't leave behind a half-initialized
+    // store if they're obviously wrong.
+    if !sub_args.import.is_empty() && cfg.cli.locked {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!(
+            "init --import needs to fetch the imports, so can't be run with --locked"
+        ));
+    }
+    let mut import_urls = Vec::new();
+    for import in &sub_args.import {
+        let url = storage::parse_import_url(&import.url, false).map_err(|reason| {
+            InvalidImportUrlError {
+                import_name: import.name.clone(),
+                import_url: import.url.clone(),
+                reason,
+            }
+        })?;
+        import_urls.push(url);
+    }
+
     let mut store = Store::create(cfg)?;
 
     let (config, audits, imports) = init_files(&cfg.metadata, cfg.cli.filter_graph.as_ref());
     store.config = config;
     store.audits = audits;
     store.imports = imports;
+    store.config.dev_safe_to_run = sub_args.dev_safe_to_run;
+    if let Some(template) = &sub_args.exemption_note {
+        let date = utc_date(std::time::SystemTime::now());
+        apply_exemption_note_template(&mut store.config, template, &date);
+    }
 
     fix_audit_as(cfg, &mut store)?;
 
+    if !sub_args.import.is_empty() {
+        for (import, url) in sub_args.import.iter().zip(import_urls) {
+            store.config.imports.insert(
+                import.name.clone(),
+                RemoteImport {
+                    url: url.to_string(),
+                    criteria_map: builtin_criteria_map(),
+                    depth: format::DEFAULT_IMPORT_DEPTH,
+                    transitive_sources: Vec::new(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        // Fetching also validates the imported audits.
+        let network = Network::acquire(cfg, Some(&store.config));
+        if let Some(network) = &network {
+            tokio::runtime::Handle::current()
+                .block_on(store.fetch_foreign_audits(network, true))?;
+        }
+
+        // Only keep exemptions for what the imports don't already cover.
+        minimize_exemptions(cfg, &mut store, network.as_ref(), false)?;
+    }
+
     store.commit()?;
 
     Ok(())
 }
 
+/// A criteria-map which trusts the foreign store's built-in criteria as our own,
+/// as nothing would be imported without any mapping at all.
+fn builtin_criteria_map() -> Vec<CriteriaMapping> {
+    [format::SAFE_TO_DEPLOY, format::SAFE_TO_RUN]
+        .into_iter()
+        .map(|criteria| CriteriaMapping {
+            ours: criteria.to_owned().into(),
+            theirs: vec![criteria.to_owned().into()],
+        })
+        .collect()
+}
+
 pub fn init_files(
     metadata: &Metadata,
     filter_graph: Option<&Vec<GraphFilter>>,
@@ -485,6 +979,9 @@ pub fn init_files(
     // Default imports file is empty
     let imports = ImportsFile {
         audits: SortedMap::new(),
+        transitive_imports: SortedMap::new(),
+        git_commits: SortedMap::new(),
+        sources: SortedMap::new(),
     };
 
     // This is the hard one
@@ -516,41 +1013,83 @@ pub fn init_files(
         }
         ConfigFile {
             default_criteria: format::get_default_criteria(),
+            decompose_criteria: format::get_default_decompose_criteria(),
+            dev_safe_to_run: false,
+            proc_macro_criteria: Vec::new(),
+            allowed_import_sources: Vec::new(),
+            third_party_registries: Vec::new(),
             imports: SortedMap::new(),
             exemptions: dependencies,
             policy: SortedMap::new(),
+            owners: SortedMap::new(),
+            notes: SortedMap::new(),
+            feature_sets: SortedMap::new(),
+            diff: Default::default(),
+            network: Default::default(),
         }
     };
 
     (config, audits, imports)
 }
 
+/// Set the notes of every exemption to `template`, with `{package}`, `{version}`
+/// and `{date}` filled in.
+fn apply_exemption_note_template(config: &mut ConfigFile, template: &str, date: &str) {
+    for (package, exemptions) in &mut config.exemptions {
+        for exemption in exemptions {
+            exemption.notes = Some(exemption_note(template, package, &exemption.version, date));
+        }
+    }
+}
+
+fn exemption_note(template: &str, package: PackageStr, version: &Version, date: &str) -> String {
+    template
+        .replace("{package}", package)
+        .replace("{version}", &version.to_string())
+        .replace("{date}", date)
+}
+
+/// Format the (UTC) day of `time` as YYYY-MM-DD.
+fn utc_date(time: std::time::SystemTime) -> String {
+    chrono::DateTime::<chrono::Utc>::from(time)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
 fn cmd_inspect(
     out: &Arc<dyn Out>,
-    cfg: &Config,
+    cfg: &PartialConfig,
+    project: Option<&Config>,
     sub_args: &InspectArgs,
 ) -> Result<(), miette::Report> {
-    let store = Store::acquire(cfg)?;
+    let store = project.map(Store::acquire).transpose()?;
     let cache = Cache::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, store.as_ref().map(|store| &store.config));
 
     let version = &sub_args.version;
     let package = &*sub_args.package;
+    check_fetchable(project, package, version)?;
 
     // Record this command for magic in `vet certify`
     cache.set_last_fetch(FetchCommand::Inspect {
         package: package.to_owned(),
         version: version.clone(),
+        source_hash: None,
     });
 
-    if sub_args.mode == FetchMode::Sourcegraph {
-        let url = format!("https://sourcegraph.com/crates/{package}@v{version}");
+    let url = match sub_args.mode {
+        FetchMode::Local => None,
+        FetchMode::Sourcegraph => Some(format!(
+            "https://sourcegraph.com/crates/{package}@v{version}"
+        )),
+        FetchMode::Web => Some(inspect_url(&sub_args.url_template, package, version)),
+    };
+    if let Some(url) = url {
         tokio::runtime::Handle::current()
             .block_on(prompt_criteria_eulas(
                 out,
-                cfg,
+                project.zip(store.as_ref()),
                 network.as_ref(),
-                &store,
                 package,
                 None,
                 version,
@@ -570,9 +1109,8 @@ fn cmd_inspect(
             cache.fetch_package(network.as_ref(), package, version),
             prompt_criteria_eulas(
                 out,
-                cfg,
+                project.zip(store.as_ref()),
                 network.as_ref(),
-                &store,
                 package,
                 None,
                 version,
@@ -583,11 +1121,27 @@ fn cmd_inspect(
         pkg.into_diagnostic()
     })?;
 
+    // Hash the pristine source before handing it to the user, so that
+    // `vet certify` can record exactly what was reviewed.
+    match storage::hash_package_source(&fetched) {
+        Ok(source_hash) => cache.set_last_fetch(FetchCommand::Inspect {
+            package: package.to_owned(),
+            version: version.clone(),
+            source_hash: Some(source_hash),
+        }),
+        Err(e) => warn!("couldn't hash the source of {package}:{version}: {e}"),
+    }
+
+    if sub_args.no_shell {
+        writeln!(out, "{}", fetched.display());
+        return Ok(());
+    }
+
+    // Loosely borrowed from cargo crev.
+    writeln!(out, "Opening nested shell in: {:#?}", fetched);
+    let shell = user_shell(|var| std::env::var_os(var));
     #[cfg(target_family = "unix")]
     {
-        // Loosely borrowed from cargo crev.
-        let shell = std::env::var_os("SHELL").unwrap();
-        writeln!(out, "Opening nested shell in: {:#?}", fetched);
         writeln!(out, "Use `exit` or Ctrl-D to finish.",);
         let mut command = std::process::Command::new(shell);
         command.current_dir(fetched.clone()).env("PWD", fetched);
@@ -596,11 +1150,45 @@ fn cmd_inspect(
 
     #[cfg(not(target_family = "unix"))]
     {
-        writeln!(out, "  fetched to {:#?}", fetched);
+        // There's no exec, so run the shell as a child and wait for it.
+        writeln!(out, "Use `exit` to finish.",);
+        let status = std::process::Command::new(&shell)
+            .current_dir(&fetched)
+            .status()
+            .into_diagnostic()
+            .wrap_err_with(|| {
+                format!(
+                    "Couldn't start {}, try --no-shell?",
+                    Path::new(&shell).display()
+                )
+            })?;
+        if !status.success() {
+            panic_any(ExitPanic(status.code().unwrap_or(-1)));
+        }
         Ok(())
     }
 }
 
+/// The user's shell, from `$SHELL` (or `%COMSPEC%` on Windows), falling back
+/// to `/bin/sh` (or `cmd.exe`) if it isn't set.
+fn user_shell(var_os: impl Fn(&str) -> Option<OsString>) -> OsString {
+    let (var, default) = if cfg!(target_family = "unix") {
+        ("SHELL", "/bin/sh")
+    } else {
+        ("COMSPEC", "cmd.exe")
+    };
+    var_os(var)
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| default.into())
+}
+
+/// Fill in the `{package}` and `{version}` of an `inspect --url-template`.
+fn inspect_url(template: &str, package: PackageStr, version: &Version) -> String {
+    template
+        .replace("{package}", package)
+        .replace("{version}", &version.to_string())
+}
+
 fn cmd_certify(
     out: &Arc<dyn Out>,
     cfg: &Config,
@@ -608,10 +1196,29 @@ fn cmd_certify(
 ) -> Result<(), miette::Report> {
     // Certify that you have reviewed a crate's source for some version / delta
     let mut store = Store::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, Some(&store.config));
 
     // Grab the last fetch and immediately drop the cache
-    let last_fetch = Cache::acquire(cfg)?.get_last_fetch();
+    let cache = Cache::acquire(cfg)?;
+    let mut last_fetch = cache.get_last_fetch();
+
+    // A first-party package audited as its crates.io twin is certified based on
+    // its local source, since that's what actually gets built. Unless we just
+    // fetched something else for it, walk the user through that source.
+    let local = sub_args
+        .package
+        .as_deref()
+        .filter(|_| sub_args.version1.is_none())
+        .and_then(|package| audit_as_crates_io_package(cfg, &store, package))
+        .filter(|local| !matches!(&last_fetch, Some(f) if f.package() == local.name));
+    if let Some(local) = local {
+        let fetch = audit_as_crates_io_fetch(&store, local);
+        if !sub_args.accept_all {
+            show_audit_as_crates_io_diff(out, &cache, network.as_ref(), local, &fetch)?;
+        }
+        last_fetch = Some(fetch);
+    }
+    drop(cache);
 
     do_cmd_certify(out, cfg, sub_args, &mut store, network.as_ref(), last_fetch)?;
 
@@ -627,6 +1234,10 @@ fn do_cmd_certify(
     network: Option<&Network>,
     last_fetch: Option<FetchCommand>,
 ) -> Result<(), CertifyError> {
+    if sub_args.all {
+        return certify_all(out, cfg, sub_args, store, network);
+    }
+
     // Before setting up magic, we need to agree on a package
     let package = if let Some(package) = &sub_args.package {
         package.clone()
@@ -638,10 +1249,16 @@ fn do_cmd_certify(
     };
 
     // FIXME: can/should we check if the version makes sense..?
-    if !sub_args.force
-        && !foreign_packages(&cfg.metadata, &store.config).any(|pkg| pkg.name == *package)
-    {
-        return Err(CertifyError::NotAPackage(package));
+    if !foreign_packages(&cfg.metadata, &store.config).any(|pkg| pkg.name == *package) {
+        if !sub_args.force {
+            return Err(CertifyError::NotAPackage(package));
+        }
+        // ERRORS: just a warning that you're holding it wrong, the audit is still recorded
+        warn!("'{package}' isn't currently one of your foreign packages, certifying anyway");
+    }
+
+    if let Some(import_name) = &sub_args.import_from {
+        return certify_from_import(out, sub_args, store, &package, import_name);
     }
 
     let dependency_criteria = if sub_args.dependency_criteria.is_empty() {
@@ -658,6 +1275,30 @@ fn do_cmd_certify(
         dep_criteria
     };
 
+    // With --from-audited, the delta is from wherever the audit graph already
+    // reaches, as suggest would pick it.
+    let delta_base = match &sub_args.version1 {
+        Some(v1) if sub_args.from_audited => {
+            let report = resolver::resolve(
+                &cfg.metadata,
+                cfg.cli.filter_graph.as_ref(),
+                store,
+                ResolveDepth::Deep,
+            );
+            // Only a delta was reviewed, so never record it as a full audit
+            match report.compute_suggested_delta_base(&package, v1) {
+                Some(base) => Some(base),
+                None => {
+                    return Err(CertifyError::NoDeltaBase {
+                        package,
+                        version: v1.clone(),
+                    })
+                }
+            }
+        }
+        _ => None,
+    };
+
     let kind = if let Some(v1) = &sub_args.version1 {
         // If explicit versions were provided, use those
         if let Some(v2) = &sub_args.version2 {
@@ -669,6 +1310,15 @@ fn do_cmd_certify(
                 },
                 dependency_criteria,
             }
+        } else if let Some(from) = delta_base {
+            // This is a delta from the closest audited version
+            AuditKind::Delta {
+                delta: Delta {
+                    from,
+                    to: v1.clone(),
+                },
+                dependency_criteria,
+            }
         } else {
             // This is a full audit
             AuditKind::Full {
@@ -676,7 +1326,7 @@ fn do_cmd_certify(
                 dependency_criteria,
             }
         }
-    } else if let Some(fetch) = last_fetch.filter(|f| f.package() == package) {
+    } else if let Some(fetch) = last_fetch.clone().filter(|f| f.package() == package) {
         // Otherwise, is we just fetched this package, use the version(s) we fetched
         match fetch {
             FetchCommand::Inspect { version, .. } => AuditKind::Full {
@@ -697,16 +1347,63 @@ fn do_cmd_certify(
         return Err(CertifyError::CouldntGuessVersion(package));
     };
 
-    let (username, who) = if let Some(who) = &sub_args.who {
-        (who.clone(), Some(who.clone()))
+    // If the base of the delta can't be fetched, the audit has to be of the
+    // whole new version instead.
+    let mut notes = sub_args.notes.clone();
+    let kind = if sub_args.base_unavailable {
+        match kind {
+            AuditKind::Delta {
+                delta,
+                dependency_criteria,
+            } => {
+                let reason = format!(
+                    "Audited in full, as a delta from version {} wasn't possible (it couldn't be fetched).",
+                    delta.from
+                );
+                notes = Some(match notes {
+                    Some(notes) => format!("{}\n{}", reason, notes),
+                    None => reason,
+                });
+                AuditKind::Full {
+                    version: delta.to,
+                    dependency_criteria,
+                }
+            }
+            _ => return Err(CertifyError::BaseUnavailableWithoutDelta),
+        }
     } else {
-        let user_info = get_user_info()?;
-        let who = format!("{} <{}>", user_info.username, user_info.email);
-        (user_info.username, Some(who))
+        kind
     };
 
+    let (username, who) = get_who(sub_args.who.as_ref());
+
     let criteria_mapper = CriteriaMapper::new(&store.audits.criteria);
 
+    // Even with --force, any criteria named on the cli must actually exist
+    for criteria in sub_args
+        .criteria
+        .iter()
+        .chain(sub_args.dependency_criteria.iter().map(|arg| &arg.criteria))
+    {
+        if !criteria_mapper.index.contains_key(criteria) {
+            return Err(CertifyError::UnknownCriteria {
+                criteria: criteria.clone(),
+                valid_names: criteria_mapper.criteria_name_list(),
+            });
+        }
+    }
+
+    // Without a terminal we can't prompt the user, so everything we'd prompt
+    // for must have been given on the command line.
+    if !out.is_term() {
+        if sub_args.criteria.is_empty() {
+            return Err(CertifyError::CriteriaNotGiven);
+        }
+        if !sub_args.accept_all {
+            return Err(CertifyError::StatementNotAccepted);
+        }
+    }
+
     let criteria_names = if sub_args.criteria.is_empty() {
         let (from, to) = match &kind {
             AuditKind::Full { version, .. } => (None, version),
@@ -806,21 +1503,13 @@ fn do_cmd_certify(
         .criteria_names(&criteria_set)
         .collect::<Vec<_>>();
 
-    let what_version = match &kind {
-        AuditKind::Full { version, .. } => {
-            format!("version {}", version)
-        }
-        AuditKind::Delta { delta, .. } => {
-            format!("the changes from version {} to {}", delta.from, delta.to)
-        }
-        AuditKind::Violation { .. } => unreachable!(),
-    };
     let statement = format!(
         "I, {}, certify that I have audited {} of {} in accordance with the above criteria.",
-        username, what_version, package,
+        username,
+        what_was_audited(&kind),
+        package,
     );
 
-    let mut notes = sub_args.notes.clone();
     if !sub_args.accept_all {
         // Get all the EULAs at once
         let eulas = tokio::runtime::Handle::current().block_on(join_all(
@@ -889,6 +1578,21 @@ fn do_cmd_certify(
         };
     }
 
+    // If this is a full audit of exactly what was last inspected, record the
+    // hash of the source which was reviewed. Otherwise, if it's the version in
+    // the current build, record the hash of the source cargo unpacked for it.
+    let source_hash = match (&kind, last_fetch) {
+        (
+            AuditKind::Full { version, .. },
+            Some(FetchCommand::Inspect {
+                package: fetched_package,
+                version: fetched_version,
+                source_hash,
+            }),
+        ) if fetched_package == package && &fetched_version == version => source_hash,
+        _ => local_source_hash(cfg, store, &package, &kind),
+    };
+
     let new_entry = AuditEntry {
         kind: kind.clone(),
         criteria: criteria_names
@@ -897,6 +1601,7 @@ fn do_cmd_certify(
             .collect(),
         who,
         notes,
+        source_hash,
     };
 
     store
@@ -908,23 +1613,438 @@ fn do_cmd_certify(
 
     // If we're submitting a full audit, look for a matching exemption entry to remove
     if let AuditKind::Full { version, .. } = &kind {
-        if let Some(exemption_list) = store.config.exemptions.get_mut(&package) {
-            let cur_criteria_set = criteria_mapper.criteria_from_list(criteria_names);
-            // Iterate backwards so that we can delete while iterating
-            // (will only affect indices that we've already visited!)
-            for idx in (0..exemption_list.len()).rev() {
-                let entry = &exemption_list[idx];
-                let entry_criteria_set = criteria_mapper.criteria_from_list(&entry.criteria);
-                if &entry.version == version && cur_criteria_set.contains(&entry_criteria_set) {
-                    exemption_list.remove(idx);
-                }
+        remove_satisfied_exemptions(store, &criteria_mapper, &package, version, criteria_names);
+    }
+
+    Ok(())
+}
+
+/// Certify every audit that `cargo vet suggest` would recommend, for
+/// `cargo vet certify --all`.
+fn certify_all(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &CertifyArgs,
+    store: &mut Store,
+    network: Option<&Network>,
+) -> Result<(), CertifyError> {
+    if !out.is_term() && !sub_args.accept_all {
+        return Err(CertifyError::StatementNotAccepted);
+    }
+
+    let criteria_mapper = CriteriaMapper::new(&store.audits.criteria);
+    for criteria in &sub_args.criteria {
+        if !criteria_mapper.index.contains_key(criteria) {
+            return Err(CertifyError::UnknownCriteria {
+                criteria: criteria.clone(),
+                valid_names: criteria_mapper.criteria_name_list(),
+            });
+        }
+    }
+
+    // Work out everything to certify up front, as the report borrows the store.
+    let report = resolver::resolve(
+        &cfg.metadata,
+        cfg.cli.filter_graph.as_ref(),
+        store,
+        ResolveDepth::Deep,
+    );
+    let mut planned = vec![];
+    if let Some(suggest) = report.compute_suggest(cfg, network, true)? {
+        for item in &suggest.suggestions {
+            let package = report.graph.nodes[item.package].name;
+            let criteria_set = if sub_args.criteria.is_empty() {
+                item.suggested_criteria.all.clone()
+            } else {
+                criteria_mapper.criteria_from_list(&sub_args.criteria)
+            };
+            let criteria_names = criteria_mapper
+                .criteria_names(&criteria_set)
+                .map(|name| name.to_owned())
+                .collect::<Vec<_>>();
+            // A chain of diffs is certified as the individual deltas.
+            let diffs = if item.suggested_diff.steps.is_empty() {
+                std::slice::from_ref(&item.suggested_diff)
+            } else {
+                &item.suggested_diff.steps[..]
+            };
+            for diff in diffs {
+                let kind = if diff.from == ROOT_VERSION {
+                    AuditKind::Full {
+                        version: diff.to.clone(),
+                        dependency_criteria: DependencyCriteria::new(),
+                    }
+                } else {
+                    AuditKind::Delta {
+                        delta: Delta {
+                            from: diff.from.clone(),
+                            to: diff.to.clone(),
+                        },
+                        dependency_criteria: DependencyCriteria::new(),
+                    }
+                };
+                planned.push((package.to_owned(), kind, criteria_names.clone()));
+            }
+        }
+    }
+
+    if planned.is_empty() {
+        writeln!(out, "nothing to certify");
+        return Ok(());
+    }
+
+    let (username, who) = get_who(sub_args.who.as_ref());
+    let mut certified = 0;
+    for (package, kind, criteria_names) in planned {
+        if !sub_args.accept_all {
+            writeln!(out);
+            writeln!(
+                out,
+                "I, {}, certify that I have audited {} of {} in accordance with the {} criteria.",
+                username,
+                what_was_audited(&kind),
+                package,
+                criteria_names.join(", "),
+            );
+            let answer = out.read_line_with_prompt("certify? (y/N) > ")?;
+            if !answer.trim().eq_ignore_ascii_case("y") {
+                continue;
+            }
+        }
+
+        let source_hash = local_source_hash(cfg, store, &package, &kind);
+        if let AuditKind::Full { version, .. } = &kind {
+            remove_satisfied_exemptions(
+                store,
+                &criteria_mapper,
+                &package,
+                version,
+                criteria_names.iter().map(|name| &**name),
+            );
+        }
+        store
+            .audits
+            .audits
+            .entry(package)
+            .or_insert(vec![])
+            .push(AuditEntry {
+                kind,
+                criteria: criteria_names.into_iter().map(|name| name.into()).collect(),
+                who: who.clone(),
+                notes: sub_args.notes.clone(),
+                source_hash,
+            });
+        certified += 1;
+    }
+
+    writeln!(out, "certified {certified} audit(s)");
+    Ok(())
+}
+
+/// What an audit covers, for the certify statement.
+fn what_was_audited(kind: &AuditKind) -> String {
+    match kind {
+        AuditKind::Full { version, .. } => format!("version {version}"),
+        AuditKind::Delta { delta, .. } => {
+            format!("the changes from version {} to {}", delta.from, delta.to)
+        }
+        AuditKind::Violation { .. } => unreachable!(),
+    }
+}
+
+/// For a full audit of the version of `package` in the current build, the
+/// hash of the source cargo unpacked for it.
+fn local_source_hash(
+    cfg: &Config,
+    store: &Store,
+    package: PackageStr,
+    kind: &AuditKind,
+) -> Option<String> {
+    let version = match kind {
+        AuditKind::Full { version, .. } => version,
+        _ => return None,
+    };
+    foreign_packages(&cfg.metadata, &store.config)
+        .find(|p| p.name == package && &p.version == version)
+        .and_then(|p| storage::hash_local_package_source(p).ok())
+}
+
+/// The first-party package named `package`, if it has `audit-as-crates-io = true`.
+fn audit_as_crates_io_package<'a>(
+    cfg: &'a Config,
+    store: &'a Store,
+    package: PackageStr,
+) -> Option<&'a Package> {
+    first_party_packages_strict(&cfg.metadata, &store.config)
+        .find(|p| p.name == package && p.is_third_party(&store.config))
+}
+
+/// What to certify for a first-party package audited as its crates.io twin:
+/// the changes since the newest earlier version we have an audit for, or the
+/// whole package if there isn't one.
+fn audit_as_crates_io_fetch(store: &Store, local: &Package) -> FetchCommand {
+    let audited_base = store
+        .audits
+        .audits
+        .get(&local.name)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| match &entry.kind {
+            AuditKind::Full { version, .. } => Some(version),
+            AuditKind::Delta { delta, .. } => Some(&delta.to),
+            AuditKind::Violation { .. } => None,
+        })
+        .filter(|&version| version < &local.version)
+        .max();
+
+    match audited_base {
+        Some(base) => FetchCommand::Diff {
+            package: local.name.clone(),
+            version1: base.clone(),
+            version2: local.version.clone(),
+        },
+        None => FetchCommand::Inspect {
+            package: local.name.clone(),
+            version: local.version.clone(),
+            source_hash: None,
+        },
+    }
+}
+
+/// Show the diff from the published version `fetch` starts from to the local
+/// source of a first-party package audited as its crates.io twin.
+fn show_audit_as_crates_io_diff(
+    out: &Arc<dyn Out>,
+    cache: &Cache,
+    network: Option<&Network>,
+    local: &Package,
+    fetch: &FetchCommand,
+) -> Result<(), miette::Report> {
+    let published = match fetch {
+        FetchCommand::Diff { version1, .. } => {
+            writeln!(
+                out,
+                "certifying the changes to {} from {} (the newest audited version) to the local source",
+                local.name, version1
+            );
+            version1
+        }
+        FetchCommand::Inspect { version, .. } => {
+            writeln!(
+                out,
+                "no earlier version of {} has been audited, so this is a full audit of the local source",
+                local.name
+            );
+            writeln!(
+                out,
+                "(any differences from the published {} {} are shown below)",
+                local.name, version
+            );
+            version
+        }
+    };
+
+    let local_src = local
+        .manifest_path
+        .parent()
+        .expect("manifest path had no parent?")
+        .as_std_path();
+    let published_src = match tokio::runtime::Handle::current().block_on(cache.fetch_package(
+        network,
+        &local.name,
+        published,
+    )) {
+        Ok(published_src) => published_src,
+        Err(e) => {
+            // ERRORS: just a warning, the user can still review the local source themselves
+            warn!(
+                "couldn't fetch {}:{} to diff against, review {} directly: {e}",
+                local.name,
+                published,
+                local_src.display()
+            );
+            return Ok(());
+        }
+    };
+    writeln!(out);
+
+    std::process::Command::new("git")
+        .arg("diff")
+        .arg("--no-index")
+        .arg(&published_src)
+        .arg(local_src)
+        .status()
+        .map_err(CommandError::CommandFailed)
+        .into_diagnostic()?;
+
+    Ok(())
+}
+
+/// Remove any exemptions of `package:version` which are satisfied by a full
+/// audit for `criteria_names`.
+fn remove_satisfied_exemptions<'a>(
+    store: &mut Store,
+    criteria_mapper: &CriteriaMapper,
+    package: PackageStr,
+    version: &Version,
+    criteria_names: impl IntoIterator<Item = &'a str>,
+) {
+    if let Some(exemption_list) = store.config.exemptions.get_mut(package) {
+        let cur_criteria_set = criteria_mapper.criteria_from_list(criteria_names);
+        // Iterate backwards so that we can delete while iterating
+        // (will only affect indices that we've already visited!)
+        for idx in (0..exemption_list.len()).rev() {
+            let entry = &exemption_list[idx];
+            let entry_criteria_set = criteria_mapper.criteria_from_list(&entry.criteria);
+            if &entry.version == version && cur_criteria_set.contains(&entry_criteria_set) {
+                exemption_list.remove(idx);
             }
-            if exemption_list.is_empty() {
-                store.config.exemptions.remove(&package);
+        }
+        if exemption_list.is_empty() {
+            store.config.exemptions.remove(package);
+        }
+    }
+}
+
+/// Copy the audits of `package` from the import `import_name` into our own
+/// audits.toml, translating their criteria into ours.
+fn certify_from_import(
+    out: &Arc<dyn Out>,
+    sub_args: &CertifyArgs,
+    store: &mut Store,
+    package: PackageStr,
+    import_name: &str,
+) -> Result<(), CertifyError> {
+    let (import, foreign_audits) = match (
+        store.config.imports.get(import_name),
+        store.imports.audits.get(import_name),
+    ) {
+        (Some(import), Some(foreign_audits)) => (import, foreign_audits),
+        _ => return Err(CertifyError::UnknownImport(import_name.to_owned())),
+    };
+    // Without a criteria-map, their criteria mean nothing here, even if the
+    // names happen to match ours.
+    if import.criteria_map.is_empty() {
+        return Err(CertifyError::NoCriteriaMap(import_name.to_owned()));
+    }
+
+    let criteria_mapper = CriteriaMapper::new(&store.audits.criteria);
+    let foreign_criteria_mapper = CriteriaMapper::new(&foreign_audits.criteria);
+    let criteria_map: Vec<(CriteriaStr, CriteriaSet)> = import
+        .criteria_map
+        .iter()
+        .filter(|mapping| {
+            mapping
+                .theirs
+                .iter()
+                .all(|c| foreign_criteria_mapper.index.contains_key(&**c))
+        })
+        .map(|mapping| {
+            let set = foreign_criteria_mapper.criteria_from_list(&mapping.theirs);
+            (&**mapping.ours, set)
+        })
+        .collect();
+
+    // Translate a list of their criteria into a (minimal) list of ours.
+    let map_criteria = |theirs: &[Spanned<ForeignCriteriaName>]| {
+        let mut local_criteria = criteria_mapper.no_criteria();
+        let foreign_criteria = foreign_criteria_mapper.criteria_from_list(
+            theirs
+                .iter()
+                .filter(|c| foreign_criteria_mapper.index.contains_key(&***c)),
+        );
+        for (local_implied, foreign_required) in &criteria_map {
+            if foreign_criteria.contains(foreign_required) {
+                criteria_mapper.set_criteria(&mut local_criteria, local_implied);
             }
         }
+        criteria_mapper
+            .criteria_names(&local_criteria)
+            .map(|name| name.to_owned().into())
+            .collect::<Vec<Spanned<CriteriaName>>>()
+    };
+
+    let wanted_version = |kind: &AuditKind| match (&sub_args.version1, &sub_args.version2, kind) {
+        (None, _, AuditKind::Violation { .. }) => false,
+        (None, _, _) => true,
+        (Some(v1), None, AuditKind::Full { version, .. }) => v1 == version,
+        (Some(v1), Some(v2), AuditKind::Delta { delta, .. }) => {
+            v1 == &delta.from && v2 == &delta.to
+        }
+        _ => false,
+    };
+
+    let provenance = format!("Imported from {} ({})", import_name, import.source_url());
+    let mut new_entries = vec![];
+    for entry in foreign_audits.audits.get(package).into_iter().flatten() {
+        if !wanted_version(&entry.kind) {
+            continue;
+        }
+        let criteria = map_criteria(&entry.criteria);
+        if criteria.is_empty() {
+            continue;
+        }
+        // Their dependency-criteria name their dependencies' criteria, which
+        // aren't translated when importing either, so they're dropped.
+        let kind = match &entry.kind {
+            AuditKind::Full { version, .. } => AuditKind::Full {
+                version: version.clone(),
+                dependency_criteria: DependencyCriteria::new(),
+            },
+            AuditKind::Delta { delta, .. } => AuditKind::Delta {
+                delta: delta.clone(),
+                dependency_criteria: DependencyCriteria::new(),
+            },
+            AuditKind::Violation { .. } => unreachable!(),
+        };
+        let notes = match sub_args.notes.as_ref().or(entry.notes.as_ref()) {
+            Some(notes) => format!("{}\n\n{}", notes, provenance),
+            None => provenance.clone(),
+        };
+        new_entries.push(AuditEntry {
+            who: sub_args.who.clone().or_else(|| entry.who.clone()),
+            criteria,
+            kind,
+            notes: Some(notes),
+            source_hash: entry.source_hash.clone(),
+        });
+    }
+
+    if new_entries.is_empty() {
+        return Err(CertifyError::NoImportedAudits {
+            import: import_name.to_owned(),
+            package: package.to_owned(),
+        });
+    }
+
+    let mut copied = 0;
+    for new_entry in new_entries {
+        let existing = store.audits.audits.entry(package.to_owned()).or_default();
+        // Don't record the same audit twice
+        if existing
+            .iter()
+            .any(|entry| entry.kind == new_entry.kind && entry.criteria == new_entry.criteria)
+        {
+            continue;
+        }
+        if let AuditKind::Full { version, .. } = &new_entry.kind {
+            let criteria_names = new_entry.criteria.iter().map(|c| &***c).collect::<Vec<_>>();
+            remove_satisfied_exemptions(store, &criteria_mapper, package, version, criteria_names);
+        }
+        store
+            .audits
+            .audits
+            .entry(package.to_owned())
+            .or_default()
+            .push(new_entry);
+        copied += 1;
     }
 
+    writeln!(
+        out,
+        "copied {} audit(s) of {} from {}",
+        copied, package, import_name
+    );
     Ok(())
 }
 
@@ -964,7 +2084,7 @@ fn guess_audit_criteria(
     resolver::resolve(
         &cfg.metadata,
         cfg.cli.filter_graph.as_ref(),
-        &store.clone_for_suggest(),
+        &store.clone_for_suggest(false),
         ResolveDepth::Deep,
     )
     .compute_suggested_criteria(package, from, to)
@@ -978,14 +2098,20 @@ fn guess_audit_criteria(
 #[allow(clippy::too_many_arguments)]
 async fn prompt_criteria_eulas(
     out: &Arc<dyn Out>,
-    cfg: &Config,
+    project: Option<(&Config, &Store)>,
     network: Option<&Network>,
-    store: &Store,
     package: PackageStr<'_>,
     from: Option<&Version>,
     to: &Version,
     url: Option<&str>,
 ) -> Result<(), io::Error> {
+    // Outside of a vetted project there are no criteria to speak of, so
+    // there's nothing to prompt about.
+    let (cfg, store) = match project {
+        Some(project) => project,
+        None => return Ok(()),
+    };
+
     let description = if let Some(from) = from {
         format!(
             "You are about to diff versions {} and {} of '{}'",
@@ -995,6 +2121,14 @@ async fn prompt_criteria_eulas(
         format!("You are about to inspect version {} of '{}'", to, package)
     };
 
+    // Packages which aren't in the dependency tree can still be fetched and
+    // diffed, but there's no point trying to guess criteria for them.
+    if !cfg.metadata.packages.iter().any(|p| p.name == package) {
+        writeln!(out, "{}", out.style().bold().apply_to(description));
+        info!("'{package}' isn't a dependency of this project, so no criteria apply to it");
+        return Ok(());
+    }
+
     // Guess which criteria the user is going to be auditing the package for.
     let criteria_names = guess_audit_criteria(cfg, store, package, from, to);
 
@@ -1076,19 +2210,13 @@ fn cmd_record_violation(
         violation: sub_args.versions.clone(),
     };
 
-    let (_username, who) = if let Some(who) = &sub_args.who {
-        (who.clone(), Some(who.clone()))
-    } else {
-        let user_info = get_user_info()?;
-        let who = format!("{} <{}>", user_info.username, user_info.email);
-        (user_info.username, Some(who))
-    };
+    let (_username, who) = get_who(sub_args.who.as_ref());
 
     let notes = sub_args.notes.clone();
 
     let criteria = if sub_args.criteria.is_empty() {
         // TODO: provide an interactive prompt for this
-        vec![store.config.default_criteria.clone().into()]
+        vec![(*store.config.default_criteria).clone().into()]
     } else {
         sub_args
             .criteria
@@ -1117,6 +2245,7 @@ fn cmd_record_violation(
         criteria,
         who,
         notes,
+        source_hash: None,
     };
 
     store
@@ -1133,33 +2262,99 @@ fn cmd_record_violation(
     Ok(())
 }
 
-fn cmd_add_exemption(
-    _out: &Arc<dyn Out>,
+fn cmd_import_crev(
+    out: &Arc<dyn Out>,
     cfg: &Config,
-    sub_args: &AddExemptionArgs,
+    sub_args: &ImportCrevArgs,
 ) -> Result<(), miette::Report> {
-    // Add an exemption entry
     let mut store = Store::acquire(cfg)?;
 
-    let dependency_criteria = if sub_args.dependency_criteria.is_empty() {
-        // TODO: look at the current audits to infer this? prompt?
-        DependencyCriteria::new()
-    } else {
-        let mut dep_criteria = DependencyCriteria::new();
-        for arg in &sub_args.dependency_criteria {
-            dep_criteria
-                .entry(arg.dependency.clone())
-                .or_insert_with(Vec::new)
-                .push(arg.criteria.clone().into());
+    let proofs = match &sub_args.proofs {
+        Some(path) => crev::CrevProofs::load(path)?,
+        None => {
+            // cargo-crev keeps the proofs it fetches in its cache, and your
+            // own in its data directory.
+            let mut proofs = crev::CrevProofs::default();
+            let dirs = [
+                dirs::cache_dir().map(|dir| dir.join("crev").join("remotes")),
+                dirs::data_local_dir().map(|dir| dir.join("crev").join("proofs")),
+            ];
+            for dir in dirs.into_iter().flatten().filter(|dir| dir.is_dir()) {
+                let loaded = crev::CrevProofs::load(&dir)?;
+                proofs.reviews.extend(loaded.reviews);
+                proofs.trusts.extend(loaded.trusts);
+                proofs.skipped += loaded.skipped;
+            }
+            proofs
         }
-        dep_criteria
     };
 
-    let notes = sub_args.notes.clone();
+    let trusted = proofs.trust_set(&sub_args.trusted_id);
+    let packages = foreign_packages(&cfg.metadata, &store.config)
+        .map(|package| (&package.name[..], &package.version));
 
-    let criteria = if sub_args.criteria.is_empty() {
+    let mut imported = 0;
+    for (package, entry) in proofs.audits_for(&trusted, packages) {
+        let audits = store.audits.audits.entry(package.clone()).or_default();
+        if audits.contains(&entry) {
+            continue;
+        }
+        if let AuditKind::Full { version, .. } = &entry.kind {
+            writeln!(
+                out,
+                "Imported {package}:{version} ({}) from {}",
+                entry.criteria[0],
+                entry.who.as_deref().unwrap_or_default()
+            );
+        }
+        audits.push(entry);
+        imported += 1;
+    }
+
+    store.commit()?;
+
+    writeln!(
+        out,
+        "Imported {imported} audits from {} crev reviews",
+        proofs.reviews.len()
+    );
+    if proofs.skipped != 0 {
+        writeln!(
+            out,
+            "Skipped {} crev proofs which couldn't be parsed or weren't validly signed",
+            proofs.skipped
+        );
+    }
+    Ok(())
+}
+
+fn cmd_add_exemption(
+    _out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &AddExemptionArgs,
+) -> Result<(), miette::Report> {
+    // Add an exemption entry
+    let mut store = Store::acquire(cfg)?;
+
+    let dependency_criteria = if sub_args.dependency_criteria.is_empty() {
+        // TODO: look at the current audits to infer this? prompt?
+        DependencyCriteria::new()
+    } else {
+        let mut dep_criteria = DependencyCriteria::new();
+        for arg in &sub_args.dependency_criteria {
+            dep_criteria
+                .entry(arg.dependency.clone())
+                .or_insert_with(Vec::new)
+                .push(arg.criteria.clone().into());
+        }
+        dep_criteria
+    };
+
+    let notes = sub_args.notes.clone();
+
+    let criteria = if sub_args.criteria.is_empty() {
         // TODO: provide an interactive prompt for this
-        vec![store.config.default_criteria.clone().into()]
+        vec![(*store.config.default_criteria).clone().into()]
     } else {
         sub_args
             .criteria
@@ -1212,8 +2407,11 @@ fn cmd_suggest(
 ) -> Result<(), miette::Report> {
     // Run the checker to validate that the current set of deps is covered by the current cargo vet store
     trace!("suggesting...");
-    let suggest_store = Store::acquire(cfg)?.clone_for_suggest();
-    let network = Network::acquire(cfg);
+    let suggest_store = Store::acquire(cfg)?.clone_for_suggest(sub_args.include_exemptions);
+    if cfg.cli.locked {
+        suggest_store.check_imports_lock()?;
+    }
+    let network = Network::acquire(cfg, Some(&suggest_store.config));
 
     // DO THE THING!!!!
     let report = resolver::resolve(
@@ -1226,12 +2424,55 @@ fn cmd_suggest(
             ResolveDepth::Deep
         },
     );
-    let suggest = report.compute_suggest(cfg, network.as_ref(), true)?;
+    let mut suggest = report.compute_suggest_preferring(
+        cfg,
+        network.as_ref(),
+        true,
+        sub_args.prefer_audited_version,
+    )?;
+    if let Some(suggest) = &mut suggest {
+        if sub_args.fail_fast {
+            if let Some((pkgidx, reason)) = suggest.failures.first() {
+                let package = &report.graph.nodes[*pkgidx];
+                return Err(SuggestError::PackageFailed {
+                    package: package.name.to_owned(),
+                    version: package.version.clone(),
+                    reason: reason.clone(),
+                }
+                .into());
+            }
+        }
+        suggest.assign_owners(
+            &report,
+            &suggest_store.config.owners,
+            sub_args.assignee.as_deref(),
+        );
+        if let Some(criteria) = &sub_args.criteria {
+            suggest.apply_criteria(&report, criteria)?;
+        }
+        if let Some(max_lines) = sub_args.max_lines {
+            suggest.apply_max_lines(max_lines);
+        }
+        if let Some(budget) = sub_args.budget {
+            suggest.apply_budget(&report, budget);
+        }
+    }
     match cfg.cli.output_format {
-        OutputFormat::Human => report
-            .print_suggest_human(out, cfg, suggest.as_ref())
-            .into_diagnostic()?,
+        OutputFormat::Human => match (&suggest, &sub_args.assignee) {
+            (Some(suggest), Some(assignee)) if suggest.suggestions.is_empty() => {
+                writeln!(out, "Nothing to suggest for {}!", assignee);
+            }
+            _ => report
+                .print_suggest_human(out, cfg, suggest.as_ref())
+                .into_diagnostic()?,
+        },
         OutputFormat::Json => report.print_json(out, cfg, suggest.as_ref())?,
+        OutputFormat::Html => report
+            .print_html(out, cfg, suggest.as_ref())
+            .into_diagnostic()?,
+        OutputFormat::Sarif => {
+            return Err(miette!("suggest doesn't support --output-format=sarif"));
+        }
     }
 
     Ok(())
@@ -1245,7 +2486,7 @@ fn cmd_regenerate_imports(
     trace!("regenerating imports...");
 
     let mut store = Store::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, Some(&store.config));
 
     if let Some(network) = &network {
         if !cfg.cli.locked {
@@ -1317,6 +2558,16 @@ fn fix_audit_as(cfg: &Config, store: &mut Store) -> Result<(), CacheAcquireError
                             .audit_as_crates_io = Some(false);
                     }
                 }
+                AuditAsError::SharesCratesIoVersion(shares) => {
+                    for err in shares.errors {
+                        store
+                            .config
+                            .policy
+                            .entry(err.package)
+                            .or_default()
+                            .audit_as_crates_io = Some(false);
+                    }
+                }
                 AuditAsError::CacheAcquire(err) => return Err(err),
             }
         }
@@ -1327,13 +2578,20 @@ fn fix_audit_as(cfg: &Config, store: &mut Store) -> Result<(), CacheAcquireError
 fn cmd_regenerate_exemptions(
     _out: &Arc<dyn Out>,
     cfg: &Config,
-    _sub_args: &RegenerateExemptionsArgs,
+    sub_args: &RegenerateExemptionsArgs,
 ) -> Result<(), miette::Report> {
     trace!("regenerating exemptions...");
     let mut store = Store::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, Some(&store.config));
 
-    minimize_exemptions(cfg, &mut store, network.as_ref())?;
+    let date = utc_date(std::time::SystemTime::now());
+    regenerate_exemptions(
+        cfg,
+        &mut store,
+        network.as_ref(),
+        &sub_args.exemption_note,
+        &date,
+    )?;
 
     // We were successful, commit the store
     store.commit()?;
@@ -1341,13 +2599,150 @@ fn cmd_regenerate_exemptions(
     Ok(())
 }
 
+fn cmd_prune(out: &Arc<dyn Out>, cfg: &Config, sub_args: &PruneArgs) -> Result<(), miette::Report> {
+    let mut store = Store::acquire(cfg)?;
+    let network = Network::acquire(cfg, Some(&store.config));
+
+    do_cmd_prune(out, cfg, sub_args, &mut store, network.as_ref())?;
+
+    if !sub_args.dry_run {
+        store.commit()?;
+    }
+    Ok(())
+}
+
+fn do_cmd_prune(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &PruneArgs,
+    store: &mut Store,
+    network: Option<&Network>,
+) -> Result<(), PruneError> {
+    let report = resolver::resolve(
+        &cfg.metadata,
+        cfg.cli.filter_graph.as_ref(),
+        store,
+        ResolveDepth::Shallow,
+    );
+    if report.has_errors() {
+        return Err(PruneError::CheckFailing);
+    }
+
+    let old_exemptions = store.config.exemptions.clone();
+    minimize_exemptions(cfg, store, network, false)?;
+
+    let criteria_list = |entry: &ExemptedDependency| {
+        entry
+            .criteria
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut changes = 0;
+    let no_entries = Vec::new();
+    let packages = old_exemptions
+        .keys()
+        .chain(store.config.exemptions.keys())
+        .collect::<SortedSet<_>>();
+    for package in packages {
+        let old_entries = old_exemptions.get(package).unwrap_or(&no_entries);
+        let new_entries = store.config.exemptions.get(package).unwrap_or(&no_entries);
+        for old in old_entries {
+            let criteria = criteria_list(old);
+            match new_entries.iter().find(|new| new.version == old.version) {
+                None => writeln!(out, "removed {package}:{} ({criteria})", old.version),
+                Some(new) if criteria_list(new) != criteria => writeln!(
+                    out,
+                    "weakened {package}:{} ({criteria} -> {})",
+                    old.version,
+                    criteria_list(new)
+                ),
+                Some(_) => continue,
+            }
+            changes += 1;
+        }
+        for new in new_entries {
+            if !old_entries.iter().any(|old| old.version == new.version) {
+                writeln!(
+                    out,
+                    "replaced with {package}:{} ({})",
+                    new.version,
+                    criteria_list(new)
+                );
+                changes += 1;
+            }
+        }
+    }
+
+    if changes == 0 {
+        writeln!(out, "nothing to prune");
+    } else if sub_args.dry_run {
+        writeln!(out, "(dry run, config.toml was not modified)");
+    }
+    Ok(())
+}
+
+/// Minimize the exemptions, keeping any with `suggest = false`, and fill in
+/// `note_template` (see
+/// [`apply_exemption_note_template`]) as the notes of any which were added.
+pub fn regenerate_exemptions(
+    cfg: &Config,
+    store: &mut Store,
+    network: Option<&Network>,
+    note_template: &str,
+    date: &str,
+) -> Result<(), MinimizeUnauditedError> {
+    let old_exemptions = store.config.exemptions.clone();
+    minimize_exemptions(cfg, store, network, true)?;
+
+    if note_template.is_empty() {
+        return Ok(());
+    }
+    let no_entries = Vec::new();
+    for (package, exemptions) in &mut store.config.exemptions {
+        let old_entries = old_exemptions.get(package).unwrap_or(&no_entries);
+        for exemption in exemptions {
+            let is_new = !old_entries
+                .iter()
+                .any(|old| old.version == exemption.version && old.criteria == exemption.criteria);
+            if is_new && exemption.notes.is_none() {
+                exemption.notes = Some(exemption_note(
+                    note_template,
+                    package,
+                    &exemption.version,
+                    date,
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Minimize the exemptions so that `check` passes with as few as possible.
+///
+/// If `keep_unsuggested` is set, exemptions with `suggest = false` are kept
+/// as-is, as they're generally hand-written.
 pub fn minimize_exemptions(
     cfg: &Config,
     store: &mut Store,
     network: Option<&Network>,
+    keep_unsuggested: bool,
 ) -> Result<(), MinimizeUnauditedError> {
-    // Set the exemption entries to nothing
-    let old_exemptions = mem::take(&mut store.config.exemptions);
+    // Set the exemption entries to nothing, other than the ones we're keeping
+    let mut old_exemptions = mem::take(&mut store.config.exemptions);
+    let mut kept_exemptions = SortedMap::<PackageName, Vec<ExemptedDependency>>::new();
+    for (package, entries) in &mut old_exemptions {
+        let (kept, rest) = mem::take(entries)
+            .into_iter()
+            .partition::<Vec<_>, _>(|entry| keep_unsuggested && !entry.suggest);
+        *entries = rest;
+        if !kept.is_empty() {
+            kept_exemptions.insert(package.clone(), kept);
+        }
+    }
+    old_exemptions.retain(|_, entries| !entries.is_empty());
+    store.config.exemptions = kept_exemptions.clone();
 
     // Try to vet
     let report = resolver::resolve(
@@ -1438,19 +2833,30 @@ pub fn minimize_exemptions(
     };
 
     // Alright there's the new exemptions
+    let mut new_exemptions = new_exemptions;
+    for (package, kept) in kept_exemptions {
+        new_exemptions.entry(package).or_default().extend(kept);
+    }
     store.config.exemptions = new_exemptions;
 
     Ok(())
 }
 
-fn cmd_diff(out: &Arc<dyn Out>, cfg: &Config, sub_args: &DiffArgs) -> Result<(), miette::Report> {
-    let store = Store::acquire(cfg)?;
+fn cmd_diff(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    project: Option<&Config>,
+    sub_args: &DiffArgs,
+) -> Result<(), miette::Report> {
+    let store = project.map(Store::acquire).transpose()?;
     let cache = Cache::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, store.as_ref().map(|store| &store.config));
 
     let version1 = &sub_args.version1;
     let version2 = &sub_args.version2;
     let package = &*sub_args.package;
+    check_fetchable(project, package, version1)?;
+    check_fetchable(project, package, version2)?;
 
     // Record this command for magic in `vet certify`
     cache.set_last_fetch(FetchCommand::Diff {
@@ -1459,15 +2865,20 @@ fn cmd_diff(out: &Arc<dyn Out>, cfg: &Config, sub_args: &DiffArgs) -> Result<(),
         version2: version2.clone(),
     });
 
-    if sub_args.mode == FetchMode::Sourcegraph {
+    if sub_args.mode == DiffMode::Sourcegraph {
+        if sub_args.word_diff || sub_args.context.is_some() || sub_args.tool.is_some() {
+            // ERRORS: immediate fatal diagnostic
+            return Err(miette!(
+                "--word-diff, --context, and --tool only apply to --mode=local"
+            ));
+        }
         let url =
             format!("https://sourcegraph.com/crates/{package}/-/compare/v{version1}...v{version2}");
         tokio::runtime::Handle::current()
             .block_on(prompt_criteria_eulas(
                 out,
-                cfg,
+                project.zip(store.as_ref()),
                 network.as_ref(),
-                &store,
                 package,
                 Some(version1),
                 version2,
@@ -1495,9 +2906,8 @@ fn cmd_diff(out: &Arc<dyn Out>, cfg: &Config, sub_args: &DiffArgs) -> Result<(),
             },
             prompt_criteria_eulas(
                 out,
-                cfg,
+                project.zip(store.as_ref()),
                 network.as_ref(),
-                &store,
                 package,
                 Some(version1),
                 version2,
@@ -1512,11 +2922,29 @@ fn cmd_diff(out: &Arc<dyn Out>, cfg: &Config, sub_args: &DiffArgs) -> Result<(),
 
     // FIXME: mask out .cargo_vcs_info.json
 
-    std::process::Command::new("git")
-        .arg("diff")
-        .arg("--no-index")
-        .arg(&fetched1)
-        .arg(&fetched2)
+    // Asking for git's presentation options overrides the configured tool,
+    // but they can't be combined with an explicit one.
+    let git_options = sub_args.word_diff || sub_args.context.is_some();
+    if git_options && sub_args.tool.is_some() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!(
+            "--word-diff and --context can't be used with --tool"
+        ));
+    }
+    let tool = sub_args.tool.as_deref().or_else(|| {
+        store
+            .as_ref()
+            .filter(|_| !git_options)?
+            .config
+            .diff
+            .tool
+            .as_deref()
+    });
+    let mut command = match tool {
+        Some(tool) => diff_tool_command(tool, &fetched1, &fetched2),
+        None => git_diff_command(sub_args, &fetched1, &fetched2),
+    };
+    command
         .status()
         .map_err(CommandError::CommandFailed)
         .into_diagnostic()?;
@@ -1524,12 +2952,51 @@ fn cmd_diff(out: &Arc<dyn Out>, cfg: &Config, sub_args: &DiffArgs) -> Result<(),
     Ok(())
 }
 
+/// The invocation of a user-configured diff `tool`, which is run by the shell
+/// (so that it can have arguments of its own) with the two directories after it.
+fn diff_tool_command(tool: &str, from: &Path, to: &Path) -> std::process::Command {
+    #[cfg(target_family = "unix")]
+    {
+        let mut command = std::process::Command::new("sh");
+        command
+            .arg("-c")
+            .arg(format!("{tool} \"$@\""))
+            .arg(tool)
+            .arg(from)
+            .arg(to);
+        command
+    }
+
+    #[cfg(not(target_family = "unix"))]
+    {
+        use std::os::windows::process::CommandExt;
+        let mut command = std::process::Command::new(user_shell(|var| std::env::var_os(var)));
+        command.arg("/C").raw_arg(tool).arg(from).arg(to);
+        command
+    }
+}
+
+/// The `git diff` invocation for displaying a diff to the user, with whatever
+/// presentation options they asked for.
+fn git_diff_command(sub_args: &DiffArgs, from: &Path, to: &Path) -> std::process::Command {
+    let mut command = std::process::Command::new("git");
+    command.arg("diff").arg("--no-index");
+    if sub_args.word_diff {
+        command.arg("--word-diff");
+    }
+    if let Some(context) = sub_args.context {
+        command.arg(format!("--unified={context}"));
+    }
+    command.arg(from).arg(to);
+    command
+}
+
 fn cmd_check(out: &Arc<dyn Out>, cfg: &Config, sub_args: &CheckArgs) -> Result<(), miette::Report> {
     // Run the checker to validate that the current set of deps is covered by the current cargo vet store
     trace!("vetting...");
 
     let mut store = Store::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let network = Network::acquire(cfg, Some(&store.config));
 
     if !cfg.cli.locked {
         // Try to update the foreign audits (imports)
@@ -1540,10 +3007,27 @@ fn cmd_check(out: &Arc<dyn Out>, cfg: &Config, sub_args: &CheckArgs) -> Result<(
 
         // Check if any of our first-parties are in the crates.io registry
         check_audit_as_crates_io(cfg, &store)?;
+    } else {
+        // We won't be fetching the imports, so make sure the ones we have are
+        // the ones config.toml asks for.
+        store.check_imports_lock()?;
+    }
+
+    // Full audits may record the hash of the source that was reviewed, which
+    // should match what cargo is actually going to build.
+    for mismatch in resolver::source_hash_mismatches(&cfg.metadata, &store) {
+        warn!(
+            "{}:{} doesn't match the source that was audited (audited {}, but {} is {})",
+            mismatch.package,
+            mismatch.version,
+            mismatch.audited,
+            mismatch.path.display(),
+            mismatch.actual
+        );
     }
 
     // DO THE THING!!!!
-    let report = resolver::resolve(
+    let mut report = resolver::resolve(
         &cfg.metadata,
         cfg.cli.filter_graph.as_ref(),
         &store,
@@ -1553,6 +3037,11 @@ fn cmd_check(out: &Arc<dyn Out>, cfg: &Config, sub_args: &CheckArgs) -> Result<(
             ResolveDepth::Deep
         },
     );
+    report.set_criteria_severity(&sub_args.deny, &sub_args.warn)?;
+    #[cfg(feature = "advisories")]
+    if let Some(advisory_db) = &sub_args.advisory_db {
+        annotate_advisories(&mut report, advisory_db)?;
+    }
 
     // Bare `cargo vet` shouldn't suggest in CI
     let suggest = if !cfg.cli.locked {
@@ -1561,11 +3050,61 @@ fn cmd_check(out: &Arc<dyn Out>, cfg: &Config, sub_args: &CheckArgs) -> Result<(
         None
     };
 
+    // Which exemptions are needed is only known if the check passed, and
+    // filtering the graph would make anything outside of it look unused.
+    let unused_exemptions =
+        if matches!(report.conclusion, Conclusion::Success(_)) && cfg.cli.filter_graph.is_none() {
+            report.unused_exemptions(&store.config.exemptions)
+        } else {
+            vec![]
+        };
+
     match cfg.cli.output_format {
-        OutputFormat::Human => report
-            .print_human(out, cfg, suggest.as_ref())
-            .into_diagnostic()?,
+        OutputFormat::Human => {
+            report
+                .print_human(out, cfg, suggest.as_ref())
+                .into_diagnostic()?;
+            print_feature_set_only_packages(out, cfg);
+            print_native_library_packages(out, cfg, &store);
+            if !unused_exemptions.is_empty() && !sub_args.deny_unused_exemptions {
+                print_unused_exemptions(out, &unused_exemptions);
+            }
+            if let Some(package) = &sub_args.explain {
+                writeln!(out);
+                report.print_explanation(out, package).into_diagnostic()?;
+            }
+        }
         OutputFormat::Json => report.print_json(out, cfg, suggest.as_ref())?,
+        OutputFormat::Html => report
+            .print_html(out, cfg, suggest.as_ref())
+            .into_diagnostic()?,
+        OutputFormat::Sarif => {
+            let config_path = cfg.metacfg.store_path().join(CONFIG_TOML);
+            let config_path = config_path.as_path_unlocked();
+            let config_text = std::fs::read_to_string(config_path).unwrap_or_default();
+            // Code scanning wants paths relative to the root of the repository
+            let config_uri = config_path
+                .strip_prefix(&cfg.metadata.workspace_root)
+                .unwrap_or(config_path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            report.print_sarif(out, &config_uri, &config_text)?;
+        }
+    }
+
+    if sub_args.deny_unused_exemptions && !unused_exemptions.is_empty() {
+        return Err(UnusedExemptionsErrors {
+            errors: unused_exemptions
+                .iter()
+                .map(|(package, entry)| UnusedExemptionError {
+                    package: (*package).clone(),
+                    version: entry.version.clone(),
+                })
+                .collect(),
+        }
+        .into());
     }
 
     // Only save imports if we succeeded, to avoid any modifications on error.
@@ -1580,68 +3119,970 @@ fn cmd_check(out: &Arc<dyn Out>, cfg: &Config, sub_args: &CheckArgs) -> Result<(
     Ok(())
 }
 
-fn cmd_fetch_imports(
-    out: &Arc<dyn Out>,
-    cfg: &Config,
-    _sub_args: &FetchImportsArgs,
+#[cfg(feature = "advisories")]
+fn annotate_advisories(
+    report: &mut resolver::ResolveReport,
+    path: &Path,
 ) -> Result<(), miette::Report> {
-    trace!("fetching imports...");
-
-    let mut store = Store::acquire(cfg)?;
-    let network = Network::acquire(cfg);
+    let db = advisories::AdvisoryDb::load(path)?;
+    report.annotate_advisories(&db);
+    Ok(())
+}
 
-    if let Some(network) = &network {
-        if !cfg.cli.locked {
-            tokio::runtime::Handle::current()
-                .block_on(store.fetch_foreign_audits(network, false))?;
-            store.commit()?;
-            return Ok(());
-        }
+fn print_unused_exemptions(out: &Arc<dyn Out>, unused: &[(&PackageName, &ExemptedDependency)]) {
+    writeln!(out);
+    writeln!(
+        out,
+        "warning: these exemptions aren't needed, `cargo vet prune` can remove them:"
+    );
+    for (package, entry) in unused {
+        let criteria = entry
+            .criteria
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(out, "  {package}:{} ({criteria})", entry.version);
     }
+}
 
-    // ERRORS: just a warning that you're holding it wrong, unclear if immediate or buffered,
-    // or if this should be a hard error, or if we should ignore the --locked flag and
-    // just do it anyway
+fn print_feature_set_only_packages(out: &Arc<dyn Out>, cfg: &Config) {
+    if cfg.feature_set_only_packages.is_empty() {
+        return;
+    }
+    writeln!(out);
     writeln!(
         out,
-        "warning: ran fetch-imports with --locked, this won't do anything!"
+        "note: these dependencies are only enabled by some of your feature-sets:"
     );
+    for package in &cfg.metadata.packages {
+        if let Some(feature_sets) = cfg.feature_set_only_packages.get(&package.id) {
+            writeln!(
+                out,
+                "  {}:{} ({})",
+                package.name,
+                package.version,
+                feature_sets.join(", ")
+            );
+        }
+    }
+}
 
-    Ok(())
+/// Native libraries are a category of risk that diffstats of Rust source don't
+/// capture well, so point out the third-party packages which link one, along
+/// with any libraries that several packages claim to link.
+fn print_native_library_packages(out: &Arc<dyn Out>, cfg: &Config, store: &Store) {
+    let native = foreign_packages(&cfg.metadata, &store.config)
+        .filter(|package| package.links.is_some())
+        .collect::<Vec<_>>();
+    if !native.is_empty() {
+        writeln!(out);
+        writeln!(
+            out,
+            "note: these dependencies link native libraries, which deserve extra scrutiny:"
+        );
+        for package in &native {
+            writeln!(
+                out,
+                "  {}:{} (links = {:?})",
+                package.name,
+                package.version,
+                package.links.as_deref().unwrap()
+            );
+        }
+    }
+
+    let mut by_library = SortedMap::<&str, Vec<&Package>>::new();
+    for package in &cfg.metadata.packages {
+        if let Some(links) = &package.links {
+            by_library.entry(links).or_default().push(package);
+        }
+    }
+    for (links, packages) in by_library {
+        if packages.len() < 2 {
+            continue;
+        }
+        writeln!(out);
+        writeln!(
+            out,
+            "warning: multiple packages link the native library {links:?}, which cargo can't build together:"
+        );
+        for package in packages {
+            writeln!(out, "  {}:{}", package.name, package.version);
+        }
+    }
 }
 
-fn cmd_dump_graph(
+fn cmd_fetch(
     out: &Arc<dyn Out>,
     cfg: &Config,
-    sub_args: &DumpGraphArgs,
+    _sub_args: &FetchArgs,
 ) -> Result<(), miette::Report> {
-    // Dump a mermaid-js graph
-    trace!("dumping...");
+    trace!("fetching imports and packages...");
 
-    let graph = resolver::DepGraph::new(&cfg.metadata, cfg.cli.filter_graph.as_ref(), None);
-    match cfg.cli.output_format {
-        OutputFormat::Human => graph.print_mermaid(out, sub_args).into_diagnostic()?,
-        OutputFormat::Json => {
-            serde_json::to_writer_pretty(&**out, &graph.nodes).into_diagnostic()?
-        }
+    let mut store = Store::acquire(cfg)?;
+    let network = Network::acquire(cfg, Some(&store.config))
+        .ok_or_else(|| miette!("fetch can't be run with --frozen, as it needs the network"))?;
+
+    if !cfg.cli.locked {
+        tokio::runtime::Handle::current().block_on(store.fetch_foreign_audits(&network, false))?;
     }
 
-    Ok(())
-}
+    fetch_all(out, cfg, &store, Some(&network))?;
 
-fn cmd_fmt(_out: &Arc<dyn Out>, cfg: &Config, _sub_args: &FmtArgs) -> Result<(), miette::Report> {
-    // Reformat all the files (just load and store them, formatting is implicit).
-    trace!("formatting...");
-    let store = Store::acquire(cfg)?;
-    store.commit()?;
+    if !cfg.cli.locked {
+        store.commit()?;
+    }
     Ok(())
 }
 
-/// Perform crimes on clap long_help to generate markdown docs
-fn cmd_help_md(
+/// Fetch every crates.io package in the graph, and everything `suggest` would
+/// need to fetch and diff (such as the audited versions it would suggest
+/// deltas from), so that later commands don't need the network.
+pub fn fetch_all(
     out: &Arc<dyn Out>,
-    _cfg: &PartialConfig,
-    _sub_args: &HelpMarkdownArgs,
+    cfg: &Config,
+    store: &Store,
+    network: Option<&Network>,
+) -> Result<(), miette::Report> {
+    let packages = foreign_packages(&cfg.metadata, &store.config)
+        .filter(|package| package.is_crates_io())
+        .collect::<Vec<_>>();
+
+    {
+        // Suggesting acquires the cache itself, so this must be released first.
+        let cache = Cache::acquire(cfg)?;
+        let progress = progress_bar(
+            "Fetching",
+            packages.len() as u64,
+            cfg.cli.output_file.is_some(),
+        );
+        let results = tokio::runtime::Handle::current().block_on(join_all(packages.iter().map(
+            |package| async {
+                progress.set_message(format!("{}:{}", package.name, package.version));
+                let result = cache
+                    .fetch_package(network, &package.name, &package.version)
+                    .await;
+                progress.inc(1);
+                result
+            },
+        )));
+        progress.finish_and_clear();
+
+        for result in results {
+            result.into_diagnostic()?;
+        }
+    }
+    writeln!(out, "Fetched {} packages", packages.len());
+
+    // Resolve the full tree as `suggest` would, which fetches and diffs what
+    // it would suggest, caching the diffstats along the way.
+    let suggest_store = store.clone_for_suggest(false);
+    let report = resolver::resolve(
+        &cfg.metadata,
+        cfg.cli.filter_graph.as_ref(),
+        &suggest_store,
+        ResolveDepth::Deep,
+    );
+    if let Some(suggest) = report.compute_suggest(cfg, network, true)? {
+        for (pkgidx, reason) in &suggest.failures {
+            let package = &report.graph.nodes[*pkgidx];
+            // There's nothing to fetch for these, so it's not a failure here.
+            if !package.is_from_other_registry {
+                warn!(
+                    "couldn't fetch what's needed to suggest audits for {}:{}: {reason}",
+                    package.name, package.version
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_fetch_imports(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    _sub_args: &FetchImportsArgs,
+) -> Result<(), miette::Report> {
+    trace!("fetching imports...");
+
+    let mut store = Store::acquire(cfg)?;
+    let network = Network::acquire(cfg, Some(&store.config));
+
+    if let Some(network) = &network {
+        if !cfg.cli.locked {
+            tokio::runtime::Handle::current()
+                .block_on(store.fetch_foreign_audits(network, false))?;
+            store.commit()?;
+            return Ok(());
+        }
+    }
+
+    // ERRORS: just a warning that you're holding it wrong, unclear if immediate or buffered,
+    // or if this should be a hard error, or if we should ignore the --locked flag and
+    // just do it anyway
+    writeln!(
+        out,
+        "warning: ran fetch-imports with --locked, this won't do anything!"
+    );
+
+    Ok(())
+}
+
+fn cmd_verify_imports_lock(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    _sub_args: &VerifyImportsLockArgs,
+) -> Result<(), miette::Report> {
+    trace!("verifying imports.lock...");
+
+    let mut store = Store::acquire(cfg)?;
+    let network = Network::acquire(cfg, Some(&store.config)).ok_or_else(|| {
+        miette!("verify-imports-lock needs to fetch imports, so can't be run with --frozen")
+    })?;
+
+    // Fetch into the store, but never commit it, so imports.lock is untouched
+    let locked = store.imports.clone();
+    tokio::runtime::Handle::current().block_on(store.fetch_foreign_audits(&network, true))?;
+
+    let differences = storage::imports_lock_differences(&locked, &store.imports);
+    if !differences.is_empty() {
+        writeln!(out, "imports.lock doesn't match the fetched imports:");
+        for difference in &differences {
+            writeln!(out, "  {difference}");
+        }
+        writeln!(out, "run `cargo vet fetch-imports` to update it");
+        panic_any(ExitPanic(-1));
+    }
+
+    writeln!(
+        out,
+        "imports.lock matches the fetched imports ({} imports)",
+        store.imports.audits.len()
+    );
+    Ok(())
+}
+
+fn cmd_verify(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    _sub_args: &VerifyArgs,
+) -> Result<(), miette::Report> {
+    trace!("verifying source hashes...");
+
+    let store = Store::acquire(cfg)?;
+    let cache = Cache::acquire(cfg)?;
+    let network = Network::acquire(cfg, Some(&store.config));
+
+    // Collect the hashes recorded for every foreign package we actually use,
+    // and can fetch from crates.io
+    let mut to_verify = Vec::new();
+    for package in foreign_packages(&cfg.metadata, &store.config) {
+        if !package.is_crates_io() {
+            continue;
+        }
+        let audits = store.audits.audits.get(&package.name);
+        for entry in audits.into_iter().flatten() {
+            if let (AuditKind::Full { version, .. }, Some(source_hash)) =
+                (&entry.kind, &entry.source_hash)
+            {
+                if *version == package.version {
+                    to_verify.push((package.name.clone(), version.clone(), source_hash.clone()));
+                }
+            }
+        }
+    }
+
+    if to_verify.is_empty() {
+        writeln!(
+            out,
+            "No audits with a source-hash apply to your dependencies"
+        );
+        return Ok(());
+    }
+
+    let mut mismatches = 0;
+    for (package, version, expected) in &to_verify {
+        let fetched = tokio::runtime::Handle::current()
+            .block_on(cache.fetch_package(network.as_ref(), package, version))
+            .into_diagnostic()?;
+        let actual = storage::hash_package_source(&fetched)
+            .into_diagnostic()
+            .wrap_err_with(|| format!("couldn't hash the source of {package}:{version}"))?;
+        if actual != *expected {
+            mismatches += 1;
+            writeln!(out, "{package}:{version} doesn't match the audited source");
+            writeln!(out, "  audited: {expected}");
+            writeln!(out, "  fetched: {actual}");
+        }
+    }
+
+    if mismatches != 0 {
+        writeln!(
+            out,
+            "Verification failed: {mismatches} of {} audited sources differ",
+            to_verify.len()
+        );
+        panic_any(ExitPanic(-1));
+    }
+
+    writeln!(out, "Verified {} audited sources", to_verify.len());
+    Ok(())
+}
+
+fn cmd_audits_of(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &AuditsOfArgs,
+) -> Result<(), miette::Report> {
+    let store = Store::acquire(cfg)?;
+    do_cmd_audits_of(out, cfg, sub_args, &store)
+}
+
+fn do_cmd_audits_of(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &AuditsOfArgs,
+    store: &Store,
+) -> Result<(), miette::Report> {
+    let needle = sub_args.who.to_lowercase();
+    let matches = store
+        .audits
+        .audits
+        .iter()
+        .flat_map(|(package, entries)| entries.iter().map(move |entry| (package, entry)))
+        .filter(|(_, entry)| {
+            entry
+                .who
+                .as_ref()
+                .map(|who| who.to_lowercase().contains(&needle))
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+
+    match cfg.cli.output_format {
+        OutputFormat::Human => {
+            if matches.is_empty() {
+                writeln!(out, "No audits by '{}'", sub_args.who);
+                return Ok(());
+            }
+            for (package, entry) in &matches {
+                let what = match &entry.kind {
+                    AuditKind::Full { version, .. } => format!("{package}:{version}"),
+                    AuditKind::Delta { delta, .. } => {
+                        format!("{package}:{} -> {}", delta.from, delta.to)
+                    }
+                    AuditKind::Violation { violation } => {
+                        format!("{package}:{violation} (violation)")
+                    }
+                };
+                let criteria = entry.criteria.iter().map(|c| &**c).collect::<Vec<_>>();
+                writeln!(
+                    out,
+                    "{what} {criteria:?} by {}",
+                    entry.who.as_deref().unwrap_or_default()
+                );
+                if let Some(notes) = &entry.notes {
+                    for line in notes.lines() {
+                        writeln!(out, "    {line}");
+                    }
+                }
+            }
+            writeln!(out);
+            writeln!(out, "{} audits by '{}'", matches.len(), sub_args.who);
+        }
+        OutputFormat::Json => {
+            let json = matches
+                .iter()
+                .map(|(package, entry)| {
+                    let mut json = serde_json::to_value(entry).into_diagnostic()?;
+                    if let Some(fields) = json.as_object_mut() {
+                        fields.retain(|_, value| !value.is_null());
+                    }
+                    json["package"] = serde_json::json!(package);
+                    Ok(json)
+                })
+                .collect::<Result<Vec<_>, miette::Report>>()?;
+            serde_json::to_writer_pretty(&**out, &json).into_diagnostic()?;
+        }
+        OutputFormat::Html => {
+            return Err(miette!("audits-of doesn't support --output-format=html"));
+        }
+        OutputFormat::Sarif => {
+            return Err(miette!("audits-of doesn't support --output-format=sarif"));
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_coverage(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    _sub_args: &CoverageArgs,
+) -> Result<(), miette::Report> {
+    let store = Store::acquire(cfg)?;
+    let report = resolver::resolve(
+        &cfg.metadata,
+        cfg.cli.filter_graph.as_ref(),
+        &store,
+        ResolveDepth::Deep,
+    );
+    let coverage = report.compute_coverage();
+
+    match cfg.cli.output_format {
+        OutputFormat::Human => coverage.print_human(out).into_diagnostic()?,
+        OutputFormat::Json => serde_json::to_writer_pretty(&**out, &coverage).into_diagnostic()?,
+        OutputFormat::Html => {
+            return Err(miette!("coverage doesn't support --output-format=html"));
+        }
+        OutputFormat::Sarif => {
+            return Err(miette!("coverage doesn't support --output-format=sarif"));
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_export_backlog(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &ExportBacklogArgs,
+) -> Result<(), miette::Report> {
+    let store = Store::acquire(cfg)?;
+    let report = resolver::resolve(
+        &cfg.metadata,
+        cfg.cli.filter_graph.as_ref(),
+        &store,
+        ResolveDepth::Deep,
+    );
+    do_cmd_export_backlog(out, sub_args, &store, &report)
+}
+
+/// One row of `export-backlog`
+#[derive(serde::Serialize)]
+struct BacklogEntry<'a> {
+    /// Either "exemption" or "failure"
+    kind: &'static str,
+    package: PackageStr<'a>,
+    version: &'a Version,
+    criteria: Vec<CriteriaStr<'a>>,
+    /// The exemption's own notes
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notes: Option<&'a str>,
+    /// The package's notes from the `notes` table
+    #[serde(skip_serializing_if = "Option::is_none")]
+    package_notes: Option<&'a str>,
+}
+
+fn do_cmd_export_backlog(
+    out: &Arc<dyn Out>,
+    sub_args: &ExportBacklogArgs,
+    store: &Store,
+    report: &resolver::ResolveReport,
+) -> Result<(), miette::Report> {
+    let package_notes = |package: PackageStr| store.config.notes.get(package).map(|n| &**n);
+
+    let mut entries = vec![];
+    for (package, exemptions) in &store.config.exemptions {
+        for exemption in exemptions {
+            entries.push(BacklogEntry {
+                kind: "exemption",
+                package,
+                version: &exemption.version,
+                criteria: exemption.criteria.iter().map(|c| &***c).collect(),
+                notes: exemption.notes.as_deref(),
+                package_notes: package_notes(package),
+            });
+        }
+    }
+    if let resolver::Conclusion::FailForVet(fail) = &report.conclusion {
+        for (&pkgidx, audit_failure) in &fail.failures {
+            let package = &report.graph.nodes[pkgidx];
+            entries.push(BacklogEntry {
+                kind: "failure",
+                package: package.name,
+                version: package.version,
+                criteria: report
+                    .criteria_mapper
+                    .all_criteria_names(&audit_failure.criteria_failures)
+                    .collect(),
+                notes: None,
+                package_notes: package_notes(package.name),
+            });
+        }
+    }
+
+    match sub_args.format {
+        BacklogFormat::Csv => {
+            fn csv_field(field: &str) -> String {
+                if field.contains(['"', ',', '\n', '\r']) {
+                    format!("\"{}\"", field.replace('"', "\"\""))
+                } else {
+                    field.to_owned()
+                }
+            }
+            writeln!(out, "kind,package,version,criteria,notes,package_notes");
+            for entry in &entries {
+                let fields = [
+                    entry.kind.to_owned(),
+                    entry.package.to_owned(),
+                    entry.version.to_string(),
+                    entry.criteria.join(", "),
+                    entry.notes.unwrap_or_default().to_owned(),
+                    entry.package_notes.unwrap_or_default().to_owned(),
+                ];
+                let row = fields
+                    .iter()
+                    .map(|field| csv_field(field))
+                    .collect::<Vec<_>>();
+                writeln!(out, "{}", row.join(","));
+            }
+        }
+        BacklogFormat::Json => {
+            serde_json::to_writer_pretty(&**out, &entries).into_diagnostic()?;
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_dump_graph(
+    out: &Arc<dyn Out>,
+    cfg: &Config,
+    sub_args: &DumpGraphArgs,
+) -> Result<(), miette::Report> {
+    // Dump a mermaid-js graph
+    trace!("dumping...");
+
+    if sub_args.resolution {
+        if cfg.cli.output_format != OutputFormat::Json {
+            return Err(miette!(
+                "dump-graph --resolution is only supported with --output-format=json"
+            ));
+        }
+        let store = Store::acquire(cfg)?;
+        let report = resolver::resolve(
+            &cfg.metadata,
+            cfg.cli.filter_graph.as_ref(),
+            &store,
+            ResolveDepth::Deep,
+        );
+        serde_json::to_writer_pretty(&**out, &report.resolution_json(&store)).into_diagnostic()?;
+        return Ok(());
+    }
+
+    // Which criteria are required depends on the policies in the store.
+    let store = if sub_args.criteria {
+        Some(Store::acquire(cfg)?)
+    } else {
+        None
+    };
+    let report = store.as_ref().map(|store| {
+        resolver::resolve(
+            &cfg.metadata,
+            cfg.cli.filter_graph.as_ref(),
+            store,
+            ResolveDepth::Shallow,
+        )
+    });
+    let required = report
+        .as_ref()
+        .zip(store.as_ref())
+        .map(|(report, store)| report.required_criteria(store));
+    let graph_without_store;
+    let graph = if let Some(report) = &report {
+        &report.graph
+    } else {
+        graph_without_store =
+            resolver::DepGraph::new(&cfg.metadata, cfg.cli.filter_graph.as_ref(), None);
+        &graph_without_store
+    };
+    match cfg.cli.output_format {
+        OutputFormat::Human => match sub_args.graph_format {
+            DumpGraphFormat::Mermaid => graph
+                .print_mermaid(out, sub_args, required.as_deref())
+                .into_diagnostic()?,
+            DumpGraphFormat::Dot => graph
+                .print_dot(out, sub_args, required.as_deref())
+                .into_diagnostic()?,
+        },
+        OutputFormat::Json => {
+            let mut nodes = serde_json::to_value(&graph.nodes).into_diagnostic()?;
+            if let (Some(required), serde_json::Value::Array(nodes)) = (&required, &mut nodes) {
+                for (node, required) in nodes.iter_mut().zip(required) {
+                    node["required_criteria"] = serde_json::json!(required);
+                }
+            }
+            serde_json::to_writer_pretty(&**out, &nodes).into_diagnostic()?
+        }
+        OutputFormat::Html => {
+            return Err(miette!("dump-graph doesn't support --output-format=html"));
+        }
+        OutputFormat::Sarif => {
+            return Err(miette!("dump-graph doesn't support --output-format=sarif"));
+        }
+    }
+
+    Ok(())
+}
+
+fn cmd_print_config(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    metacfg: &MetaConfig,
+    _sub_args: &PrintConfigArgs,
+) -> Result<(), miette::Report> {
+    let (version, version_source) = metacfg.version_with_source();
+    let (store_path, store_path_source) = metacfg.store_path_with_source();
+
+    match cfg.cli.output_format {
+        OutputFormat::Human => {
+            writeln!(out, "version = {version}  (from {version_source})");
+            writeln!(
+                out,
+                "store.path = {:?}  (from {store_path_source})",
+                store_path.display()
+            );
+            writeln!(out);
+            writeln!(out, "layers (later ones take priority):");
+            for instance in &metacfg.0 {
+                writeln!(out, "  {}", instance.source);
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "version": {
+                    "value": version,
+                    "source": version_source.to_string(),
+                },
+                "store_path": {
+                    "value": store_path,
+                    "source": store_path_source.to_string(),
+                },
+                "layers": metacfg.0.iter().map(|instance| instance.source.to_string()).collect::<Vec<_>>(),
+            });
+            serde_json::to_writer_pretty(&**out, &json).into_diagnostic()?;
+        }
+        OutputFormat::Html => {
+            return Err(miette!("print-config doesn't support --output-format=html"));
+        }
+        OutputFormat::Sarif => {
+            return Err(miette!(
+                "print-config doesn't support --output-format=sarif"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+enum DoctorStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+/// The result of one of `cargo vet doctor`'s checks
+struct DoctorCheck {
+    name: String,
+    status: DoctorStatus,
+    /// What we found (or what went wrong)
+    detail: String,
+    /// How to fix it, for failed checks
+    help: Option<String>,
+}
+
+impl DoctorCheck {
+    fn ok(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: DoctorStatus::Ok,
+            detail: detail.into(),
+            help: None,
+        }
+    }
+    fn failed(name: impl Into<String>, detail: impl Into<String>, help: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: DoctorStatus::Failed,
+            detail: detail.into(),
+            help: Some(help.into()),
+        }
+    }
+    fn skipped(name: impl Into<String>, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: DoctorStatus::Skipped,
+            detail: detail.into(),
+            help: None,
+        }
+    }
+}
+
+fn cmd_doctor(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    _sub_args: &DoctorArgs,
+) -> Result<(), miette::Report> {
+    let mut checks = Vec::new();
+
+    // git is used to show diffs with `diff --mode=local`, and for finding out
+    // who's certifying. Diffstats are computed without it.
+    let git = std::process::Command::new("git").arg("--version").output();
+    checks.push(match git {
+        Ok(output) if output.status.success() => {
+            DoctorCheck::ok("git", String::from_utf8_lossy(&output.stdout).trim())
+        }
+        Ok(output) => DoctorCheck::failed(
+            "git",
+            format!("`git --version` exited with {}", output.status),
+            "reinstall git, it's needed by `cargo vet diff --mode=local`",
+        ),
+        Err(e) => DoctorCheck::failed(
+            "git",
+            format!("couldn't run git: {e}"),
+            "install git and make sure it's on your PATH, it's needed by `cargo vet diff --mode=local`",
+        ),
+    });
+    checks.push(match get_user_info() {
+        Ok(UserInfo { username, email }) => {
+            DoctorCheck::ok("git identity", format!("{username} <{email}>"))
+        }
+        Err(e) => DoctorCheck::failed(
+            "git identity",
+            e.to_string(),
+            "set git's user.name and user.email, or pass --who to `cargo vet certify`",
+        ),
+    });
+
+    // $CARGO is how we find the cargo to run `cargo metadata` with
+    let cargo = std::env::var_os(CARGO_ENV);
+    checks.push(match &cargo {
+        Some(cargo) => match std::process::Command::new(cargo).arg("--version").output() {
+            Ok(output) if output.status.success() => DoctorCheck::ok(
+                "$CARGO",
+                format!(
+                    "{} ({})",
+                    Path::new(cargo).display(),
+                    String::from_utf8_lossy(&output.stdout).trim()
+                ),
+            ),
+            Ok(output) => DoctorCheck::failed(
+                "$CARGO",
+                format!(
+                    "`{} --version` exited with {}",
+                    Path::new(cargo).display(),
+                    output.status
+                ),
+                "make sure $CARGO points at a working cargo",
+            ),
+            Err(e) => DoctorCheck::failed(
+                "$CARGO",
+                format!("couldn't run {}: {e}", Path::new(cargo).display()),
+                "make sure $CARGO points at a working cargo",
+            ),
+        },
+        None => DoctorCheck::failed(
+            "$CARGO",
+            "$CARGO isn't set",
+            "run cargo vet as `cargo vet`, so that cargo sets $CARGO",
+        ),
+    });
+
+    // CARGO_HOME's registry is where we look for packages before downloading them
+    checks.push(match home::cargo_home() {
+        Ok(cargo_home) if cargo_home.is_dir() => {
+            DoctorCheck::ok("CARGO_HOME", cargo_home.display().to_string())
+        }
+        Ok(cargo_home) => DoctorCheck::failed(
+            "CARGO_HOME",
+            format!("{} doesn't exist", cargo_home.display()),
+            "set CARGO_HOME to the directory cargo keeps its registry in",
+        ),
+        Err(e) => DoctorCheck::failed(
+            "CARGO_HOME",
+            format!("couldn't resolve CARGO_HOME: {e}"),
+            "set CARGO_HOME to the directory cargo keeps its registry in",
+        ),
+    });
+    checks.push(match storage::find_cargo_registry() {
+        Ok(registry) if !registry.crates_io_srcs().is_empty() => DoctorCheck::ok(
+            "cargo registry",
+            registry
+                .crates_io_srcs()
+                .iter()
+                .map(|src| src.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        Ok(registry) => DoctorCheck::failed(
+            "cargo registry",
+            format!("{} doesn't exist", registry.src().display()),
+            "build your project with cargo to populate the registry, or packages will be downloaded instead",
+        ),
+        Err(e) => DoctorCheck::failed(
+            "cargo registry",
+            format!("couldn't find cargo's registry: {e}"),
+            "build your project with cargo to populate the registry, or packages will be downloaded instead",
+        ),
+    });
+
+    // Packages are unpacked and diffed via temporary files
+    let temp_dir = std::env::temp_dir();
+    checks.push(match tempfile::tempfile_in(&temp_dir) {
+        Ok(_) => DoctorCheck::ok("temporary directory", temp_dir.display().to_string()),
+        Err(e) => DoctorCheck::failed(
+            "temporary directory",
+            format!("couldn't write to {}: {e}", temp_dir.display()),
+            "set TMPDIR to a writable directory",
+        ),
+    });
+
+    // The project itself, and the imports it wants to fetch
+    let metadata = if cargo.is_some() {
+        let metadata = exec_cargo_metadata(&cfg.cli, &cargo_metadata_command(&cfg.cli));
+        checks.push(match &metadata {
+            Ok(metadata) => DoctorCheck::ok("cargo metadata", metadata.workspace_root.to_string()),
+            Err(e) => DoctorCheck::failed(
+                "cargo metadata",
+                e.to_string(),
+                "run cargo vet from inside your project, or pass --manifest-path",
+            ),
+        });
+        metadata.ok()
+    } else {
+        checks.push(DoctorCheck::skipped("cargo metadata", "$CARGO isn't set"));
+        None
+    };
+    if let Some(metadata) = metadata {
+        let config = compute_metacfg(&metadata, cfg.cli.store_path.as_deref()).map(|metacfg| {
+            Store::is_init(&metacfg).then(|| (Store::peek_config(&metacfg), metacfg.store_path()))
+        });
+        match config {
+            Err(e) => checks.push(DoctorCheck::failed(
+                "[metadata.vet]",
+                e.to_string(),
+                "fix the [metadata.vet] table in your Cargo.toml",
+            )),
+            Ok(None) => checks.push(DoctorCheck::skipped(
+                "store",
+                "not initialized, run `cargo vet init`",
+            )),
+            Ok(Some((Err(e), store_path))) => checks.push(DoctorCheck::failed(
+                "store",
+                e.to_string(),
+                format!(
+                    "fix {} (`cargo vet check-self` may say more)",
+                    store_path.join(storage::CONFIG_TOML).display()
+                ),
+            )),
+            Ok(Some((Ok(config), store_path))) => {
+                checks.push(DoctorCheck::ok(
+                    "store",
+                    store_path.as_path_unlocked().display().to_string(),
+                ));
+                let network = Network::acquire(cfg, Some(&config));
+                for (name, import) in &config.imports {
+                    let check_name = format!("import {name}");
+                    let network = if let Some(network) = &network {
+                        network
+                    } else {
+                        checks.push(DoctorCheck::skipped(check_name, "--frozen"));
+                        continue;
+                    };
+                    let result = url::Url::parse(import.source_url())
+                        .into_diagnostic()
+                        .and_then(|url| {
+                            if import.git.is_some() {
+                                tokio::runtime::Handle::current()
+                                    .block_on(storage::fetch_git_import(
+                                        network, name, import, url, None,
+                                    ))
+                                    .map(|_| ())
+                                    .into_diagnostic()
+                            } else {
+                                tokio::runtime::Handle::current()
+                                    .block_on(network.download(url))
+                                    .map(|_| ())
+                                    .into_diagnostic()
+                            }
+                        });
+                    checks.push(match result {
+                        Ok(_) => DoctorCheck::ok(check_name, import.source_url()),
+                        Err(e) => DoctorCheck::failed(
+                            check_name,
+                            format!("couldn't fetch {}: {e}", import.source_url()),
+                            "check your network connection and the import's url in config.toml, or pass --frozen to work offline",
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    let failed = checks
+        .iter()
+        .filter(|check| matches!(check.status, DoctorStatus::Failed))
+        .count();
+
+    match cfg.cli.output_format {
+        OutputFormat::Human => {
+            for check in &checks {
+                let status = match check.status {
+                    DoctorStatus::Ok => "ok",
+                    DoctorStatus::Failed => "FAILED",
+                    DoctorStatus::Skipped => "skipped",
+                };
+                writeln!(out, "{status:>7}  {}: {}", check.name, check.detail);
+                if let Some(help) = &check.help {
+                    writeln!(out, "           help: {help}");
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::json!({
+                "checks": checks.iter().map(|check| serde_json::json!({
+                    "name": check.name,
+                    "status": match check.status {
+                        DoctorStatus::Ok => "ok",
+                        DoctorStatus::Failed => "failed",
+                        DoctorStatus::Skipped => "skipped",
+                    },
+                    "detail": check.detail,
+                    "help": check.help,
+                })).collect::<Vec<_>>(),
+            });
+            serde_json::to_writer_pretty(&**out, &json).into_diagnostic()?;
+        }
+        OutputFormat::Html => {
+            return Err(miette!("doctor doesn't support --output-format=html"));
+        }
+        OutputFormat::Sarif => {
+            return Err(miette!("doctor doesn't support --output-format=sarif"));
+        }
+    }
+
+    if failed > 0 {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!("{failed} of {} checks failed", checks.len()));
+    }
+    Ok(())
+}
+
+fn cmd_fmt(_out: &Arc<dyn Out>, cfg: &Config, _sub_args: &FmtArgs) -> Result<(), miette::Report> {
+    // Reformat all the files (just load and store them, formatting is implicit).
+    trace!("formatting...");
+    let store = Store::acquire(cfg)?;
+    store.commit()?;
+    Ok(())
+}
+
+/// Perform crimes on clap long_help to generate markdown docs
+fn cmd_help_md(
+    out: &Arc<dyn Out>,
+    _cfg: &PartialConfig,
+    _sub_args: &HelpMarkdownArgs,
 ) -> Result<(), miette::Report> {
     let app_name = "cargo-vet";
     let pretty_app_name = "cargo vet";
@@ -1767,6 +4208,56 @@ fn cmd_help_md(
     Ok(())
 }
 
+fn cmd_check_self(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &CheckSelfArgs,
+) -> Result<(), miette::Report> {
+    let path = sub_args
+        .store_path
+        .clone()
+        .or_else(|| cfg.cli.store_path.clone())
+        .unwrap_or_else(|| PathBuf::from(storage::DEFAULT_STORE));
+    if !path.join(storage::CONFIG_TOML).exists() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!("No store found at {}", path.display()));
+    }
+
+    // Acquiring the store does all the validation
+    Store::acquire_path(&flock::Filesystem::new(path.clone()), cfg.lock_timeout())?;
+
+    writeln!(out, "Store at {} is consistent", path.display());
+    Ok(())
+}
+
+fn cmd_sign(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &SignArgs,
+) -> Result<(), miette::Report> {
+    let path = sub_args
+        .store_path
+        .clone()
+        .or_else(|| cfg.cli.store_path.clone())
+        .unwrap_or_else(|| PathBuf::from(storage::DEFAULT_STORE));
+    if !path.join(storage::CONFIG_TOML).exists() {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!("No store found at {}", path.display()));
+    }
+
+    // Make sure we're signing a valid store, and hold the lock while signing
+    // so that audits.toml can't change underneath us.
+    let _store = Store::acquire_path(&flock::Filesystem::new(path.clone()), cfg.lock_timeout())?;
+
+    // config.toml is signed too, as importers following our own imports trust
+    // the imports (and keys) it lists.
+    for file in [storage::AUDITS_TOML, storage::CONFIG_TOML] {
+        let signature = signing::sign(&sub_args.key, &path.join(file))?;
+        writeln!(out, "Wrote signature to {}", signature.display());
+    }
+    Ok(())
+}
+
 fn cmd_gc(
     out: &Arc<dyn Out>,
     cfg: &PartialConfig,
@@ -1784,6 +4275,12 @@ fn cmd_gc(
         return Ok(());
     }
 
+    if sub_args.clear_diff_cache {
+        let cleared = cache.clear_diff_cache();
+        writeln!(out, "cleared {cleared} diffstats from the diff-cache");
+        return Ok(());
+    }
+
     if sub_args.max_package_age_days.is_nan() {
         return Err(miette!("max package age cannot be NaN"));
     }
@@ -1795,6 +4292,141 @@ fn cmd_gc(
     Ok(())
 }
 
+fn cmd_explain_diff_cache(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &ExplainDiffCacheArgs,
+) -> Result<(), miette::Report> {
+    let cache = Cache::acquire(cfg)?;
+    let network = Network::acquire(cfg, None);
+
+    let diff_cache = cache.diff_cache();
+    if diff_cache.is_empty() {
+        writeln!(out, "The diff-cache is empty");
+        return Ok(());
+    }
+
+    let mut stale = 0;
+    for (package, diffs) in &diff_cache {
+        writeln!(out, "{package}:");
+        for (delta, diffstat) in diffs {
+            write!(
+                out,
+                "  {} -> {}: {} ({} lines)",
+                delta.from,
+                delta.to,
+                diffstat.raw.trim(),
+                diffstat.count
+            );
+            if sub_args.validate {
+                let fresh = tokio::runtime::Handle::current().block_on(cache.recompute_diffstat(
+                    network.as_ref(),
+                    package,
+                    delta,
+                ));
+                match fresh {
+                    Ok(fresh) if fresh.count == diffstat.count => write!(out, " [ok]"),
+                    Ok(fresh) => {
+                        stale += 1;
+                        write!(
+                            out,
+                            " [stale, now {} ({} lines)]",
+                            fresh.raw.trim(),
+                            fresh.count
+                        )
+                    }
+                    Err(e) => write!(out, " [couldn't recompute: {e}]"),
+                }
+            }
+            writeln!(out);
+        }
+    }
+
+    if sub_args.validate {
+        writeln!(out);
+        writeln!(out, "{stale} stale entries");
+    }
+    Ok(())
+}
+
+fn cmd_export_diff_cache(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &ExportDiffCacheArgs,
+) -> Result<(), miette::Report> {
+    let cache = Cache::acquire(cfg)?;
+    let diff_cache = cache.diff_cache();
+    let count = diff_cache.values().map(|diffs| diffs.len()).sum::<usize>();
+
+    storage::store_diff_cache_file(&sub_args.path, diff_cache)
+        .into_diagnostic()
+        .wrap_err_with(|| format!("couldn't write {}", sub_args.path.display()))?;
+
+    writeln!(
+        out,
+        "exported {count} diffstats to {}",
+        sub_args.path.display()
+    );
+    Ok(())
+}
+
+fn cmd_import_diff_cache(
+    out: &Arc<dyn Out>,
+    cfg: &PartialConfig,
+    sub_args: &ImportDiffCacheArgs,
+) -> Result<(), miette::Report> {
+    let cache = Cache::acquire(cfg)?;
+    let network = Network::acquire(cfg, None);
+
+    let mut imported = storage::load_diff_cache_file(&sub_args.path)
+        .wrap_err_with(|| format!("couldn't read {}", sub_args.path.display()))?;
+
+    let mut rejected = 0;
+    if sub_args.validate {
+        for (package, diffs) in &mut imported {
+            diffs.retain(|delta, diffstat| {
+                let fresh = tokio::runtime::Handle::current().block_on(cache.recompute_diffstat(
+                    network.as_ref(),
+                    package,
+                    delta,
+                ));
+                match fresh {
+                    Ok(fresh) if fresh.count == diffstat.count => true,
+                    Ok(fresh) => {
+                        warn!(
+                            "not importing {package} {} -> {}: it claims {} lines, but it's really {}",
+                            delta.from, delta.to, diffstat.count, fresh.count
+                        );
+                        rejected += 1;
+                        false
+                    }
+                    Err(e) => {
+                        warn!(
+                            "not importing {package} {} -> {}: couldn't recompute it: {e}",
+                            delta.from, delta.to
+                        );
+                        rejected += 1;
+                        false
+                    }
+                }
+            });
+        }
+    }
+
+    let (added, conflicting) = cache.merge_diff_cache(imported);
+    writeln!(out, "imported {added} new diffstats");
+    if conflicting > 0 {
+        writeln!(
+            out,
+            "skipped {conflicting} which disagreed with your diff-cache (see `cargo vet explain-diff-cache --validate`)"
+        );
+    }
+    if rejected > 0 {
+        writeln!(out, "rejected {rejected} which failed validation");
+    }
+    Ok(())
+}
+
 // Utils
 
 struct UserInfo {
@@ -1819,12 +4451,55 @@ fn get_user_info() -> Result<UserInfo, UserInfoError> {
             .map_err(CommandError::BadOutput)
     }
 
-    let username = get_git_config("user.name").map_err(UserInfoError::UserCommandFailed)?;
-    let email = get_git_config("user.email").map_err(UserInfoError::EmailCommandFailed)?;
+    // Like git itself, let $GIT_AUTHOR_NAME and $GIT_AUTHOR_EMAIL take priority
+    fn get_env_or_git_config(env_name: &str, value_name: &str) -> Result<String, CommandError> {
+        match std::env::var(env_name) {
+            Ok(value) if !value.trim().is_empty() => Ok(value.trim().to_string()),
+            _ => get_git_config(value_name),
+        }
+    }
+
+    let username = get_env_or_git_config("GIT_AUTHOR_NAME", "user.name")
+        .map_err(UserInfoError::UserCommandFailed)?;
+    let email = get_env_or_git_config("GIT_AUTHOR_EMAIL", "user.email")
+        .map_err(UserInfoError::EmailCommandFailed)?;
 
     Ok(UserInfo { username, email })
 }
 
+/// Get the `who` to record for a new audit entry, along with the name to use
+/// when addressing the user, preferring an explicit `--who`, then the git
+/// identity, then `$USER`, and finally a placeholder to be fixed up by hand.
+fn get_who(explicit: Option<&String>) -> (String, Option<String>) {
+    if let Some(who) = explicit {
+        return (who.clone(), Some(who.clone()));
+    }
+    let error = match get_user_info() {
+        Ok(user_info) => {
+            let who = format!("{} <{}>", user_info.username, user_info.email);
+            return (user_info.username, Some(who));
+        }
+        Err(e) => e,
+    };
+    match std::env::var("USER") {
+        Ok(user) if !user.trim().is_empty() => {
+            let user = user.trim().to_string();
+            warn!("couldn't get your git identity ({error}), recording the audit as '{user}'");
+            (user.clone(), Some(user))
+        }
+        _ => {
+            warn!(
+                "couldn't get your git identity ({error}), recording the audit as '{WHO_PLACEHOLDER}'; \
+                 set git's user.name and user.email, or pass --who"
+            );
+            (
+                WHO_PLACEHOLDER.to_string(),
+                Some(WHO_PLACEHOLDER.to_string()),
+            )
+        }
+    }
+}
+
 async fn eula_for_criteria(
     network: Option<&Network>,
     criteria_map: &SortedMap<CriteriaName, CriteriaEntry>,
@@ -1887,6 +4562,34 @@ async fn eula_for_criteria(
     format!("Could not download criteria description, it should be available at {url}")
 }
 
+/// Refuse to fetch a package which this project only gets from a registry
+/// other than crates.io. We can only fetch from crates.io, and its package of
+/// the same name and version would be different code.
+fn check_fetchable(
+    project: Option<&Config>,
+    package: PackageStr,
+    version: &Version,
+) -> Result<(), miette::Report> {
+    let project = match project {
+        Some(project) => project,
+        None => return Ok(()),
+    };
+    let mut matching = project
+        .metadata
+        .packages
+        .iter()
+        .filter(|p| p.name == package && p.version == *version)
+        .peekable();
+    if matching.peek().is_some() && matching.all(|p| p.is_from_other_registry()) {
+        // ERRORS: immediate fatal diagnostic
+        return Err(miette!(
+            "{package}:{version} is from a registry other than crates.io, which cargo vet \
+             can't fetch from; review its source from that registry instead"
+        ));
+    }
+    Ok(())
+}
+
 /// All third-party packages, with the audit-as-crates-io policy applied
 fn foreign_packages<'a>(
     metadata: &'a Metadata,
@@ -1896,21 +4599,20 @@ fn foreign_packages<'a>(
     metadata
         .packages
         .iter()
-        .filter(|package| package.is_third_party(&config.policy))
+        .filter(|package| package.is_third_party(config))
 }
 
 /// All first-party packages, **without** the audit-as-crates-io policy applied
 /// (because it's used for validating that field's value).
 fn first_party_packages_strict<'a>(
     metadata: &'a Metadata,
-    _config: &'a ConfigFile,
+    config: &'a ConfigFile,
 ) -> impl Iterator<Item = &'a Package> + 'a {
-    // Opposite of third-party, but with an empty `policy`
-    let empty_policy = SortedMap::new();
+    // Opposite of third-party, but ignoring the `policy`
     metadata
         .packages
         .iter()
-        .filter(move |package| !package.is_third_party(&empty_policy))
+        .filter(move |package| !package.is_from_third_party_registry(config))
 }
 
 fn check_audit_as_crates_io(cfg: &Config, store: &Store) -> Result<(), AuditAsErrors> {
@@ -1919,18 +4621,41 @@ fn check_audit_as_crates_io(cfg: &Config, store: &Store) -> Result<(), AuditAsEr
     })?;
     let mut needs_audit_as_entry = vec![];
     let mut shouldnt_be_audit_as = vec![];
+    let mut shares_crates_io_version = vec![];
+
+    let crates_io_versions = cfg
+        .metadata
+        .packages
+        .iter()
+        .filter(|package| package.is_crates_io())
+        .map(|package| (&package.name, &package.version))
+        .collect::<FastSet<_>>();
 
     'packages: for package in first_party_packages_strict(&cfg.metadata, &store.config) {
-        let audit_policy = store
-            .config
-            .policy
-            .get(&package.name)
+        let audit_policy = format::policy_for(&store.config.policy, &package.name)
             .and_then(|policy| policy.audit_as_crates_io);
         if audit_policy == Some(false) {
             // They've explicitly said this is first-party so we don't care about what's in the registry
             continue;
         }
 
+        if audit_policy == Some(true)
+            && crates_io_versions.contains(&(&package.name, &package.version))
+        {
+            // The real crates.io package is also in the graph, so this one
+            // can't be trusted because of audits of that one.
+            shares_crates_io_version.push(SharesCratesIoVersionError {
+                package: package.name.clone(),
+                version: package.version.clone(),
+                source_repr: package
+                    .source
+                    .as_ref()
+                    .map(|source| source.repr.clone())
+                    .unwrap_or_else(|| "a local path".to_owned()),
+            });
+            continue;
+        }
+
         if let Some(index_entry) = cache.query_package_from_index(&package.name) {
             if storage::exact_version(&index_entry, &package.version).is_some() {
                 // We found a version of this package in the registry!
@@ -1956,7 +4681,10 @@ fn check_audit_as_crates_io(cfg: &Config, store: &Store) -> Result<(), AuditAsEr
         }
     }
 
-    if !needs_audit_as_entry.is_empty() || !shouldnt_be_audit_as.is_empty() {
+    if !needs_audit_as_entry.is_empty()
+        || !shouldnt_be_audit_as.is_empty()
+        || !shares_crates_io_version.is_empty()
+    {
         let mut errors = vec![];
         if !needs_audit_as_entry.is_empty() {
             errors.push(AuditAsError::NeedsAuditAs(NeedsAuditAsErrors {
@@ -1968,6 +4696,13 @@ fn check_audit_as_crates_io(cfg: &Config, store: &Store) -> Result<(), AuditAsEr
                 errors: shouldnt_be_audit_as,
             }));
         }
+        if !shares_crates_io_version.is_empty() {
+            errors.push(AuditAsError::SharesCratesIoVersion(
+                SharesCratesIoVersionErrors {
+                    errors: shares_crates_io_version,
+                },
+            ));
+        }
         return Err(AuditAsErrors { errors });
     }
 
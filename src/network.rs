@@ -9,49 +9,153 @@
 
 use std::{
     ffi::{OsStr, OsString},
+    future::Future,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
-use reqwest::{Client, Url};
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+    Client, Proxy, RequestBuilder, Response, StatusCode, Url,
+};
+use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
+use tracing::{trace, warn};
 
-use crate::{errors::DownloadError, PartialConfig};
+use crate::{
+    errors::{CommandError, DownloadError, GitFetchError},
+    format::{ConfigFile, FastMap},
+    storage::{CACHE_GIT, CACHE_HTTP},
+    PartialConfig,
+};
 
 pub struct Network {
     /// The HTTP client all requests go through
     client: Client,
+    /// The proxy from `network.proxy`, which git has to be told about
+    /// separately.
+    git_proxy: Option<String>,
     /// Semaphore preventing exceeding the maximum number of connections.
     connection_semaphore: tokio::sync::Semaphore,
+    /// Where responses for [`Network::download_cached`][] are kept, if anywhere.
+    http_cache_dir: Option<PathBuf>,
+    /// Where repositories for [`Network::fetch_git_files`][] are kept, if
+    /// anywhere. Without one, each fetch clones into a temporary directory.
+    git_cache_dir: Option<PathBuf>,
+    /// A lock for each cached repository, so that imports from the same
+    /// repository don't fetch into it at the same time.
+    git_locks: Mutex<FastMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>,
 }
 
-static DEFAULT_TIMEOUT_SECS: u64 = 60;
+/// Which commit of a git repository to fetch.
+#[derive(Clone, Copy, Debug)]
+pub enum GitReference<'a> {
+    /// Whatever the remote's `HEAD` points to
+    DefaultBranch,
+    /// The tip of a branch
+    Branch(&'a str),
+    /// Anything `git rev-parse` understands, like a commit hash or a tag
+    Rev(&'a str),
+}
+
+/// The validators of a cached response, sent back to the server to check
+/// whether our copy is still up to date.
+#[derive(Serialize, Deserialize)]
+struct CacheValidators {
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    last_modified: Option<String>,
+}
 
 const MAX_CONCURRENT_CONNECTIONS: usize = 40;
 
+/// How many times a request is tried before giving up on a transient failure.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// How long to wait before the first retry. Each retry waits twice as long as
+/// the last.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// How long to wait for a request if neither `--network-timeout` nor the
+/// store's `network.timeout` says otherwise.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
 impl Network {
     /// Acquire access to the network
     ///
     /// There should only ever be one Network instance instantiated. Do it early
     /// and then pass it around by-ref.
-    pub fn acquire(cfg: &PartialConfig) -> Option<Self> {
+    ///
+    /// Requests go through the proxies given by the usual environment
+    /// variables, unless the store's config.toml sets `network.proxy`.
+    pub fn acquire(cfg: &PartialConfig, config: Option<&ConfigFile>) -> Option<Self> {
         if cfg.cli.frozen {
             None
         } else {
-            // TODO: make this configurable on the CLI or something
-            let timeout = Duration::from_secs(DEFAULT_TIMEOUT_SECS);
-            // TODO: make this configurable on the CLI or something
-            let client = Client::builder()
-                .timeout(timeout)
-                .build()
-                .expect("Couldn't construct HTTP Client?");
+            let timeout = request_timeout(cfg, config);
+            let mut builder = Client::builder().connect_timeout(timeout).timeout(timeout);
+            let mut git_proxy = None;
+            if let Some(proxy) = config.and_then(|config| config.network.proxy.as_ref()) {
+                // This is normally validated when the store is loaded, but
+                // `cargo vet doctor` gets here with a store that isn't.
+                match Proxy::all(proxy) {
+                    Ok(valid) => {
+                        builder = builder.proxy(valid);
+                        git_proxy = Some(proxy.clone());
+                    }
+                    Err(error) => warn!("ignoring invalid network.proxy {:?}: {}", proxy, error),
+                }
+            }
+            let client = builder.build().expect("Couldn't construct HTTP Client?");
+            // Unit tests shouldn't touch the real cache
+            let (http_cache_dir, git_cache_dir) = if cfg.mock_cache {
+                (None, None)
+            } else {
+                (
+                    Some(cfg.cache_dir.join(CACHE_HTTP)),
+                    Some(cfg.cache_dir.join(CACHE_GIT)),
+                )
+            };
             Some(Self {
                 client,
+                git_proxy,
                 connection_semaphore: tokio::sync::Semaphore::new(MAX_CONCURRENT_CONNECTIONS),
+                http_cache_dir,
+                git_cache_dir,
+                git_locks: Mutex::new(FastMap::new()),
             })
         }
     }
 
+    /// Get a Network which caches to the given directory, for unit tests.
+    #[cfg(test)]
+    pub fn mock_with_http_cache(http_cache_dir: PathBuf) -> Self {
+        Self {
+            // Tests only talk to local servers, so don't go through any proxy
+            client: Client::builder().no_proxy().build().unwrap(),
+            git_proxy: None,
+            connection_semaphore: tokio::sync::Semaphore::new(MAX_CONCURRENT_CONNECTIONS),
+            http_cache_dir: Some(http_cache_dir),
+            git_cache_dir: None,
+            git_locks: Mutex::new(FastMap::new()),
+        }
+    }
+
+    /// Get a Network which also keeps git repositories in the given directory,
+    /// for unit tests.
+    #[cfg(test)]
+    pub fn mock_with_git_cache(http_cache_dir: PathBuf, git_cache_dir: PathBuf) -> Self {
+        Self {
+            git_cache_dir: Some(git_cache_dir),
+            ..Self::mock_with_http_cache(http_cache_dir)
+        }
+    }
+
     /// Download a file and persist it to disk
     pub async fn download_and_persist(
         &self,
@@ -69,41 +173,38 @@ impl Network {
                 .await
                 .expect("Semaphore dropped?!");
 
-            let mut res = self
-                .client
-                .get(url.clone())
-                .send()
-                .await
-                .and_then(|res| res.error_for_status())
-                .map_err(|error| DownloadError::FailedToStartDownload {
-                    url: url.clone(),
-                    error,
-                })?;
-
-            let mut download_tmp =
-                tokio::fs::File::create(&download_tmp_path)
-                    .await
-                    .map_err(|error| DownloadError::FailedToCreateDownload {
-                        target: download_tmp_path.clone(),
-                        error,
-                    })?;
-            while let Some(chunk) =
-                res.chunk()
-                    .await
-                    .map_err(|error| DownloadError::FailedToReadDownload {
-                        url: url.clone(),
-                        error,
-                    })?
-            {
-                let network_bytes = &chunk[..];
-                download_tmp
-                    .write_all(network_bytes)
-                    .await
-                    .map_err(|error| DownloadError::FailedToWriteDownload {
-                        target: download_tmp_path.clone(),
-                        error,
-                    })?;
-            }
+            let (url, download_tmp_path) = (&url, &download_tmp_path);
+            self.fetch(
+                url,
+                || self.client.get(url.clone()),
+                |mut res| async move {
+                    let mut download_tmp = tokio::fs::File::create(&download_tmp_path)
+                        .await
+                        .map_err(|error| DownloadError::FailedToCreateDownload {
+                            target: download_tmp_path.clone(),
+                            error,
+                        })?;
+                    while let Some(chunk) =
+                        res.chunk()
+                            .await
+                            .map_err(|error| DownloadError::FailedToReadDownload {
+                                url: url.clone(),
+                                error,
+                            })?
+                    {
+                        let network_bytes = &chunk[..];
+                        download_tmp
+                            .write_all(network_bytes)
+                            .await
+                            .map_err(|error| DownloadError::FailedToWriteDownload {
+                                target: download_tmp_path.clone(),
+                                error,
+                            })?;
+                    }
+                    Ok(())
+                },
+            )
+            .await?;
         }
 
         // Rename the downloaded file into the final location.
@@ -122,37 +223,403 @@ impl Network {
     }
 
     /// Download a file into memory
+    ///
+    /// `file://` urls (which imports may use) are read straight from disk.
     pub async fn download(&self, url: Url) -> Result<Vec<u8>, DownloadError> {
+        if url.scheme() == "file" {
+            return read_file(&url).await;
+        }
+
         let _permit = self
             .connection_semaphore
             .acquire()
             .await
             .expect("Semaphore dropped?!");
 
-        let mut res = self
-            .client
-            .get(url.clone())
-            .send()
+        self.fetch(
+            &url,
+            || self.client.get(url.clone()),
+            |res| read_body(res, &url),
+        )
+        .await
+    }
+
+    /// Download a file into memory, keeping a copy in the cache.
+    ///
+    /// If we already have a copy, it's revalidated with a conditional request
+    /// (`If-None-Match`/`If-Modified-Since`) and reused if the server says it
+    /// hasn't changed. Servers which don't support this just send the whole
+    /// file again, and any trouble with the cache itself is only a warning.
+    pub async fn download_cached(&self, url: Url) -> Result<Vec<u8>, DownloadError> {
+        let cache_dir = match &self.http_cache_dir {
+            // There's no point caching a local file
+            Some(cache_dir) if url.scheme() != "file" => cache_dir,
+            _ => return self.download(url).await,
+        };
+        let key = cache_key(&url);
+        let body_path = cache_dir.join(&key);
+        let validators_path = cache_dir.join(format!("{key}.json"));
+        let cached = read_cached(&url, &body_path, &validators_path).await;
+
+        let _permit = self
+            .connection_semaphore
+            .acquire()
             .await
-            .and_then(|res| res.error_for_status())
-            .map_err(|error| DownloadError::FailedToStartDownload {
-                url: url.clone(),
-                error,
-            })?;
+            .expect("Semaphore dropped?!");
 
-        let mut output = vec![];
-        while let Some(chunk) =
-            res.chunk()
+        let request = || {
+            let mut request = self.client.get(url.clone());
+            if let Some((validators, _)) = &cached {
+                if let Some(etag) = &validators.etag {
+                    request = request.header(IF_NONE_MATCH, etag);
+                }
+                if let Some(last_modified) = &validators.last_modified {
+                    request = request.header(IF_MODIFIED_SINCE, last_modified);
+                }
+            }
+            request
+        };
+        let (url, cached) = (&url, &cached);
+        let read = |res: Response| async move {
+            if res.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+                return Ok(None);
+            }
+            let header = |name| {
+                res.headers()
+                    .get(name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| value.to_owned())
+            };
+            let validators = CacheValidators {
+                url: url.to_string(),
+                etag: header(ETAG),
+                last_modified: header(LAST_MODIFIED),
+            };
+            Ok(Some((validators, read_body(res, url).await?)))
+        };
+        let (validators, output) = match self.fetch(url, request, read).await? {
+            Some(fetched) => fetched,
+            None => {
+                trace!("{} not modified, using the cached copy", url);
+                let (_, body) = cached.as_ref().unwrap();
+                return Ok(body.clone());
+            }
+        };
+
+        if let Err(error) = write_cached(
+            cache_dir,
+            &body_path,
+            &validators_path,
+            &validators,
+            &output,
+        )
+        .await
+        {
+            warn!("couldn't cache the download of {}: {}", url, error);
+        }
+
+        Ok(output)
+    }
+
+    /// Send the request made by `request` and `read` its response, retrying
+    /// transient failures.
+    ///
+    /// Timeouts, lost connections (even partway through the body) and server
+    /// errors (5xx) are retried up to [`MAX_ATTEMPTS`][] times, backing off
+    /// exponentially between attempts. Anything else, like a 404, fails
+    /// straight away.
+    async fn fetch<T, F>(
+        &self,
+        url: &Url,
+        request: impl Fn() -> RequestBuilder,
+        read: impl Fn(Response) -> F,
+    ) -> Result<T, DownloadError>
+    where
+        F: Future<Output = Result<T, DownloadError>>,
+    {
+        let mut delay = INITIAL_RETRY_DELAY;
+        let mut attempt = 1;
+        loop {
+            let result = match request()
+                .send()
                 .await
-                .map_err(|error| DownloadError::FailedToReadDownload {
+                .and_then(|res| res.error_for_status())
+            {
+                Ok(res) => read(res).await,
+                Err(error) => Err(DownloadError::FailedToStartDownload {
                     url: url.clone(),
                     error,
-                })?
-        {
-            let network_bytes = &chunk[..];
-            output.extend_from_slice(network_bytes);
+                }),
+            };
+            match result {
+                Err(
+                    DownloadError::FailedToStartDownload { error, .. }
+                    | DownloadError::FailedToReadDownload { error, .. },
+                ) if attempt < MAX_ATTEMPTS && is_transient(&error) => {
+                    warn!(
+                        "fetching {} failed, retrying in {:?}: {}",
+                        url, delay, error
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                result => return result,
+            }
         }
+    }
 
-        Ok(output)
+    /// Fetch `paths` from the given commit of a git repository, returning the
+    /// full hash of that commit along with the files' contents (in the same
+    /// order as `paths`).
+    ///
+    /// Repositories are kept (bare) in the cache, so a `rev` which is a full
+    /// commit hash that has already been fetched doesn't hit the network
+    /// again. Branches and tags can move, so they're always fetched.
+    pub async fn fetch_git_files(
+        &self,
+        repo: &Url,
+        reference: GitReference<'_>,
+        paths: &[&str],
+    ) -> Result<(String, Vec<Vec<u8>>), GitFetchError> {
+        let _tempdir;
+        let git_dir = if let Some(cache_dir) = &self.git_cache_dir {
+            cache_dir.join(cache_key(repo))
+        } else {
+            _tempdir = tempfile::tempdir().map_err(|error| GitFetchError::CreateCheckout {
+                target: std::env::temp_dir(),
+                error,
+            })?;
+            _tempdir.path().to_owned()
+        };
+
+        // Several imports may share a repository, and fetching into it at the
+        // same time would race on its refs.
+        let repo_lock = self
+            .git_locks
+            .lock()
+            .unwrap()
+            .entry(git_dir.clone())
+            .or_default()
+            .clone();
+        let _repo_guard = repo_lock.lock().await;
+
+        if !git_dir.join("HEAD").exists() {
+            tokio::fs::create_dir_all(&git_dir).await.map_err(|error| {
+                GitFetchError::CreateCheckout {
+                    target: git_dir.clone(),
+                    error,
+                }
+            })?;
+            self.git(&git_dir, &["init", "--quiet", "--bare"]).await?;
+        }
+
+        let commit = {
+            let _permit = self
+                .connection_semaphore
+                .acquire()
+                .await
+                .expect("Semaphore dropped?!");
+            let verify = |rev: &str| format!("{rev}^{{commit}}");
+            match reference {
+                GitReference::Rev(rev) => {
+                    // A pinned commit we already have doesn't need fetching.
+                    let cached = if is_commit_hash(rev) {
+                        self.git(
+                            &git_dir,
+                            &["rev-parse", "--verify", "--quiet", &verify(rev)],
+                        )
+                        .await
+                        .ok()
+                    } else {
+                        None
+                    };
+                    match cached {
+                        Some(commit) => commit,
+                        None => {
+                            self.git(
+                                &git_dir,
+                                &[
+                                    "fetch",
+                                    "--quiet",
+                                    "--force",
+                                    repo.as_str(),
+                                    "+refs/heads/*:refs/heads/*",
+                                    "+refs/tags/*:refs/tags/*",
+                                ],
+                            )
+                            .await?;
+                            self.git(&git_dir, &["rev-parse", "--verify", &verify(rev)])
+                                .await?
+                        }
+                    }
+                }
+                GitReference::Branch(branch) => {
+                    // Fetch into a ref of our own rather than reading
+                    // FETCH_HEAD, which any fetch overwrites.
+                    let local = format!("refs/cargo-vet/heads/{branch}");
+                    let refspec = format!("+refs/heads/{branch}:{local}");
+                    self.git(&git_dir, &["fetch", "--quiet", repo.as_str(), &refspec])
+                        .await?;
+                    self.git(&git_dir, &["rev-parse", "--verify", &verify(&local)])
+                        .await?
+                }
+                GitReference::DefaultBranch => {
+                    let local = "refs/cargo-vet/HEAD";
+                    let refspec = format!("+HEAD:{local}");
+                    self.git(&git_dir, &["fetch", "--quiet", repo.as_str(), &refspec])
+                        .await?;
+                    self.git(&git_dir, &["rev-parse", "--verify", &verify(local)])
+                        .await?
+                }
+            }
+        };
+        let commit = String::from_utf8(commit)
+            .map_err(CommandError::BadOutput)?
+            .trim()
+            .to_owned();
+
+        let mut contents = Vec::with_capacity(paths.len());
+        for path in paths {
+            contents.push(
+                self.git(&git_dir, &["show", &format!("{commit}:{path}")])
+                    .await?,
+            );
+        }
+        Ok((commit, contents))
+    }
+
+    /// Run git against the (bare) repository at `git_dir`, returning its
+    /// stdout.
+    async fn git(&self, git_dir: &Path, args: &[&str]) -> Result<Vec<u8>, GitFetchError> {
+        let mut command = tokio::process::Command::new("git");
+        if let Some(proxy) = &self.git_proxy {
+            command.arg("-c").arg(format!("http.proxy={proxy}"));
+        }
+        let out = command
+            .arg("--git-dir")
+            .arg(git_dir)
+            .args(args)
+            .stdin(std::process::Stdio::null())
+            .output()
+            .await
+            .map_err(CommandError::CommandFailed)?;
+        if !out.status.success() {
+            return Err(GitFetchError::GitFailed {
+                args: args.join(" "),
+                stderr: String::from_utf8_lossy(&out.stderr).trim().to_owned(),
+            });
+        }
+        Ok(out.stdout)
+    }
+}
+
+/// How long to wait for each request: `--network-timeout`, or the store's
+/// `network.timeout`, or [`DEFAULT_TIMEOUT`][].
+pub fn request_timeout(cfg: &PartialConfig, config: Option<&ConfigFile>) -> Duration {
+    cfg.cli
+        .network_timeout
+        .or_else(|| config.and_then(|config| config.network.timeout))
+        .map_or(DEFAULT_TIMEOUT, Duration::from_secs)
+}
+
+/// Whether `rev` is a full (SHA-1 or SHA-256) commit hash, rather than a name
+/// which might point somewhere else the next time it's fetched.
+fn is_commit_hash(rev: &str) -> bool {
+    (rev.len() == 40 || rev.len() == 64) && rev.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Whether a failed request might succeed if it's tried again
+fn is_transient(error: &reqwest::Error) -> bool {
+    match error.status() {
+        Some(status) => status.is_server_error(),
+        None => error.is_timeout() || error.is_connect() || error.is_request() || error.is_body(),
+    }
+}
+
+/// Read the whole body of a response into memory
+async fn read_body(mut res: Response, url: &Url) -> Result<Vec<u8>, DownloadError> {
+    let mut output = vec![];
+    while let Some(chunk) =
+        res.chunk()
+            .await
+            .map_err(|error| DownloadError::FailedToReadDownload {
+                url: url.clone(),
+                error,
+            })?
+    {
+        let network_bytes = &chunk[..];
+        output.extend_from_slice(network_bytes);
+    }
+
+    Ok(output)
+}
+
+/// Read the file a `file://` url points to
+async fn read_file(url: &Url) -> Result<Vec<u8>, DownloadError> {
+    let error = |error| DownloadError::FailedToReadFile {
+        url: url.clone(),
+        error,
+    };
+    let path = url.to_file_path().map_err(|()| {
+        error(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "not a local path",
+        ))
+    })?;
+    tokio::fs::read(path).await.map_err(error)
+}
+
+/// The name of the cache entry for a url (a hex SHA-256 of the url)
+fn cache_key(url: &Url) -> String {
+    ring::digest::digest(&ring::digest::SHA256, url.as_str().as_bytes())
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Load a previously cached response for `url`, if we have a usable one
+async fn read_cached(
+    url: &Url,
+    body_path: &Path,
+    validators_path: &Path,
+) -> Option<(CacheValidators, Vec<u8>)> {
+    let validators = tokio::fs::read(validators_path).await.ok()?;
+    let validators: CacheValidators = serde_json::from_slice(&validators).ok()?;
+    if validators.url != url.as_str() {
+        return None;
+    }
+    let body = tokio::fs::read(body_path).await.ok()?;
+    Some((validators, body))
+}
+
+/// Cache a response, if it came with any validators to revalidate it with later
+async fn write_cached(
+    cache_dir: &Path,
+    body_path: &Path,
+    validators_path: &Path,
+    validators: &CacheValidators,
+    body: &[u8],
+) -> std::io::Result<()> {
+    // Remove the validators first so that an interrupted write can't pair
+    // them with the wrong body.
+    match tokio::fs::remove_file(validators_path).await {
+        Ok(()) => {}
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+        Err(error) => return Err(error),
+    }
+    if validators.etag.is_none() && validators.last_modified.is_none() {
+        let _ = tokio::fs::remove_file(body_path).await;
+        return Ok(());
+    }
+
+    tokio::fs::create_dir_all(cache_dir).await?;
+    let validators = serde_json::to_vec(validators)?;
+    for (path, contents) in [(body_path, body), (validators_path, &validators[..])] {
+        let tmp_path = PathBuf::from(OsString::from_iter([path.as_os_str(), OsStr::new(".part")]));
+        tokio::fs::write(&tmp_path, contents).await?;
+        tokio::fs::rename(&tmp_path, path).await?;
     }
+    Ok(())
 }
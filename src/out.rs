@@ -6,6 +6,7 @@
 
 use crate::editor::Editor;
 use console::{Style, Term};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::{fmt, fs::File, io};
 
 /// Object-safe extension of `std::io::Write` with extra features for
@@ -107,3 +108,19 @@ impl io::Write for &'_ dyn Out {
         Ok(())
     }
 }
+
+/// Create a progress bar for a long-running operation of `len` steps, drawn on
+/// stderr with `prefix` in front of it.
+///
+/// It isn't drawn if stderr isn't a terminal, or if `hidden` (which callers
+/// set when output is going to a file with `--output-file`).
+pub fn progress_bar(prefix: &'static str, len: u64, hidden: bool) -> ProgressBar {
+    if hidden {
+        return ProgressBar::hidden();
+    }
+    let style =
+        ProgressStyle::with_template("{prefix:>12.bold.cyan} [{bar:25}] {pos}/{len}: {wide_msg}")
+            .expect("invalid progress bar template")
+            .progress_chars("=> ");
+    ProgressBar::new(len).with_style(style).with_prefix(prefix)
+}
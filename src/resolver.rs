@@ -33,10 +33,19 @@
 //!         * as with third-parties, this is done per-criteria so we can granularly blame deps
 //!     * if there is a policy.dependency_criteria, then that dep isn't inherited normally
 //!       and is instead effectively no_criteria or all_criteria based on whether it passes or not
+//!     * build-deps are checked the same way, except that policy.build_dependency_criteria
+//!       takes priority over policy.dependency_criteria
+//!     * dev-deps are checked the same way (against policy.dev_criteria), except that
+//!       policy.dev_dependency_criteria takes priority over policy.dependency_criteria
 //!
 //! * resolve_self_policy: if there is a policy.criteria (or it's a root), then we check
 //!   the resolved criteria against that policy
-//!     * on success, we set ourselves to all_criteria
+//!     * with config.dev_safe_to_run, dev-only packages without a policy.criteria
+//!       get an implicit policy of safe-to-run
+//!     * with config.proc_macro_criteria, third-party proc-macros without a policy.criteria
+//!       must also satisfy those criteria, on top of whatever their dependents require
+//!     * on success, we set ourselves to all_criteria (unless the only policy was the
+//!       proc-macro one, which doesn't replace our dependents' requirements)
 //!     * on failure, we set ourselves to no_criteria
 //!     * **This is the check that matters!** Anything that fails this check is registered
 //!       as a "root (policy) failure" and will be fed into the blame phase.
@@ -61,27 +70,29 @@
 //! * take the blame and do a huge pile of diffstats on the reachable versions
 //!   (from search_for_path) to figure out which audits to recommend for which criteria
 
-use cargo_metadata::{DependencyKind, Metadata, Node, PackageId, Version};
+use cargo_metadata::{DependencyKind, Metadata, Node, Package, PackageId, Version};
 use core::fmt;
 use futures_util::future::join_all;
 use miette::IntoDiagnostic;
 use serde::Serialize;
 use serde_json::json;
+use std::ops::Bound;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tracing::{error, trace, trace_span, warn};
+use tracing::{trace, trace_span, warn};
 
 use crate::errors::SuggestError;
 use crate::format::{
-    self, AuditKind, CriteriaName, CriteriaStr, Delta, DiffStat, ExemptedDependency, ImportName,
-    PackageName, PackageStr, PolicyEntry,
+    self, package_glob_matches, AuditKind, CriteriaName, CriteriaStr, Delta, DiffStat,
+    ExemptedDependency, ImportName, OwnerName, PackageName, PackageStr,
 };
 use crate::format::{FastMap, FastSet, SortedMap, SortedSet};
 use crate::network::Network;
-use crate::out::Out;
+use crate::out::{progress_bar, Out};
 use crate::serialization::spanned::Spanned;
 use crate::{
-    AuditEntry, Cache, Config, CriteriaEntry, DumpGraphArgs, GraphFilter, GraphFilterProperty,
-    GraphFilterQuery, PackageExt, Store,
+    AuditEntry, Cache, Config, ConfigFile, CriteriaEntry, DumpGraphArgs, DumpGraphDepth,
+    GraphFilter, GraphFilterProperty, GraphFilterQuery, PackageExt, PreferAuditedVersion, Store,
 };
 
 /// A report of the results of running `resolve`.
@@ -93,6 +104,8 @@ pub struct ResolveReport<'a> {
     pub graph: DepGraph<'a>,
     /// Mappings between criteria names and CriteriaSets/Indices.
     pub criteria_mapper: CriteriaMapper,
+    /// Per-package notes from the `notes` table in config.toml.
+    pub notes: &'a SortedMap<PackageName, String>,
 
     /// Low-level results for each package's individual criteria resolving analysis,
     /// indexed by [`PackageIdx`][].
@@ -100,6 +113,14 @@ pub struct ResolveReport<'a> {
 
     /// The final conclusion of our analysis.
     pub conclusion: Conclusion,
+
+    /// Criteria which, when missing, are only warned about instead of being
+    /// errors (see [`ResolveReport::set_criteria_severity`]).
+    pub warned_criteria: CriteriaSet,
+
+    /// The ids of the known advisories affecting each third-party package,
+    /// which are only looked up with the `advisories` feature.
+    pub advisories: SortedMap<PackageIdx, Vec<String>>,
 }
 
 #[derive(Debug, Clone)]
@@ -147,7 +168,7 @@ pub enum ViolationConflict {
     },
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum AuditSource {
     OwnAudits,
     Foreign(ImportName),
@@ -158,6 +179,13 @@ pub struct Suggest {
     pub suggestions: Vec<SuggestItem>,
     pub suggestions_by_criteria: SortedMap<CriteriaName, Vec<SuggestItem>>,
     pub total_lines: u64,
+    /// How many suggestions were left out to fit within a review budget
+    pub deferred: usize,
+    /// How many suggestions were left out for having more than `--max-lines`
+    pub too_large: usize,
+    /// Packages which we couldn't suggest anything for because fetching or
+    /// diffing them failed, along with the reason
+    pub failures: Vec<(PackageIdx, String)>,
 }
 
 #[derive(Debug, Clone)]
@@ -166,6 +194,8 @@ pub struct SuggestItem {
     pub suggested_criteria: CriteriaFailureSet,
     pub suggested_diff: DiffRecommendation,
     pub notable_parents: String,
+    /// Who is responsible for this audit, according to the `owners` table
+    pub owner: Option<OwnerName>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -173,6 +203,94 @@ pub struct DiffRecommendation {
     pub from: Version,
     pub to: Version,
     pub diffstat: DiffStat,
+    /// If reviewing a chain of diffs through intermediate published versions
+    /// is cheaper than reviewing `from -> to` directly, the individual diffs to
+    /// review in order. `diffstat` is then the total over the whole chain.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub steps: Vec<DiffRecommendation>,
+}
+
+/// The most intermediate published versions we're willing to fetch when
+/// looking for a cheaper chain of diffs between an audited version and the
+/// one we need, as every one of them has to be downloaded and diffed.
+const MAX_INTERMEDIATE_VERSIONS: usize = 8;
+
+impl DiffRecommendation {
+    /// Combine a chain of diffs into a single recommendation.
+    fn chain(steps: Vec<DiffRecommendation>) -> Self {
+        let count = steps.iter().map(|step| step.diffstat.count).sum();
+        DiffRecommendation {
+            from: steps.first().unwrap().from.clone(),
+            to: steps.last().unwrap().to.clone(),
+            diffstat: DiffStat {
+                raw: format!("{} lines over {} diffs", count, steps.len()),
+                count,
+            },
+            steps,
+        }
+    }
+
+    /// The command(s) to run to review this diff.
+    pub fn command(&self, package: PackageStr) -> String {
+        if !self.steps.is_empty() {
+            return self
+                .steps
+                .iter()
+                .map(|step| step.command(package))
+                .collect::<Vec<_>>()
+                .join(" && ");
+        }
+        if self.from == ROOT_VERSION {
+            format!("cargo vet inspect {} {}", package, self.to)
+        } else {
+            format!("cargo vet diff {} {} {}", package, self.from, self.to)
+        }
+    }
+}
+
+/// Find the cheapest chain of diffs which starts at any of the `sources` and
+/// ends at any of the `targets`, treating each diff as an edge weighted by its
+/// diffstat. Ties are broken in favour of shorter chains.
+pub fn cheapest_diff_chain<'a>(
+    sources: &SortedSet<&Version>,
+    targets: &SortedSet<&Version>,
+    diffs: &'a [DiffRecommendation],
+) -> Option<Vec<&'a DiffRecommendation>> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    // The best known (cost, length) of a chain reaching each version, and the
+    // diff which got us there.
+    let mut best = SortedMap::<&Version, ((u64, usize), Option<&DiffRecommendation>)>::new();
+    let mut queue = BinaryHeap::new();
+    for &source in sources {
+        best.insert(source, ((0, 0), None));
+        queue.push(Reverse(((0, 0), source)));
+    }
+
+    while let Some(Reverse((cost, version))) = queue.pop() {
+        if best[version].0 < cost {
+            continue;
+        }
+        if targets.contains(version) {
+            let mut chain = Vec::new();
+            let mut cur = version;
+            while let Some(diff) = best[cur].1 {
+                chain.push(diff);
+                cur = &diff.from;
+            }
+            chain.reverse();
+            return Some(chain);
+        }
+        for diff in diffs.iter().filter(|diff| &diff.from == version) {
+            let next = (cost.0 + diff.diffstat.count, cost.1 + 1);
+            if !matches!(best.get(&diff.to), Some((known, _)) if *known <= next) {
+                best.insert(&diff.to, (next, Some(diff)));
+                queue.push(Reverse((next, &diff.to)));
+            }
+        }
+    }
+    None
 }
 
 /// Set of booleans, 64 should be Enough For Anyone (but abstracting in case not).
@@ -232,10 +350,17 @@ pub struct PackageNode<'a> {
     pub is_workspace_member: bool,
     /// Whether this package is third-party (from crates.io)
     pub is_third_party: bool,
+    /// Whether this package is from a registry other than crates.io, so its
+    /// source can't be fetched for review
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_from_other_registry: bool,
     /// Whether this package is a root in the "normal" build graph
     pub is_root: bool,
     /// Whether this package only shows up in dev (test/bench) builds
     pub is_dev_only: bool,
+    /// Whether this package is a proc-macro crate
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub is_proc_macro: bool,
 }
 
 /// Don't serialize path package ids, not stable across systems
@@ -266,6 +391,49 @@ pub struct ResolveResult<'a> {
     pub needed_exemption: bool,
 }
 
+/// How much of the third-party dependency tree is covered by each criteria.
+#[derive(Debug, Clone, Serialize)]
+pub struct Coverage {
+    /// The number of third-party packages in the tree.
+    pub total: usize,
+    /// Coverage for each criteria.
+    pub criteria: SortedMap<CriteriaName, CriteriaCoverage>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CriteriaCoverage {
+    /// Packages which satisfy the criteria (possibly thanks to exemptions).
+    pub vetted: usize,
+    /// Packages which satisfy the criteria without any exemptions.
+    pub audited: usize,
+}
+
+impl Coverage {
+    /// Print a compact one-line summary, e.g.
+    /// `safe-to-deploy: 380/412 (92%), safe-to-run: 400/412 (97%)`.
+    pub fn print_human(&self, out: &Arc<dyn Out>) -> Result<(), std::io::Error> {
+        let summary = self
+            .criteria
+            .iter()
+            .map(|(criteria, coverage)| {
+                if self.total == 0 {
+                    format!("{criteria}: 0/0")
+                } else {
+                    format!(
+                        "{criteria}: {}/{} ({}%)",
+                        coverage.vetted,
+                        self.total,
+                        coverage.vetted * 100 / self.total
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(out, "{summary}");
+        Ok(())
+    }
+}
+
 pub type PolicyFailures = SortedMap<PackageIdx, CriteriaSet>;
 /// (FailedPackage, Failures, is_dev)
 pub type RootFailures = Vec<(PackageIdx, PolicyFailures, bool)>;
@@ -282,6 +450,9 @@ pub enum SearchResult<'a> {
     Connected {
         /// Whether we found a path to a fully_audited entry
         fully_audited: bool,
+        /// The audits (and exemptions) making up the path we found, in order.
+        /// Empty for first-party packages, which have no audits of their own.
+        path: Vec<AuditStep<'a>>,
     },
     /// We failed to find a *proper* path, criteria not valid, but adding in failing
     /// edges caused by our dependencies not meeting criteria created a connection!
@@ -305,6 +476,20 @@ pub enum SearchResult<'a> {
     },
 }
 
+/// One audit (or exemption) in a path that validated a criteria.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditStep<'a> {
+    /// The version the audit starts from, which is [`ROOT_VERSION`][] for
+    /// full audits and exemptions.
+    pub from: &'a Version,
+    /// The version the audit ends at.
+    pub to: &'a Version,
+    /// Where the audit came from. Exemptions are always our own.
+    pub source: AuditSource,
+    /// Whether this step is an exemption rather than an audit.
+    pub is_exemption: bool,
+}
+
 /// A graph of the audits for a package.
 ///
 /// The nodes of the graph are Versions and the edges are audits.
@@ -347,6 +532,8 @@ pub struct DeltaEdge<'a> {
     /// Whether this edge represents an exemption. These will initially
     /// be ignored, and then used only if we can't find a path.
     is_exemption: bool,
+    /// Where the audit this edge represents came from.
+    source: AuditSource,
 }
 
 fn builtin_criteria() -> SortedMap<CriteriaName, CriteriaEntry> {
@@ -403,9 +590,14 @@ impl CriteriaMapper {
             ) {
                 for implied in implies {
                     let idx = index[&**implied];
+                    // Already visited, this also keeps cycles (which validation
+                    // rejects locally, but foreign audits may contain) from
+                    // recursing forever.
+                    if result.has_criteria(idx) {
+                        continue;
+                    }
                     result.set_criteria(idx);
 
-                    // FIXME: we should detect infinite implies loops?
                     let further_implies = &list[idx].1.implies[..];
                     recursive_implies(result, further_implies, index, list);
                 }
@@ -475,6 +667,11 @@ impl CriteriaMapper {
             .map(|idx| &*self.list[idx].0)
     }
 
+    /// The names of every criteria, in definition order.
+    pub fn criteria_name_list(&self) -> Vec<CriteriaName> {
+        self.list.iter().map(|(name, _)| name.clone()).collect()
+    }
+
     pub fn all_criteria_names<'a>(
         &'a self,
         criteria: &'a CriteriaFailureSet,
@@ -660,10 +857,8 @@ impl<'a> DepGraph<'a> {
     pub fn new(
         metadata: &'a Metadata,
         filter_graph: Option<&Vec<GraphFilter>>,
-        policy: Option<&SortedMap<PackageName, PolicyEntry>>,
+        config: Option<&ConfigFile>,
     ) -> Self {
-        let empty_override = SortedMap::new();
-        let policy = policy.unwrap_or(&empty_override);
         let package_list = &*metadata.packages;
         let resolve_list = &*metadata
             .resolve
@@ -681,6 +876,22 @@ impl<'a> DepGraph<'a> {
             .map(|(idx, pkg)| (&pkg.id, idx))
             .collect();
 
+        // A package from elsewhere which is `audit-as-crates-io` would share its
+        // audits with the real crates.io package of the same name and version.
+        // If both are in the graph they're clearly different code, so the
+        // impostor is kept first-party to stop audits of one vouching for the other.
+        let crates_io_versions = package_list
+            .iter()
+            .filter(|package| package.is_crates_io())
+            .map(|package| (&*package.name, &package.version))
+            .collect::<FastSet<_>>();
+        let is_third_party = |package: &Package| {
+            config.map_or(package.is_crates_io(), |config| {
+                package.is_third_party(config)
+            }) && (package.is_crates_io()
+                || !crates_io_versions.contains(&(&*package.name, &package.version)))
+        };
+
         // Do a first-pass where we populate skeletons of the primary nodes
         // and setup the interners, which will only ever refer to these nodes
         let mut interner_by_pkgid = SortedMap::<&PackageId, PackageIdx>::new();
@@ -693,7 +904,12 @@ impl<'a> DepGraph<'a> {
                 package_id: &resolve_node.id,
                 name: &package.name,
                 version: &package.version,
-                is_third_party: package.is_third_party(policy),
+                is_third_party: is_third_party(package),
+                is_from_other_registry: package.is_from_other_registry(),
+                is_proc_macro: package
+                    .targets
+                    .iter()
+                    .any(|target| target.kind.iter().any(|kind| kind == "proc-macro")),
                 // These will get (re)computed later
                 normal_deps: vec![],
                 build_deps: vec![],
@@ -869,9 +1085,13 @@ impl<'a> DepGraph<'a> {
             topo_index,
         };
 
-        // Now apply filters, if any
+        // Now apply filters, if any. Even without filters, prune any packages
+        // which cargo reported but which aren't reachable from the workspace
+        // (e.g. optional dependencies which no enabled feature activates).
         if let Some(filters) = filter_graph {
             result.filter(filters)
+        } else if result.topo_index.len() != result.nodes.len() {
+            result.filter(&[])
         } else {
             result
         }
@@ -968,8 +1188,10 @@ impl<'a> DepGraph<'a> {
                 reverse_deps: SortedSet::new(),
                 is_workspace_member: package.is_workspace_member,
                 is_third_party: package.is_third_party,
+                is_from_other_registry: package.is_from_other_registry,
                 is_root: package.is_root,
                 is_dev_only: package.is_dev_only,
+                is_proc_macro: package.is_proc_macro,
             });
             interner_by_pkgid.insert(package.package_id, new_idx);
         }
@@ -1021,17 +1243,16 @@ impl<'a> DepGraph<'a> {
         }
     }
 
-    pub fn print_mermaid(
+    /// The nodes shown at the given `--depth` of `dump-graph`, and the subset
+    /// of them whose dependencies are shown.
+    fn visible_nodes(
         &self,
-        out: &Arc<dyn Out>,
-        sub_args: &DumpGraphArgs,
-    ) -> Result<(), std::io::Error> {
+        depth: DumpGraphDepth,
+    ) -> (SortedSet<PackageIdx>, SortedSet<PackageIdx>) {
         use crate::DumpGraphDepth::*;
-        let depth = sub_args.depth;
 
         let mut visible_nodes = SortedSet::new();
         let mut nodes_with_children = SortedSet::new();
-        let mut shown = SortedSet::new();
 
         for (idx, package) in self.nodes.iter().enumerate() {
             if (package.is_root && depth >= Roots)
@@ -1050,6 +1271,33 @@ impl<'a> DepGraph<'a> {
             }
         }
 
+        (visible_nodes, nodes_with_children)
+    }
+
+    /// The label of a node in `dump-graph`, including the criteria it's
+    /// required to satisfy if they were computed.
+    fn node_label(&self, idx: PackageIdx, required: Option<&[Vec<CriteriaName>]>) -> String {
+        let package = &self.nodes[idx];
+        match required {
+            Some(required) if !required[idx].is_empty() => format!(
+                "{}:{} ({})",
+                package.name,
+                package.version,
+                required[idx].join(", ")
+            ),
+            _ => format!("{}:{}", package.name, package.version),
+        }
+    }
+
+    pub fn print_mermaid(
+        &self,
+        out: &Arc<dyn Out>,
+        sub_args: &DumpGraphArgs,
+        required: Option<&[Vec<CriteriaName>]>,
+    ) -> Result<(), std::io::Error> {
+        let (visible_nodes, nodes_with_children) = self.visible_nodes(sub_args.depth);
+        let mut shown = SortedSet::new();
+
         writeln!(out, "graph LR");
 
         writeln!(out, "    subgraph roots");
@@ -1058,8 +1306,8 @@ impl<'a> DepGraph<'a> {
             if package.is_root && shown.insert(idx) {
                 writeln!(
                     out,
-                    "        node{idx}{{{}:{}}}",
-                    package.name, package.version
+                    "        node{idx}{{{}}}",
+                    self.node_label(idx, required)
                 );
             }
         }
@@ -1071,8 +1319,8 @@ impl<'a> DepGraph<'a> {
             if package.is_workspace_member && shown.insert(idx) {
                 writeln!(
                     out,
-                    "        node{idx}[/{}:{}/]",
-                    package.name, package.version
+                    "        node{idx}[/{}/]",
+                    self.node_label(idx, required)
                 );
             }
         }
@@ -1082,24 +1330,15 @@ impl<'a> DepGraph<'a> {
         for &idx in &visible_nodes {
             let package = &self.nodes[idx];
             if !package.is_third_party && shown.insert(idx) {
-                writeln!(
-                    out,
-                    "        node{idx}[{}:{}]",
-                    package.name, package.version
-                );
+                writeln!(out, "        node{idx}[{}]", self.node_label(idx, required));
             }
         }
         writeln!(out, "    end");
 
         writeln!(out, "    subgraph third-party");
         for &idx in &visible_nodes {
-            let package = &self.nodes[idx];
             if shown.insert(idx) {
-                writeln!(
-                    out,
-                    "        node{idx}({}:{})",
-                    package.name, package.version
-                );
+                writeln!(out, "        node{idx}({})", self.node_label(idx, required));
             }
         }
         writeln!(out, "    end");
@@ -1115,6 +1354,50 @@ impl<'a> DepGraph<'a> {
 
         Ok(())
     }
+
+    /// Like [`DepGraph::print_mermaid`][], but as a graphviz DOT graph, with
+    /// first-party packages drawn as boxes and each kind of dependency edge
+    /// drawn differently.
+    pub fn print_dot(
+        &self,
+        out: &Arc<dyn Out>,
+        sub_args: &DumpGraphArgs,
+        required: Option<&[Vec<CriteriaName>]>,
+    ) -> Result<(), std::io::Error> {
+        let (visible_nodes, nodes_with_children) = self.visible_nodes(sub_args.depth);
+
+        writeln!(out, "digraph {{");
+        for &idx in &visible_nodes {
+            let package = &self.nodes[idx];
+            let shape = if package.is_third_party {
+                "ellipse"
+            } else {
+                "box"
+            };
+            let label = self.node_label(idx, required).replace('"', "\\\"");
+            writeln!(out, "    node{idx} [label=\"{label}\", shape={shape}];");
+        }
+        for &idx in &nodes_with_children {
+            let package = &self.nodes[idx];
+            for (deps, kind, style) in [
+                (&package.normal_deps, "normal", "solid"),
+                (&package.build_deps, "build", "dashed"),
+                (&package.dev_deps, "dev", "dotted"),
+            ] {
+                for &dep_idx in deps {
+                    if visible_nodes.contains(&dep_idx) {
+                        writeln!(
+                            out,
+                            "    node{idx} -> node{dep_idx} [label=\"{kind}\", style={style}];"
+                        );
+                    }
+                }
+            }
+        }
+        writeln!(out, "}}");
+
+        Ok(())
+    }
 }
 
 // Dummy values for corner cases
@@ -1127,6 +1410,75 @@ pub enum ResolveDepth {
     Shallow,
 }
 
+/// A full audit whose recorded source hash differs from the source cargo
+/// unpacked for the current build.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceHashMismatch<'a> {
+    pub package: PackageStr<'a>,
+    pub version: &'a Version,
+    pub path: PathBuf,
+    pub audited: &'a str,
+    pub actual: String,
+}
+
+/// Find every third-party package whose locally unpacked source doesn't match
+/// the source hash of a full audit of its version.
+///
+/// Packages whose source isn't available locally are skipped, as are audits
+/// which didn't record a hash.
+pub fn source_hash_mismatches<'a>(
+    metadata: &'a Metadata,
+    store: &'a Store,
+) -> Vec<SourceHashMismatch<'a>> {
+    let mut mismatches = Vec::new();
+    for package in &metadata.packages {
+        if !package.is_third_party(&store.config) {
+            continue;
+        }
+        let audited = store
+            .audits
+            .audits
+            .get(&package.name)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| match (&entry.kind, &entry.source_hash) {
+                (AuditKind::Full { version, .. }, Some(hash)) if *version == package.version => {
+                    Some(&hash[..])
+                }
+                _ => None,
+            })
+            .collect::<SortedSet<_>>();
+        if audited.is_empty() {
+            continue;
+        }
+
+        let actual = match crate::storage::hash_local_package_source(package) {
+            Ok(actual) => actual,
+            Err(e) => {
+                trace!(
+                    "couldn't hash the local source of {}:{}: {}",
+                    package.name,
+                    package.version,
+                    e
+                );
+                continue;
+            }
+        };
+        for audited in audited {
+            if audited != actual {
+                mismatches.push(SourceHashMismatch {
+                    package: &package.name,
+                    version: &package.version,
+                    path: package.manifest_path.parent().unwrap().into(),
+                    audited,
+                    actual: actual.clone(),
+                });
+            }
+        }
+    }
+    mismatches
+}
+
 pub fn resolve<'a>(
     metadata: &'a Metadata,
     filter_graph: Option<&Vec<GraphFilter>>,
@@ -1136,10 +1488,22 @@ pub fn resolve<'a>(
     let _resolve_span = trace_span!("validate").entered();
     // A large part of our algorithm is unioning and intersecting criteria, so we map all
     // the criteria into indexed boolean sets (*whispers* an integer with lots of bits).
-    let graph = DepGraph::new(metadata, filter_graph, Some(&store.config.policy));
+    let graph = DepGraph::new(metadata, filter_graph, Some(&store.config));
     // trace!("built DepGraph: {:#?}", graph);
     trace!("built DepGraph!");
 
+    // Policy patterns which don't match anything are probably typos
+    for pattern in store.config.policy.keys() {
+        if pattern.contains('*')
+            && !graph
+                .nodes
+                .iter()
+                .any(|package| package_glob_matches(pattern, package.name))
+        {
+            warn!("policy pattern '{pattern}' doesn't match any packages");
+        }
+    }
+
     let criteria_mapper = CriteriaMapper::new(&store.audits.criteria);
     trace!("built CriteriaMapper!");
 
@@ -1241,8 +1605,11 @@ pub fn resolve<'a>(
     if !violations.is_empty() {
         return ResolveReport {
             graph,
+            warned_criteria: criteria_mapper.no_criteria(),
             criteria_mapper,
+            notes: &store.config.notes,
             results,
+            advisories: SortedMap::new(),
             conclusion: Conclusion::FailForViolationConflict(FailForViolationConflict {
                 violations,
             }),
@@ -1297,8 +1664,11 @@ pub fn resolve<'a>(
     if !failures.is_empty() {
         return ResolveReport {
             graph,
+            warned_criteria: criteria_mapper.no_criteria(),
             criteria_mapper,
+            notes: &store.config.notes,
             results,
+            advisories: SortedMap::new(),
             conclusion: Conclusion::FailForVet(FailForVet {
                 failures,
                 suggest: None,
@@ -1329,8 +1699,11 @@ pub fn resolve<'a>(
 
     ResolveReport {
         graph,
+        warned_criteria: criteria_mapper.no_criteria(),
         criteria_mapper,
+        notes: &store.config.notes,
         results,
+        advisories: SortedMap::new(),
         conclusion: Conclusion::Success(Success {
             vetted_with_exemptions,
             vetted_partially,
@@ -1388,12 +1761,14 @@ fn resolve_third_party<'a>(
             criteria: criteria.clone(),
             dependency_criteria: dependency_criteria.clone(),
             is_exemption: false,
+            source: AuditSource::OwnAudits,
         });
         backward_audits.entry(to_ver).or_default().push(DeltaEdge {
             version: from_ver,
             criteria,
             dependency_criteria,
             is_exemption: false,
+            source: AuditSource::OwnAudits,
         });
     }
 
@@ -1411,7 +1786,7 @@ fn resolve_third_party<'a>(
             .iter()
             .map(|mapping| {
                 let set = foreign_criteria_mapper.criteria_from_list(&mapping.theirs);
-                (&*mapping.ours, set)
+                (&**mapping.ours, set)
             })
             .collect();
 
@@ -1456,12 +1831,14 @@ fn resolve_third_party<'a>(
                 criteria: local_criteria.clone(),
                 dependency_criteria: Default::default(),
                 is_exemption: false,
+                source: AuditSource::Foreign(foreign_name.clone()),
             });
             backward_audits.entry(to_ver).or_default().push(DeltaEdge {
                 version: from_ver,
                 criteria: local_criteria,
                 dependency_criteria: Default::default(),
                 is_exemption: false,
+                source: AuditSource::Foreign(foreign_name.clone()),
             });
         }
     }
@@ -1655,12 +2032,14 @@ fn resolve_third_party<'a>(
                 criteria: criteria.clone(),
                 dependency_criteria: dependency_criteria.clone(),
                 is_exemption: true,
+                source: AuditSource::OwnAudits,
             });
             backward_audits.entry(to_ver).or_default().push(DeltaEdge {
                 version: from_ver,
                 criteria,
                 dependency_criteria,
                 is_exemption: true,
+                source: AuditSource::OwnAudits,
             });
         }
     }
@@ -1678,15 +2057,22 @@ fn resolve_third_party<'a>(
             criteria_mapper,
             package,
             results,
+            store.config.decompose_criteria,
         );
         match result {
-            SearchResult::Connected { fully_audited } => {
+            SearchResult::Connected {
+                fully_audited,
+                path,
+            } => {
                 // We found a path, hooray, criteria validated!
                 if fully_audited {
                     fully_audited_criteria.unioned_with(criteria);
                 }
                 validated_criteria.unioned_with(criteria);
-                search_results.push(SearchResult::Connected { fully_audited });
+                search_results.push(SearchResult::Connected {
+                    fully_audited,
+                    path,
+                });
             }
             SearchResult::PossiblyConnected { failed_deps } => {
                 // We failed but found a possible solution if our dependencies were better.
@@ -1708,6 +2094,7 @@ fn resolve_third_party<'a>(
                     criteria_mapper,
                     package,
                     results,
+                    store.config.decompose_criteria,
                 );
                 if let SearchResult::Disconnected {
                     reachable_from_root: reachable_from_target,
@@ -1753,6 +2140,7 @@ fn search_for_path<'a>(
     criteria_mapper: &CriteriaMapper,
     package: &PackageNode<'a>,
     results: &mut [ResolveResult],
+    decompose_criteria: bool,
 ) -> SearchResult<'a> {
     // Search for any path through the graph with edges that satisfy cur_criteria.
     // Finding any path validates that we satisfy that criteria. All we're doing is
@@ -1781,10 +2169,16 @@ fn search_for_path<'a>(
     let mut failed_deps = SortedMap::<PackageIdx, CriteriaSet>::new();
 
     // Search State
-    let mut search_stack = vec![from_version];
+    //
+    // Each entry on the stack also remembers the node and edge it was reached by,
+    // which is recorded in `came_from` when it's visited so that we can report
+    // the path we found.
+    let mut search_stack: Vec<(&'a Version, Option<(&'a Version, &DeltaEdge<'a>)>)> =
+        vec![(from_version, None)];
     let mut visited = SortedSet::new();
-    let mut deferred_exemptions_entries = vec![];
-    let mut deferred_failed_edges = vec![];
+    let mut came_from = SortedMap::new();
+    let mut deferred_exemptions_entries: Vec<(&Version, _)> = vec![];
+    let mut deferred_failed_edges: Vec<(&Version, _)> = vec![];
 
     // Loop until we find a path or run out of deferred edges.
     loop {
@@ -1793,7 +2187,7 @@ fn search_for_path<'a>(
         // we've completely run out of options.
         if let Some(node) = deferred_exemptions_entries.pop() {
             // Don't bother if we got to that node some other way.
-            if visited.contains(node) {
+            if visited.contains(node.0) {
                 continue;
             }
             // Ok at this point we officially "need" the exemptions edge. If the search still
@@ -1803,7 +2197,7 @@ fn search_for_path<'a>(
             search_stack.push(node);
         } else if let Some(node) = deferred_failed_edges.pop() {
             // Don't bother if we got to that node some other way.
-            if visited.contains(node) {
+            if visited.contains(node.0) {
                 continue;
             }
             // Ok at this point we officially "need" the failed edge. If the search still
@@ -1814,10 +2208,18 @@ fn search_for_path<'a>(
         }
 
         // Do Depth-First-Search
-        while let Some(cur_version) = search_stack.pop() {
+        while let Some((cur_version, via)) = search_stack.pop() {
             // Don't revisit nodes, there's never an advantage to doing so, and because deltas
             // can go both forwards and backwards in time, cycles are a real concern!
-            visited.insert(cur_version);
+            // A node can be on the stack several times (once per edge leading to it), so
+            // this also keeps the search linear in the size of the audit graph for crates
+            // with hundreds of versions and dense delta coverage.
+            if !visited.insert(cur_version) {
+                continue;
+            }
+            if let Some(via) = via {
+                came_from.insert(cur_version, via);
+            }
             if cur_version == to_version {
                 // Success! Nothing more to do.
                 found_path = true;
@@ -1850,10 +2252,18 @@ fn search_for_path<'a>(
                         // e.g. a safe-to-deploy audit with some deps that are only safe-to-run
                         // still audits for safe-to-run, but not safe-to-deploy. Similarly so for
                         // `[safe-to-run, some-other-criteria]` validating each criteria individually.
+                        //
+                        // If `decompose-criteria = false`, the dependency must instead satisfy
+                        // everything the audit claims.
+                        let default_req = if decompose_criteria {
+                            cur_criteria
+                        } else {
+                            &edge.criteria
+                        };
                         let dep_req = edge
                             .dependency_criteria
                             .get(&*dep_package.name)
-                            .unwrap_or(cur_criteria);
+                            .unwrap_or(default_req);
 
                         if !dep_vet_result.contains(dep_req) {
                             failed_deps
@@ -1867,14 +2277,15 @@ fn search_for_path<'a>(
                     if deps_satisfied {
                         // Ok yep, this edge is usable! But defer it if it's an exemption.
                         if edge.is_exemption {
-                            deferred_exemptions_entries.push(edge.version);
+                            deferred_exemptions_entries
+                                .push((edge.version, Some((cur_version, edge))));
                         } else {
-                            search_stack.push(edge.version);
+                            search_stack.push((edge.version, Some((cur_version, edge))));
                         }
                     } else {
                         // Remember this edge failed, if we can't find any path we'll speculatively
                         // re-enable it.
-                        deferred_failed_edges.push(edge.version);
+                        deferred_failed_edges.push((edge.version, Some((cur_version, edge))));
                     }
                 }
             }
@@ -1890,9 +2301,22 @@ fn search_for_path<'a>(
 
     // It's only a success if we found a path and used no 'failed' edges.
     if found_path && !needed_failed_edges {
-        // Complete success!
+        // Complete success! Walk back from the target to recover the path.
+        let mut path = vec![];
+        let mut cur_version = to_version;
+        while let Some(&(prev_version, edge)) = came_from.get(cur_version) {
+            path.push(AuditStep {
+                from: prev_version,
+                to: cur_version,
+                source: edge.source.clone(),
+                is_exemption: edge.is_exemption,
+            });
+            cur_version = prev_version;
+        }
+        path.reverse();
         SearchResult::Connected {
             fully_audited: !needed_exemption,
+            path,
         }
     } else if found_path {
         // Failure, but it's clearly the fault of our deps.
@@ -1908,6 +2332,122 @@ fn search_for_path<'a>(
     }
 }
 
+/// A package's own policy: the criteria it's required to have, whatever its
+/// dependents require of it.
+struct OwnPolicy {
+    criteria: CriteriaSet,
+    /// Whether this is the policy of dev-only packages (`dev-safe-to-run`).
+    is_dev_only: bool,
+    /// Whether this is only the proc-macro policy, which doesn't excuse the
+    /// package from what its dependents require.
+    only_proc_macro: bool,
+}
+
+fn own_policy(
+    store: &Store,
+    criteria_mapper: &CriteriaMapper,
+    package: &PackageNode,
+) -> Option<OwnPolicy> {
+    let entry = format::policy_for(&store.config.policy, package.name);
+    let explicit_policy = entry.and_then(|p| p.criteria.as_ref());
+    let is_dev_only =
+        explicit_policy.is_none() && package.is_dev_only && store.config.dev_safe_to_run;
+    let proc_macro_policy = explicit_policy.is_none()
+        && package.is_third_party
+        && package.is_proc_macro
+        && !store.config.proc_macro_criteria.is_empty();
+    let mut criteria = if let Some(c) = explicit_policy {
+        Some(criteria_mapper.criteria_from_list(c))
+    } else if package.is_root {
+        Some(criteria_mapper.criteria_from_list([format::DEFAULT_POLICY_CRITERIA]))
+    } else if is_dev_only {
+        Some(criteria_mapper.criteria_from_list([format::DEFAULT_POLICY_DEV_CRITERIA]))
+    } else {
+        None
+    };
+    let only_proc_macro = criteria.is_none() && proc_macro_policy;
+    if proc_macro_policy {
+        criteria
+            .get_or_insert_with(|| criteria_mapper.no_criteria())
+            .unioned_with(&criteria_mapper.criteria_from_list(&store.config.proc_macro_criteria));
+    }
+    criteria.map(|criteria| OwnPolicy {
+        criteria,
+        is_dev_only,
+        only_proc_macro,
+    })
+}
+
+/// The criteria a workspace member's dev-dependencies are required to have
+/// (its `dev-criteria`).
+fn dev_policy(
+    store: &Store,
+    criteria_mapper: &CriteriaMapper,
+    package: &PackageNode,
+) -> CriteriaSet {
+    let entry = format::policy_for(&store.config.policy, package.name);
+    if let Some(c) = entry.and_then(|p| p.dev_criteria.as_ref()) {
+        criteria_mapper.criteria_from_list(c)
+    } else {
+        criteria_mapper.criteria_from_list([format::DEFAULT_POLICY_DEV_CRITERIA])
+    }
+}
+
+/// The `dependency-criteria` of a package's policy which apply to its
+/// dependencies of the given kind, by dependency name. The ones specific to
+/// build or dev-dependencies take priority over the shared ones.
+fn policy_dependency_criteria<'a>(
+    store: &'a Store,
+    criteria_mapper: &CriteriaMapper,
+    package: &PackageNode,
+    kind: DependencyKind,
+) -> FastMap<PackageStr<'a>, CriteriaSet> {
+    format::policy_for(&store.config.policy, package.name)
+        .map(|policy| {
+            let kind_dependency_criteria = match kind {
+                DependencyKind::Normal => None,
+                DependencyKind::Development => Some(&policy.dev_dependency_criteria),
+                DependencyKind::Build => Some(&policy.build_dependency_criteria),
+                _ => unreachable!(),
+            };
+            policy
+                .dependency_criteria
+                .iter()
+                .chain(kind_dependency_criteria.into_iter().flatten())
+                .map(|(dep_name, criteria)| {
+                    (&**dep_name, criteria_mapper.criteria_from_list(criteria))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The criteria a normal or build dependency is required to have for the
+/// package to have `criteria`. If the package has an explicit policy for the
+/// dependency, that's all that matters. Otherwise the dependency has to have
+/// the same criteria. A package which is both a normal and a build dependency
+/// has to satisfy the requirements of both edges.
+fn dependency_requirement(
+    graph: &DepGraph,
+    criteria_mapper: &CriteriaMapper,
+    pkgidx: PackageIdx,
+    depidx: PackageIdx,
+    criteria: &CriteriaSet,
+    dep_criteria: &FastMap<PackageStr, CriteriaSet>,
+    build_dep_criteria: &FastMap<PackageStr, CriteriaSet>,
+) -> CriteriaSet {
+    let package = &graph.nodes[pkgidx];
+    let dep_name = graph.nodes[depidx].name;
+    let mut required_criteria = criteria_mapper.no_criteria();
+    if package.normal_deps.contains(&depidx) {
+        required_criteria.unioned_with(dep_criteria.get(dep_name).unwrap_or(criteria));
+    }
+    if package.build_deps.contains(&depidx) {
+        required_criteria.unioned_with(build_dep_criteria.get(dep_name).unwrap_or(criteria));
+    }
+    required_criteria
+}
+
 fn resolve_first_party<'a>(
     store: &'a Store,
     graph: &DepGraph<'a>,
@@ -1921,21 +2461,11 @@ fn resolve_first_party<'a>(
     // In this pass we properly use package.is_root, but in the next pass all nodes are "roots"
     let package = &graph.nodes[pkgidx];
 
-    // Get custom policies for our dependencies
-    let dep_criteria = store
-        .config
-        .policy
-        .get(package.name)
-        .map(|policy| {
-            policy
-                .dependency_criteria
-                .iter()
-                .map(|(dep_name, criteria)| {
-                    (&**dep_name, criteria_mapper.criteria_from_list(criteria))
-                })
-                .collect::<FastMap<_, _>>()
-        })
-        .unwrap_or_default();
+    // Get custom policies for our dependencies.
+    let dep_criteria =
+        policy_dependency_criteria(store, criteria_mapper, package, DependencyKind::Normal);
+    let build_dep_criteria =
+        policy_dependency_criteria(store, criteria_mapper, package, DependencyKind::Build);
 
     // Compute whether we have each criteria based on our dependencies
     let mut validated_criteria = criteria_mapper.no_criteria();
@@ -1944,15 +2474,20 @@ fn resolve_first_party<'a>(
         // Find any build/normal dependencies that don't satisfy this criteria
         let mut failed_deps = SortedMap::new();
         for &depidx in &package.normal_and_build_deps {
-            // If we have an explicit policy for dependency, that's all that matters.
-            // Otherwise just use the current criteria to "inherit" the results of our deps.
-            let dep_name = graph.nodes[depidx].name;
-            let required_criteria = dep_criteria.get(dep_name).unwrap_or(criteria);
-            if !results[depidx].contains(required_criteria) {
+            let required_criteria = dependency_requirement(
+                graph,
+                criteria_mapper,
+                pkgidx,
+                depidx,
+                criteria,
+                &dep_criteria,
+                &build_dep_criteria,
+            );
+            if !results[depidx].contains(&required_criteria) {
                 failed_deps
                     .entry(depidx)
                     .or_insert_with(|| criteria_mapper.no_criteria())
-                    .unioned_with(required_criteria);
+                    .unioned_with(&required_criteria);
             }
         }
 
@@ -1960,6 +2495,7 @@ fn resolve_first_party<'a>(
             // All our deps passed the test, so we have this criteria
             search_results.push(SearchResult::Connected {
                 fully_audited: true,
+                path: vec![],
             });
             validated_criteria.unioned_with(criteria);
         } else {
@@ -1991,13 +2527,18 @@ fn resolve_self_policy<'a>(
     let package = &graph.nodes[pkgidx];
 
     // Now check that we pass our own policy
-    let entry = store.config.policy.get(package.name);
-    let own_policy = if let Some(c) = entry.and_then(|p| p.criteria.as_ref()) {
-        trace!("  explicit policy: {:?}", c);
-        criteria_mapper.criteria_from_list(c)
-    } else if package.is_root {
-        trace!("  root policy: {:?}", [format::DEFAULT_POLICY_CRITERIA]);
-        criteria_mapper.criteria_from_list([format::DEFAULT_POLICY_CRITERIA])
+    let OwnPolicy {
+        criteria: own_policy,
+        is_dev_only: dev_only_policy,
+        only_proc_macro: only_proc_macro_policy,
+    } = if let Some(own_policy) = own_policy(store, criteria_mapper, package) {
+        trace!(
+            "  policy: {:?}",
+            criteria_mapper
+                .criteria_names(&own_policy.criteria)
+                .collect::<Vec<_>>()
+        );
+        own_policy
     } else {
         trace!("  has no policy, done");
         // We have no policy, we're done
@@ -2029,12 +2570,21 @@ fn resolve_self_policy<'a>(
         }
     }
 
-    if policy_failures.is_empty() {
+    if policy_failures.is_empty() && only_proc_macro_policy {
+        // We passed the proc-macro policy, but our dependents may still need
+        // more from us, so leave our validated criteria alone.
+        trace!("  passed proc-macro policy");
+    } else if policy_failures.is_empty() {
         // We had a policy and it passed, so now we're validated for all criteria
         // because our parents can never require anything else of us. No need
         // to update search_results, they'll be masked out by validated_criteria(?)
         trace!("  passed policy, all_criteria");
         results[pkgidx].validated_criteria = criteria_mapper.all_criteria();
+        if dev_only_policy && results[pkgidx].fully_audited_criteria.contains(&own_policy) {
+            // A dev-only package which was fully audited for its policy
+            // shouldn't look like it needed an exemption to its dependents.
+            results[pkgidx].fully_audited_criteria = criteria_mapper.all_criteria();
+        }
     } else {
         // We had a policy and it failed, so now we're invalid for all criteria(?)
         trace!("  failed policy, no_criteria");
@@ -2056,27 +2606,15 @@ fn resolve_dev<'a>(
     // so the default root dev policy will always be applicable.
     let package = &graph.nodes[pkgidx];
 
-    // Get custom policies for our dependencies
-    let dep_criteria = store
-        .config
-        .policy
-        .get(package.name)
-        .map(|policy| {
-            policy
-                .dependency_criteria
-                .iter()
-                .map(|(dep_name, criteria)| {
-                    (&**dep_name, criteria_mapper.criteria_from_list(criteria))
-                })
-                .collect::<FastMap<_, _>>()
-        })
-        .unwrap_or_default();
+    // Get custom policies for our dependencies.
+    let dep_criteria =
+        policy_dependency_criteria(store, criteria_mapper, package, DependencyKind::Development);
 
     // Compute whether we have each criteria based on our dependencies
     let mut validated_criteria = criteria_mapper.no_criteria();
     let mut search_results = vec![];
     for criteria in criteria_mapper.all_criteria_iter() {
-        // Find any build/normal dependencies that don't satisfy this criteria
+        // Find any dev dependencies that don't satisfy this criteria
         let mut failed_deps = SortedMap::new();
         for &depidx in &package.dev_deps {
             // If we have an explicit policy for dependency, that's all that matters.
@@ -2095,6 +2633,7 @@ fn resolve_dev<'a>(
             // All our deps passed the test, so we have this criteria
             search_results.push(SearchResult::Connected {
                 fully_audited: true,
+                path: vec![],
             });
             validated_criteria.unioned_with(criteria);
         } else {
@@ -2114,14 +2653,13 @@ fn resolve_dev<'a>(
     // results[pkgidx].validated_criteria = validated_criteria;
 
     // Now check that we pass our own policy
-    let entry = store.config.policy.get(package.name);
-    let own_policy = if let Some(c) = entry.and_then(|p| p.dev_criteria.as_ref()) {
-        trace!("  explicit policy: {:?}", c);
-        criteria_mapper.criteria_from_list(c)
-    } else {
-        trace!("  root policy: {:?}", [format::DEFAULT_POLICY_DEV_CRITERIA]);
-        criteria_mapper.criteria_from_list([format::DEFAULT_POLICY_DEV_CRITERIA])
-    };
+    let own_policy = dev_policy(store, criteria_mapper, package);
+    trace!(
+        "  dev policy: {:?}",
+        criteria_mapper
+            .criteria_names(&own_policy)
+            .collect::<Vec<_>>()
+    );
 
     let mut policy_failures = PolicyFailures::new();
     for criteria_idx in own_policy.indices() {
@@ -2337,12 +2875,82 @@ fn visit_failures<'a, T>(
 
 impl<'a> ResolveReport<'a> {
     pub fn has_errors(&self) -> bool {
-        // Just check the conclusion
-        !matches!(self.conclusion, Conclusion::Success(_))
+        match &self.conclusion {
+            Conclusion::Success(_) => false,
+            Conclusion::FailForViolationConflict(_) => true,
+            Conclusion::FailForVet(fail) => fail
+                .failures
+                .values()
+                .any(|failure| !self.is_warning(failure)),
+        }
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        match &self.conclusion {
+            Conclusion::FailForVet(fail) => fail
+                .failures
+                .values()
+                .any(|failure| self.is_warning(failure)),
+            _ => false,
+        }
+    }
+
+    /// Whether this failure is only missing criteria we've been asked to warn about.
+    fn is_warning(&self, failure: &AuditFailure) -> bool {
+        self.warned_criteria
+            .contains(failure.criteria_failures.all())
+    }
+
+    /// Choose which missing criteria are errors, and which are only warnings.
+    ///
+    /// By default every missing criteria is an error. If any criteria are
+    /// denied, then only those are errors and all others are warnings. Warned
+    /// criteria are always warnings, unless they're also denied, in which case
+    /// they're errors.
+    ///
+    /// A failing package is an error if any of the criteria it's missing are.
+    pub fn set_criteria_severity(
+        &mut self,
+        deny: &[CriteriaName],
+        warn: &[CriteriaName],
+    ) -> Result<(), miette::Report> {
+        let mut warned_criteria = if deny.is_empty() {
+            self.criteria_mapper.no_criteria()
+        } else {
+            self.criteria_mapper.all_criteria()
+        };
+        let index = |criteria: &CriteriaName| {
+            self.criteria_mapper
+                .index
+                .get(criteria)
+                .copied()
+                .ok_or_else(|| miette::miette!("'{criteria}' is not a valid criteria name"))
+        };
+        for criteria in warn {
+            warned_criteria.set_criteria(index(criteria)?);
+        }
+        let mut denied_criteria = self.criteria_mapper.no_criteria();
+        for criteria in deny {
+            denied_criteria.set_criteria(index(criteria)?);
+        }
+        warned_criteria.clear_criteria(&denied_criteria);
+        self.warned_criteria = warned_criteria;
+        Ok(())
     }
 
-    pub fn _has_warnings(&self) -> bool {
-        false
+    /// Look up the known advisories affecting each third-party package in the
+    /// graph, so that they're warned about when the report is printed.
+    #[cfg(feature = "advisories")]
+    pub fn annotate_advisories(&mut self, db: &crate::advisories::AdvisoryDb) {
+        self.advisories = self
+            .graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, package)| package.is_third_party)
+            .map(|(pkgidx, package)| (pkgidx, db.ids_for(package.name, package.version)))
+            .filter(|(_, ids)| !ids.is_empty())
+            .collect();
     }
 
     pub fn compute_suggest(
@@ -2350,6 +2958,23 @@ impl<'a> ResolveReport<'a> {
         cfg: &Config,
         network: Option<&Network>,
         allow_deltas: bool,
+    ) -> Result<Option<Suggest>, SuggestError> {
+        self.compute_suggest_preferring(
+            cfg,
+            network,
+            allow_deltas,
+            PreferAuditedVersion::SmallestDiff,
+        )
+    }
+
+    /// Like `compute_suggest`, but with control over which audited version
+    /// deltas are suggested from.
+    pub fn compute_suggest_preferring(
+        &self,
+        cfg: &Config,
+        network: Option<&Network>,
+        allow_deltas: bool,
+        prefer: PreferAuditedVersion,
     ) -> Result<Option<Suggest>, SuggestError> {
         let _suggest_span = trace_span!("suggest").entered();
         let fail = if let Conclusion::FailForVet(fail) = &self.conclusion {
@@ -2361,13 +2986,29 @@ impl<'a> ResolveReport<'a> {
 
         let cache = Cache::acquire(cfg)?;
 
-        let mut suggestions = tokio::runtime::Handle::current()
+        // Fetching and diffing can take a while on a big tree, so show how far
+        // along we are. The total grows as each package's candidates are found.
+        let progress = progress_bar("Diffing", 0, cfg.cli.output_file.is_some());
+
+        let suggestions = tokio::runtime::Handle::current()
             .block_on(join_all(fail.failures.iter().map(
                 |(failure_idx, audit_failure)| async {
                     let failure_idx = *failure_idx;
                     let package = &self.graph.nodes[failure_idx];
                     let result = &self.results[failure_idx];
 
+                    // We can only fetch packages from crates.io, and suggesting
+                    // the crates.io package of the same name would point the
+                    // reviewer at different code.
+                    if package.is_from_other_registry {
+                        return Some(Err((
+                            failure_idx,
+                            "it's from a registry other than crates.io, so review its source \
+                             from there and record the audit with `cargo vet certify`"
+                                .to_owned(),
+                        )));
+                    }
+
                     // Precompute some "notable" parents
                     let notable_parents = {
                         let mut reverse_deps = self.graph.nodes[failure_idx]
@@ -2427,34 +3068,92 @@ impl<'a> ResolveReport<'a> {
 
                     // Now suggest solutions of those failures
                     let mut candidates = SortedSet::new();
+                    // Diffs between published versions which lie between an
+                    // audited version and a version we need, in case reviewing
+                    // a chain of them is cheaper than reviewing the direct diff.
+                    let mut chain_candidates = SortedSet::new();
                     if allow_deltas {
-                        // If we're allowed deltas than try to find a bridge from src and dest
-                        for &dest in from_target.as_ref().unwrap() {
-                            let mut closest_above = None;
-                            let mut closest_below = None;
-                            for &src in from_root.as_ref().unwrap() {
-                                if src < dest {
-                                    if let Some(closest) = closest_below {
-                                        if src > closest {
-                                            closest_below = Some(src);
-                                        }
-                                    } else {
-                                        closest_below = Some(src);
-                                    }
-                                } else if let Some(closest) = closest_above {
-                                    if src < closest {
-                                        closest_above = Some(src);
-                                    }
-                                } else {
-                                    closest_above = Some(src);
-                                }
+                        // If we're allowed deltas than try to find a bridge from src and dest.
+                        // The reachable sets are sorted, so the closest versions on either side
+                        // of dest can be looked up directly instead of scanning all of them.
+                        let from_root = from_root.as_ref().unwrap();
+                        // The root version is always reachable, but only
+                        // counts as audited if nothing else is.
+                        let audited = from_root
+                            .iter()
+                            .filter(|&&version| version != &ROOT_VERSION)
+                            .copied()
+                            .collect::<Vec<_>>();
+                        let preferred = match prefer {
+                            PreferAuditedVersion::SmallestDiff => None,
+                            PreferAuditedVersion::Highest => {
+                                Some(audited.last().copied().unwrap_or(&ROOT_VERSION))
                             }
-
-                            for closest in closest_below.into_iter().chain(closest_above) {
+                            PreferAuditedVersion::Lowest => {
+                                Some(audited.first().copied().unwrap_or(&ROOT_VERSION))
+                            }
+                        };
+                        let published = cache
+                            .query_package_from_index(package.name)
+                            .map(|krate| {
+                                krate
+                                    .versions()
+                                    .iter()
+                                    .filter(|v| !v.is_yanked())
+                                    .filter_map(|v| v.version().parse::<Version>().ok())
+                                    .collect::<SortedSet<_>>()
+                            })
+                            .unwrap_or_default();
+                        for &dest in from_target.as_ref().unwrap() {
+                            let sources = if let Some(preferred) = preferred {
+                                vec![preferred]
+                            } else {
+                                let closest_below =
+                                    from_root.range::<&Version, _>(..dest).next_back();
+                                let closest_above = from_root.range::<&Version, _>(dest..).next();
+                                closest_below
+                                    .into_iter()
+                                    .chain(closest_above)
+                                    .copied()
+                                    .collect()
+                            };
+
+                            for closest in sources {
                                 candidates.insert(Delta {
                                     from: closest.clone(),
                                     to: dest.clone(),
                                 });
+
+                                if closest == &ROOT_VERSION || closest == dest {
+                                    continue;
+                                }
+                                let mut chain = if closest < dest {
+                                    published
+                                        .range::<Version, _>((
+                                            Bound::Excluded(closest),
+                                            Bound::Excluded(dest),
+                                        ))
+                                        .collect::<Vec<_>>()
+                                } else {
+                                    published
+                                        .range::<Version, _>((
+                                            Bound::Excluded(dest),
+                                            Bound::Excluded(closest),
+                                        ))
+                                        .rev()
+                                        .collect::<Vec<_>>()
+                                };
+                                if chain.is_empty() || chain.len() > MAX_INTERMEDIATE_VERSIONS {
+                                    continue;
+                                }
+                                chain.insert(0, closest);
+                                chain.push(dest);
+                                for hop in chain.windows(2) {
+                                    chain_candidates.insert(Delta {
+                                        from: hop[0].clone(),
+                                        to: hop[1].clone(),
+                                    });
+                                }
                             }
                         }
                     } else {
@@ -2467,43 +3166,104 @@ impl<'a> ResolveReport<'a> {
                         }
                     }
 
-                    let diffstats = join_all(candidates.iter().map(|delta| async {
-                        match cache
-                            .fetch_and_diffstat_package(network, package.name, delta)
-                            .await
-                        {
-                            Ok(diffstat) => Some(DiffRecommendation {
+                    let diff = |delta: &Delta| {
+                        let delta = delta.clone();
+                        progress.inc_length(1);
+                        async {
+                            progress.set_message(format!("{}:{}", package.name, delta.to));
+                            let result = cache
+                                .fetch_and_diffstat_package(network, package.name, &delta)
+                                .await;
+                            progress.inc(1);
+                            result.map(|diffstat| DiffRecommendation {
                                 diffstat,
-                                from: delta.from.clone(),
-                                to: delta.to.clone(),
-                            }),
+                                from: delta.from,
+                                to: delta.to,
+                                steps: vec![],
+                            })
+                        }
+                    };
+                    let (diffstats, chain_diffstats) = futures_util::future::join(
+                        join_all(candidates.iter().map(diff)),
+                        join_all(chain_candidates.difference(&candidates).map(diff)),
+                    )
+                    .await;
+
+                    // We don't want to actually error out completely here, as
+                    // other packages might still successfully diff! Record the
+                    // failure so the caller can decide what to do about it.
+                    let mut first_error = None;
+                    let diffs = diffstats
+                        .into_iter()
+                        .filter_map(|diffstat| match diffstat {
+                            Ok(diffstat) => Some(diffstat),
                             Err(err) => {
-                                // We don't want to actually error out completely here,
-                                // as other packages might still successfully diff!
-                                error!(
-                                    "error diffing {}:{}: {:?}",
-                                    package.name, package.version, err
-                                );
+                                first_error.get_or_insert(err);
                                 None
                             }
+                        })
+                        .collect::<Vec<_>>();
+                    let mut suggested_diff =
+                        diffs.iter().min_by_key(|diff| diff.diffstat.count).cloned();
+
+                    // Only recommend a chain of reviews if it's strictly
+                    // cheaper than the best direct diff.
+                    if !chain_candidates.is_empty() {
+                        // A hop which couldn't be diffed just rules out the
+                        // chains through it, but shouldn't go unnoticed.
+                        let mut all_diffs = diffs;
+                        for diffstat in chain_diffstats {
+                            match diffstat {
+                                Ok(diffstat) => all_diffs.push(diffstat),
+                                Err(err) => warn!(
+                                    "couldn't diff {} for a chain of audits: {}",
+                                    package.name,
+                                    miette::Report::new(err)
+                                ),
+                            }
                         }
-                    }))
-                    .await;
-
-                    Some(SuggestItem {
-                        package: failure_idx,
-                        suggested_diff: diffstats
-                            .into_iter()
-                            .flatten()
-                            .min_by_key(|diff| diff.diffstat.count)?,
-                        suggested_criteria: audit_failure.criteria_failures.clone(),
-                        notable_parents,
-                    })
+                        let sources = candidates.iter().map(|delta| &delta.from).collect();
+                        let targets = candidates.iter().map(|delta| &delta.to).collect();
+                        if let Some(chain) = cheapest_diff_chain(&sources, &targets, &all_diffs) {
+                            let chain =
+                                DiffRecommendation::chain(chain.into_iter().cloned().collect());
+                            let cheaper = match &suggested_diff {
+                                Some(direct) => chain.diffstat.count < direct.diffstat.count,
+                                None => true,
+                            };
+                            if chain.steps.len() > 1 && cheaper {
+                                suggested_diff = Some(chain);
+                            }
+                        }
+                    }
+                    match (suggested_diff, first_error) {
+                        (Some(suggested_diff), _) => Some(Ok(SuggestItem {
+                            package: failure_idx,
+                            suggested_diff,
+                            suggested_criteria: audit_failure.criteria_failures.clone(),
+                            notable_parents,
+                            owner: None,
+                        })),
+                        (None, Some(err)) => {
+                            let reason = miette::Report::new(err).to_string();
+                            Some(Err((failure_idx, reason)))
+                        }
+                        (None, None) => None,
+                    }
                 },
             )))
             .into_iter()
             .flatten()
             .collect::<Vec<_>>();
+        progress.finish_and_clear();
+        let failures = suggestions
+            .iter()
+            .filter_map(|result| result.as_ref().err().cloned())
+            .collect::<Vec<_>>();
+        let mut suggestions = suggestions
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect::<Vec<_>>();
 
         let total_lines = suggestions
             .iter()
@@ -2533,6 +3293,9 @@ impl<'a> ResolveReport<'a> {
             suggestions,
             suggestions_by_criteria,
             total_lines,
+            deferred: 0,
+            too_large: 0,
+            failures,
         }))
     }
 
@@ -2587,6 +3350,68 @@ impl<'a> ResolveReport<'a> {
             .collect()
     }
 
+    /// Given a package name and a version to be certified, find the audited (or
+    /// exempted) version which a delta to it would best be certified from.
+    ///
+    /// Like `cargo vet suggest`, this picks the version closest to `to` which
+    /// is reachable from the root of the audit graph for every failing
+    /// criteria. Returns `None` if there is no such version, in which case a
+    /// full audit is needed.
+    pub fn compute_suggested_delta_base(
+        &self,
+        package_name: PackageStr<'_>,
+        to: &Version,
+    ) -> Option<Version> {
+        let fail = if let Conclusion::FailForVet(fail) = &self.conclusion {
+            fail
+        } else {
+            return None;
+        };
+
+        for (&failure_idx, audit_failure) in &fail.failures {
+            let package = &self.graph.nodes[failure_idx];
+            if package.name != package_name {
+                continue;
+            }
+
+            let result = &self.results[failure_idx];
+            let mut from_root = None::<SortedSet<&Version>>;
+            for criteria_idx in audit_failure.criteria_failures.all().indices() {
+                let search_result = &result.search_results[criteria_idx];
+                if let SearchResult::Disconnected {
+                    reachable_from_root,
+                    reachable_from_target,
+                } = search_result
+                {
+                    if !reachable_from_target.contains(to) {
+                        continue;
+                    }
+                    from_root = Some(match from_root {
+                        Some(from_root) => &from_root & reachable_from_root,
+                        None => reachable_from_root.clone(),
+                    });
+                }
+            }
+
+            // The root version is always reachable, but a delta from it is
+            // just a full audit.
+            if let Some(from_root) = from_root {
+                let closest_below = from_root
+                    .range::<&Version, _>(..to)
+                    .next_back()
+                    .filter(|&&version| version != &ROOT_VERSION);
+                let closest_above = from_root
+                    .range::<&Version, _>((Bound::Excluded(to), Bound::Unbounded))
+                    .next();
+                if let Some(&closest) = closest_below.or(closest_above) {
+                    return Some(closest.clone());
+                }
+            }
+        }
+
+        None
+    }
+
     /// Print a full human-readable report
     pub fn print_human(
         &self,
@@ -2595,9 +3420,43 @@ impl<'a> ResolveReport<'a> {
         suggest: Option<&Suggest>,
     ) -> Result<(), std::io::Error> {
         match &self.conclusion {
-            Conclusion::Success(res) => res.print_human(out, self, cfg),
-            Conclusion::FailForViolationConflict(res) => res.print_human(out, self, cfg),
-            Conclusion::FailForVet(res) => res.print_human(out, self, cfg, suggest),
+            Conclusion::Success(res) => res.print_human(out, self, cfg)?,
+            Conclusion::FailForViolationConflict(res) => res.print_human(out, self, cfg)?,
+            Conclusion::FailForVet(res) => res.print_human(out, self, cfg, suggest)?,
+        }
+        self.print_advisories_human(out);
+        Ok(())
+    }
+
+    /// Warn about the vetted packages with known advisories. Unvetted ones
+    /// already have their advisories listed alongside what they're missing.
+    fn print_advisories_human(&self, out: &Arc<dyn Out>) {
+        let is_unvetted = |pkgidx: &PackageIdx| match &self.conclusion {
+            Conclusion::FailForVet(fail) => fail.failures.contains_key(pkgidx),
+            _ => false,
+        };
+        let vetted = self
+            .advisories
+            .iter()
+            .filter(|(pkgidx, _)| !is_unvetted(pkgidx))
+            .collect::<Vec<_>>();
+        if vetted.is_empty() {
+            return;
+        }
+        writeln!(out);
+        writeln!(
+            out,
+            "warning: these vetted dependencies have known advisories:"
+        );
+        for (&pkgidx, ids) in vetted {
+            let package = &self.graph.nodes[pkgidx];
+            writeln!(
+                out,
+                "  {}:{} ({})",
+                package.name,
+                package.version,
+                ids.join(", ")
+            );
         }
     }
 
@@ -2617,6 +3476,351 @@ impl<'a> ResolveReport<'a> {
         Ok(())
     }
 
+    /// The criteria each package is required to have, from pushing the
+    /// policies of the packages which have one down through their
+    /// dependencies, by the same rules `resolve` checks them with. The
+    /// dev-dependencies of workspace members are required to have the
+    /// member's `dev-criteria`.
+    ///
+    /// The `dependency-criteria` of audits aren't taken into account, as they
+    /// only apply to whichever audits were used.
+    pub fn required_criteria(&self, store: &Store) -> Vec<Vec<CriteriaName>> {
+        let graph = &self.graph;
+        let criteria_mapper = &self.criteria_mapper;
+
+        // Packages with a policy of their own ignore what their dependents
+        // require of them.
+        let mut required = vec![criteria_mapper.no_criteria(); graph.nodes.len()];
+        let mut has_policy = vec![false; graph.nodes.len()];
+        for (idx, package) in graph.nodes.iter().enumerate() {
+            if let Some(policy) = own_policy(store, criteria_mapper, package) {
+                required[idx] = policy.criteria;
+                has_policy[idx] = !policy.only_proc_macro;
+            }
+        }
+
+        let require = |required: &mut [CriteriaSet], depidx: PackageIdx, criteria: &CriteriaSet| {
+            if !has_policy[depidx] && !required[depidx].contains(criteria) {
+                required[depidx].unioned_with(criteria);
+                true
+            } else {
+                false
+            }
+        };
+        // The singleton set (with its implied criteria) of each required criteria
+        let each_criteria = |criteria: &CriteriaSet| {
+            criteria_mapper
+                .all_criteria_iter()
+                .enumerate()
+                .filter(|(idx, _)| criteria.has_criteria(*idx))
+                .map(|(_, criteria)| criteria.clone())
+                .collect::<Vec<_>>()
+        };
+
+        for package in &graph.nodes {
+            if !package.is_workspace_member {
+                continue;
+            }
+            let dep_criteria = policy_dependency_criteria(
+                store,
+                criteria_mapper,
+                package,
+                DependencyKind::Development,
+            );
+            for criteria in each_criteria(&dev_policy(store, criteria_mapper, package)) {
+                for &depidx in &package.dev_deps {
+                    let criteria = dep_criteria
+                        .get(graph.nodes[depidx].name)
+                        .unwrap_or(&criteria);
+                    require(&mut required, depidx, criteria);
+                }
+            }
+        }
+
+        // Dependents come before their dependencies in reverse topological
+        // order, but dev-dependencies can introduce cycles, so repeat until
+        // nothing changes.
+        let dep_criteria = graph
+            .nodes
+            .iter()
+            .map(|package| {
+                policy_dependency_criteria(store, criteria_mapper, package, DependencyKind::Normal)
+            })
+            .collect::<Vec<_>>();
+        let build_dep_criteria = graph
+            .nodes
+            .iter()
+            .map(|package| {
+                policy_dependency_criteria(store, criteria_mapper, package, DependencyKind::Build)
+            })
+            .collect::<Vec<_>>();
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &idx in graph.topo_index.iter().rev() {
+                for criteria in each_criteria(&required[idx]) {
+                    for &depidx in &graph.nodes[idx].normal_and_build_deps {
+                        let criteria = dependency_requirement(
+                            graph,
+                            criteria_mapper,
+                            idx,
+                            depidx,
+                            &criteria,
+                            &dep_criteria[idx],
+                            &build_dep_criteria[idx],
+                        );
+                        changed |= require(&mut required, depidx, &criteria);
+                    }
+                }
+            }
+        }
+        required
+            .iter()
+            .map(|criteria| {
+                criteria_mapper
+                    .criteria_names(criteria)
+                    .map(|name| name.to_owned())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The `exemptions` entries which weren't needed to vet anything, either
+    /// because their package isn't in the graph, or because audits (or other
+    /// exemptions) already covered everything its reverse-dependencies need.
+    ///
+    /// This is only meaningful if the check succeeded, as a failed search
+    /// can't tell us which exemptions would have been needed.
+    pub fn unused_exemptions<'b>(
+        &self,
+        exemptions: &'b SortedMap<PackageName, Vec<ExemptedDependency>>,
+    ) -> Vec<(&'b PackageName, &'b ExemptedDependency)> {
+        let mut used = SortedSet::new();
+        for (node, result) in self.graph.nodes.iter().zip(&self.results) {
+            if !result.needed_exemption {
+                continue;
+            }
+            for search_result in &result.search_results {
+                if let SearchResult::Connected { path, .. } = search_result {
+                    for step in path.iter().filter(|step| step.is_exemption) {
+                        used.insert((node.name, step.to));
+                    }
+                }
+            }
+        }
+
+        exemptions
+            .iter()
+            .flat_map(|(package, entries)| entries.iter().map(move |entry| (package, entry)))
+            .filter(|(package, entry)| !used.contains(&(&***package, &entry.version)))
+            .collect()
+    }
+
+    /// The chains of audits, deltas, and exemptions which satisfied the
+    /// criteria of a third-party package, each described like "full audit
+    /// @3.0.0 + delta 3.0.0 -> 5.0.0", along with the criteria it satisfied.
+    /// Criteria which were validated by the same path are grouped together,
+    /// as implied criteria usually are.
+    pub fn audit_paths(&self, pkgidx: PackageIdx) -> Vec<(String, Vec<CriteriaStr<'_>>)> {
+        let mut paths = Vec::<(&[AuditStep], Vec<CriteriaStr>)>::new();
+        let result = &self.results[pkgidx];
+        for (criteria_idx, search_result) in result.search_results.iter().enumerate() {
+            if let SearchResult::Connected { path, .. } = search_result {
+                let name = &*self.criteria_mapper.list[criteria_idx].0;
+                match paths.iter_mut().find(|(p, _)| *p == &path[..]) {
+                    Some((_, names)) => names.push(name),
+                    None => paths.push((path, vec![name])),
+                }
+            }
+        }
+
+        paths
+            .into_iter()
+            .map(|(path, criteria)| {
+                let steps = path
+                    .iter()
+                    .map(|step| {
+                        let mut desc = if step.is_exemption {
+                            format!("exemption @{}", step.to)
+                        } else if step.from == &ROOT_VERSION {
+                            format!("full audit @{}", step.to)
+                        } else {
+                            format!("delta {} -> {}", step.from, step.to)
+                        };
+                        if let AuditSource::Foreign(import_name) = &step.source {
+                            desc.push_str(&format!(" from {import_name}"));
+                        }
+                        desc
+                    })
+                    .collect::<Vec<_>>();
+                (steps.join(" + "), criteria)
+            })
+            .collect()
+    }
+
+    /// Print which audits and exemptions satisfied each criteria of every
+    /// version of `package` in the graph (for `cargo vet --explain`).
+    pub fn print_explanation(
+        &self,
+        out: &Arc<dyn Out>,
+        package: PackageStr,
+    ) -> Result<(), std::io::Error> {
+        let mut found = false;
+        for (pkgidx, node) in self.graph.nodes.iter().enumerate() {
+            if node.name != package {
+                continue;
+            }
+            found = true;
+            if !node.is_third_party {
+                writeln!(
+                    out,
+                    "{}:{}: first-party, so it only needs its dependencies to be vetted",
+                    node.name, node.version
+                );
+                continue;
+            }
+
+            let paths = self.audit_paths(pkgidx);
+            if paths.is_empty() {
+                writeln!(
+                    out,
+                    "{}:{}: no criteria are satisfied",
+                    node.name, node.version
+                );
+            }
+            for (path, criteria) in paths {
+                writeln!(
+                    out,
+                    "{}:{}: {} ({})",
+                    node.name,
+                    node.version,
+                    path,
+                    criteria.join(", ")
+                );
+            }
+        }
+        if !found {
+            writeln!(out, "{package} isn't in your dependency graph");
+        }
+        Ok(())
+    }
+
+    /// Dump the graph along with everything the resolver considered and
+    /// concluded for each package, for debugging resolution decisions.
+    ///
+    /// Like `dump-graph` itself, the format of this isn't guaranteed.
+    pub fn resolution_json(&self, store: &Store) -> serde_json::Value {
+        let names = |criteria: &CriteriaSet| {
+            criteria
+                .indices()
+                .map(|idx| &*self.criteria_mapper.list[idx].0)
+                .collect::<Vec<_>>()
+        };
+        let violations = match &self.conclusion {
+            Conclusion::FailForViolationConflict(fail) => Some(&fail.violations),
+            _ => None,
+        };
+        let failures = match &self.conclusion {
+            Conclusion::FailForVet(fail) => Some(&fail.failures),
+            _ => None,
+        };
+
+        let nodes = self.graph.nodes.iter().enumerate().map(|(pkgidx, package)| {
+            let mut json = serde_json::to_value(package).unwrap();
+            if !package.is_third_party {
+                return json;
+            }
+            let result = &self.results[pkgidx];
+
+            let search_results = result
+                .search_results
+                .iter()
+                .enumerate()
+                .map(|(criteria_idx, search_result)| {
+                    let search_result = match search_result {
+                        SearchResult::Connected {
+                            fully_audited,
+                            path,
+                        } => json!({
+                            "result": "connected",
+                            "fully_audited": fully_audited,
+                            "path": path.iter().map(|step| json!({
+                                "from": step.from,
+                                "to": step.to,
+                                "source": step.source,
+                                "exemption": step.is_exemption,
+                            })).collect::<Vec<_>>(),
+                        }),
+                        SearchResult::PossiblyConnected { failed_deps } => json!({
+                            "result": "possibly-connected",
+                            "failed_deps": failed_deps.iter().map(|(&dep_idx, criteria)| {
+                                let dep = &self.graph.nodes[dep_idx];
+                                json!({
+                                    "name": dep.name,
+                                    "version": dep.version,
+                                    "criteria": names(criteria),
+                                })
+                            }).collect::<Vec<_>>(),
+                        }),
+                        SearchResult::Disconnected {
+                            reachable_from_root,
+                            reachable_from_target,
+                        } => json!({
+                            "result": "disconnected",
+                            "reachable_from_root": reachable_from_root,
+                            "reachable_from_target": reachable_from_target,
+                        }),
+                    };
+                    (&*self.criteria_mapper.list[criteria_idx].0, search_result)
+                })
+                .collect::<SortedMap<_, _>>();
+
+            let audits = store
+                .audits
+                .audits
+                .get(package.name)
+                .into_iter()
+                .flatten()
+                .map(|audit| (AuditSource::OwnAudits, audit))
+                .chain(store.imports.audits.iter().flat_map(|(import_name, audits)| {
+                    audits
+                        .audits
+                        .get(package.name)
+                        .into_iter()
+                        .flatten()
+                        .map(|audit| (AuditSource::Foreign(import_name.clone()), audit))
+                }))
+                .map(|(source, audit)| json!({ "source": source, "audit": audit }))
+                .collect::<Vec<_>>();
+
+            let verdict = if matches!(violations, Some(v) if v.contains_key(&pkgidx)) {
+                "violation"
+            } else if matches!(failures, Some(f) if f.contains_key(&pkgidx)) {
+                "failed"
+            } else if !result.needed_exemption {
+                "vetted-fully"
+            } else if result.directly_exempted {
+                "vetted-with-exemptions"
+            } else {
+                "vetted-partially"
+            };
+
+            json["resolution"] = json!({
+                "verdict": verdict,
+                "validated_criteria": names(&result.validated_criteria),
+                "fully_audited_criteria": names(&result.fully_audited_criteria),
+                "directly_exempted": result.directly_exempted,
+                "needed_exemption": result.needed_exemption,
+                "search_results": search_results,
+                "audits": audits,
+                "exemptions": store.config.exemptions.get(package.name).cloned().unwrap_or_default(),
+            });
+            json
+        });
+
+        json!(nodes.collect::<Vec<_>>())
+    }
+
     /// Print a full human-readable report
     pub fn print_json(
         &self,
@@ -2624,13 +3828,19 @@ impl<'a> ResolveReport<'a> {
         _cfg: &Config,
         suggest: Option<&Suggest>,
     ) -> Result<(), miette::Report> {
-        let result = match &self.conclusion {
+        let mut result = match &self.conclusion {
             Conclusion::Success(success) => {
                 let json_package = |pkgidx: &PackageIdx| {
                     let package = &self.graph.nodes[*pkgidx];
                     json!({
                         "name": package.name,
                         "version": package.version,
+                        "audit_paths": self.audit_paths(*pkgidx).into_iter().map(|(path, criteria)| {
+                            json!({
+                                "path": path,
+                                "criteria": criteria,
+                            })
+                        }).collect::<Vec<_>>(),
                     })
                 };
                 json!({
@@ -2652,36 +3862,406 @@ impl<'a> ResolveReport<'a> {
                 // FIXME: How to report confidence for suggested criteria?
                 let json_suggest_item = |item: &SuggestItem| {
                     let package = &self.graph.nodes[item.package];
-                    json!({
+                    let mut json = json!({
                         "name": package.name,
                         "notable_parents": item.notable_parents,
                         "suggested_criteria": self.criteria_mapper.all_criteria_names(&item.suggested_criteria).collect::<Vec<_>>(),
                         "suggested_diff": item.suggested_diff,
-                    })
+                    });
+                    if let Some(owner) = &item.owner {
+                        json["owner"] = json!(owner);
+                    }
+                    if let Some(notes) = self.notes.get(package.name) {
+                        json["notes"] = json!(notes);
+                    }
+                    json
                 };
                 json!({
                     "conclusion": "fail (vetting)",
                     "failures": fail.failures.iter().map(|(&pkgidx, audit_fail)| {
                         let package = &self.graph.nodes[pkgidx];
-                        json!({
+                        let mut json = json!({
                             "name": package.name,
                             "version": package.version,
                             "missing_criteria": self.criteria_mapper.all_criteria_names(&audit_fail.criteria_failures).collect::<Vec<_>>(),
-                        })
+                        });
+                        if let Some(notes) = self.notes.get(package.name) {
+                            json["notes"] = json!(notes);
+                        }
+                        if self.is_warning(audit_fail) {
+                            json["warning"] = json!(true);
+                        }
+                        if package.is_proc_macro {
+                            json["proc_macro"] = json!(true);
+                        }
+                        json
                     }).collect::<Vec<_>>(),
-                    "suggest": suggest.map(|suggest| json!({
-                        "suggestions": suggest.suggestions.iter().map(json_suggest_item).collect::<Vec<_>>(),
-                        "suggest_by_criteria": suggest.suggestions_by_criteria.iter().map(|(criteria, items)| (criteria, items.iter().map(json_suggest_item).collect::<Vec<_>>())).collect::<SortedMap<_,_>>(),
-                        "total_lines": suggest.total_lines,
-                    })),
+                    "suggest": suggest.map(|suggest| {
+                        let mut json = json!({
+                            "suggestions": suggest.suggestions.iter().map(json_suggest_item).collect::<Vec<_>>(),
+                            "suggest_by_criteria": suggest.suggestions_by_criteria.iter().map(|(criteria, items)| (criteria, items.iter().map(json_suggest_item).collect::<Vec<_>>())).collect::<SortedMap<_,_>>(),
+                            "total_lines": suggest.total_lines,
+                        });
+                        if !suggest.failures.is_empty() {
+                            json["failures"] = suggest.failures.iter().map(|(pkgidx, reason)| {
+                                let package = &self.graph.nodes[*pkgidx];
+                                json!({
+                                    "name": package.name,
+                                    "version": package.version,
+                                    "reason": reason,
+                                })
+                            }).collect();
+                        }
+                        json
+                    }),
                 })
             }
         };
+        if !self.advisories.is_empty() {
+            result["advisories"] = json!(self
+                .advisories
+                .iter()
+                .map(|(pkgidx, ids)| {
+                    let package = &self.graph.nodes[*pkgidx];
+                    let key = format!("{}:{}", package.name, package.version);
+                    (key, ids)
+                })
+                .collect::<SortedMap<_, _>>());
+        }
 
         serde_json::to_writer_pretty(&**out, &result).into_diagnostic()?;
 
         Ok(())
     }
+
+    /// Print the failures as a SARIF 2.1.0 log, for code scanning tools.
+    ///
+    /// Results point at the package's `exemptions` entry in config.toml if it
+    /// has one, and at config.toml as a whole otherwise. `config_uri` is the
+    /// path of config.toml relative to the root of the repository, and
+    /// `config_text` its contents.
+    pub fn print_sarif(
+        &self,
+        out: &Arc<dyn Out>,
+        config_uri: &str,
+        config_text: &str,
+    ) -> Result<(), miette::Report> {
+        let location = |package: &PackageNode| {
+            let mut location = json!({
+                "physicalLocation": {
+                    "artifactLocation": { "uri": config_uri },
+                },
+            });
+            if let Some(line) = find_exemptions_line(config_text, package.name) {
+                location["physicalLocation"]["region"] = json!({ "startLine": line });
+            }
+            location
+        };
+
+        let mut results = vec![];
+        match &self.conclusion {
+            Conclusion::Success(_) => {}
+            Conclusion::FailForViolationConflict(fail) => {
+                for &pkgidx in fail.violations.keys() {
+                    let package = &self.graph.nodes[pkgidx];
+                    results.push(json!({
+                        "ruleId": SARIF_RULE_VIOLATION,
+                        "level": "error",
+                        "message": {
+                            "text": format!(
+                                "{}:{} is covered by audits or exemptions which conflict with a violation",
+                                package.name, package.version
+                            ),
+                        },
+                        "locations": [location(package)],
+                    }));
+                }
+            }
+            Conclusion::FailForVet(fail) => {
+                for (&pkgidx, audit_fail) in &fail.failures {
+                    let package = &self.graph.nodes[pkgidx];
+                    let missing = self
+                        .criteria_mapper
+                        .all_criteria_names(&audit_fail.criteria_failures)
+                        .collect::<Vec<_>>();
+                    results.push(json!({
+                        "ruleId": SARIF_RULE_UNAUDITED,
+                        "level": if self.is_warning(audit_fail) { "warning" } else { "error" },
+                        "message": {
+                            "text": format!(
+                                "{}:{} is missing {:?}",
+                                package.name, package.version, missing
+                            ),
+                        },
+                        "locations": [location(package)],
+                    }));
+                }
+            }
+        }
+
+        let sarif = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "cargo-vet",
+                        "informationUri": "https://mozilla.github.io/cargo-vet/",
+                        "rules": [
+                            {
+                                "id": SARIF_RULE_UNAUDITED,
+                                "shortDescription": {
+                                    "text": "Dependency hasn't been audited for the criteria it requires",
+                                },
+                            },
+                            {
+                                "id": SARIF_RULE_VIOLATION,
+                                "shortDescription": {
+                                    "text": "Dependency's audits conflict with a violation",
+                                },
+                            },
+                        ],
+                    },
+                },
+                "results": results,
+            }],
+        });
+        serde_json::to_writer_pretty(&**out, &sarif).into_diagnostic()?;
+
+        Ok(())
+    }
+
+    /// Count how many third-party packages satisfy each criteria.
+    pub fn compute_coverage(&self) -> Coverage {
+        let third_party = self
+            .graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, package)| package.is_third_party)
+            .map(|(pkgidx, _)| &self.results[pkgidx])
+            .collect::<Vec<_>>();
+
+        let criteria = self
+            .criteria_mapper
+            .list
+            .iter()
+            .enumerate()
+            .map(|(criteria_idx, (criteria_name, _))| {
+                let coverage = CriteriaCoverage {
+                    vetted: third_party
+                        .iter()
+                        .filter(|result| result.validated_criteria.has_criteria(criteria_idx))
+                        .count(),
+                    audited: third_party
+                        .iter()
+                        .filter(|result| result.fully_audited_criteria.has_criteria(criteria_idx))
+                        .count(),
+                };
+                (criteria_name.clone(), coverage)
+            })
+            .collect();
+
+        Coverage {
+            total: third_party.len(),
+            criteria,
+        }
+    }
+
+    /// Print a self-contained HTML page summarizing the report, for sharing with
+    /// people who won't read terminal output.
+    pub fn print_html(
+        &self,
+        out: &Arc<dyn Out>,
+        _cfg: &Config,
+        suggest: Option<&Suggest>,
+    ) -> Result<(), std::io::Error> {
+        // Classify every third-party package
+        let mut rows = vec![];
+        for &pkgidx in &self.graph.topo_index {
+            let package = &self.graph.nodes[pkgidx];
+            if !package.is_third_party {
+                continue;
+            }
+            let result = &self.results[pkgidx];
+            let failure = match &self.conclusion {
+                Conclusion::FailForVet(fail) => fail.failures.get(&pkgidx),
+                _ => None,
+            };
+            let has_violation = match &self.conclusion {
+                Conclusion::FailForViolationConflict(fail) => fail.violations.contains_key(&pkgidx),
+                _ => false,
+            };
+            let (status, criteria) = if has_violation {
+                ("violation", vec![])
+            } else if let Some(failure) = failure {
+                (
+                    "unvetted",
+                    self.criteria_mapper
+                        .all_criteria_names(&failure.criteria_failures)
+                        .collect(),
+                )
+            } else if !result.needed_exemption {
+                (
+                    "fully audited",
+                    self.criteria_mapper
+                        .criteria_names(&result.fully_audited_criteria)
+                        .collect(),
+                )
+            } else if result.directly_exempted {
+                (
+                    "exempted",
+                    self.criteria_mapper
+                        .criteria_names(&result.validated_criteria)
+                        .collect(),
+                )
+            } else {
+                (
+                    "partially audited",
+                    self.criteria_mapper
+                        .criteria_names(&result.validated_criteria)
+                        .collect::<Vec<_>>(),
+                )
+            };
+            rows.push((package, status, criteria));
+        }
+        rows.sort_by_key(|(package, ..)| (package.name, package.version));
+
+        let conclusion = match &self.conclusion {
+            Conclusion::Success(_) => "Vetting Succeeded",
+            Conclusion::FailForViolationConflict(_) => "Violations Found",
+            Conclusion::FailForVet(_) if !self.has_errors() => "Vetting Succeeded, with warnings",
+            Conclusion::FailForVet(_) => "Vetting Failed",
+        };
+        let mut counts = SortedMap::<&str, usize>::new();
+        for (_, status, _) in &rows {
+            *counts.entry(status).or_default() += 1;
+        }
+
+        writeln!(out, "<!DOCTYPE html>");
+        writeln!(out, "<html lang=\"en\">");
+        writeln!(out, "<head>");
+        writeln!(out, "<meta charset=\"utf-8\">");
+        writeln!(out, "<title>cargo vet: {conclusion}</title>");
+        writeln!(out, "<style>{HTML_REPORT_STYLE}</style>");
+        writeln!(out, "</head>");
+        writeln!(out, "<body>");
+        writeln!(out, "<h1>{conclusion}</h1>");
+        writeln!(out, "<ul class=\"summary\">");
+        writeln!(out, "<li>{} third-party packages</li>", rows.len());
+        for (status, count) in &counts {
+            writeln!(out, "<li>{count} {status}</li>");
+        }
+        writeln!(out, "</ul>");
+
+        writeln!(out, "<table class=\"sortable\">");
+        writeln!(
+            out,
+            "<thead><tr><th>Package</th><th>Version</th><th>Status</th><th>Criteria</th><th>Notes</th></tr></thead>"
+        );
+        writeln!(out, "<tbody>");
+        for (package, status, criteria) in &rows {
+            let criteria = if *status == "unvetted" {
+                format!("missing {}", criteria.join(", "))
+            } else {
+                criteria.join(", ")
+            };
+            writeln!(
+                out,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                status.replace(' ', "-"),
+                html_escape(package.name),
+                package.version,
+                status,
+                html_escape(&criteria),
+                html_escape(self.notes.get(package.name).map_or("", |n| n.as_str())),
+            );
+        }
+        writeln!(out, "</tbody>");
+        writeln!(out, "</table>");
+
+        if let Some(suggest) = suggest {
+            writeln!(out, "<h2>Recommended audits</h2>");
+            writeln!(out, "<table class=\"sortable\">");
+            writeln!(
+                out,
+                "<thead><tr><th>Command</th><th>Criteria</th><th>Lines</th><th>Used by</th></tr></thead>"
+            );
+            writeln!(out, "<tbody>");
+            for item in &suggest.suggestions {
+                let package = &self.graph.nodes[item.package];
+                let cmd = item.suggested_diff.command(package.name);
+                let criteria = self
+                    .criteria_mapper
+                    .all_criteria_names(&item.suggested_criteria)
+                    .collect::<Vec<_>>();
+                writeln!(
+                    out,
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                    html_escape(&cmd),
+                    html_escape(&criteria.join(", ")),
+                    item.suggested_diff.diffstat.count,
+                    html_escape(&item.notable_parents),
+                );
+            }
+            writeln!(out, "</tbody>");
+            writeln!(out, "</table>");
+            writeln!(
+                out,
+                "<p>estimated audit backlog: {} lines</p>",
+                suggest.total_lines
+            );
+        }
+
+        writeln!(out, "<script>{HTML_REPORT_SCRIPT}</script>");
+        writeln!(out, "</body>");
+        writeln!(out, "</html>");
+        Ok(())
+    }
+}
+
+const HTML_REPORT_STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 2em; }
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; }
+th { background: #eee; cursor: pointer; }
+tr.unvetted, tr.violation { background: #fdd; }
+tr.exempted, tr.partially-audited { background: #ffd; }
+";
+
+/// Makes every table with class "sortable" sortable by clicking on its headers.
+const HTML_REPORT_SCRIPT: &str = "
+document.querySelectorAll('table.sortable').forEach(table => {
+  table.querySelectorAll('th').forEach((th, col) => {
+    let asc = true;
+    th.addEventListener('click', () => {
+      const body = table.tBodies[0];
+      const rows = Array.from(body.rows);
+      rows.sort((a, b) => {
+        const x = a.cells[col].textContent, y = b.cells[col].textContent;
+        return (asc ? 1 : -1) * x.localeCompare(y, undefined, {numeric: true});
+      });
+      asc = !asc;
+      rows.forEach(row => body.appendChild(row));
+    });
+  });
+});
+";
+
+/// Escape a string for use as HTML text.
+fn html_escape(s: &str) -> String {
+    let mut result = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&#39;"),
+            c => result.push(c),
+        }
+    }
+    result
 }
 
 impl Success {
@@ -2738,29 +4318,231 @@ impl Success {
 }
 
 impl Suggest {
+    /// Label each suggestion with its owner from the `owners` table in config.toml,
+    /// and if an `assignee` is given, discard any suggestions they don't own.
+    ///
+    /// If multiple patterns match a package, the longest (most specific) one wins.
+    pub fn assign_owners(
+        &mut self,
+        report: &ResolveReport,
+        owners: &SortedMap<String, OwnerName>,
+        assignee: Option<&str>,
+    ) {
+        let owner_of = |item: &SuggestItem| {
+            let package = &report.graph.nodes[item.package];
+            owners
+                .iter()
+                .filter(|(pattern, _)| package_glob_matches(pattern, package.name))
+                .max_by_key(|(pattern, _)| pattern.len())
+                .map(|(_, owner)| owner.clone())
+        };
+        let keep = |item: &SuggestItem| assignee.is_none() || item.owner.as_deref() == assignee;
+
+        for item in &mut self.suggestions {
+            item.owner = owner_of(item);
+        }
+        self.suggestions.retain(keep);
+        for suggestions in self.suggestions_by_criteria.values_mut() {
+            for item in suggestions.iter_mut() {
+                item.owner = owner_of(item);
+            }
+            suggestions.retain(keep);
+        }
+        self.suggestions_by_criteria
+            .retain(|_, suggestions| !suggestions.is_empty());
+        self.total_lines = self
+            .suggestions
+            .iter()
+            .map(|s| s.suggested_diff.diffstat.count)
+            .sum();
+    }
+
+    /// Narrow the suggestions down to a batch whose combined diffstat fits
+    /// within `budget` lines, preferring the audits which unblock the most
+    /// packages per line reviewed.
+    ///
+    /// The impact of an audit is the package itself plus everything which
+    /// transitively depends on it, as those are the packages whose audit gaps
+    /// it helps close. This is a knapsack problem, so we solve it greedily by
+    /// impact density rather than exactly.
+    pub fn apply_budget(&mut self, report: &ResolveReport, budget: u64) {
+        let impact = |item: &SuggestItem| {
+            let mut seen = SortedSet::new();
+            let mut queue = vec![item.package];
+            while let Some(idx) = queue.pop() {
+                if seen.insert(idx) {
+                    queue.extend(report.graph.nodes[idx].reverse_deps.iter().copied());
+                }
+            }
+            seen.len() as u64
+        };
+
+        let mut candidates = self
+            .suggestions
+            .iter()
+            .map(|item| {
+                (
+                    item.package,
+                    impact(item),
+                    item.suggested_diff.diffstat.count,
+                )
+            })
+            .collect::<Vec<_>>();
+        // Sort by impact per line, highest first, comparing the cross products
+        // to avoid dividing by zero-line diffs. Ties go to the smaller diff.
+        candidates.sort_by(|(_, impact_a, lines_a), (_, impact_b, lines_b)| {
+            (*impact_b as u128 * *lines_a as u128)
+                .cmp(&(*impact_a as u128 * *lines_b as u128))
+                .then(lines_a.cmp(lines_b))
+        });
+
+        let mut remaining = budget;
+        let mut selected = SortedSet::new();
+        for (package, _, lines) in candidates {
+            if lines <= remaining {
+                remaining -= lines;
+                selected.insert(package);
+            }
+        }
+
+        self.deferred += self.retain(|item| selected.contains(&item.package));
+    }
+
+    /// Drop the suggestions whose diffs have more than `max_lines` lines, so
+    /// that only the quick reviews are left.
+    pub fn apply_max_lines(&mut self, max_lines: u64) {
+        self.too_large += self.retain(|item| item.suggested_diff.diffstat.count <= max_lines);
+    }
+
+    /// Drop the suggestions which wouldn't help satisfy `criteria`.
+    ///
+    /// Auditing a package for a criteria which implies `criteria` also
+    /// satisfies it, so those suggestions are kept too.
+    pub fn apply_criteria(
+        &mut self,
+        report: &ResolveReport,
+        criteria: CriteriaStr,
+    ) -> Result<(), miette::Report> {
+        let idx = report
+            .criteria_mapper
+            .index
+            .get(criteria)
+            .copied()
+            .ok_or_else(|| miette::miette!("'{criteria}' is not a valid criteria name"))?;
+        self.retain(|item| item.suggested_criteria.all().has_criteria(idx));
+        Ok(())
+    }
+
+    /// Keep only the suggestions matching `keep`, returning how many were
+    /// dropped.
+    fn retain(&mut self, keep: impl Fn(&SuggestItem) -> bool) -> usize {
+        let before = self.suggestions.len();
+        self.suggestions.retain(&keep);
+        for suggestions in self.suggestions_by_criteria.values_mut() {
+            suggestions.retain(&keep);
+        }
+        self.suggestions_by_criteria
+            .retain(|_, suggestions| !suggestions.is_empty());
+        self.total_lines = self
+            .suggestions
+            .iter()
+            .map(|s| s.suggested_diff.diffstat.count)
+            .sum();
+        before - self.suggestions.len()
+    }
+
     pub fn print_human(
         &self,
         out: &Arc<dyn Out>,
         report: &ResolveReport,
     ) -> Result<(), std::io::Error> {
+        // If owners have been assigned, group by owner first (with unowned last),
+        // and then by criteria within each owner.
+        let mut owners = self
+            .suggestions
+            .iter()
+            .map(|item| item.owner.as_ref())
+            .collect::<SortedSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        owners.sort_by_key(|owner| owner.is_none());
+
+        for owner in owners {
+            self.print_human_for_owner(out, report, owner)?;
+        }
+
+        writeln!(out, "estimated audit backlog: {} lines", self.total_lines);
+        if self.deferred > 0 {
+            writeln!(
+                out,
+                "{} more {} did not fit within the review budget",
+                self.deferred,
+                if self.deferred == 1 {
+                    "suggestion"
+                } else {
+                    "suggestions"
+                }
+            );
+        }
+        if self.too_large > 0 {
+            writeln!(
+                out,
+                "{} more {} over the maximum number of lines",
+                self.too_large,
+                if self.too_large == 1 {
+                    "suggestion was"
+                } else {
+                    "suggestions were"
+                }
+            );
+        }
+        if !self.failures.is_empty() {
+            writeln!(out, "couldn't suggest audits for these packages:");
+            for (package, reason) in &self.failures {
+                let package = &report.graph.nodes[*package];
+                writeln!(out, "  {}:{}: {}", package.name, package.version, reason);
+            }
+        }
+        writeln!(out);
+        writeln!(out, "Use |cargo vet certify| to record the audits.");
+
+        Ok(())
+    }
+
+    fn print_human_for_owner(
+        &self,
+        out: &Arc<dyn Out>,
+        report: &ResolveReport,
+        owner: Option<&OwnerName>,
+    ) -> Result<(), std::io::Error> {
+        let any_owners = self.suggestions.iter().any(|item| item.owner.is_some());
         for (criteria, suggestions) in &self.suggestions_by_criteria {
-            writeln!(out, "recommended audits for {}:", criteria);
+            let suggestions = suggestions
+                .iter()
+                .filter(|item| item.owner.as_ref() == owner)
+                .collect::<Vec<_>>();
+            if suggestions.is_empty() {
+                continue;
+            }
+            match owner {
+                Some(owner) => {
+                    writeln!(
+                        out,
+                        "recommended audits for {} (owned by {}):",
+                        criteria, owner
+                    )
+                }
+                None if any_owners => {
+                    writeln!(out, "recommended audits for {} (no owner):", criteria)
+                }
+                None => writeln!(out, "recommended audits for {}:", criteria),
+            }
 
             let strings = suggestions
                 .iter()
                 .map(|item| {
                     let package = &report.graph.nodes[item.package];
-                    let cmd = if item.suggested_diff.from == ROOT_VERSION {
-                        format!(
-                            "cargo vet inspect {} {}",
-                            package.name, item.suggested_diff.to
-                        )
-                    } else {
-                        format!(
-                            "cargo vet diff {} {} {}",
-                            package.name, item.suggested_diff.from, item.suggested_diff.to
-                        )
-                    };
+                    let cmd = item.suggested_diff.command(package.name);
                     let parents = format!("(used by {})", item.notable_parents);
                     let diffstat = if item.suggested_diff.from == ROOT_VERSION {
                         format!("({} lines)", item.suggested_diff.diffstat.count)
@@ -2783,7 +4565,7 @@ impl Suggest {
                 max1 = max1.max(console::measure_text_width(s1));
             }
 
-            for (s0, s1, s2, style) in strings {
+            for ((s0, s1, s2, style), item) in strings.into_iter().zip(&suggestions) {
                 write!(
                     out,
                     "{}",
@@ -2798,15 +4580,32 @@ impl Suggest {
                     "{}",
                     style.apply_to(format_args!("  {s1:width$}  {s2}", width = max1))
                 );
+                let package = &report.graph.nodes[item.package];
+                print_package_notes(out, report.notes.get(package.name), "      ");
+            }
+
+            // With several criteria to triage, say how much work each one is.
+            if self.suggestions_by_criteria.len() > 1 {
+                let lines: u64 = suggestions
+                    .iter()
+                    .map(|item| item.suggested_diff.diffstat.count)
+                    .sum();
+                writeln!(
+                    out,
+                    "    subtotal: {} {}, {} lines",
+                    suggestions.len(),
+                    if suggestions.len() == 1 {
+                        "crate"
+                    } else {
+                        "crates"
+                    },
+                    lines
+                );
             }
 
             writeln!(out);
         }
 
-        writeln!(out, "estimated audit backlog: {} lines", self.total_lines);
-        writeln!(out);
-        writeln!(out, "Use |cargo vet certify| to record the audits.");
-
         Ok(())
     }
 }
@@ -2819,18 +4618,22 @@ impl FailForVet {
         _cfg: &Config,
         suggest: Option<&Suggest>,
     ) -> Result<(), std::io::Error> {
-        writeln!(out, "Vetting Failed!");
+        if report.has_errors() {
+            writeln!(out, "Vetting Failed!");
+        } else {
+            writeln!(out, "Vetting Succeeded, with warnings!");
+        }
         writeln!(out);
         writeln!(out, "{} unvetted dependencies:", self.failures.len());
         let mut failures = self
             .failures
             .iter()
-            .map(|(&failed_idx, failure)| (&report.graph.nodes[failed_idx], failure))
+            .map(|(&failed_idx, failure)| (failed_idx, &report.graph.nodes[failed_idx], failure))
             .collect::<Vec<_>>();
-        failures.sort_by_key(|(failed, _)| failed.version);
-        failures.sort_by_key(|(failed, _)| failed.name);
-        failures.sort_by_key(|(_, failure)| failure.criteria_failures.is_fully_unconfident());
-        for (failed_package, failed_audit) in failures {
+        failures.sort_by_key(|(_, failed, _)| failed.version);
+        failures.sort_by_key(|(_, failed, _)| failed.name);
+        failures.sort_by_key(|(_, _, failure)| failure.criteria_failures.is_fully_unconfident());
+        for (failed_idx, failed_package, failed_audit) in failures {
             let confident_criteria = report
                 .criteria_mapper
                 .confident_criteria_names(&failed_audit.criteria_failures)
@@ -2840,7 +4643,16 @@ impl FailForVet {
                 .unconfident_criteria_names(&failed_audit.criteria_failures)
                 .collect::<Vec<_>>();
 
-            let label = format!("  {}:{}", failed_package.name, failed_package.version);
+            let mut label = format!("  {}:{}", failed_package.name, failed_package.version);
+            if failed_package.is_proc_macro {
+                label.push_str(" (proc-macro)");
+            }
+            if report.is_warning(failed_audit) {
+                label.push_str(" (warning)");
+            }
+            if let Some(ids) = report.advisories.get(&failed_idx) {
+                label.push_str(&format!(" ({})", ids.join(", ")));
+            }
             if !confident_criteria.is_empty() {
                 writeln!(out, "{} missing {:?}", label, confident_criteria);
             }
@@ -2859,6 +4671,7 @@ impl FailForVet {
                     ))
                 );
             }
+            print_package_notes(out, report.notes.get(failed_package.name), "    ");
         }
 
         // Suggest output generally requires hitting the network.
@@ -2871,6 +4684,43 @@ impl FailForVet {
     }
 }
 
+const SARIF_RULE_UNAUDITED: &str = "cargo-vet/unaudited";
+const SARIF_RULE_VIOLATION: &str = "cargo-vet/violation";
+
+/// Find the (1-based) line of the first `[[exemptions.<package>]]` header in
+/// config.toml, if there is one.
+fn find_exemptions_line(config_text: &str, package: PackageStr) -> Option<usize> {
+    let quoted = format!("{package:?}");
+    config_text
+        .lines()
+        .position(|line| {
+            let key = line
+                .trim()
+                .strip_prefix("[[")
+                .and_then(|rest| rest.strip_suffix("]]"))
+                .map(|key| key.trim())
+                .and_then(|key| key.strip_prefix("exemptions."));
+            matches!(key, Some(key) if key == package || key == quoted)
+        })
+        .map(|idx| idx + 1)
+}
+
+/// Print the `notes` for a package from config.toml (if any), one line at a time.
+fn print_package_notes(out: &Arc<dyn Out>, notes: Option<&String>, indent: &str) {
+    if let Some(notes) = notes {
+        for (i, line) in notes.trim().lines().enumerate() {
+            let prefix = if i == 0 { "note: " } else { "      " };
+            writeln!(
+                out,
+                "{}",
+                out.style()
+                    .dim()
+                    .apply_to(format_args!("{indent}{prefix}{line}"))
+            );
+        }
+    }
+}
+
 impl FailForViolationConflict {
     fn print_human(
         &self,
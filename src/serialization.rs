@@ -203,6 +203,9 @@ pub mod audit {
         #[serde(default)]
         dependency_criteria: DependencyCriteria,
         notes: Option<String>,
+        #[serde(rename = "source-hash")]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        source_hash: Option<String>,
     }
 
     impl TryFrom<AuditEntryAll> for AuditEntry {
@@ -229,11 +232,16 @@ pub mod audit {
                         .to_string(),
                 ),
             };
+            let kind = kind?;
+            if val.source_hash.is_some() && !matches!(kind, AuditKind::Full { .. }) {
+                return Err("'source-hash' is only supported for full audits".to_string());
+            }
             Ok(AuditEntry {
                 who: val.who,
                 notes: val.notes,
                 criteria: val.criteria,
-                kind: kind?,
+                kind,
+                source_hash: val.source_hash,
             })
         }
     }
@@ -261,6 +269,7 @@ pub mod audit {
                 delta,
                 violation,
                 dependency_criteria,
+                source_hash: val.source_hash,
             }
         }
     }
@@ -605,6 +614,7 @@ mod test {
                         dependency_criteria: dc_long,
                     },
                     notes: Some("notes go here!".to_owned()),
+                    source_hash: None,
                 },
                 AuditEntry {
                     who: None,
@@ -614,6 +624,7 @@ mod test {
                         dependency_criteria: dc_short,
                     },
                     notes: Some("notes go here!".to_owned()),
+                    source_hash: None,
                 },
             ],
         );
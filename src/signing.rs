@@ -0,0 +1,185 @@
+//! Signing published audits.toml files, and verifying the signatures of
+//! imported ones.
+//!
+//! Two kinds of keys are supported, both of which are handled by the usual
+//! tools for them rather than by us:
+//!
+//! * ssh keys, signed and verified with `ssh-keygen -Y` (in the
+//!   [`SSH_SIGNATURE_NAMESPACE`] namespace), with signatures in a `.sig` file
+//! * minisign keys, signed with `minisign -S`, with signatures in a `.minisig`
+//!   file
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use crate::errors::{CommandError, SignError, SignatureError};
+
+/// The namespace ssh signatures of audits.toml files are made in, so that they
+/// can't be confused with signatures made for anything else.
+pub const SSH_SIGNATURE_NAMESPACE: &str = "cargo-vet";
+
+/// The extension of the file holding an ssh signature.
+pub const SSH_SIGNATURE_EXTENSION: &str = "sig";
+
+/// The extension of the file holding a minisign signature.
+pub const MINISIGN_SIGNATURE_EXTENSION: &str = "minisig";
+
+/// The `public-key` of an import in config.toml.
+pub enum PublicKey {
+    /// An ssh public key, in the `ssh-ed25519 AAAA... comment` format of
+    /// `.pub` files and `authorized_keys`.
+    Ssh(String),
+    /// A base64 minisign public key (`RW...`).
+    Minisign(minisign_verify::PublicKey),
+}
+
+impl PublicKey {
+    pub fn parse(key: &str) -> Result<Self, String> {
+        let key = key.trim();
+        if key.contains('\n') {
+            return Err("public keys must be on a single line".to_owned());
+        }
+        let first_word = key.split_whitespace().next().unwrap_or("");
+        if first_word.starts_with("ssh-")
+            || first_word.starts_with("ecdsa-")
+            || first_word.starts_with("sk-")
+        {
+            return Ok(PublicKey::Ssh(key.to_owned()));
+        }
+        minisign_verify::PublicKey::from_base64(key)
+            .map(PublicKey::Minisign)
+            .map_err(|_| "not an ssh or minisign public key".to_owned())
+    }
+
+    /// The extension of the file next to a signed audits.toml which holds
+    /// its signature.
+    pub fn signature_extension(&self) -> &'static str {
+        match self {
+            PublicKey::Ssh(_) => SSH_SIGNATURE_EXTENSION,
+            PublicKey::Minisign(_) => MINISIGN_SIGNATURE_EXTENSION,
+        }
+    }
+
+    /// Check that `signature` is a signature of `data` made by this key.
+    pub async fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), SignatureError> {
+        match self {
+            PublicKey::Ssh(key) => verify_ssh(key, data, signature).await,
+            PublicKey::Minisign(key) => {
+                let signature = std::str::from_utf8(signature)
+                    .map_err(|_| SignatureError::Minisign(minisign_verify::Error::InvalidEncoding))
+                    .and_then(|signature| {
+                        minisign_verify::Signature::decode(signature)
+                            .map_err(SignatureError::Minisign)
+                    })?;
+                key.verify(data, &signature, false)
+                    .map_err(SignatureError::Minisign)
+            }
+        }
+    }
+}
+
+async fn verify_ssh(key: &str, data: &[u8], signature: &[u8]) -> Result<(), SignatureError> {
+    // ssh-keygen wants both the allowed signers and the signature as files.
+    let temp_file = |contents: &[u8]| {
+        let mut file = tempfile::NamedTempFile::new()?;
+        file.write_all(contents)?;
+        file.flush()?;
+        Ok(file)
+    };
+    let allowed_signers = temp_file(format!("{SSH_SIGNATURE_NAMESPACE} {key}\n").as_bytes())
+        .map_err(SignatureError::TempFile)?;
+    let signature_file = temp_file(signature).map_err(SignatureError::TempFile)?;
+
+    let data_file = temp_file(data).map_err(SignatureError::TempFile)?;
+    let stdin = data_file.reopen().map_err(SignatureError::TempFile)?;
+
+    let out = tokio::process::Command::new("ssh-keygen")
+        .args(["-Y", "verify", "-f"])
+        .arg(allowed_signers.path())
+        .args([
+            "-I",
+            SSH_SIGNATURE_NAMESPACE,
+            "-n",
+            SSH_SIGNATURE_NAMESPACE,
+            "-s",
+        ])
+        .arg(signature_file.path())
+        .stdin(stdin)
+        .output()
+        .await
+        .map_err(CommandError::CommandFailed)?;
+    if !out.status.success() {
+        return Err(SignatureError::SshRejected(
+            String::from_utf8_lossy(&out.stderr).trim().to_owned(),
+        ));
+    }
+    Ok(())
+}
+
+/// Sign the file at `path` with the secret key at `key`, writing the
+/// signature next to it. Returns the path of the signature.
+///
+/// Whether `key` is an ssh or minisign key is detected from its contents.
+/// Either tool may prompt for the key's passphrase.
+pub fn sign(key: &Path, path: &Path) -> Result<PathBuf, SignError> {
+    let key_contents = std::fs::read_to_string(key).map_err(|error| SignError::ReadKey {
+        path: key.to_owned(),
+        error,
+    })?;
+    let is_minisign =
+        matches!(key_contents.lines().next(), Some(line) if line.contains("minisign"));
+
+    let extension = if is_minisign {
+        MINISIGN_SIGNATURE_EXTENSION
+    } else {
+        SSH_SIGNATURE_EXTENSION
+    };
+    let mut signature_path = path.as_os_str().to_owned();
+    signature_path.push(".");
+    signature_path.push(extension);
+    let signature_path = PathBuf::from(signature_path);
+
+    // Either tool may need to prompt for the key's passphrase, so they're
+    // left to report their own errors.
+    let (tool, status) = if is_minisign {
+        let status = std::process::Command::new("minisign")
+            .arg("-S")
+            .arg("-s")
+            .arg(key)
+            .arg("-m")
+            .arg(path)
+            .arg("-x")
+            .arg(&signature_path)
+            .status()
+            .map_err(CommandError::CommandFailed)?;
+        ("minisign", status)
+    } else {
+        // Pass the data on stdin so that the signature is written to stdout,
+        // rather than ssh-keygen prompting before replacing an existing one.
+        let data = std::fs::File::open(path).map_err(|error| SignError::ReadData {
+            path: path.to_owned(),
+            error,
+        })?;
+        let out = std::process::Command::new("ssh-keygen")
+            .args(["-Y", "sign", "-n", SSH_SIGNATURE_NAMESPACE, "-f"])
+            .arg(key)
+            .stdin(data)
+            .stderr(Stdio::inherit())
+            .output()
+            .map_err(CommandError::CommandFailed)?;
+        if out.status.success() {
+            std::fs::write(&signature_path, &out.stdout).map_err(|error| {
+                SignError::WriteSignature {
+                    path: signature_path.clone(),
+                    error,
+                }
+            })?;
+        }
+        ("ssh-keygen", out.status)
+    };
+    if !status.success() {
+        return Err(SignError::Failed { tool, status });
+    }
+    Ok(signature_path)
+}
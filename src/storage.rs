@@ -11,8 +11,11 @@ use std::{
 use cargo_metadata::Version;
 use crates_index::Index;
 use flate2::read::GzDecoder;
-use futures_util::future::{join_all, try_join_all};
-use miette::{NamedSource, SourceOffset};
+use futures_util::{
+    future::{join_all, try_join_all, LocalBoxFuture},
+    FutureExt,
+};
+use miette::NamedSource;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use tar::Archive;
@@ -20,23 +23,32 @@ use tracing::{error, info, log::warn, trace};
 
 use crate::{
     errors::{
-        CacheAcquireError, CacheCommitError, CommandError, CriteriaChangeError,
-        CriteriaChangeErrors, DiffError, FetchAndDiffError, FetchAuditError, FetchError,
-        FlockError, InvalidCriteriaError, JsonParseError, LoadJsonError, LoadTomlError, SourceFile,
-        StoreAcquireError, StoreCommitError, StoreCreateError, StoreJsonError, StoreTomlError,
-        StoreValidateError, StoreValidateErrors, TomlParseError, UnpackError,
+        CacheAcquireError, CacheCommitError, CriteriaChangeError, CriteriaChangeErrors,
+        DeprecatedFieldWarning, DiffError, FetchAndDiffError, FetchAuditError, FetchError,
+        FlockError, ImpliesCycleError, ImportsLockOutdatedError, InvalidCriteriaError,
+        InvalidImportUrlError, InvalidProxyError, InvalidPublicKeyError, JsonParseError,
+        LoadDiffCacheError, LoadJsonError, LoadTomlError, SourceFile, StoreAcquireError,
+        StoreCommitError, StoreCreateError, StoreJsonError, StoreTomlError, StoreValidateError,
+        StoreValidateErrors, TomlParseError, UnpackError,
     },
     flock::{FileLock, Filesystem},
     format::{
-        AuditsFile, CommandHistory, ConfigFile, CriteriaName, Delta, DiffCache, DiffStat, FastMap,
-        FetchCommand, ImportsFile, MetaConfig, PackageStr, SortedMap, SAFE_TO_DEPLOY, SAFE_TO_RUN,
+        AuditEntry, AuditKind, AuditsFile, CommandHistory, ConfigFile, CriteriaEntry, CriteriaName,
+        Delta, DiffCache, DiffCacheFile, DiffStat, FastMap, FetchCommand, ImportName, ImportsFile,
+        MetaConfig, PackageStr, RemoteImport, SortedMap, TransitiveImport, DEFAULT_GIT_IMPORT_PATH,
+        DEFAULT_IMPORT_DEPTH, DIFF_CACHE_VERSION, SAFE_TO_DEPLOY, SAFE_TO_RUN,
     },
-    network::Network,
+    network::{GitReference, Network},
     resolver,
     serialization::{spanned::Spanned, to_formatted_toml},
-    Config, PartialConfig,
+    signing::PublicKey,
+    Config, PartialConfig, CARGO_TOML,
 };
 
+/// Top-level fields in config.toml which have been renamed, but which we still
+/// accept under their old name, as (old, new) pairs.
+const DEPRECATED_CONFIG_FIELDS: &[(&str, &str)] = &[("unaudited", "exemptions")];
+
 // tmp cache for various shenanigans
 const CACHE_DIFF_CACHE: &str = "diff-cache.toml";
 const CACHE_COMMAND_HISTORY: &str = "command-history.json";
@@ -44,6 +56,10 @@ const CACHE_EMPTY_PACKAGE: &str = "empty";
 const CACHE_REGISTRY_SRC: &str = "src";
 const CACHE_REGISTRY_CACHE: &str = "cache";
 const CACHE_VET_LOCK: &str = ".vet-lock";
+/// Responses kept to revalidate with conditional requests (e.g. imported audits)
+pub const CACHE_HTTP: &str = "http";
+/// Git repositories fetched for imports
+pub const CACHE_GIT: &str = "git";
 
 // Files which are allowed to appear in the root of the cache directory, and
 // will not be GC'd
@@ -54,31 +70,52 @@ const CACHE_ALLOWED_FILES: &[&str] = &[
     CACHE_REGISTRY_SRC,
     CACHE_REGISTRY_CACHE,
     CACHE_VET_LOCK,
+    CACHE_HTTP,
+    CACHE_GIT,
 ];
 
 // Various cargo values
 const CARGO_REGISTRY_SRC: &str = "src";
 const CARGO_REGISTRY_CACHE: &str = "cache";
 const CARGO_OK_FILE: &str = ".cargo-ok";
+const CARGO_CHECKSUM_FILE: &str = ".cargo-checksum.json";
 const CARGO_OK_BODY: &str = "ok";
+/// How cargo names crates.io's dirs in the registry, for the git index and the
+/// sparse protocol respectively.
+const CRATES_IO_REGISTRY_PREFIXES: &[&str] = &["github.com-", "index.crates.io-"];
 
 pub const DEFAULT_STORE: &str = "supply-chain";
 
-const AUDITS_TOML: &str = "audits.toml";
-const CONFIG_TOML: &str = "config.toml";
+pub const AUDITS_TOML: &str = "audits.toml";
+pub const CONFIG_TOML: &str = "config.toml";
 const IMPORTS_LOCK: &str = "imports.lock";
 
 // FIXME: This is a completely arbitrary number, and may be too high or too low.
 const MAX_CONCURRENT_DIFFS: usize = 40;
 
+/// The default for `--max-fetch-size`. crates.io only accepts `.crate` files of
+/// up to 10MiB, so this leaves plenty of room for very compressible sources.
+pub const DEFAULT_MAX_FETCH_SIZE: u64 = 1024 * 1024 * 1024;
+/// The default for `--max-fetch-files`.
+pub const DEFAULT_MAX_FETCH_FILES: u64 = 100_000;
+
+/// Limits on what we're willing to unpack from a fetched `.crate` file.
+#[derive(Debug, Clone, Copy)]
+pub struct UnpackLimits {
+    /// Maximum total size of all files, in bytes
+    pub max_size: u64,
+    /// Maximum number of entries in the archive
+    pub max_files: u64,
+}
+
 struct StoreLock {
     config: FileLock,
 }
 
 impl StoreLock {
-    fn new(store: &Filesystem) -> Result<Self, FlockError> {
+    fn new(store: &Filesystem, timeout: Duration) -> Result<Self, FlockError> {
         Ok(StoreLock {
-            config: store.open_rw(CONFIG_TOML, "vet store")?,
+            config: store.open_rw_with_timeout(CONFIG_TOML, "vet store", timeout)?,
         })
     }
     fn read_config(&self) -> io::Result<impl Read + '_> {
@@ -134,18 +171,31 @@ impl Store {
         let root = cfg.metacfg.store_path();
         root.create_dir().map_err(StoreCreateError::CouldntCreate)?;
 
-        let lock = StoreLock::new(&root)?;
+        let lock = StoreLock::new(&root, cfg.lock_timeout())?;
 
         Ok(Self {
             lock: Some(lock),
             config: ConfigFile {
-                default_criteria: String::new(),
+                default_criteria: String::new().into(),
+                decompose_criteria: true,
+                dev_safe_to_run: false,
+                proc_macro_criteria: Vec::new(),
+                allowed_import_sources: Vec::new(),
+                third_party_registries: Vec::new(),
                 imports: SortedMap::new(),
                 policy: SortedMap::new(),
+                owners: SortedMap::new(),
+                notes: SortedMap::new(),
+                feature_sets: SortedMap::new(),
+                diff: Default::default(),
+                network: Default::default(),
                 exemptions: SortedMap::new(),
             },
             imports: ImportsFile {
                 audits: SortedMap::new(),
+                transitive_imports: SortedMap::new(),
+                git_commits: SortedMap::new(),
+                sources: SortedMap::new(),
             },
             audits: AuditsFile {
                 criteria: SortedMap::new(),
@@ -164,15 +214,25 @@ impl Store {
 
     /// Acquire an existing store
     pub fn acquire(cfg: &Config) -> Result<Self, StoreAcquireError> {
-        let root = cfg.metacfg.store_path();
+        Self::acquire_path(&cfg.metacfg.store_path(), cfg.lock_timeout())
+    }
 
+    /// Acquire an existing store at the given path, without needing any
+    /// information about the current project.
+    ///
+    /// If another process holds the store's lock, we wait up to `timeout`
+    /// for it to be released before giving up.
+    pub fn acquire_path(root: &Filesystem, timeout: Duration) -> Result<Self, StoreAcquireError> {
         // Before we do anything else, acquire an exclusive lock on the
-        // config.toml file in the store.
+        // config.toml file in the store. This serializes concurrent cargo-vet
+        // processes so that they can't clobber each other's changes.
         // XXX: Consider acquiring a non-exclusive lock in cases where an
         // exclusive one isn't needed.
-        let lock = StoreLock::new(&root)?;
+        let lock = StoreLock::new(root, timeout)?;
 
-        let (config_src, config): (_, ConfigFile) = load_toml(CONFIG_TOML, lock.read_config()?)?;
+        let mut config_text = String::new();
+        lock.read_config()?.read_to_string(&mut config_text)?;
+        let (config_src, config): (_, ConfigFile) = load_toml(CONFIG_TOML, config_text.as_bytes())?;
         let (audits_src, audits): (_, AuditsFile) = load_toml(AUDITS_TOML, lock.read_audits()?)?;
         let (imports_src, imports): (_, ImportsFile) =
             load_toml(IMPORTS_LOCK, lock.read_imports()?)?;
@@ -190,9 +250,27 @@ impl Store {
         // Check that the store isn't corrupt
         store.validate()?;
 
+        // Let the user know if they should migrate to newer names
+        for warning in
+            find_deprecated_fields(&store.config_src, &config_text, DEPRECATED_CONFIG_FIELDS)
+        {
+            warn!("{:?}", miette::Report::new(warning));
+        }
+
         Ok(store)
     }
 
+    /// Read the config file without acquiring the store's lock or validating it.
+    ///
+    /// This is only intended for the handful of settings that are needed before
+    /// we're fully set up (e.g. to decide how to run `cargo metadata`).
+    pub fn peek_config(metacfg: &MetaConfig) -> Result<ConfigFile, StoreAcquireError> {
+        let path = metacfg.store_path().join(CONFIG_TOML);
+        let file = File::open(path.as_path_unlocked())?;
+        let (_, config) = load_toml(CONFIG_TOML, file)?;
+        Ok(config)
+    }
+
     /// Create a mock store
     #[cfg(test)]
     pub fn mock(config: ConfigFile, audits: AuditsFile, imports: ImportsFile) -> Self {
@@ -232,16 +310,34 @@ impl Store {
         Ok(store)
     }
 
+    /// Serialize the store's files as `commit` would write them, returning the
+    /// contents of config.toml, audits.toml, and imports.lock.
+    #[cfg(test)]
+    pub fn mock_commit(&self) -> (String, String, String) {
+        let mut config = vec![];
+        let mut audits = vec![];
+        let mut imports = vec![];
+        store_config(&mut config, self.config.clone()).unwrap();
+        store_audits(&mut audits, self.audits.clone()).unwrap();
+        store_imports(&mut imports, self.imports.clone()).unwrap();
+        (
+            String::from_utf8(config).unwrap(),
+            String::from_utf8(audits).unwrap(),
+            String::from_utf8(imports).unwrap(),
+        )
+    }
+
     /// Create a clone of the store for use to resolve `suggest`.
     ///
     /// This cloned store will not contain `exemptions` entries from the config,
-    /// unless they're marked as `suggest = false`, such that the resolver will
-    /// identify these missing audits when generating a report.
+    /// unless they're marked as `suggest = false` (and `include_all_exemptions`
+    /// isn't set), such that the resolver will identify these missing audits
+    /// when generating a report.
     ///
     /// Unlike the primary store created with `Store::acquire` or
     /// `Store::create`, this store will not hold the store lock, and cannot be
     /// committed to disk by calling `commit()`.
-    pub fn clone_for_suggest(&self) -> Self {
+    pub fn clone_for_suggest(&self, include_all_exemptions: bool) -> Self {
         let mut clone = Self {
             lock: None,
             config: self.config.clone(),
@@ -253,7 +349,7 @@ impl Store {
         };
         // Delete all exemptions entries except those that are suggest=false
         for versions in &mut clone.config.exemptions.values_mut() {
-            versions.retain(|e| !e.suggest);
+            versions.retain(|e| !e.suggest && !include_all_exemptions);
         }
         clone
     }
@@ -298,6 +394,7 @@ impl Store {
             source_code: &SourceFile,
             valid: &Arc<Vec<CriteriaName>>,
             errors: &mut Vec<InvalidCriteriaError>,
+            entry: &dyn Fn() -> String,
             criteria: &[Spanned<CriteriaName>],
         ) {
             for criteria in criteria {
@@ -306,6 +403,7 @@ impl Store {
                         source_code: source_code.clone(),
                         span: Spanned::span(criteria),
                         invalid: criteria.to_string(),
+                        entry: entry(),
                         valid_names: valid.clone(),
                     })
                 }
@@ -325,65 +423,115 @@ impl Store {
         let no_criteria = vec![];
         let mut invalid_criteria_errors = vec![];
 
-        for (_package, entries) in &self.config.exemptions {
-            for entry in entries {
+        check_criteria(
+            &self.config_src,
+            &valid_criteria,
+            &mut invalid_criteria_errors,
+            &|| "default-criteria".to_owned(),
+            std::slice::from_ref(&self.config.default_criteria),
+        );
+        for (package, entries) in &self.config.exemptions {
+            let entry = || format!("exemptions.{package}");
+            for exemption in entries {
                 check_criteria(
                     &self.config_src,
                     &valid_criteria,
                     &mut invalid_criteria_errors,
-                    &entry.criteria,
+                    &entry,
+                    &exemption.criteria,
                 );
-                for (_dep_package, dep_criteria) in &entry.dependency_criteria {
+                for (_dep_package, dep_criteria) in &exemption.dependency_criteria {
                     check_criteria(
                         &self.config_src,
                         &valid_criteria,
                         &mut invalid_criteria_errors,
+                        &entry,
                         dep_criteria,
                     );
                 }
             }
         }
-        for (_package, policy) in &self.config.policy {
+        check_criteria(
+            &self.config_src,
+            &valid_criteria,
+            &mut invalid_criteria_errors,
+            &|| "proc-macro-criteria".to_owned(),
+            &self.config.proc_macro_criteria,
+        );
+        for (package, policy) in &self.config.policy {
+            let entry = || format!("policy.{package}");
             check_criteria(
                 &self.config_src,
                 &valid_criteria,
                 &mut invalid_criteria_errors,
+                &entry,
                 policy.criteria.as_ref().unwrap_or(&no_criteria),
             );
             check_criteria(
                 &self.config_src,
                 &valid_criteria,
                 &mut invalid_criteria_errors,
+                &entry,
                 policy.dev_criteria.as_ref().unwrap_or(&no_criteria),
             );
-            for (_dep_package, dep_criteria) in &policy.dependency_criteria {
+            for (_dep_package, dep_criteria) in policy
+                .dependency_criteria
+                .iter()
+                .chain(&policy.dev_dependency_criteria)
+                .chain(&policy.build_dependency_criteria)
+            {
                 check_criteria(
                     &self.config_src,
                     &valid_criteria,
                     &mut invalid_criteria_errors,
+                    &entry,
                     dep_criteria,
                 );
             }
         }
-        for (_new_criteria, entry) in &self.audits.criteria {
+        for (import_name, import) in &self.config.imports {
+            // The foreign side of the mapping is checked against the
+            // imported criteria when the mapping is used.
+            for mapping in &import.criteria_map {
+                check_criteria(
+                    &self.config_src,
+                    &valid_criteria,
+                    &mut invalid_criteria_errors,
+                    &|| format!("imports.{import_name}.criteria-map"),
+                    std::slice::from_ref(&mapping.ours),
+                );
+            }
+        }
+        for (new_criteria, criteria_entry) in &self.audits.criteria {
             // TODO: check that new_criteria isn't shadowing a builtin criteria
             check_criteria(
                 &self.audits_src,
                 &valid_criteria,
                 &mut invalid_criteria_errors,
-                &entry.implies,
+                &|| format!("criteria.{new_criteria}"),
+                &criteria_entry.implies,
             );
         }
-        for (_package, entries) in &self.audits.audits {
-            for entry in entries {
+        let implies_cycle_errors = find_implies_cycles(&self.audits.criteria)
+            .into_iter()
+            .map(|(cycle, span)| ImpliesCycleError {
+                source_code: self.audits_src.clone(),
+                span,
+                cycle,
+            })
+            .collect::<Vec<_>>();
+        for (package, entries) in &self.audits.audits {
+            let entry = || format!("audits.{package}");
+            for audit in entries {
                 // TODO: check that new_criteria isn't shadowing a builtin criteria
                 check_criteria(
                     &self.audits_src,
                     &valid_criteria,
                     &mut invalid_criteria_errors,
-                    &entry.criteria,
+                    &entry,
+                    &audit.criteria,
                 );
-                match &entry.kind {
+                match &audit.kind {
                     crate::format::AuditKind::Full {
                         dependency_criteria,
                         ..
@@ -393,6 +541,7 @@ impl Store {
                                 &self.audits_src,
                                 &valid_criteria,
                                 &mut invalid_criteria_errors,
+                                &entry,
                                 dep_criteria,
                             );
                         }
@@ -406,6 +555,7 @@ impl Store {
                                 &self.audits_src,
                                 &valid_criteria,
                                 &mut invalid_criteria_errors,
+                                &entry,
                                 dep_criteria,
                             );
                         }
@@ -415,9 +565,46 @@ impl Store {
             }
         }
 
+        let invalid_import_url_errors = self.config.imports.iter().filter_map(|(name, import)| {
+            check_import_source(import)
+                .err()
+                .map(|reason| InvalidImportUrlError {
+                    import_name: name.clone(),
+                    import_url: import.source_url().to_owned(),
+                    reason,
+                })
+        });
+
+        let invalid_public_key_errors = self
+            .config
+            .imports
+            .iter()
+            .filter_map(|(name, import)| import_public_key(name, import).err());
+
+        let invalid_proxy_errors = self.config.network.proxy.iter().filter_map(|proxy| {
+            Url::parse(proxy)
+                .map_err(|error| error.to_string())
+                .and_then(|url| {
+                    reqwest::Proxy::all(url).map_err(|_| "unsupported scheme".to_owned())
+                })
+                .err()
+                .map(|reason| InvalidProxyError {
+                    proxy: proxy.clone(),
+                    reason,
+                })
+        });
+
         let errors = invalid_criteria_errors
             .into_iter()
             .map(StoreValidateError::InvalidCriteria)
+            .chain(
+                implies_cycle_errors
+                    .into_iter()
+                    .map(StoreValidateError::ImpliesCycle),
+            )
+            .chain(invalid_import_url_errors.map(StoreValidateError::InvalidImportUrl))
+            .chain(invalid_public_key_errors.map(StoreValidateError::InvalidPublicKey))
+            .chain(invalid_proxy_errors.map(StoreValidateError::InvalidProxy))
             .collect::<Vec<_>>();
         if !errors.is_empty() {
             return Err(StoreValidateErrors { errors });
@@ -426,15 +613,103 @@ impl Store {
         Ok(())
     }
 
+    /// Check that imports.lock was fetched for the imports currently in
+    /// config.toml, for `--locked` runs which won't fetch them to fix it up.
+    ///
+    /// This can't tell whether the audits at an import's url have changed
+    /// since (see `cargo vet verify-imports-lock`), just whether the lock is
+    /// missing an import, has one which was since removed, or fetched one
+    /// from what's now a different kind of source.
+    pub fn check_imports_lock(&self) -> Result<(), ImportsLockOutdatedError> {
+        let mut differences = vec![];
+        for (name, import) in &self.config.imports {
+            if !self.imports.audits.contains_key(name) {
+                differences.push(format!("'{name}' is in config.toml, but not imports.lock"));
+                continue;
+            }
+            let locked_from_git = self.imports.git_commits.contains_key(name);
+            if import.git.is_some() && !locked_from_git {
+                differences.push(format!("'{name}' is now imported from git"));
+            } else if import.git.is_none() && locked_from_git {
+                differences.push(format!("'{name}' is no longer imported from git"));
+            } else if let Some(locked_source) = self.imports.sources.get(name) {
+                // Older imports.lock files don't record the source.
+                if locked_source != import.source_url() {
+                    differences.push(format!(
+                        "'{name}' is now imported from {}, not {locked_source}",
+                        import.source_url()
+                    ));
+                }
+            }
+        }
+        for name in self.imports.audits.keys() {
+            if !self.config.imports.contains_key(name) {
+                differences.push(format!("'{name}' is in imports.lock, but not config.toml"));
+            }
+        }
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(ImportsLockOutdatedError { differences })
+        }
+    }
+
     /// Fetch foreign audits, only call this is we're not --locked
     pub async fn fetch_foreign_audits(
         &mut self,
         network: &Network,
         accept_changes: bool,
     ) -> Result<(), FetchAuditError> {
+        // Check every url up front so that we don't make any requests if
+        // any of them is untrusted.
+        let mut urls = Vec::with_capacity(self.config.imports.len());
+        for (name, import) in &self.config.imports {
+            let url =
+                Url::parse(import.source_url()).map_err(|error| FetchAuditError::InvalidUrl {
+                    import_url: import.source_url().to_owned(),
+                    import_name: name.clone(),
+                    error,
+                })?;
+            if !import_source_allowed(&self.config.allowed_import_sources, &url) {
+                return Err(FetchAuditError::UntrustedUrl {
+                    import_name: name.clone(),
+                    import_url: import.source_url().to_owned(),
+                });
+            }
+            urls.push((name, import, url));
+        }
+
+        let allowed_import_sources = &self.config.allowed_import_sources;
         let raw_new_imports =
-            try_join_all(self.config.imports.iter().map(|(name, import)| async {
-                let audit_file = fetch_foreign_audit(network, name, &import.url).await?;
+            try_join_all(urls.into_iter().map(|(name, import, url)| async move {
+                let mut transitive = vec![];
+                let mut git_commit = None;
+                let public_key =
+                    import_public_key(name, import).map_err(|error| StoreValidateErrors {
+                        errors: vec![StoreValidateError::InvalidPublicKey(error)],
+                    })?;
+                let audit_file = if import.git.is_some() {
+                    let (commit, audit_file) =
+                        fetch_git_import(network, name, import, url, public_key.as_ref()).await?;
+                    git_commit = Some(commit);
+                    audit_file
+                } else {
+                    fetch_import_tree(
+                        network,
+                        ImportTreeLimits {
+                            allowed_import_sources,
+                            transitive_sources: &import.transitive_sources,
+                        },
+                        name,
+                        url.clone(),
+                        public_key,
+                        import.depth,
+                        vec![(name.clone(), url)],
+                        &mut transitive,
+                    )
+                    .await?
+                };
                 // Fetch the descriptions to cache them and check that they haven't changed
                 // FIXME: this should probably treat failing to fetch as an error but eula_for_criteria
                 // hides errors... should we have two versions? Or make it the caller's problem?
@@ -446,15 +721,76 @@ impl Store {
                     )
                 }))
                 .await;
-                Ok::<_, FetchAuditError>((name.clone(), audit_file, new_descs))
+                Ok::<_, FetchAuditError>((
+                    name.clone(),
+                    audit_file,
+                    new_descs,
+                    transitive,
+                    git_commit,
+                ))
             }))
             .await?;
 
         let mut new_imports = ImportsFile {
             audits: SortedMap::new(),
+            transitive_imports: SortedMap::new(),
+            git_commits: SortedMap::new(),
+            sources: self
+                .config
+                .imports
+                .iter()
+                .map(|(name, import)| (name.clone(), import.source_url().to_owned()))
+                .collect(),
         };
         let mut criteria_changes = vec![];
-        for (import_name, mut audits_file, new_descs) in raw_new_imports {
+        for (import_name, mut audits_file, new_descs, transitive, git_commit) in raw_new_imports {
+            if !transitive.is_empty() {
+                new_imports
+                    .transitive_imports
+                    .insert(import_name.clone(), transitive);
+            }
+            if let Some(git_commit) = git_commit {
+                new_imports
+                    .git_commits
+                    .insert(import_name.clone(), git_commit);
+            }
+            // Check that the criteria don't imply anything different to
+            // before, as that changes what they mean just as much.
+            if !accept_changes {
+                let implies_list = |entry: &CriteriaEntry| {
+                    let mut implies = entry
+                        .implies
+                        .iter()
+                        .map(|name| name.to_string())
+                        .collect::<Vec<_>>();
+                    implies.sort();
+                    implies.dedup();
+                    if implies.is_empty() {
+                        "(nothing)".to_owned()
+                    } else {
+                        implies.join(", ")
+                    }
+                };
+                for (criteria_name, new_entry) in &audits_file.criteria {
+                    if let Some(old_entry) = self
+                        .imports
+                        .audits
+                        .get(&import_name)
+                        .and_then(|file| file.criteria.get(criteria_name))
+                    {
+                        let (old, new) = (implies_list(old_entry), implies_list(new_entry));
+                        if old != new {
+                            criteria_changes.push(CriteriaChangeError {
+                                import_name: import_name.clone(),
+                                criteria_name: criteria_name.clone(),
+                                what: "implies",
+                                old,
+                                new,
+                            });
+                        }
+                    }
+                }
+            }
             for (criteria_name, new_desc) in new_descs {
                 if !accept_changes {
                     // Check that the new description doesn't modify an existing old one
@@ -469,8 +805,9 @@ impl Store {
                             criteria_changes.push(CriteriaChangeError {
                                 import_name: import_name.clone(),
                                 criteria_name: criteria_name.to_owned(),
-                                old_desc: old_desc.clone(),
-                                new_desc,
+                                what: "description",
+                                old: old_desc.clone(),
+                                new: new_desc,
                             });
                             continue;
                         }
@@ -495,6 +832,7 @@ impl Store {
         }
 
         // Accept the new imports. These will only be committed if the current command succeeds.
+        sort_imports(&mut new_imports);
         self.imports = new_imports;
 
         // Now do one last validation to catch corrupt imports
@@ -503,32 +841,572 @@ impl Store {
     }
 }
 
+/// The subset of a foreign config.toml we look at when following its imports.
+#[derive(Deserialize)]
+struct ForeignConfigFile {
+    #[serde(default)]
+    imports: SortedMap<ImportName, RemoteImport>,
+}
+
+/// Which urls we're willing to follow when fetching an import's own imports.
+#[derive(Clone, Copy)]
+struct ImportTreeLimits<'a> {
+    allowed_import_sources: &'a [String],
+    transitive_sources: &'a [String],
+}
+
+impl ImportTreeLimits<'_> {
+    fn allows(&self, url: &Url) -> bool {
+        import_source_allowed(self.allowed_import_sources, url)
+            && import_source_allowed(self.transitive_sources, url)
+    }
+}
+
+/// Fetch the foreign audits.toml at `url`, and if `depth` is non-zero, also
+/// follow the imports in the config.toml next to it, merging their audits in
+/// (translated into the criteria of the store which imports them).
+///
+/// `path` is the chain of imports followed to reach `url`, starting with the
+/// directly imported store and ending with `url` itself, and every store
+/// reached this way is recorded in `transitive`. Imports which would
+/// form a cycle, or which aren't allowed by `limits`, are skipped.
+///
+/// If `public_key` is given, the audits.toml at `url` must be signed by it, as
+/// must the config.toml next to it, which names the nested imports and their
+/// keys.
+#[allow(clippy::too_many_arguments)]
+fn fetch_import_tree<'a>(
+    network: &'a Network,
+    limits: ImportTreeLimits<'a>,
+    name: &'a str,
+    url: Url,
+    public_key: Option<PublicKey>,
+    depth: u64,
+    path: Vec<(ImportName, Url)>,
+    transitive: &'a mut Vec<TransitiveImport>,
+) -> LocalBoxFuture<'a, Result<AuditsFile, FetchAuditError>> {
+    async move {
+        let mut audit_file =
+            fetch_foreign_audit(network, name, url.clone(), public_key.as_ref()).await?;
+        if depth == 0 {
+            return Ok(audit_file);
+        }
+
+        let config_url = url
+            .join(CONFIG_TOML)
+            .map_err(|error| FetchAuditError::InvalidUrl {
+                import_url: url.to_string(),
+                import_name: name.to_owned(),
+                error,
+            })?;
+        let foreign_config = match fetch_foreign_config(
+            network,
+            name,
+            config_url.clone(),
+            public_key.as_ref(),
+        )
+        .await
+        {
+            Ok(config) => config,
+            Err(error @ FetchAuditError::BadSignature { .. }) => return Err(error),
+            Err(error) => {
+                warn!(
+                    "couldn't follow the imports of {}, failed to fetch {}: {}",
+                    name, config_url, error
+                );
+                return Ok(audit_file);
+            }
+        };
+
+        for (nested_name, nested_import) in &foreign_config.imports {
+            let nested_url = match Url::parse(&nested_import.url) {
+                Ok(nested_url) => nested_url,
+                Err(error) => {
+                    warn!(
+                        "skipping import {} of {}, invalid url {}: {}",
+                        nested_name, name, nested_import.url, error
+                    );
+                    continue;
+                }
+            };
+            if let Some(start) = path.iter().position(|(_, seen)| seen == &nested_url) {
+                let cycle = path[start..]
+                    .iter()
+                    .map(|(name, url)| format!("{name} ({url})"))
+                    .chain([format!("{nested_name} ({nested_url})")])
+                    .collect::<Vec<_>>();
+                warn!(
+                    "skipping import {} of {}, it was already imported (cycle: {})",
+                    nested_name,
+                    name,
+                    cycle.join(" -> ")
+                );
+                continue;
+            }
+            if !limits.allows(&nested_url) {
+                warn!(
+                    "skipping import {} of {}, {} is not an allowed import source",
+                    nested_name, name, nested_url
+                );
+                continue;
+            }
+            let nested_public_key = match import_public_key(nested_name, nested_import) {
+                Ok(nested_public_key) => nested_public_key,
+                Err(error) => {
+                    warn!("skipping import {} of {}: {}", nested_name, name, error);
+                    continue;
+                }
+            };
+
+            let mut nested_path = path.clone();
+            nested_path.push((nested_name.clone(), nested_url.clone()));
+            transitive.push(TransitiveImport {
+                // The directly imported store is already named by the import
+                path: nested_path[1..]
+                    .iter()
+                    .map(|(name, _)| name.clone())
+                    .collect(),
+                url: nested_import.url.clone(),
+            });
+            let nested_audits = fetch_import_tree(
+                network,
+                limits,
+                nested_name,
+                nested_url,
+                nested_public_key,
+                depth - 1,
+                nested_path,
+                transitive,
+            )
+            .await?;
+            merge_transitive_audits(&mut audit_file, nested_import, &nested_audits);
+        }
+
+        Ok(audit_file)
+    }
+    .boxed_local()
+}
+
+/// Find the cycles in the `implies` relation of `criteria`, returning each
+/// cycle (starting and ending with the same criteria) along with the span of
+/// the `implies` entry which closes it.
+///
+/// References to criteria which aren't defined in `criteria` are ignored,
+/// they're reported separately (and builtin criteria can't imply anything).
+fn find_implies_cycles(
+    criteria: &SortedMap<CriteriaName, CriteriaEntry>,
+) -> Vec<(Vec<CriteriaName>, miette::SourceSpan)> {
+    enum Visit {
+        InProgress,
+        Done,
+    }
+
+    fn visit<'a>(
+        name: &'a str,
+        criteria: &'a SortedMap<CriteriaName, CriteriaEntry>,
+        visits: &mut FastMap<&'a str, Visit>,
+        stack: &mut Vec<&'a str>,
+        cycles: &mut Vec<(Vec<CriteriaName>, miette::SourceSpan)>,
+    ) {
+        visits.insert(name, Visit::InProgress);
+        stack.push(name);
+        for implied in &criteria[name].implies {
+            let implied_name = match criteria.get_key_value(&**implied) {
+                Some((implied_name, _)) => implied_name,
+                None => continue,
+            };
+            match visits.get(&**implied_name) {
+                Some(Visit::InProgress) => {
+                    let start = stack.iter().position(|n| *n == &**implied_name).unwrap();
+                    let cycle = stack[start..]
+                        .iter()
+                        .chain([&&**implied_name])
+                        .map(|n| n.to_string())
+                        .collect();
+                    cycles.push((cycle, Spanned::span(implied)));
+                }
+                Some(Visit::Done) => {}
+                None => visit(implied_name, criteria, visits, stack, cycles),
+            }
+        }
+        stack.pop();
+        visits.insert(name, Visit::Done);
+    }
+
+    let mut visits = FastMap::new();
+    let mut cycles = vec![];
+    for name in criteria.keys() {
+        if !visits.contains_key(&**name) {
+            visit(name, criteria, &mut visits, &mut vec![], &mut cycles);
+        }
+    }
+    cycles
+}
+
+/// Merge the audits of a store imported by `into` (through `import`) into
+/// `into`, translating their criteria with the import's `criteria-map`.
+///
+/// Audits which don't map to any of `into`'s criteria are dropped, as are
+/// audits with dependency-criteria (which we don't know how to map).
+pub fn merge_transitive_audits(into: &mut AuditsFile, import: &RemoteImport, nested: &AuditsFile) {
+    let nested_mapper = resolver::CriteriaMapper::new(&nested.criteria);
+    let is_nested_criteria = |c: &str| nested_mapper.index.contains_key(c);
+    let is_our_criteria =
+        |c: &str| into.criteria.contains_key(c) || c == SAFE_TO_DEPLOY || c == SAFE_TO_RUN;
+    let criteria_map = import
+        .criteria_map
+        .iter()
+        .filter(|mapping| {
+            is_our_criteria(&mapping.ours) && mapping.theirs.iter().all(|c| is_nested_criteria(c))
+        })
+        .map(|mapping| {
+            (
+                mapping.ours.clone(),
+                nested_mapper.criteria_from_list(&mapping.theirs),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for (package, entries) in &nested.audits {
+        for entry in entries {
+            let has_dependency_criteria = match &entry.kind {
+                AuditKind::Full {
+                    dependency_criteria,
+                    ..
+                }
+                | AuditKind::Delta {
+                    dependency_criteria,
+                    ..
+                } => !dependency_criteria.is_empty(),
+                AuditKind::Violation { .. } => false,
+            };
+            if has_dependency_criteria {
+                continue;
+            }
+
+            let nested_criteria = nested_mapper.criteria_from_list(
+                entry
+                    .criteria
+                    .iter()
+                    .filter(|c| is_nested_criteria(c))
+                    .map(|c| &**c),
+            );
+            let mut criteria: Vec<Spanned<CriteriaName>> = vec![];
+            for (ours, theirs) in &criteria_map {
+                if nested_criteria.contains(theirs) && !criteria.iter().any(|c| **c == **ours) {
+                    criteria.push(ours.clone());
+                }
+            }
+            if criteria.is_empty() {
+                continue;
+            }
+
+            let entries = into.audits.entry(package.clone()).or_default();
+            entries.push(AuditEntry {
+                criteria,
+                ..entry.clone()
+            });
+            entries.sort();
+        }
+    }
+}
+
+async fn fetch_foreign_config(
+    network: &Network,
+    name: &str,
+    url: Url,
+    public_key: Option<&PublicKey>,
+) -> Result<ForeignConfigFile, FetchAuditError> {
+    let config_bytes = download_signed(network, name, url, public_key).await?;
+    let config_string = String::from_utf8(config_bytes).map_err(LoadTomlError::from)?;
+    let config_file: ForeignConfigFile = toml::de::from_str(&config_string)
+        .map_err(|error| TomlParseError::new(name, config_string.clone(), error))
+        .map_err(LoadTomlError::from)?;
+    Ok(config_file)
+}
+
 async fn fetch_foreign_audit(
     network: &Network,
     name: &str,
-    url: &str,
+    url: Url,
+    public_key: Option<&PublicKey>,
 ) -> Result<AuditsFile, FetchAuditError> {
-    let parsed_url = Url::parse(url).map_err(|error| FetchAuditError::InvalidUrl {
-        import_url: url.to_owned(),
-        import_name: name.to_owned(),
-        error,
-    })?;
-    let audit_bytes = network.download(parsed_url).await?;
-    let audit_string = String::from_utf8(audit_bytes).map_err(LoadTomlError::from)?;
-    let audit_source = Arc::new(NamedSource::new(name, audit_string.clone()));
-    let audit_file: AuditsFile = toml::de::from_str(&audit_string)
-        .map_err(|error| {
-            let (line, col) = error.line_col().unwrap_or((0, 0));
-            TomlParseError {
-                source_code: audit_source,
-                span: SourceOffset::from_location(&audit_string, line + 1, col + 1),
+    let audit_bytes = download_signed(network, name, url, public_key).await?;
+    Ok(parse_foreign_audit(name, audit_bytes)?)
+}
+
+/// Download a file of the import `name`. If `public_key` is given, it must be
+/// signed by it, with the signature next to the file.
+async fn download_signed(
+    network: &Network,
+    name: &str,
+    url: Url,
+    public_key: Option<&PublicKey>,
+) -> Result<Vec<u8>, FetchAuditError> {
+    // Name the import, as it's being fetched alongside all the others
+    let download = |url: Url| async {
+        network
+            .download_cached(url)
+            .await
+            .map_err(|error| FetchAuditError::ImportDownload {
+                import_name: name.to_owned(),
                 error,
-            }
+            })
+    };
+    let bytes = download(url.clone()).await?;
+    if let Some(public_key) = public_key {
+        let mut signature_url = url.clone();
+        signature_url.set_path(&format!(
+            "{}.{}",
+            url.path(),
+            public_key.signature_extension()
+        ));
+        let signature = download(signature_url).await?;
+        verify_import_signature(name, public_key, &bytes, &signature).await?;
+    }
+    Ok(bytes)
+}
+
+/// The parsed `public-key` of an import, if it has one.
+fn import_public_key(
+    name: &str,
+    import: &RemoteImport,
+) -> Result<Option<PublicKey>, InvalidPublicKeyError> {
+    import
+        .public_key
+        .as_deref()
+        .map(PublicKey::parse)
+        .transpose()
+        .map_err(|reason| InvalidPublicKeyError {
+            import_name: name.to_owned(),
+            reason,
         })
-        .map_err(LoadTomlError::from)?;
+}
+
+async fn verify_import_signature(
+    name: &str,
+    public_key: &PublicKey,
+    audit_bytes: &[u8],
+    signature: &[u8],
+) -> Result<(), FetchAuditError> {
+    public_key
+        .verify(audit_bytes, signature)
+        .await
+        .map_err(|error| FetchAuditError::BadSignature {
+            import_name: name.to_owned(),
+            error,
+        })
+}
+
+/// Fetch the foreign audits.toml of an import from `git`, returning the commit
+/// it was read from along with it.
+///
+/// If `public_key` is given, the audits.toml must be signed by it, with the
+/// signature committed next to it.
+pub async fn fetch_git_import(
+    network: &Network,
+    name: &str,
+    import: &RemoteImport,
+    repo: Url,
+    public_key: Option<&PublicKey>,
+) -> Result<(String, AuditsFile), FetchAuditError> {
+    let reference = match (&import.rev, &import.branch) {
+        (Some(rev), _) => GitReference::Rev(rev),
+        (None, Some(branch)) => GitReference::Branch(branch),
+        (None, None) => GitReference::DefaultBranch,
+    };
+    let path = import.path.as_deref().unwrap_or(DEFAULT_GIT_IMPORT_PATH);
+    let signature_path =
+        public_key.map(|public_key| format!("{}.{}", path, public_key.signature_extension()));
+    let paths = [Some(path), signature_path.as_deref()];
+    let (commit, mut contents) = network
+        .fetch_git_files(
+            &repo,
+            reference,
+            &paths.into_iter().flatten().collect::<Vec<_>>(),
+        )
+        .await
+        .map_err(|error| FetchAuditError::ImportGit {
+            import_name: name.to_owned(),
+            error,
+        })?;
+    let audit_bytes = contents.remove(0);
+    if let (Some(public_key), Some(signature)) = (public_key, contents.pop()) {
+        verify_import_signature(name, public_key, &audit_bytes, &signature).await?;
+    }
+    Ok((commit, parse_foreign_audit(name, audit_bytes)?))
+}
+
+fn parse_foreign_audit(name: &str, audit_bytes: Vec<u8>) -> Result<AuditsFile, LoadTomlError> {
+    let audit_string = String::from_utf8(audit_bytes)?;
+    let audit_file: AuditsFile = toml::de::from_str(&audit_string)
+        .map_err(|error| TomlParseError::new(name, audit_string.clone(), error))?;
     Ok(audit_file)
 }
 
+/// Parse the `url` (or, if `git` is set, the `git` repository) of an entry in
+/// config.toml's `imports`, rejecting anything we wouldn't be able to fetch
+/// from, so that mistakes are reported when the store is loaded rather than
+/// halfway through fetching.
+///
+/// Either may be a local `file://` url, such as for a store checked out next
+/// to this one. Git can also fetch over `ssh://` and `git://`.
+///
+/// Returns the parsed (and so normalized) url on success.
+pub fn parse_import_url(url: &str, git: bool) -> Result<Url, String> {
+    if url.contains(char::is_whitespace) {
+        return Err("urls can't contain whitespace".to_owned());
+    }
+    let parsed = Url::parse(url).map_err(|error| error.to_string())?;
+    match parsed.scheme() {
+        "http" | "https" | "file" => Ok(parsed),
+        "ssh" | "git" if git => Ok(parsed),
+        scheme => Err(format!("unsupported scheme '{scheme}'")),
+    }
+}
+
+/// Check the source of an entry in config.toml's `imports`, which is either a
+/// `url`, or a `git` repository (with its `rev`, `branch` and `path`).
+///
+/// Returns the parsed url of the source on success.
+pub fn check_import_source(import: &RemoteImport) -> Result<Url, String> {
+    let git = if let Some(git) = &import.git {
+        git
+    } else {
+        if import.rev.is_some() || import.branch.is_some() || import.path.is_some() {
+            return Err("`rev`, `branch` and `path` only apply to imports from `git`".to_owned());
+        }
+        return parse_import_url(&import.url, false);
+    };
+    if !import.url.is_empty() {
+        return Err("imports can't have both a `url` and a `git` repository".to_owned());
+    }
+    if import.rev.is_some() && import.branch.is_some() {
+        return Err("`rev` and `branch` can't both be given".to_owned());
+    }
+    if import.depth != DEFAULT_IMPORT_DEPTH {
+        return Err("`depth` isn't supported for imports from `git`".to_owned());
+    }
+    parse_import_url(git, true)
+}
+
+/// Check whether an import url is permitted by the `allowed-import-sources` list
+/// in config.toml. An empty list allows everything.
+///
+/// Entries are either bare hosts, which must match the url's host exactly, or
+/// url prefixes, which must match the url's scheme, host, and port and be a
+/// prefix of its path, ending at a `/` (so `https://example.com/foo` allows
+/// `https://example.com/foo/audits.toml`, but not `https://example.com/foobar`).
+pub fn import_source_allowed(allowed: &[String], url: &Url) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    allowed.iter().any(|entry| {
+        if !entry.contains("://") {
+            return matches!(url.host_str(), Some(host) if host.eq_ignore_ascii_case(entry));
+        }
+        match Url::parse(entry) {
+            Ok(prefix) => {
+                prefix.scheme() == url.scheme()
+                    && prefix.host_str() == url.host_str()
+                    && prefix.port_or_known_default() == url.port_or_known_default()
+                    && matches!(
+                        url.path().strip_prefix(prefix.path().trim_end_matches('/')),
+                        Some(rest) if rest.is_empty() || rest.starts_with('/')
+                    )
+            }
+            // An unparseable entry can't match anything
+            Err(_) => false,
+        }
+    })
+}
+
+/// Summarize how two versions of imports.lock differ, one line per difference.
+/// An empty result means they're equivalent.
+pub fn imports_lock_differences(locked: &ImportsFile, fetched: &ImportsFile) -> Vec<String> {
+    // Compare through their serialized form, as that's what's committed
+    fn same<T: Serialize>(a: &T, b: &T) -> bool {
+        serde_json::to_value(a).ok() == serde_json::to_value(b).ok()
+    }
+
+    let mut differences = vec![];
+    let import_names = locked
+        .audits
+        .keys()
+        .chain(fetched.audits.keys())
+        .collect::<std::collections::BTreeSet<_>>();
+    for import_name in import_names {
+        let (locked_audits, fetched_audits) = match (
+            locked.audits.get(import_name),
+            fetched.audits.get(import_name),
+        ) {
+            (Some(locked_audits), Some(fetched_audits)) => (locked_audits, fetched_audits),
+            (None, _) => {
+                differences.push(format!("{import_name}: missing from imports.lock"));
+                continue;
+            }
+            (_, None) => {
+                differences.push(format!("{import_name}: no longer imported"));
+                continue;
+            }
+        };
+
+        let criteria_names = locked_audits
+            .criteria
+            .keys()
+            .chain(fetched_audits.criteria.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for criteria_name in criteria_names {
+            let locked_entry = locked_audits.criteria.get(criteria_name);
+            let fetched_entry = fetched_audits.criteria.get(criteria_name);
+            if !same(&locked_entry, &fetched_entry) {
+                differences.push(format!("{import_name}: criteria '{criteria_name}' differs"));
+            }
+        }
+
+        let packages = locked_audits
+            .audits
+            .keys()
+            .chain(fetched_audits.audits.keys())
+            .collect::<std::collections::BTreeSet<_>>();
+        for package in packages {
+            let locked_entries = locked_audits.audits.get(package);
+            let fetched_entries = fetched_audits.audits.get(package);
+            if !same(&locked_entries, &fetched_entries) {
+                differences.push(format!(
+                    "{import_name}: audits of {package} differ ({} locked, {} fetched)",
+                    locked_entries.map_or(0, |e| e.len()),
+                    fetched_entries.map_or(0, |e| e.len()),
+                ));
+            }
+        }
+
+        if !same(
+            &locked.transitive_imports.get(import_name),
+            &fetched.transitive_imports.get(import_name),
+        ) {
+            differences.push(format!("{import_name}: transitive imports differ"));
+        }
+        if locked.git_commits.get(import_name) != fetched.git_commits.get(import_name) {
+            differences.push(format!("{import_name}: git commit differs"));
+        }
+        if locked.sources.get(import_name) != fetched.sources.get(import_name) {
+            differences.push(format!("{import_name}: source differs"));
+        }
+    }
+    differences
+}
+
+/// Whether the index says `version` of a crate was yanked (or doesn't know of
+/// it at all, e.g. because it was deleted).
+pub fn is_yanked_or_missing(krate: &crates_index::Crate, version: &Version) -> bool {
+    let version = version.to_string();
+    match krate.versions().iter().find(|v| v.version() == version) {
+        Some(v) => v.is_yanked(),
+        None => true,
+    }
+}
+
 /// A Registry in CARGO_HOME (usually the crates.io one)
 pub struct CargoRegistry {
     /// The queryable index
@@ -537,6 +1415,9 @@ pub struct CargoRegistry {
     base_dir: PathBuf,
     /// The name of the registry (`github.com-1ecc6299db9ec823`)
     registry: OsString,
+    /// The src dirs which may hold unpacked crates.io fetches (see
+    /// [`find_crates_io_srcs`][]).
+    crates_io_srcs: Vec<PathBuf>,
 }
 
 impl CargoRegistry {
@@ -550,6 +1431,10 @@ impl CargoRegistry {
             .join(CARGO_REGISTRY_CACHE)
             .join(&self.registry)
     }
+    /// Get every src dir which may hold unpacked crates.io fetches
+    pub fn crates_io_srcs(&self) -> &[PathBuf] {
+        &self.crates_io_srcs
+    }
     // Could also include the index, not reason to do that yet
 }
 
@@ -574,12 +1459,16 @@ pub struct Cache {
     root: Option<PathBuf>,
     /// Cargo's crates.io package registry (in CARGO_HOME) for us to query opportunistically
     cargo_registry: Option<CargoRegistry>,
+    /// A directory of unpacked packages to check before `cargo_registry` (--registry-src)
+    registry_src: Option<PathBuf>,
     /// Path to the DiffCache (for when we want to save it back)
     diff_cache_path: Option<PathBuf>,
     /// Path to the CommandHistory (for when we want to save it back)
     command_history_path: Option<PathBuf>,
     /// Semaphore preventing exceeding the maximum number of concurrent diffs.
     diff_semaphore: tokio::sync::Semaphore,
+    /// Limits on the size of packages we're willing to unpack.
+    unpack_limits: UnpackLimits,
     /// Common mutable state for the cache which can be mutated concurrently
     /// from multiple tasks.
     state: Mutex<CacheState>,
@@ -625,9 +1514,14 @@ impl Cache {
                 _lock: None,
                 root: None,
                 cargo_registry: None,
+                registry_src: None,
                 diff_cache_path: None,
                 command_history_path: None,
                 diff_semaphore: tokio::sync::Semaphore::new(MAX_CONCURRENT_DIFFS),
+                unpack_limits: UnpackLimits {
+                    max_size: cfg.cli.max_fetch_size,
+                    max_files: cfg.cli.max_fetch_files,
+                },
                 state: Mutex::new(CacheState {
                     diff_cache: DiffCache::new(),
                     command_history: CommandHistory::default(),
@@ -670,10 +1564,24 @@ impl Cache {
             .diff_cache
             .clone()
             .unwrap_or_else(|| root.join(CACHE_DIFF_CACHE));
-        let diff_cache: DiffCache = File::open(&diff_cache_path)
-            .ok()
-            .and_then(|f| load_toml(CACHE_DIFF_CACHE, f).map(|v| v.1).ok())
-            .unwrap_or_default();
+        let diff_cache = match load_diff_cache_file(&diff_cache_path) {
+            Ok(diff_cache) => diff_cache,
+            Err(LoadDiffCacheError::Toml(LoadTomlError::IoError(e)))
+                if e.kind() == io::ErrorKind::NotFound =>
+            {
+                DiffCache::new()
+            }
+            Err(e) => {
+                // ERRORS: the diff-cache is just a cache, so we can recover by
+                // recomputing everything, but it's worth knowing why.
+                warn!(
+                    "discarding diff-cache at {}: {}",
+                    diff_cache_path.display(),
+                    e
+                );
+                DiffCache::new()
+            }
+        };
 
         // Setup the command_history.
         let command_history_path = root.join(CACHE_COMMAND_HISTORY);
@@ -695,7 +1603,12 @@ impl Cache {
             diff_cache_path: Some(diff_cache_path),
             command_history_path: Some(command_history_path),
             cargo_registry: cargo_registry.ok(),
+            registry_src: cfg.cli.registry_src.clone(),
             diff_semaphore: tokio::sync::Semaphore::new(MAX_CONCURRENT_DIFFS),
+            unpack_limits: UnpackLimits {
+                max_size: cfg.cli.max_fetch_size,
+                max_files: cfg.cli.max_fetch_files,
+            },
             state: Mutex::new(CacheState {
                 diff_cache,
                 command_history,
@@ -754,16 +1667,53 @@ impl Cache {
                     return Ok(root.join(CACHE_EMPTY_PACKAGE));
                 }
 
-                let dir_name = format!("{}-{}", package, version);
+                let dir_name = package_dir_name(package, version);
 
-                // First try to get a cached copy from cargo's registry.
-                if let Some(reg) = self.cargo_registry.as_ref() {
-                    let fetched_src = reg.src().join(&dir_name);
-                    if fetch_is_ok(&fetched_src).await {
+                // First try any packages we were pointed at explicitly.
+                if let Some(registry_src) = &self.registry_src {
+                    if let Some(fetched_src) =
+                        find_in_registry_src(registry_src, package, version).await
+                    {
                         return Ok(fetched_src);
                     }
                 }
 
+                // The checksum of the .crate, according to the registry index.
+                let expected_checksum = self
+                    .query_package_from_index(package)
+                    .and_then(|krate| exact_version(&krate, version).map(|v| *v.checksum()));
+                if expected_checksum.is_none() {
+                    info!(
+                        "{}:{} isn't in the registry index, so its checksum can't be verified",
+                        package, version
+                    );
+                }
+
+                // Cargo's registry may already have the .crate. Its unpacked
+                // sources could have been modified since (by a build script,
+                // say), so rather than use those we unpack the .crate
+                // ourselves, once it's been checked against the index.
+                let mut cargo_crate = None;
+                if let (Some(reg), Some(expected)) =
+                    (self.cargo_registry.as_ref(), expected_checksum)
+                {
+                    for src in reg.crates_io_srcs() {
+                        let candidate = reg
+                            .base_dir
+                            .join(CARGO_REGISTRY_CACHE)
+                            .join(src.file_name().unwrap_or_default())
+                            .join(format!("{}.crate", dir_name));
+                        if candidate.exists()
+                            && verify_crate_checksum(package, version, candidate.clone(), expected)
+                                .await
+                                .is_ok()
+                        {
+                            cargo_crate = Some(candidate);
+                            break;
+                        }
+                    }
+                }
+
                 // Paths for the fetched package and checkout in our local cache.
                 let fetched_package = root
                     .join(CACHE_REGISTRY_CACHE)
@@ -792,30 +1742,63 @@ impl Cache {
                 .await
                 .expect("failed to join");
 
-                // If the file isn't in our local cache, make sure to download it.
+                // If the file isn't in our local cache, copy it from cargo's, or
+                // else make sure to download it.
                 let file = match cached_file {
                     Ok(file) => file,
                     Err(_) => {
-                        let network = network.ok_or_else(|| FetchError::Frozen {
-                            package: package.to_owned(),
-                            version: version.clone(),
-                        })?;
-
-                        // We don't have it, so download it
-                        let url =
-                            format!("https://crates.io/api/v1/crates/{package}/{version}/download");
-                        let url = Url::parse(&url).map_err(|error| FetchError::InvalidUrl {
-                            url: url.clone(),
-                            error,
-                        })?;
-                        info!(
-                            "downloading package {}:{} from {} to {}",
-                            package,
-                            version,
-                            url,
-                            fetched_package.display()
-                        );
-                        network.download_and_persist(url, &fetched_package).await?;
+                        if let Some(cargo_crate) = cargo_crate {
+                            info!(
+                                "copying package {}:{} from {} to {}",
+                                package,
+                                version,
+                                cargo_crate.display(),
+                                fetched_package.display()
+                            );
+                            tokio::fs::copy(&cargo_crate, &fetched_package)
+                                .await
+                                .map_err(|error| FetchError::OpenCached {
+                                    target: cargo_crate.clone(),
+                                    error,
+                                })?;
+                        } else {
+                            let network = network.ok_or_else(|| FetchError::Frozen {
+                                package: package.to_owned(),
+                                version: version.clone(),
+                            })?;
+
+                            // We don't have it, so download it
+                            let url = crates_io_download_url(package, version);
+                            let url = Url::parse(&url).map_err(|error| FetchError::InvalidUrl {
+                                url: url.clone(),
+                                error,
+                            })?;
+                            info!(
+                                "downloading package {}:{} from {} to {}",
+                                package,
+                                version,
+                                url,
+                                fetched_package.display()
+                            );
+                            if let Err(error) =
+                                network.download_and_persist(url, &fetched_package).await
+                            {
+                                // If there's nothing to download, explain the most
+                                // likely reason, unless the index knows better.
+                                let yanked = error.is_not_found()
+                                    && self
+                                        .query_package_from_index(package)
+                                        .map(|krate| is_yanked_or_missing(&krate, version))
+                                        .unwrap_or(true);
+                                if yanked {
+                                    return Err(FetchError::Yanked {
+                                        package: package.to_owned(),
+                                        version: version.clone(),
+                                    });
+                                }
+                                return Err(error.into());
+                            }
+                        }
 
                         let fetched_package_ = fetched_package.clone();
                         tokio::task::spawn_blocking(move || File::open(&fetched_package_))
@@ -828,7 +1811,10 @@ impl Cache {
                     }
                 };
 
-                // TODO(#116): take the SHA2 of the bytes and compare it to what the registry says
+                if let Some(expected) = expected_checksum {
+                    verify_crate_checksum(package, version, fetched_package.clone(), expected)
+                        .await?;
+                }
 
                 if fetch_is_ok(&fetched_src).await {
                     Ok(fetched_src)
@@ -841,8 +1827,9 @@ impl Cache {
                         fetched_src.display()
                     );
                     // The tarball needs to be unpacked, so do so.
+                    let limits = self.unpack_limits;
                     tokio::task::spawn_blocking(move || {
-                        unpack_package(&file, &fetched_src)
+                        unpack_package(&file, &fetched_src, limits)
                             .map(|_| fetched_src)
                             .map_err(|error| FetchError::Unpack {
                                 src: fetched_package.clone(),
@@ -874,58 +1861,12 @@ impl Cache {
         // to diffstat some packages and still produce some useful output
         trace!("diffstating {version1:#?} {version2:#?}");
         // FIXME: mask out .cargo_vcs_info.json
-        // FIXME: look into libgit2 vs just calling git
-
-        let out = tokio::process::Command::new("git")
-            .arg("diff")
-            .arg("--no-index")
-            .arg("--shortstat")
-            .arg(version1)
-            .arg(version2)
-            .output()
-            .await
-            .map_err(CommandError::CommandFailed)?;
-
-        let status = out.status.code().unwrap_or(-1);
-        // 0 = empty
-        // 1 = some diff
-        if status != 0 && status != 1 {
-            Err(CommandError::BadStatus(status))?;
-        }
-
-        let diffstat = String::from_utf8(out.stdout).map_err(CommandError::BadOutput)?;
-
-        let count = if diffstat.is_empty() {
-            0
-        } else {
-            // 3 files changed, 9 insertions(+), 3 deletions(-)
-            let mut parts = diffstat.split(',');
-            parts.next().unwrap(); // Discard files
-
-            fn parse_diffnum(part: Option<&str>) -> Option<u64> {
-                part?.trim().split_once(' ')?.0.parse().ok()
-            }
-
-            let added: u64 = parse_diffnum(parts.next()).unwrap_or(0);
-            let removed: u64 = parse_diffnum(parts.next()).unwrap_or(0);
-
-            // ERRORS: Arguably this should just be an error but it's more of a
-            // "have I completely misunderstood this format, if so let me know"
-            // panic, so the assert *is* what I want..?
-            assert_eq!(
-                parts.next(),
-                None,
-                "diffstat had more parts than expected? {}",
-                diffstat
-            );
-
-            added + removed
-        };
 
-        Ok(DiffStat {
-            raw: diffstat,
-            count,
-        })
+        let version1 = version1.to_owned();
+        let version2 = version2.to_owned();
+        tokio::task::spawn_blocking(move || diffstat_dirs(&version1, &version2))
+            .await
+            .expect("failed to join")
     }
 
     #[tracing::instrument(skip(self, network), err)]
@@ -981,8 +1922,12 @@ impl Cache {
 
         let diffstat = once_cell
             .get_or_try_init(|| async {
-                let from = self.fetch_package(network, package, &delta.from).await?;
-                let to = self.fetch_package(network, package, &delta.to).await?;
+                // Fetch both sides at once. Either may already be fetched (or
+                // being fetched) for another delta, in which case that's reused.
+                let (from, to) = tokio::try_join!(
+                    self.fetch_package(network, package, &delta.from),
+                    self.fetch_package(network, package, &delta.to)
+                )?;
 
                 // Have fetches, do a real diffstat
                 let diffstat = self.diffstat_package(&from, &to).await?;
@@ -1003,6 +1948,57 @@ impl Cache {
         Ok(diffstat.clone())
     }
 
+    /// Remove every entry from the diff-cache, returning how many there were.
+    pub fn clear_diff_cache(&self) -> usize {
+        let mut guard = self.state.lock().unwrap();
+        let diff_cache = mem::take(&mut guard.diff_cache);
+        diff_cache.values().map(|diffs| diffs.len()).sum()
+    }
+
+    /// Get a copy of the current contents of the diff-cache.
+    pub fn diff_cache(&self) -> DiffCache {
+        self.state.lock().unwrap().diff_cache.clone()
+    }
+
+    /// Merge diffstats computed elsewhere into the diff-cache. Entries we
+    /// already have are kept as-is. Returns how many entries were added, and
+    /// how many were skipped because they disagreed with ours.
+    pub fn merge_diff_cache(&self, other: DiffCache) -> (usize, usize) {
+        let mut guard = self.state.lock().unwrap();
+        let mut added = 0;
+        let mut conflicting = 0;
+        for (package, diffs) in other {
+            let ours = guard.diff_cache.entry(package).or_default();
+            for (delta, diffstat) in diffs {
+                match ours.get(&delta) {
+                    Some(existing) if existing.count != diffstat.count => conflicting += 1,
+                    Some(_) => {}
+                    None => {
+                        ours.insert(delta, diffstat);
+                        added += 1;
+                    }
+                }
+            }
+        }
+        guard.diff_cache.retain(|_, diffs| !diffs.is_empty());
+        (added, conflicting)
+    }
+
+    /// Compute the diffstat for a delta from scratch, ignoring (and not
+    /// updating) the diff-cache. This is used to check for stale entries.
+    pub async fn recompute_diffstat(
+        &self,
+        network: Option<&Network>,
+        package: PackageStr<'_>,
+        delta: &Delta,
+    ) -> Result<DiffStat, FetchAndDiffError> {
+        let (from, to) = tokio::try_join!(
+            self.fetch_package(network, package, &delta.from),
+            self.fetch_package(network, package, &delta.to)
+        )?;
+        Ok(self.diffstat_package(&from, &to).await?)
+    }
+
     /// Run a garbage-collection pass over the cache, removing any files which
     /// aren't supposed to be there, or which haven't been touched for an
     /// extended period of time.
@@ -1143,21 +2139,166 @@ pub fn exact_version<'a>(
     None
 }
 
+/// Check that the .crate at `path` has the SHA-256 checksum the registry index
+/// records for `package:version`.
+async fn verify_crate_checksum(
+    package: PackageStr<'_>,
+    version: &Version,
+    path: PathBuf,
+    expected: [u8; 32],
+) -> Result<(), FetchError> {
+    let path_ = path.clone();
+    let actual = tokio::task::spawn_blocking(move || crate_file_checksum(&path_))
+        .await
+        .expect("failed to join")
+        .map_err(|error| FetchError::OpenCached {
+            target: path.clone(),
+            error,
+        })?;
+    if actual == expected {
+        return Ok(());
+    }
+    Err(FetchError::ChecksumMismatch {
+        package: package.to_owned(),
+        version: version.clone(),
+        target: path,
+        expected: hex_string(&expected),
+        actual: hex_string(&actual),
+    })
+}
+
+/// The SHA-256 of a .crate file, as recorded in the registry index.
+pub fn crate_file_checksum(path: &Path) -> io::Result<[u8; 32]> {
+    let mut file = File::open(path)?;
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buf = [0; 64 * 1024];
+    loop {
+        let len = file.read(&mut buf)?;
+        if len == 0 {
+            break;
+        }
+        ctx.update(&buf[..len]);
+    }
+    let mut checksum = [0; 32];
+    checksum.copy_from_slice(ctx.finish().as_ref());
+    Ok(checksum)
+}
+
+fn hex_string(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Computes the [`hash_package_source`] of the source cargo unpacked for a
+/// package in the current build.
+pub fn hash_local_package_source(package: &cargo_metadata::Package) -> io::Result<String> {
+    let root = package.manifest_path.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} has no parent directory", package.manifest_path),
+        )
+    })?;
+    hash_package_source(root.as_std_path())
+}
+
+/// Computes a stable hash of the unpacked source of a package.
+///
+/// Every file under `root` (other than cargo's `.cargo-ok` marker, and the
+/// `.cargo-checksum.json` which `cargo vendor` adds) is visited
+/// in sorted order of its `/`-separated relative path, and its path, length and
+/// contents are fed into a SHA-256 digest. The result is formatted as
+/// `sha256:<hex>` so that other algorithms can be introduced later.
+pub fn hash_package_source(root: &Path) -> io::Result<String> {
+    fn collect_files(
+        root: &Path,
+        dir: &Path,
+        files: &mut Vec<(String, PathBuf)>,
+    ) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                collect_files(root, &path, files)?;
+            } else {
+                let relative = path.strip_prefix(root).unwrap();
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                if name != CARGO_OK_FILE && name != CARGO_CHECKSUM_FILE {
+                    files.push((name, path));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    collect_files(root, root, &mut files)?;
+    files.sort();
+
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    for (name, path) in files {
+        let contents = fs::read(&path)?;
+        ctx.update(name.as_bytes());
+        ctx.update(&[0]);
+        ctx.update(&(contents.len() as u64).to_le_bytes());
+        ctx.update(&contents);
+    }
+    Ok(format!("sha256:{}", hex_string(ctx.finish().as_ref())))
+}
+
 #[tracing::instrument(err)]
-fn unpack_package(tarball: &File, unpack_dir: &Path) -> Result<(), UnpackError> {
+pub fn unpack_package(
+    tarball: &File,
+    unpack_dir: &Path,
+    limits: UnpackLimits,
+) -> Result<(), UnpackError> {
     // If we get here and the unpack_dir exists, this implies we had a previously failed fetch,
     // blast it away so we can have a clean slate!
     if unpack_dir.exists() {
         fs::remove_dir_all(unpack_dir)?;
     }
     fs::create_dir(unpack_dir)?;
+    let result = unpack_package_entries(tarball, unpack_dir, limits);
+    if let Err(UnpackError::TooLarge { .. } | UnpackError::TooManyFiles { .. }) = &result {
+        // Don't leave a huge partial unpack lying around on disk.
+        let _ = fs::remove_dir_all(unpack_dir);
+    }
+    result
+}
+
+fn unpack_package_entries(
+    tarball: &File,
+    unpack_dir: &Path,
+    limits: UnpackLimits,
+) -> Result<(), UnpackError> {
     let lockfile = unpack_dir.join(CARGO_OK_FILE);
     let gz = GzDecoder::new(tarball);
     let mut tar = Archive::new(gz);
     let prefix = unpack_dir.file_name().unwrap();
     let parent = unpack_dir.parent().unwrap();
+    let mut total_size = 0u64;
+    let mut total_files = 0u64;
     for entry in tar.entries()? {
         let mut entry = entry.map_err(UnpackError::ArchiveIterate)?;
+
+        // Check the limits using the sizes declared in the headers *before*
+        // writing anything, as that's exactly how much the tar reader will
+        // read for each entry.
+        total_files += 1;
+        if total_files > limits.max_files {
+            return Err(UnpackError::TooManyFiles {
+                limit: limits.max_files,
+            });
+        }
+        total_size = total_size.saturating_add(entry.size());
+        if total_size > limits.max_size {
+            return Err(UnpackError::TooLarge {
+                limit: limits.max_size,
+            });
+        }
+
         let entry_path = entry
             .path()
             .map_err(UnpackError::ArchiveEntry)?
@@ -1212,6 +2353,38 @@ async fn fetch_is_ok(fetch: &Path) -> bool {
     }
 }
 
+/// Find a package in a directory of unpacked packages given by --registry-src.
+///
+/// Both cargo's registry layout (`foo-1.2.3`) and `cargo vendor`'s (`foo`, or
+/// `foo-1.2.3` with `--versioned-dirs` or for all but one of several versions)
+/// are understood. Unlike cargo's registry there's no `.cargo-ok` marker, so a
+/// `Cargo.toml` is taken as evidence that the package was fully unpacked.
+pub async fn find_in_registry_src(
+    registry_src: &Path,
+    package: PackageStr<'_>,
+    version: &Version,
+) -> Option<PathBuf> {
+    let versioned = registry_src.join(package_dir_name(package, version));
+    if tokio::fs::metadata(versioned.join(CARGO_TOML))
+        .await
+        .is_ok()
+    {
+        return Some(versioned);
+    }
+
+    let unversioned = registry_src.join(package);
+    let manifest = tokio::fs::read_to_string(unversioned.join(CARGO_TOML))
+        .await
+        .ok()?;
+    let manifest: toml::Value = toml::from_str(&manifest).ok()?;
+    let manifest_version = manifest.get("package")?.get("version")?.as_str()?;
+    if Version::parse(manifest_version).ok().as_ref() == Some(version) {
+        Some(unversioned)
+    } else {
+        None
+    }
+}
+
 /// Based on the type of file for an entry, either recursively remove the
 /// directory, or remove the file. This is intended to be roughly equivalent to
 /// `rm -r`.
@@ -1253,7 +2426,105 @@ async fn should_keep_package(
     }
 }
 
-fn find_cargo_registry() -> Result<CargoRegistry, crates_index::Error> {
+/// Compute the diffstat between two directories, like
+/// `git diff --no-index --shortstat`.
+///
+/// Both directories are loaded into an in-memory git object database so that
+/// libgit2 can diff them as trees without touching the disk or needing a `git`
+/// binary to be installed.
+pub fn diffstat_dirs(version1: &Path, version2: &Path) -> Result<DiffStat, DiffError> {
+    let repo = git2::Repository::from_odb(git2::Odb::new()?)?;
+    let odb = repo.odb()?;
+    let _mempack = odb.add_new_mempack_backend(1000)?;
+
+    let tree1 = write_dir_tree(&repo, &odb, version1)?;
+    let tree2 = write_dir_tree(&repo, &odb, version2)?;
+    let tree1 = repo.find_tree(tree1)?;
+    let tree2 = repo.find_tree(tree2)?;
+
+    let mut diff = repo.diff_tree_to_tree(Some(&tree1), Some(&tree2), None)?;
+    // git diff detects renames by default, so do the same to keep the counts
+    // comparable.
+    diff.find_similar(Some(git2::DiffFindOptions::new().renames(true)))?;
+    let stats = diff.stats()?;
+
+    let raw = if stats.files_changed() == 0 {
+        String::new()
+    } else {
+        let buf = stats.to_buf(git2::DiffStatsFormat::SHORT, 80)?;
+        String::from_utf8_lossy(&buf).into_owned()
+    };
+
+    Ok(DiffStat {
+        raw,
+        count: (stats.insertions() + stats.deletions()) as u64,
+    })
+}
+
+/// Recursively write the contents of `dir` into `odb` as a git tree, returning
+/// the tree's id. Empty directories produce empty trees, which are skipped by
+/// their parents just like git would.
+fn write_dir_tree(
+    repo: &git2::Repository,
+    odb: &git2::Odb,
+    dir: &Path,
+) -> Result<git2::Oid, DiffError> {
+    let mut builder = repo.treebuilder(None)?;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        let name = entry.file_name();
+        if file_type.is_dir() {
+            let tree = write_dir_tree(repo, odb, &path)?;
+            if !repo.find_tree(tree)?.is_empty() {
+                builder.insert(&name, tree, 0o040000)?;
+            }
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(&path)?;
+            let blob = odb.write(git2::ObjectType::Blob, target.to_string_lossy().as_bytes())?;
+            builder.insert(&name, blob, 0o120000)?;
+        } else {
+            let blob = odb.write(git2::ObjectType::Blob, &fs::read(&path)?)?;
+            let mode = if is_executable(&entry.metadata()?) {
+                0o100755
+            } else {
+                0o100644
+            };
+            builder.insert(&name, blob, mode)?;
+        }
+    }
+    Ok(builder.write()?)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    false
+}
+
+/// The name cargo uses for a package's unpacked directory in the registry (and
+/// the stem of its `.crate` file). Build metadata is kept, so `foo 1.2.3+extra`
+/// is `foo-1.2.3+extra`.
+pub fn package_dir_name(package: PackageStr, version: &Version) -> String {
+    format!("{}-{}", package, version)
+}
+
+/// The url to download a package from crates.io.
+///
+/// crates.io redirects to a static file server which would decode a literal
+/// `+` in build metadata as a space, so it needs to be escaped.
+pub fn crates_io_download_url(package: PackageStr, version: &Version) -> String {
+    let version = version.to_string().replace('+', "%2B");
+    format!("https://crates.io/api/v1/crates/{package}/{version}/download")
+}
+
+pub fn find_cargo_registry() -> Result<CargoRegistry, crates_index::Error> {
     // ERRORS: all of this is genuinely fallible internal workings
     // but if these path adjustments don't work then something is very fundamentally wrong
 
@@ -1261,14 +2532,93 @@ fn find_cargo_registry() -> Result<CargoRegistry, crates_index::Error> {
 
     let base_dir = index.path().parent().unwrap().parent().unwrap().to_owned();
     let registry = index.path().file_name().unwrap().to_owned();
+    let crates_io_srcs = find_crates_io_srcs(&base_dir, &registry);
 
     Ok(CargoRegistry {
         index,
         base_dir,
         registry,
+        crates_io_srcs,
     })
 }
 
+/// Find the src dirs under `$CARGO_HOME/registry` which may hold unpacked
+/// crates.io packages, which is more than just the one for `registry` (the
+/// git index): with the sparse protocol cargo unpacks them under
+/// `index.crates.io-*` instead, and both may exist. Only dirs which exist are
+/// returned, with `registry`'s first.
+pub fn find_crates_io_srcs(base_dir: &Path, registry: &OsStr) -> Vec<PathBuf> {
+    let src_dir = base_dir.join(CARGO_REGISTRY_SRC);
+    let mut names = fs::read_dir(&src_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .map(|entry| entry.file_name())
+        .filter(|name| {
+            let name = name.to_string_lossy();
+            CRATES_IO_REGISTRY_PREFIXES
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+        })
+        .collect::<Vec<_>>();
+    names.sort();
+    names.sort_by_key(|name| name != registry);
+    names.into_iter().map(|name| src_dir.join(name)).collect()
+}
+
+/// Find uses of the given deprecated top-level fields in a toml file, whether
+/// as a key (`old = ...`) or in a table header (`[old.foo]`, `[[old.foo]]`).
+/// Each field is only reported once, where it's first used, to avoid drowning
+/// the user in warnings.
+///
+/// The corresponding serde types are expected to accept the old names with an
+/// `alias`, this just lets us tell the user about it.
+pub fn find_deprecated_fields(
+    source_code: &SourceFile,
+    text: &str,
+    fields: &[(&'static str, &'static str)],
+) -> Vec<DeprecatedFieldWarning> {
+    let document = match text.parse::<toml_edit::Document>() {
+        Ok(document) => document,
+        // The file has already been parsed, so this shouldn't happen, and
+        // there's nothing to warn about if it does.
+        Err(_) => return Vec::new(),
+    };
+    fields
+        .iter()
+        .filter_map(|&(old, new)| {
+            let (key, _) = document.get_key_value(old)?;
+            let start = find_root_key(&document, text, old).unwrap_or(0);
+            let len = key.to_repr().as_raw().len();
+            Some(DeprecatedFieldWarning {
+                source_code: source_code.clone(),
+                span: (start..start + len).into(),
+                old,
+                new,
+            })
+        })
+        .collect()
+}
+
+/// The offset in `text` (which `document` was parsed from) of the first use of
+/// the top-level `key`.
+///
+/// toml_edit doesn't keep track of where things were parsed from, but it does
+/// reproduce the original text exactly, so mark where the key is and find the
+/// marker in the rendered document.
+fn find_root_key(document: &toml_edit::Document, text: &str, key: &str) -> Option<usize> {
+    const MARKER: &str = "\0";
+    if document.to_string() != text {
+        return None;
+    }
+    let mut marked = document.clone();
+    let decor = marked.as_table_mut().key_decor_mut(key)?;
+    let prefix = decor.prefix().unwrap_or_default().to_owned();
+    decor.set_prefix(format!("{prefix}{MARKER}"));
+    marked.to_string().find(MARKER)
+}
+
 fn load_toml<T>(file_name: &str, reader: impl Read) -> Result<(SourceFile, T), LoadTomlError>
 where
     T: for<'a> Deserialize<'a>,
@@ -1281,15 +2631,7 @@ where
     // have access to the input string, but NamedSource consumes and erases it.
     match result {
         Ok(toml) => Ok((Arc::new(NamedSource::new(file_name, string)), toml)),
-        Err(error) => {
-            let (line, col) = error.line_col().unwrap_or((0, 0));
-            let span = SourceOffset::from_location(&string, line + 1, col);
-            Err(TomlParseError {
-                source_code: Arc::new(NamedSource::new(file_name, string)),
-                span,
-                error,
-            })?
-        }
+        Err(error) => Err(TomlParseError::new(file_name, string, error))?,
     }
 }
 fn store_toml<T>(mut writer: impl Write, heading: &str, val: T) -> Result<(), StoreTomlError>
@@ -1346,18 +2688,56 @@ fn store_config(writer: impl Write, mut config: ConfigFile) -> Result<(), StoreT
     store_toml(writer, heading, config)?;
     Ok(())
 }
-fn store_imports(writer: impl Write, imports: ImportsFile) -> Result<(), StoreTomlError> {
+/// Put the audits of each import in a canonical order, the same one used for
+/// audits.toml, so that a source adding or removing an audit shows up as just
+/// that in the diff of imports.lock.
+fn sort_imports(imports: &mut ImportsFile) {
+    for audits_file in imports.audits.values_mut() {
+        audits_file
+            .audits
+            .values_mut()
+            .for_each(|entries| entries.sort());
+    }
+}
+fn store_imports(writer: impl Write, mut imports: ImportsFile) -> Result<(), StoreTomlError> {
     let heading = r###"
 # cargo-vet imports lock
 "###;
+    sort_imports(&mut imports);
 
     store_toml(writer, heading, imports)?;
     Ok(())
 }
+
+/// Read a diff-cache from a file, e.g. one shared by `cargo vet export-diff-cache`.
+///
+/// Fails if the file was written with a different version of the diff-cache
+/// format.
+pub fn load_diff_cache_file(path: &Path) -> Result<DiffCache, LoadDiffCacheError> {
+    let file = File::open(path).map_err(LoadTomlError::from)?;
+    let (_, file): (_, DiffCacheFile) = load_toml(CACHE_DIFF_CACHE, file)?;
+    if file.version != DIFF_CACHE_VERSION {
+        return Err(LoadDiffCacheError::VersionMismatch {
+            found: file.version,
+            expected: DIFF_CACHE_VERSION,
+        });
+    }
+    Ok(file.diffs)
+}
+
+/// Write a diff-cache to a file, in the same format as the cache's own.
+pub fn store_diff_cache_file(path: &Path, diff_cache: DiffCache) -> Result<(), StoreTomlError> {
+    store_diff_cache(File::create(path)?, diff_cache)
+}
+
 fn store_diff_cache(writer: impl Write, diff_cache: DiffCache) -> Result<(), StoreTomlError> {
     let heading = "";
 
-    store_toml(writer, heading, diff_cache)?;
+    let file = DiffCacheFile {
+        version: DIFF_CACHE_VERSION,
+        diffs: diff_cache,
+    };
+    store_toml(writer, heading, file)?;
     Ok(())
 }
 fn store_command_history(
@@ -0,0 +1,89 @@
+use super::*;
+
+use crate::advisories::AdvisoryDb;
+
+/// Write an advisory database with one advisory per `(id, package, toml)`,
+/// where `toml` is the rest of the advisory's front matter.
+fn mock_advisory_db(advisories: &[(&str, &str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (id, package, toml) in advisories {
+        let package_dir = dir.path().join("crates").join(package);
+        std::fs::create_dir_all(&package_dir).unwrap();
+        std::fs::write(
+            package_dir.join(format!("{id}.md")),
+            format!(
+                "```toml\n[advisory]\nid = \"{id}\"\npackage = \"{package}\"\ndate = 2023-01-01\n{toml}\n```\n\n# Something bad\n"
+            ),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+#[test]
+fn mock_simple_advisories() {
+    // Advisories should be listed for vetted packages as a warning, and next
+    // to unvetted ones, but only if they affect the version in the graph.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = files_full_audited(&metadata);
+    audits.audits.remove("transitive-third-party1");
+
+    let advisory_db = mock_advisory_db(&[
+        (
+            "RUSTSEC-2023-0001",
+            "third-party1",
+            "[versions]\npatched = [\">= 11.0.0\"]",
+        ),
+        (
+            "RUSTSEC-2023-0002",
+            "third-party2",
+            "[versions]\npatched = [\">= 5.0.0\"]",
+        ),
+        (
+            "RUSTSEC-2023-0003",
+            "third-party2",
+            "withdrawn = 2023-02-01\n[versions]\npatched = []",
+        ),
+        (
+            "RUSTSEC-2023-0004",
+            "transitive-third-party1",
+            "[versions]\npatched = []\nunaffected = [\"< 5.0.0\"]",
+        ),
+        ("RUSTSEC-2023-0005", "root-package", ""),
+    ]);
+    let db = AdvisoryDb::load(advisory_db.path()).unwrap();
+
+    let store = Store::mock(config, audits, imports);
+    let report = || {
+        let mut report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+        report.annotate_advisories(&db);
+        report
+    };
+
+    let output = get_report(&metadata, report());
+    insta::assert_snapshot!("mock-simple-advisories", output);
+    let output = get_report_json(&metadata, report());
+    insta::assert_snapshot!("mock-simple-advisories-json", output);
+}
+
+#[test]
+fn advisory_missing_front_matter() {
+    let dir = tempfile::tempdir().unwrap();
+    let package_dir = dir.path().join("crates").join("third-party1");
+    std::fs::create_dir_all(&package_dir).unwrap();
+    std::fs::write(
+        package_dir.join("RUSTSEC-2023-0001.md"),
+        "# Something bad\n",
+    )
+    .unwrap();
+
+    let error = AdvisoryDb::load(dir.path()).unwrap_err();
+    assert!(matches!(
+        error,
+        crate::errors::AdvisoryDbError::MissingFrontMatter { .. }
+    ));
+}
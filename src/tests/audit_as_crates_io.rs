@@ -232,3 +232,51 @@ fn cycle_audit_as_crates_io() {
     let output = get_audit_as_crates_io(&cfg, &store);
     insta::assert_snapshot!("cycle-audit-as-crates-io", output);
 }
+
+#[test]
+fn git_fork_audit_as_crates_io() {
+    // A git fork with the same name and version as a crates.io package in the
+    // graph can't be audit-as-crates-io, as it would share the real one's audits.
+    let _enter = TEST_RUNTIME.enter();
+
+    let mock = MockMetadata::git_fork();
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_full_audited(&metadata);
+    for (name, audit_as) in [
+        ("root-package", false),
+        ("first-party", false),
+        ("third-party1", true),
+    ] {
+        config
+            .policy
+            .insert(name.to_owned(), audit_as_policy(Some(audit_as)));
+    }
+
+    // The fork should be treated independently of the crates.io package
+    let graph = crate::resolver::DepGraph::new(&metadata, None, Some(&config));
+    let third_party1 = graph
+        .nodes
+        .iter()
+        .filter(|node| node.name == "third-party1")
+        .map(|node| (node.package_id.repr.as_str(), node.is_third_party))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        third_party1,
+        vec![
+            (
+                "third-party1 10.0.0 (git+https://github.com/fork/third-party1#0123456789abcdef)",
+                false
+            ),
+            (
+                "third-party1 10.0.0 (registry+https://github.com/rust-lang/crates.io-index)",
+                true
+            ),
+        ]
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg(&metadata);
+
+    let output = get_audit_as_crates_io(&cfg, &store);
+    insta::assert_snapshot!("git-fork-audit-as-crates-io", output);
+}
@@ -0,0 +1,45 @@
+use super::*;
+
+#[test]
+fn audits_of() {
+    let audits = r##"
+[[audits.serde]]
+who = "Alice Smith <alice@example.com>"
+criteria = "safe-to-deploy"
+version = "1.0.0"
+notes = "looks fine"
+
+[[audits.serde]]
+who = "Bob <bob@example.com>"
+criteria = "safe-to-deploy"
+delta = "1.0.0 -> 1.1.0"
+
+[[audits.serde_derive]]
+who = "alice@example.com"
+criteria = ["safe-to-run", "safe-to-deploy"]
+delta = "1.0.0 -> 1.1.0"
+
+[[audits.toml]]
+criteria = "safe-to-run"
+version = "0.5.0"
+"##;
+    let store = Store::mock_acquire(EMPTY_CONFIG, audits, EMPTY_IMPORTS).unwrap();
+    let metadata = MockMetadata::simple().metadata();
+
+    let run = |args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::AuditsOf(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        let output = BasicTestOutput::new();
+        crate::do_cmd_audits_of(&output.clone().as_dyn(), &cfg, sub_args, &store).unwrap();
+        output.to_string()
+    };
+
+    let human = run(&["cargo", "vet", "audits-of", "ALICE"]);
+    let json = run(&["cargo", "vet", "audits-of", "bob@", "--output-format=json"]);
+    let none = run(&["cargo", "vet", "audits-of", "carol"]);
+    insta::assert_snapshot!("audits-of", format!("{human}\n{json}\n\n{none}"));
+}
@@ -0,0 +1,137 @@
+use super::*;
+
+#[test]
+fn build_metadata_package_paths() {
+    let version = "1.2.3+rust1.60".parse().unwrap();
+    assert_eq!(
+        crate::storage::package_dir_name("foo", &version),
+        "foo-1.2.3+rust1.60"
+    );
+    let url = crate::storage::crates_io_download_url("foo", &version);
+    assert_eq!(
+        url,
+        "https://crates.io/api/v1/crates/foo/1.2.3%2Brust1.60/download"
+    );
+    // Escaping survives being parsed as a url
+    assert_eq!(reqwest::Url::parse(&url).unwrap().as_str(), url);
+
+    let version = "1.2.3".parse().unwrap();
+    assert_eq!(
+        crate::storage::package_dir_name("foo", &version),
+        "foo-1.2.3"
+    );
+    assert_eq!(
+        crate::storage::crates_io_download_url("foo", &version),
+        "https://crates.io/api/v1/crates/foo/1.2.3/download"
+    );
+}
+
+#[test]
+fn crates_io_registry_srcs() {
+    let base_dir = tempfile::tempdir().unwrap();
+    let src = base_dir.path().join("src");
+    let git = "github.com-1ecc6299db9ec823";
+    let sparse = "index.crates.io-6f17d22bba15001f";
+    for name in [sparse, git, "my-registry.example.com-0123456789abcdef"] {
+        std::fs::create_dir_all(src.join(name)).unwrap();
+    }
+
+    // The registry of the index comes first, then any others for crates.io
+    let srcs = crate::storage::find_crates_io_srcs(base_dir.path(), git.as_ref());
+    assert_eq!(srcs, [src.join(git), src.join(sparse)]);
+    let srcs = crate::storage::find_crates_io_srcs(base_dir.path(), sparse.as_ref());
+    assert_eq!(srcs, [src.join(sparse), src.join(git)]);
+
+    // Only the sparse registry's dir exists
+    std::fs::remove_dir(src.join(git)).unwrap();
+    let srcs = crate::storage::find_crates_io_srcs(base_dir.path(), git.as_ref());
+    assert_eq!(srcs, [src.join(sparse)]);
+
+    // Nothing fetched yet
+    let srcs = crate::storage::find_crates_io_srcs(&base_dir.path().join("nope"), git.as_ref());
+    assert!(srcs.is_empty());
+}
+
+#[test]
+fn registry_src_layouts() {
+    let _enter = TEST_RUNTIME.enter();
+    let registry_src = tempfile::tempdir().unwrap();
+    let manifest = |dir: &str, version: &str| {
+        let dir = registry_src.path().join(dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"whatever\"\nversion = \"{version}\"\n"),
+        )
+        .unwrap();
+    };
+    // cargo's registry layout
+    manifest("registry-style-1.0.0", "1.0.0");
+    // cargo vendor's layout, with an older version in a versioned dir
+    manifest("vendored", "2.0.0");
+    manifest("vendored-1.0.0", "1.0.0");
+    // Not unpacked
+    std::fs::create_dir_all(registry_src.path().join("empty-1.0.0")).unwrap();
+
+    let find = |package: &str, version: &str| {
+        tokio::runtime::Handle::current()
+            .block_on(crate::storage::find_in_registry_src(
+                registry_src.path(),
+                package,
+                &version.parse().unwrap(),
+            ))
+            .map(|path| path.strip_prefix(registry_src.path()).unwrap().to_owned())
+    };
+
+    assert_eq!(
+        find("registry-style", "1.0.0"),
+        Some("registry-style-1.0.0".into())
+    );
+    assert_eq!(find("registry-style", "2.0.0"), None);
+    assert_eq!(find("vendored", "2.0.0"), Some("vendored".into()));
+    assert_eq!(find("vendored", "1.0.0"), Some("vendored-1.0.0".into()));
+    assert_eq!(find("vendored", "3.0.0"), None);
+    assert_eq!(find("empty", "1.0.0"), None);
+    assert_eq!(find("missing", "1.0.0"), None);
+}
+
+#[test]
+fn fetch_checksum_mismatch() {
+    // The mock registry knows the checksum third-party1's .crate should have,
+    // so a cached .crate with any other contents must be rejected.
+    let cache_dir = tempfile::tempdir().unwrap();
+    let tarballs = cache_dir.path().join("cache");
+    std::fs::create_dir_all(&tarballs).unwrap();
+    let tampered = tarballs.join("third-party1-10.0.0.crate");
+    std::fs::write(&tampered, "").unwrap();
+    assert_eq!(
+        crate::storage::crate_file_checksum(&tampered)
+            .unwrap()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    let crate::cli::FakeCli::Vet(cli) =
+        clap::Parser::try_parse_from(["cargo", "vet", "--locked", "--frozen"]).unwrap();
+    let cfg = crate::PartialConfig {
+        cli,
+        cache_dir: cache_dir.path().to_owned(),
+        mock_cache: false,
+    };
+    let cache = crate::storage::Cache::acquire(&cfg).unwrap();
+
+    let error = TEST_RUNTIME
+        .block_on(cache.fetch_package(None, "third-party1", &"10.0.0".parse().unwrap()))
+        .unwrap_err();
+    assert!(
+        matches!(error, crate::errors::FetchError::ChecksumMismatch { .. }),
+        "{error:?}"
+    );
+    assert!(!cache_dir
+        .path()
+        .join("src")
+        .join("third-party1-10.0.0")
+        .exists());
+}
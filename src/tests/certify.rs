@@ -1,4 +1,6 @@
 use super::*;
+use crate::errors::CertifyError;
+use crate::format::FetchCommand;
 use std::fmt::Write;
 
 #[test]
@@ -147,3 +149,692 @@ fn mock_simple_certify_flow() {
 
     insta::assert_snapshot!("mock-simple-certify-flow", result);
 }
+
+#[test]
+fn mock_simple_certify_force() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    // Not in the tree, so this should be rejected without --force
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "not-yet-added",
+            "1.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+        ],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::NotAPackage(_)), "{err:?}");
+
+    // --force doesn't let you get away with bogus criteria
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "not-yet-added",
+            "1.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "bogus",
+            "--accept-all",
+            "--force",
+        ],
+    )
+    .unwrap_err();
+    if let CertifyError::UnknownCriteria {
+        criteria,
+        valid_names,
+    } = &err
+    {
+        assert_eq!(criteria, "bogus");
+        assert!(valid_names.iter().any(|name| name == "reviewed"));
+        assert!(valid_names.iter().any(|name| name == SAFE_TO_DEPLOY));
+    } else {
+        panic!("unexpected error: {err:?}");
+    }
+
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "not-yet-added",
+            "1.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+            "--force",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+
+    let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+
+    insta::assert_snapshot!("mock-simple-certify-force", audits.to_string());
+}
+
+#[test]
+fn mock_simple_certify_non_interactive() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+
+    // No callbacks, so this isn't a terminal and can't prompt
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--who",
+            "testing",
+            "--accept-all",
+        ],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::CriteriaNotGiven), "{err:?}");
+
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+        ],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::StatementNotAccepted), "{err:?}");
+    assert!(!store.audits.audits.contains_key("third-party1"));
+
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--notes",
+            "looks fine",
+            "--accept-all",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+    let entries = &store.audits.audits["third-party1"];
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].notes.as_deref(), Some("looks fine"));
+}
+
+#[test]
+fn mock_simple_certify_source_hash() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, args: &[&str], last_fetch: FetchCommand| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(
+            &output.clone().as_dyn(),
+            &cfg,
+            sub_args,
+            store,
+            None,
+            Some(last_fetch),
+        )
+    };
+
+    // Certifying what was just inspected records its hash
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+        ],
+        FetchCommand::Inspect {
+            package: "third-party1".to_owned(),
+            version: ver(DEFAULT_VER),
+            source_hash: Some("sha256:0123456789abcdef".to_owned()),
+        },
+    )
+    .expect("do_cmd_certify failed");
+
+    // Certifying a different version than was inspected doesn't
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party2",
+            "5.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+        ],
+        FetchCommand::Inspect {
+            package: "third-party2".to_owned(),
+            version: ver(DEFAULT_VER),
+            source_hash: Some("sha256:fedcba9876543210".to_owned()),
+        },
+    )
+    .expect("do_cmd_certify failed");
+
+    let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+
+    insta::assert_snapshot!("mock-simple-certify-source-hash", audits.to_string());
+}
+
+#[test]
+fn mock_simple_certify_audit_as_crates_io() {
+    // Certifying a first-party package which is audited as its crates.io twin
+    // reviews its local source, as a delta from the newest audited version if
+    // there is one.
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (mut config, audits, imports) = files_inited(&metadata);
+    config
+        .policy
+        .insert("first-party".to_owned(), audit_as_policy(Some(true)));
+
+    let output = BasicTestOutput::new();
+    let cfg = mock_cfg_args(
+        &metadata,
+        [
+            "cargo",
+            "vet",
+            "certify",
+            "first-party",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+        ],
+    );
+    let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!();
+    };
+
+    let mut result = String::new();
+    for earlier_audit in [None, Some(full_audit(ver(5), "reviewed"))] {
+        let mut store = Store::mock(config.clone(), audits.clone(), imports.clone());
+        if let Some(audit) = earlier_audit {
+            store
+                .audits
+                .audits
+                .insert("first-party".to_owned(), vec![audit]);
+        }
+        assert!(crate::audit_as_crates_io_package(&cfg, &store, "third-party1").is_none());
+        let local = crate::audit_as_crates_io_package(&cfg, &store, "first-party")
+            .expect("first-party should be audited as crates.io");
+        let fetch = crate::audit_as_crates_io_fetch(&store, local);
+
+        crate::do_cmd_certify(
+            &output.clone().as_dyn(),
+            &cfg,
+            sub_args,
+            &mut store,
+            None,
+            Some(fetch),
+        )
+        .expect("do_cmd_certify failed");
+
+        let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+        writeln!(result, "{audits}").unwrap();
+    }
+
+    insta::assert_snapshot!("mock-simple-certify-audit-as-crates-io", result);
+}
+
+#[test]
+fn mock_simple_certify_import_from() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let peer = Store::mock_acquire(
+        r##"
+[imports.peer]
+url = "https://peer.example.com/audits.toml"
+criteria-map = [
+    { theirs = "peer-reviewed", ours = "reviewed" },
+]
+
+[imports.unmapped]
+url = "https://unmapped.example.com/audits.toml"
+criteria-map = []
+"##,
+        r##"
+[criteria.reviewed]
+description = "reviewed"
+
+[audits]
+"##,
+        r##"
+[audits.peer.criteria.peer-reviewed]
+description = "reviewed by a peer"
+
+[[audits.peer.audits.third-party1]]
+who = "Alice <alice@example.com>"
+criteria = "peer-reviewed"
+version = "10.0.0"
+notes = "looks fine"
+dependency-criteria = { transitive-third-party1 = "peer-reviewed" }
+
+[[audits.peer.audits.third-party1]]
+criteria = "peer-reviewed"
+delta = "9.0.0 -> 10.0.0"
+
+[[audits.peer.audits.third-party2]]
+criteria = "safe-to-run"
+version = "10.0.0"
+
+[audits.unmapped.criteria.reviewed]
+description = "reviewed by someone else"
+
+[[audits.unmapped.audits.third-party1]]
+criteria = "reviewed"
+version = "10.0.0"
+"##,
+    )
+    .unwrap();
+    store.config.imports = peer.config.imports;
+    store.imports = peer.imports;
+
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "--import-from",
+            "bogus",
+        ],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::UnknownImport(_)), "{err:?}");
+
+    // Without a criteria-map, their criteria mean nothing to us, even though
+    // the names match.
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "--import-from",
+            "unmapped",
+        ],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::NoCriteriaMap(_)), "{err:?}");
+
+    // Their only audit of third-party2 isn't covered by the criteria-map
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party2",
+            "--import-from",
+            "peer",
+        ],
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, CertifyError::NoImportedAudits { .. }),
+        "{err:?}"
+    );
+
+    // Only the full audit of 10.0.0 is copied, without its dependency-criteria
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--import-from",
+            "peer",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+
+    // Copying it again is a no-op
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--import-from",
+            "peer",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+
+    let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+
+    insta::assert_snapshot!("mock-simple-certify-import-from", audits.to_string());
+}
+
+#[test]
+fn mock_simple_certify_base_unavailable() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    // Only makes sense for deltas
+    let err = certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "10.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--accept-all",
+            "--base-unavailable",
+        ],
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, CertifyError::BaseUnavailableWithoutDelta),
+        "{err:?}"
+    );
+
+    certify(
+        &mut store,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "third-party1",
+            "9.0.0",
+            "10.0.0",
+            "--who",
+            "testing",
+            "--criteria",
+            "reviewed",
+            "--notes",
+            "nothing scary",
+            "--accept-all",
+            "--base-unavailable",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+
+    let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+
+    insta::assert_snapshot!("mock-simple-certify-base-unavailable", audits.to_string());
+}
+
+#[test]
+fn mock_simple_certify_all() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, mut audits, imports) = files_no_exemptions(&metadata);
+
+    // Give one package an older audit, so that a delta is suggested for it.
+    audits.audits.insert(
+        "third-party2".to_owned(),
+        vec![full_audit(ver(5), "reviewed")],
+    );
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let certify = |store: &mut Store, output: &Arc<BasicTestOutput>, args: &[&str]| {
+        let cfg = mock_cfg_args(&metadata, args);
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    // Without a terminal, the statements can't be accepted at a prompt.
+    let err = certify(
+        &mut store,
+        &BasicTestOutput::new(),
+        &["cargo", "vet", "certify", "--all", "--who", "testing"],
+    )
+    .unwrap_err();
+    assert!(matches!(err, CertifyError::StatementNotAccepted), "{err:?}");
+    assert!(!store.audits.audits.contains_key("third-party1"));
+
+    let output = BasicTestOutput::with_callbacks(|_| Ok("y\n".to_owned()), |_| unreachable!());
+    certify(
+        &mut store,
+        &output,
+        &["cargo", "vet", "certify", "--all", "--who", "testing"],
+    )
+    .expect("do_cmd_certify failed");
+
+    let audits = crate::serialization::to_formatted_toml(&store.audits).unwrap();
+
+    let result = format!("OUTPUT:\n{}\nAUDITS:\n{}", output, audits);
+
+    insta::assert_snapshot!("mock-simple-certify-all", result);
+
+    // Everything has been certified now.
+    let output = BasicTestOutput::new();
+    certify(
+        &mut store,
+        &output,
+        &[
+            "cargo",
+            "vet",
+            "certify",
+            "--all",
+            "--who",
+            "testing",
+            "--accept-all",
+        ],
+    )
+    .expect("do_cmd_certify failed");
+    assert_eq!(output.to_string(), "nothing to certify\n");
+}
+
+#[test]
+fn mock_simple_certify_from_audited() {
+    let mock = MockMetadata::simple();
+
+    let _enter = TEST_RUNTIME.enter();
+    let metadata = mock.metadata();
+
+    let (config, mut audits, imports) = files_no_exemptions(&metadata);
+
+    audits.audits.insert(
+        "third-party2".to_owned(),
+        vec![
+            full_audit(ver(3), "reviewed"),
+            full_audit(ver(5), "reviewed"),
+            full_audit(ver(DEFAULT_VER + 5), "reviewed"),
+        ],
+    );
+
+    let mut store = Store::mock(config, audits, imports);
+
+    let output = BasicTestOutput::new();
+
+    let certify = |store: &mut Store, package: &str| -> Result<(), CertifyError> {
+        let cfg = mock_cfg_args(
+            &metadata,
+            [
+                "cargo",
+                "vet",
+                "certify",
+                package,
+                "10.0.0",
+                "--from-audited",
+                "--who",
+                "testing",
+                "--criteria",
+                "reviewed",
+                "--accept-all",
+            ],
+        );
+        let sub_args = if let Some(crate::cli::Commands::Certify(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        crate::do_cmd_certify(&output.clone().as_dyn(), &cfg, sub_args, store, None, None)
+    };
+
+    // The closest audited version below is preferred.
+    certify(&mut store, "third-party2").expect("do_cmd_certify failed");
+    assert_eq!(
+        store.audits.audits["third-party2"].last().unwrap().kind,
+        AuditKind::Delta {
+            delta: Delta {
+                from: ver(5),
+                to: ver(DEFAULT_VER),
+            },
+            dependency_criteria: Default::default(),
+        }
+    );
+
+    // Without any audited version, only a delta was reviewed, so nothing is
+    // certified rather than a full audit.
+    let error = certify(&mut store, "third-party1").unwrap_err();
+    assert!(
+        matches!(error, CertifyError::NoDeltaBase { .. }),
+        "{error:?}"
+    );
+    assert!(!store.audits.audits.contains_key("third-party1"));
+}
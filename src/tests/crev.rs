@@ -0,0 +1,326 @@
+use ring::signature::{Ed25519KeyPair, KeyPair};
+
+use super::*;
+
+use crate::crev::CrevProofs;
+
+/// A deterministic crev identity for the given name.
+fn key(name: &str) -> Ed25519KeyPair {
+    let mut seed = [0; 32];
+    seed[..name.len()].copy_from_slice(name.as_bytes());
+    Ed25519KeyPair::from_seed_unchecked(&seed).unwrap()
+}
+
+/// The crev id of the given name, which is its public key.
+fn id(name: &str) -> String {
+    base64::encode_config(key(name).public_key(), base64::URL_SAFE_NO_PAD)
+}
+
+/// Wrap a proof's body in the given headers, signed by `signer`.
+fn signed(header: &str, body: &str, signer: &str) -> String {
+    let signature =
+        base64::encode_config(key(signer).sign(body.as_bytes()), base64::URL_SAFE_NO_PAD);
+    format!(
+        "-----BEGIN CREV {header}-----\n{body}-----BEGIN CREV {header} SIGNATURE-----\n{signature}\n-----END CREV {header}-----\n"
+    )
+}
+
+/// A package review proof, in the format written by older versions of crev.
+fn review(from: &str, package: &str, version: &str, review: &str) -> String {
+    dated_review(from, package, version, review, "2022-06-01T00:00:00+00:00")
+}
+
+/// A package review made at the given date.
+fn dated_review(from: &str, package: &str, version: &str, review: &str, date: &str) -> String {
+    signed_review(from, from, package, version, review, date)
+}
+
+/// A package review claiming to be from `from`, but signed by `signer`.
+fn forged_review(from: &str, signer: &str, package: &str, version: &str, review: &str) -> String {
+    signed_review(
+        from,
+        signer,
+        package,
+        version,
+        review,
+        "2022-06-01T00:00:00+00:00",
+    )
+}
+
+fn signed_review(
+    from: &str,
+    signer: &str,
+    package: &str,
+    version: &str,
+    review: &str,
+    date: &str,
+) -> String {
+    let body = format!(
+        r#"version: -1
+date: "{date}"
+from:
+  id-type: crev
+  id: {}
+  url: "https://github.com/{from}/crev-proofs"
+package:
+  source: "https://crates.io"
+  name: {package}
+  version: {version}
+  digest: AAAA
+review:
+{review}
+comment: "Looks fine"
+"#,
+        id(from)
+    );
+    signed("PACKAGE REVIEW", &body, signer)
+}
+
+/// A trust proof, in the format written by newer versions of crev.
+fn trust(from: &str, trusted: &str, level: &str) -> String {
+    let body = format!(
+        r#"kind: trust
+version: -1
+date: "2022-06-01T00:00:00+00:00"
+from:
+  id-type: crev
+  id: {}
+ids:
+  - id-type: crev
+    id: {}
+trust: {level}
+"#,
+        id(from),
+        id(trusted)
+    );
+    signed("PROOF", &body, from)
+}
+
+fn mock_proofs(files: &[(&str, String)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, contents) in files {
+        let path = dir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn mock_simple_import_crev() {
+    // Only positive reviews by trusted ids of packages in the graph should be
+    // imported, with criteria depending on how careful the review was.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (config, _, _) = files_no_exemptions(&metadata);
+
+    let careful = "  thoroughness: medium\n  understanding: high\n  rating: positive";
+    let quick = "  thoroughness: low\n  understanding: medium\n  rating: strong";
+    let none = "  thoroughness: none\n  understanding: high\n  rating: positive";
+    let negative = "  thoroughness: high\n  understanding: high\n  rating: negative";
+
+    let proofs = mock_proofs(&[
+        (
+            "alice/reviews/2022-06.proof.crev",
+            [
+                review("alice", "third-party1", "10.0.0", careful),
+                review("alice", "third-party2", "10.0.0", quick),
+                review("alice", "transitive-third-party1", "10.0.0", none),
+                review("alice", "third-party2", "9.0.0", careful),
+                review("alice", "not-in-tree", "10.0.0", careful),
+            ]
+            .concat(),
+        ),
+        (
+            "alice/trust/2022-06.proof.crev",
+            [
+                trust("alice", "bob", "medium"),
+                trust("alice", "eve", "low"),
+            ]
+            .concat(),
+        ),
+        (
+            "bob/reviews/2022-06.proof.crev",
+            review("bob", "transitive-third-party1", "10.0.0", careful),
+        ),
+        (
+            "eve/reviews/2022-06.proof.crev",
+            review("eve", "third-party1", "10.0.0", quick),
+        ),
+        (
+            "mallory/reviews/2022-06.proof.crev",
+            review("mallory", "third-party2", "10.0.0", negative),
+        ),
+        (
+            "mallory/reviews/2022-07.proof.crev",
+            forged_review("alice", "mallory", "third-party2", "10.0.0", careful),
+        ),
+        (".git/ignored.crev", "not a proof".to_owned()),
+    ]);
+    let proofs = CrevProofs::load(proofs.path()).unwrap();
+    assert_eq!(proofs.reviews.len(), 8);
+    assert_eq!(proofs.trusts.len(), 2);
+    assert_eq!(proofs.skipped, 1);
+
+    let trusted = proofs.trust_set(&[id("alice"), id("mallory")]);
+    let packages = metadata
+        .packages
+        .iter()
+        .filter(|package| package.is_third_party(&config))
+        .map(|package| (&package.name[..], &package.version));
+    let mut audits = proofs.audits_for(&trusted, packages);
+    audits.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let summary = audits
+        .iter()
+        .map(|(package, entry)| {
+            (
+                &package[..],
+                &entry.criteria[0][..],
+                entry.who.as_deref().unwrap(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "third-party1",
+                SAFE_TO_DEPLOY,
+                "https://github.com/alice/crev-proofs"
+            ),
+            (
+                "third-party2",
+                SAFE_TO_RUN,
+                "https://github.com/alice/crev-proofs"
+            ),
+            (
+                "transitive-third-party1",
+                SAFE_TO_DEPLOY,
+                "https://github.com/bob/crev-proofs"
+            ),
+        ]
+    );
+    assert_eq!(
+        audits[0].1.notes.as_deref(),
+        Some(&*format!(
+            "Looks fine\n\nImported from the cargo-crev review by {}",
+            id("alice")
+        ))
+    );
+}
+
+#[test]
+fn crev_invalid_proof() {
+    // A proof which can't be parsed should be skipped, not stop the others
+    // from being loaded.
+    let careful = "  thoroughness: medium\n  understanding: high\n  rating: positive";
+    let proofs = mock_proofs(&[
+        (
+            "alice/reviews/2022-06.proof.crev",
+            "-----BEGIN CREV PACKAGE REVIEW-----\nfrom: [\n-----END CREV PACKAGE REVIEW-----\n"
+                .to_owned(),
+        ),
+        (
+            "alice/reviews/2022-07.proof.crev",
+            review("alice", "third-party1", "10.0.0", careful),
+        ),
+    ]);
+
+    let proofs = CrevProofs::load(proofs.path()).unwrap();
+    assert_eq!(proofs.reviews.len(), 1);
+    assert_eq!(proofs.skipped, 1);
+}
+
+#[test]
+fn crev_latest_review_and_distrust() {
+    // Only the newest review by an id of a package should count, and an id
+    // distrusted by one of the roots shouldn't be trusted even if another
+    // root trusts it.
+
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (config, _, _) = files_no_exemptions(&metadata);
+
+    let careful = "  thoroughness: medium\n  understanding: high\n  rating: positive";
+    let negative = "  thoroughness: high\n  understanding: high\n  rating: negative";
+
+    let proofs = mock_proofs(&[
+        (
+            "alice/reviews/2022-06.proof.crev",
+            [
+                dated_review(
+                    "alice",
+                    "third-party1",
+                    "10.0.0",
+                    careful,
+                    "2022-06-01T00:00:00+00:00",
+                ),
+                dated_review(
+                    "alice",
+                    "third-party2",
+                    "10.0.0",
+                    negative,
+                    "2022-06-01T00:00:00+00:00",
+                ),
+            ]
+            .concat(),
+        ),
+        (
+            // Loaded before the older reviews, which shouldn't matter.
+            "alice/reviews/2022-05.proof.crev",
+            [
+                dated_review(
+                    "alice",
+                    "third-party1",
+                    "10.0.0",
+                    negative,
+                    "2022-07-01T00:00:00+00:00",
+                ),
+                dated_review(
+                    "alice",
+                    "third-party2",
+                    "10.0.0",
+                    careful,
+                    "2022-07-01T00:00:00+00:00",
+                ),
+            ]
+            .concat(),
+        ),
+        (
+            "alice/trust/2022-06.proof.crev",
+            trust("alice", "bob", "high"),
+        ),
+        (
+            "carol/trust/2022-06.proof.crev",
+            trust("carol", "bob", "distrust"),
+        ),
+        (
+            "bob/reviews/2022-06.proof.crev",
+            review("bob", "transitive-third-party1", "10.0.0", careful),
+        ),
+    ]);
+    let proofs = CrevProofs::load(proofs.path()).unwrap();
+
+    let alice_only = proofs.trust_set(&[id("alice")]);
+    assert!(alice_only.contains(&id("bob")));
+
+    let trusted = proofs.trust_set(&[id("alice"), id("carol")]);
+    assert!(trusted.contains(&id("alice")));
+    assert!(trusted.contains(&id("carol")));
+    assert!(!trusted.contains(&id("bob")));
+
+    let packages = metadata
+        .packages
+        .iter()
+        .filter(|package| package.is_third_party(&config))
+        .map(|package| (&package.name[..], &package.version));
+    let audits = proofs.audits_for(&trusted, packages);
+
+    let summary = audits
+        .iter()
+        .map(|(package, entry)| (&package[..], &entry.criteria[0][..]))
+        .collect::<Vec<_>>();
+    assert_eq!(summary, vec![("third-party2", SAFE_TO_DEPLOY)]);
+}
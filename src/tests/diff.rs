@@ -0,0 +1,199 @@
+use super::*;
+
+#[test]
+fn diff_package_outside_tree() {
+    // None of the mock trees (or their registries) know about this package,
+    // but as long as the tarballs are available we can still diff it.
+    let cache_dir = tempfile::tempdir().unwrap();
+    let tarballs = cache_dir.path().join("cache");
+    std::fs::create_dir_all(&tarballs).unwrap();
+    for (version, lib) in [
+        ("1.0.0", "pub fn a() {}\n"),
+        ("1.1.0", "pub fn a() {}\npub fn b() {}\n"),
+    ] {
+        let dir_name = format!("not-in-any-tree-{version}");
+        let file = std::fs::File::create(tarballs.join(format!("{dir_name}.crate"))).unwrap();
+        let gz = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for (name, contents) in [("Cargo.toml", "[package]\n"), ("src/lib.rs", lib)] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(
+                &mut header,
+                format!("{dir_name}/{name}"),
+                contents.as_bytes(),
+            )
+            .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    let crate::cli::FakeCli::Vet(cli) =
+        clap::Parser::try_parse_from(["cargo", "vet", "--locked", "--frozen"]).unwrap();
+    let cfg = crate::PartialConfig {
+        cli,
+        cache_dir: cache_dir.path().to_owned(),
+        mock_cache: false,
+    };
+    let cache = crate::storage::Cache::acquire(&cfg).unwrap();
+
+    let delta = Delta {
+        from: "1.0.0".parse().unwrap(),
+        to: "1.1.0".parse().unwrap(),
+    };
+    let diffstat = TEST_RUNTIME
+        .block_on(cache.fetch_and_diffstat_package(None, "not-in-any-tree", &delta))
+        .unwrap();
+    assert_eq!(diffstat.count, 1);
+}
+
+#[test]
+fn diff_presentation_args() {
+    let git_args = |args: &[&str]| {
+        let crate::cli::FakeCli::Vet(cli) = clap::Parser::try_parse_from(
+            [
+                "cargo",
+                "vet",
+                "diff",
+                "foo",
+                "1.0.0",
+                "1.1.0",
+                "--mode=local",
+            ]
+            .iter()
+            .chain(args),
+        )
+        .unwrap();
+        let sub_args = if let Some(crate::cli::Commands::Diff(sub_args)) = &cli.command {
+            sub_args
+        } else {
+            unreachable!("didn't parse as a diff command");
+        };
+        crate::git_diff_command(sub_args, "a".as_ref(), "b".as_ref())
+            .get_args()
+            .map(|arg| arg.to_str().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(git_args(&[]), ["diff", "--no-index", "a", "b"]);
+    assert_eq!(
+        git_args(&["--word-diff", "--context", "10"]),
+        [
+            "diff",
+            "--no-index",
+            "--word-diff",
+            "--unified=10",
+            "a",
+            "b"
+        ]
+    );
+}
+
+#[test]
+fn diff_tool() {
+    let config = r##"
+[diff]
+tool = "delta --side-by-side"
+"##;
+
+    let store = Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    let tool = store.config.diff.tool.as_deref().unwrap();
+    assert_eq!(tool, "delta --side-by-side");
+
+    // The tool is run by the shell, so that it can have its own arguments.
+    if cfg!(not(target_family = "unix")) {
+        return;
+    }
+    let command = crate::diff_tool_command(tool, "a".as_ref(), "b".as_ref());
+    assert_eq!(command.get_program(), "sh");
+    assert_eq!(
+        command
+            .get_args()
+            .map(|arg| arg.to_str().unwrap())
+            .collect::<Vec<_>>(),
+        [
+            "-c",
+            "delta --side-by-side \"$@\"",
+            "delta --side-by-side",
+            "a",
+            "b"
+        ]
+    );
+}
+
+#[test]
+fn diff_web_mode_rejected() {
+    // Only `inspect` has a url to open, so `diff` doesn't accept `--mode=web`.
+    let parse = |mode: &str| {
+        crate::cli::FakeCli::try_parse_from(["cargo", "vet", "diff", "foo", "1.0.0", "1.1.0", mode])
+    };
+    assert!(parse("--mode=local").is_ok());
+    assert_eq!(
+        parse("--mode=web").err().unwrap().kind(),
+        clap::ErrorKind::InvalidValue
+    );
+}
+
+#[test]
+fn inspect_no_shell() {
+    let _enter = TEST_RUNTIME.enter();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let registry_src = tempfile::tempdir().unwrap();
+    let fetched = registry_src.path().join("third-party1-10.0.0");
+    std::fs::create_dir_all(&fetched).unwrap();
+    std::fs::write(fetched.join("Cargo.toml"), "[package]\n").unwrap();
+
+    let crate::cli::FakeCli::Vet(mut cli) = crate::cli::FakeCli::try_parse_from([
+        "cargo".as_ref(),
+        "vet".as_ref(),
+        "inspect".as_ref(),
+        "third-party1".as_ref(),
+        "10.0.0".as_ref(),
+        "--mode=local".as_ref(),
+        "--no-shell".as_ref(),
+        "--locked".as_ref(),
+        "--frozen".as_ref(),
+        "--registry-src".as_ref(),
+        registry_src.path().as_os_str(),
+    ])
+    .unwrap();
+    let sub_args = if let Some(crate::cli::Commands::Inspect(sub_args)) = cli.command.take() {
+        sub_args
+    } else {
+        unreachable!("didn't parse as an inspect command");
+    };
+    let cfg = crate::PartialConfig {
+        cli,
+        cache_dir: cache_dir.path().to_owned(),
+        mock_cache: false,
+    };
+
+    // With `--no-shell` the fetched path is printed instead of exec-ing a shell.
+    let output = BasicTestOutput::new();
+    crate::cmd_inspect(&output.clone().as_dyn(), &cfg, None, &sub_args).unwrap();
+    assert_eq!(output.to_string(), format!("{}\n", fetched.display()));
+}
+
+#[test]
+fn user_shell_fallback() {
+    let (var, default) = if cfg!(target_family = "unix") {
+        ("SHELL", "/bin/sh")
+    } else {
+        ("COMSPEC", "cmd.exe")
+    };
+    let env = |value: Option<&'static str>| {
+        move |name: &str| {
+            assert_eq!(name, var);
+            value.map(OsString::from)
+        }
+    };
+
+    assert_eq!(
+        crate::user_shell(env(Some("/usr/bin/fish"))),
+        "/usr/bin/fish"
+    );
+    assert_eq!(crate::user_shell(env(None)), default);
+    assert_eq!(crate::user_shell(env(Some(""))), default);
+}
@@ -0,0 +1,126 @@
+use super::*;
+use crate::format::{DiffCache, DiffStat};
+
+#[test]
+fn merge_diff_cache() {
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let cfg = mock_cfg(&metadata);
+    let cache = crate::storage::Cache::acquire(&cfg).unwrap();
+
+    let diff_cache = |entries: &[(&str, u64, u64, u64)]| {
+        let mut diff_cache = DiffCache::new();
+        for &(package, from, to, count) in entries {
+            diff_cache.entry(package.to_owned()).or_default().insert(
+                Delta {
+                    from: ver(from),
+                    to: ver(to),
+                },
+                DiffStat {
+                    raw: format!("+{count}"),
+                    count,
+                },
+            );
+        }
+        diff_cache
+    };
+
+    let (added, conflicting) = cache.merge_diff_cache(diff_cache(&[("foo", 1, 2, 10)]));
+    assert_eq!((added, conflicting), (1, 0));
+
+    // Entries we already have win, whether or not they agree.
+    let (added, conflicting) = cache.merge_diff_cache(diff_cache(&[
+        ("foo", 1, 2, 99),
+        ("foo", 2, 3, 5),
+        ("bar", 0, 1, 7),
+    ]));
+    assert_eq!((added, conflicting), (2, 1));
+    let counts = |diff_cache: DiffCache| {
+        diff_cache
+            .into_iter()
+            .flat_map(|(package, diffs)| {
+                diffs
+                    .into_iter()
+                    .map(move |(delta, diffstat)| (package.clone(), delta, diffstat.count))
+            })
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        counts(cache.diff_cache()),
+        counts(diff_cache(&[
+            ("foo", 1, 2, 10),
+            ("foo", 2, 3, 5),
+            ("bar", 0, 1, 7)
+        ]))
+    );
+}
+
+#[test]
+fn diff_cache_versioning() {
+    use crate::errors::LoadDiffCacheError;
+    use crate::format::DIFF_CACHE_VERSION;
+    use crate::storage::{load_diff_cache_file, store_diff_cache_file};
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("diff-cache.toml");
+
+    // Caches from before the format was versioned are rejected.
+    std::fs::write(
+        &path,
+        "[foo.\"1.0.0 -> 2.0.0\"]\nraw = \"+10\"\ncount = 10\n",
+    )
+    .unwrap();
+    match load_diff_cache_file(&path) {
+        Err(LoadDiffCacheError::VersionMismatch { found, expected }) => {
+            assert_eq!((found, expected), (0, DIFF_CACHE_VERSION))
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+
+    // What we write, we can read back.
+    std::fs::write(
+        &path,
+        format!(
+            "version = {DIFF_CACHE_VERSION}\n\n[diffs.foo.\"1.0.0 -> 2.0.0\"]\nraw = \"+10\"\ncount = 10\n"
+        ),
+    )
+    .unwrap();
+    let diff_cache = load_diff_cache_file(&path).unwrap();
+    assert_eq!(diff_cache["foo"].values().next().unwrap().count, 10);
+    store_diff_cache_file(&path, diff_cache).unwrap();
+    assert_eq!(load_diff_cache_file(&path).unwrap()["foo"].len(), 1);
+}
+
+#[test]
+fn diffstat_dirs_counts() {
+    let old = tempfile::tempdir().unwrap();
+    let new = tempfile::tempdir().unwrap();
+    let write = |dir: &tempfile::TempDir, path: &str, contents: &str| {
+        let path = dir.path().join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    };
+
+    write(&old, "Cargo.toml", "[package]\nname = \"foo\"\n");
+    write(&old, "src/lib.rs", "fn a() {}\nfn b() {}\nfn c() {}\n");
+    write(&old, "src/removed.rs", "gone\n");
+    write(&new, "Cargo.toml", "[package]\nname = \"foo\"\n");
+    write(
+        &new,
+        "src/lib.rs",
+        "fn a() {}\nfn B() {}\nfn c() {}\nfn d() {}\n",
+    );
+    write(&new, "src/added.rs", "one\ntwo\n");
+    std::fs::create_dir_all(new.path().join("empty")).unwrap();
+
+    let same = crate::storage::diffstat_dirs(old.path(), old.path()).unwrap();
+    assert_eq!(same.raw, "");
+    assert_eq!(same.count, 0);
+
+    let diffstat = crate::storage::diffstat_dirs(old.path(), new.path()).unwrap();
+    assert_eq!(
+        diffstat.raw.trim(),
+        "3 files changed, 4 insertions(+), 2 deletions(-)"
+    );
+    assert_eq!(diffstat.count, 6);
+}
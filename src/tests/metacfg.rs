@@ -0,0 +1,115 @@
+use super::*;
+
+#[test]
+fn print_config_sources() {
+    // Each setting comes from the last layer which sets it.
+    let mock = MockMetadata::simple();
+    let mut metadata = mock.metadata();
+    metadata.workspace_metadata = json!({
+        "vet": { "store": { "path": "custom-store" } },
+    });
+    let metacfg = crate::compute_metacfg(&metadata, None).unwrap();
+    let cfg = mock_cfg(&metadata);
+
+    let output = BasicTestOutput::new();
+    crate::cmd_print_config(
+        &output.clone().as_dyn(),
+        &cfg._rest,
+        &metacfg,
+        &crate::cli::PrintConfigArgs {},
+    )
+    .unwrap();
+    let store_path = metadata.workspace_root.join("custom-store");
+    assert_eq!(
+        output.to_string(),
+        format!(
+            "version = 1  (from defaults)\n\
+             store.path = {store_path:?}  (from [workspace.metadata.vet])\n\
+             \n\
+             layers (later ones take priority):\n  \
+             defaults\n  \
+             [workspace.metadata.vet]\n"
+        )
+    );
+}
+
+#[test]
+fn manifest_path_directory() {
+    // --manifest-path may name the directory containing the Cargo.toml, and
+    // bad paths are errors of our own rather than cargo's.
+    let _enter = TEST_RUNTIME.enter();
+    let dir = tempfile::tempdir().unwrap();
+    let manifest = dir.path().join("Cargo.toml");
+    std::fs::write(&manifest, "[package]\n").unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+
+    assert_eq!(
+        crate::resolve_manifest_path(dir.path().to_owned()).unwrap(),
+        manifest
+    );
+    assert_eq!(
+        crate::resolve_manifest_path(manifest.clone()).unwrap(),
+        manifest
+    );
+    assert_eq!(
+        crate::resolve_manifest_path(dir.path().join("src"))
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "--manifest-path {} is a directory without a Cargo.toml",
+            dir.path().join("src").display()
+        )
+    );
+    assert_eq!(
+        crate::resolve_manifest_path(dir.path().join("missing"))
+            .unwrap_err()
+            .to_string(),
+        format!(
+            "--manifest-path {} doesn't exist",
+            dir.path().join("missing").display()
+        )
+    );
+}
+
+#[test]
+fn store_path_flag() {
+    // --store-path overrides [metadata.vet], and is where init creates the store.
+    let _enter = TEST_RUNTIME.enter();
+    let store_dir = tempfile::tempdir().unwrap();
+    let store_path = store_dir.path().join("out-of-tree-store");
+    let mock = MockMetadata::simple();
+    let mut metadata = mock.metadata();
+    metadata.workspace_metadata = json!({
+        "vet": { "store": { "path": "custom-store" } },
+    });
+
+    let mut cfg = mock_cfg_args(
+        &metadata,
+        [
+            "cargo".as_ref(),
+            "vet".as_ref(),
+            "init".as_ref(),
+            "--store-path".as_ref(),
+            store_path.as_os_str(),
+        ],
+    );
+    cfg.metacfg = crate::compute_metacfg(&metadata, cfg.cli.store_path.as_deref()).unwrap();
+    let (path, source) = cfg.metacfg.store_path_with_source();
+    assert_eq!(path, store_path);
+    assert_eq!(source, crate::format::MetaConfigSource::Cli);
+
+    let sub_args = if let Some(crate::cli::Commands::Init(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!("didn't parse as an init command");
+    };
+    let output = BasicTestOutput::new();
+    crate::cmd_init(&output.clone().as_dyn(), &cfg, sub_args).unwrap();
+    assert!(store_path.join("config.toml").exists());
+    assert!(!metadata
+        .workspace_root
+        .join("custom-store")
+        .as_std_path()
+        .exists());
+    Store::acquire(&cfg).unwrap();
+}
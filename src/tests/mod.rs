@@ -23,8 +23,17 @@ use crate::{
     PackageExt, PartialConfig, SortedMap, Store,
 };
 
+#[cfg(feature = "advisories")]
+mod advisories;
 mod audit_as_crates_io;
+mod audits_of;
+mod cache;
 mod certify;
+mod crev;
+mod diff;
+mod diff_cache;
+mod metacfg;
+mod network;
 mod regenerate_unaudited;
 mod store_parsing;
 mod vet;
@@ -33,6 +42,12 @@ mod violations;
 // Some room above and below
 const DEFAULT_VER: u64 = 10;
 const DEFAULT_CRIT: CriteriaStr = "reviewed";
+const GIT_FORK: &str = "https://github.com/fork/third-party1";
+const ALTERNATE_REGISTRY: &str = "https://example.com/alternate-index";
+
+const EMPTY_CONFIG: &str = "\n";
+const EMPTY_AUDITS: &str = "[audits]\n";
+const EMPTY_IMPORTS: &str = "[audits]\n";
 
 lazy_static::lazy_static! {
     static ref TEST_RUNTIME: tokio::runtime::Runtime = {
@@ -67,7 +82,8 @@ lazy_static::lazy_static! {
 struct MockMetadata {
     packages: Vec<MockPackage>,
     pkgids: Vec<String>,
-    idx_by_name_and_ver: BTreeMap<PackageStr<'static>, BTreeMap<Version, usize>>,
+    idx_by_name_and_ver:
+        BTreeMap<PackageStr<'static>, BTreeMap<(Version, Option<&'static str>), usize>>,
 }
 
 struct MockPackage {
@@ -76,7 +92,16 @@ struct MockPackage {
     deps: Vec<MockDependency>,
     dev_deps: Vec<MockDependency>,
     build_deps: Vec<MockDependency>,
+    /// Optional dependencies which no enabled feature activates. These are
+    /// declared in the package's metadata, but aren't part of the resolve.
+    optional_deps: Vec<MockDependency>,
     targets: Vec<&'static str>,
+    /// The native library this package declares it links (`links = "..."`)
+    links: Option<&'static str>,
+    /// The git repository this package comes from, instead of crates.io
+    git: Option<&'static str>,
+    /// The index of the registry this package comes from, instead of crates.io
+    registry: Option<&'static str>,
     is_workspace: bool,
     is_first_party: bool,
 }
@@ -84,6 +109,8 @@ struct MockPackage {
 struct MockDependency {
     name: &'static str,
     version: Version,
+    /// The git repository of the package depended on, if it isn't from crates.io
+    git: Option<&'static str>,
 }
 
 pub struct MockRegistry {
@@ -208,7 +235,11 @@ impl Default for MockPackage {
             deps: vec![],
             dev_deps: vec![],
             build_deps: vec![],
+            optional_deps: vec![],
             targets: vec!["lib"],
+            links: None,
+            git: None,
+            registry: None,
             is_workspace: false,
             is_first_party: false,
         }
@@ -233,6 +264,7 @@ fn dep_ver(name: &'static str, version: u64) -> MockDependency {
     MockDependency {
         name,
         version: ver(version),
+        git: None,
     }
 }
 
@@ -240,7 +272,7 @@ fn dep_ver(name: &'static str, version: u64) -> MockDependency {
 fn default_exemptions(version: Version, config: &ConfigFile) -> ExemptedDependency {
     ExemptedDependency {
         version,
-        criteria: vec![config.default_criteria.clone().into()],
+        criteria: vec![(*config.default_criteria).clone().into()],
         dependency_criteria: DependencyCriteria::new(),
         notes: None,
         suggest: true,
@@ -288,6 +320,7 @@ fn delta_audit(from: Version, to: Version, criteria: CriteriaStr) -> AuditEntry
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![criteria.to_string().into()],
         kind: AuditKind::Delta {
             delta,
@@ -312,6 +345,7 @@ fn delta_audit_dep(
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![criteria.to_string().into()],
         kind: AuditKind::Delta {
             delta,
@@ -332,6 +366,7 @@ fn full_audit(version: Version, criteria: CriteriaStr) -> AuditEntry {
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![criteria.to_string().into()],
         kind: AuditKind::Full {
             version,
@@ -347,6 +382,7 @@ fn full_audit_m(
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: criteria.into_iter().map(|s| s.into().into()).collect(),
         kind: AuditKind::Full {
             version,
@@ -368,6 +404,7 @@ fn full_audit_dep(
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![criteria.to_string().into()],
         kind: AuditKind::Full {
             version,
@@ -388,6 +425,7 @@ fn violation_hard(version: VersionReq) -> AuditEntry {
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![SAFE_TO_RUN.to_string().into()],
         kind: AuditKind::Violation { violation: version },
     }
@@ -397,6 +435,7 @@ fn violation(version: VersionReq, criteria: CriteriaStr) -> AuditEntry {
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: vec![criteria.to_string().into()],
         kind: AuditKind::Violation { violation: version },
     }
@@ -409,6 +448,7 @@ fn violation_m(
     AuditEntry {
         who: None,
         notes: None,
+        source_hash: None,
         criteria: criteria.into_iter().map(|s| s.into().into()).collect(),
         kind: AuditKind::Violation { violation: version },
     }
@@ -420,6 +460,8 @@ fn default_policy() -> PolicyEntry {
         criteria: None,
         dev_criteria: None,
         dependency_criteria: SortedMap::new(),
+        dev_dependency_criteria: SortedMap::new(),
+        build_dependency_criteria: SortedMap::new(),
         notes: None,
     }
 }
@@ -720,9 +762,113 @@ impl MockMetadata {
         ])
     }
 
+    fn optional_deps() -> Self {
+        // A tree where an optional dependency is listed by cargo metadata, but
+        // isn't activated by any enabled feature.
+        //
+        //                                    Graph
+        // =======================================================================================
+        //
+        //                                 root-package
+        //                                /            .
+        //                       third-party1      (third-party-optional)
+        //
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root-package",
+                is_workspace: true,
+                is_first_party: true,
+                deps: vec![dep("third-party1")],
+                optional_deps: vec![dep("third-party-optional")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party1",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party-optional",
+                ..Default::default()
+            },
+        ])
+    }
+
+    fn git_fork() -> Self {
+        // A tree where a git fork of a crates.io package, with the same name
+        // and version, is used alongside the crates.io package itself.
+        //
+        //                                    Graph
+        // =======================================================================================
+        //
+        //                                 root-package
+        //                                /            \
+        //                       first-party          third-party1
+        //                            |
+        //                   third-party1 (git)
+        //
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root-package",
+                is_workspace: true,
+                is_first_party: true,
+                deps: vec![dep("first-party"), dep("third-party1")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "first-party",
+                is_first_party: true,
+                deps: vec![MockDependency {
+                    git: Some(GIT_FORK),
+                    ..dep("third-party1")
+                }],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party1",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party1",
+                git: Some(GIT_FORK),
+                ..Default::default()
+            },
+        ])
+    }
+
+    fn alternate_registry() -> Self {
+        // A tree with a package from a registry other than crates.io.
+        //
+        //                                    Graph
+        // =======================================================================================
+        //
+        //                                 root-package
+        //                                /            \
+        //                   third-party1              internal (alternate registry)
+        //
+        MockMetadata::new(vec![
+            MockPackage {
+                name: "root-package",
+                is_workspace: true,
+                is_first_party: true,
+                deps: vec![dep("third-party1"), dep("internal")],
+                ..Default::default()
+            },
+            MockPackage {
+                name: "third-party1",
+                ..Default::default()
+            },
+            MockPackage {
+                name: "internal",
+                registry: Some(ALTERNATE_REGISTRY),
+                ..Default::default()
+            },
+        ])
+    }
+
     fn new(packages: Vec<MockPackage>) -> Self {
         let mut pkgids = vec![];
-        let mut idx_by_name_and_ver = BTreeMap::<PackageStr, BTreeMap<Version, usize>>::new();
+        let mut idx_by_name_and_ver =
+            BTreeMap::<PackageStr, BTreeMap<(Version, Option<&str>), usize>>::new();
 
         for (idx, package) in packages.iter().enumerate() {
             let pkgid = if package.is_first_party {
@@ -730,6 +876,16 @@ impl MockMetadata {
                     "{} {} (path+file:///C:/FAKE/{})",
                     package.name, package.version, package.name
                 )
+            } else if let Some(git) = package.git {
+                format!(
+                    "{} {} (git+{}#0123456789abcdef)",
+                    package.name, package.version, git
+                )
+            } else if let Some(registry) = package.registry {
+                format!(
+                    "{} {} (registry+{})",
+                    package.name, package.version, registry
+                )
             } else {
                 format!(
                     "{} {} (registry+https://github.com/rust-lang/crates.io-index)",
@@ -740,7 +896,7 @@ impl MockMetadata {
             let old = idx_by_name_and_ver
                 .entry(package.name)
                 .or_default()
-                .insert(package.version.clone(), idx);
+                .insert((package.version.clone(), package.git), idx);
             assert!(
                 old.is_none(),
                 "duplicate version {} {}",
@@ -757,20 +913,28 @@ impl MockMetadata {
     }
 
     fn pkgid(&self, package: &MockPackage) -> &str {
-        self.pkgid_by(package.name, &package.version)
+        self.pkgid_from(package.name, &package.version, package.git)
     }
 
     fn pkgid_by(&self, name: PackageStr, version: &Version) -> &str {
-        &self.pkgids[self.idx_by_name_and_ver[name][version]]
+        self.pkgid_from(name, version, None)
+    }
+
+    fn pkgid_from(&self, name: PackageStr, version: &Version, git: Option<&str>) -> &str {
+        &self.pkgids[self.idx_by_name_and_ver[name][&(version.clone(), git)]]
     }
 
-    fn package_by(&self, name: PackageStr, version: &Version) -> &MockPackage {
-        &self.packages[self.idx_by_name_and_ver[name][version]]
+    fn package_by(&self, dep: &MockDependency) -> &MockPackage {
+        &self.packages[self.idx_by_name_and_ver[dep.name][&(dep.version.clone(), dep.git)]]
     }
 
     fn source(&self, package: &MockPackage) -> Value {
         if package.is_first_party {
             json!(null)
+        } else if let Some(git) = package.git {
+            json!(format!("git+{}#0123456789abcdef", git))
+        } else if let Some(registry) = package.registry {
+            json!(format!("registry+{}", registry))
         } else {
             json!("registry+https://github.com/rust-lang/crates.io-index")
         }
@@ -788,7 +952,7 @@ impl MockMetadata {
                 "source": self.source(package),
                 "dependencies": package.deps.iter().chain(&package.dev_deps).chain(&package.build_deps).map(|dep| json!({
                     "name": dep.name,
-                    "source": self.source(self.package_by(dep.name, &dep.version)),
+                    "source": self.source(self.package_by(dep)),
                     "req": format!("={}", dep.version),
                     "kind": null,
                     "rename": null,
@@ -797,7 +961,18 @@ impl MockMetadata {
                     "features": [],
                     "target": null,
                     "registry": null
-                })).collect::<Vec<_>>(),
+                })).chain(package.optional_deps.iter().map(|dep| json!({
+                    "name": dep.name,
+                    "source": self.source(self.package_by(dep)),
+                    "req": format!("={}", dep.version),
+                    "kind": null,
+                    "rename": null,
+                    "optional": true,
+                    "uses_default_features": true,
+                    "features": [],
+                    "target": null,
+                    "registry": null
+                }))).collect::<Vec<_>>(),
                 "targets": package.targets.iter().map(|target| json!({
                     "kind": [
                         target
@@ -824,7 +999,7 @@ impl MockMetadata {
                 "homepage": null,
                 "documentation": null,
                 "edition": "2015",
-                "links": null,
+                "links": package.links,
                 "default_run": null,
                 "rust_version": null
             })).collect::<Vec<_>>(),
@@ -837,22 +1012,22 @@ impl MockMetadata {
             }).collect::<Vec<_>>(),
             "resolve": {
                 "nodes": self.packages.iter().map(|package| {
-                    let mut all_deps = BTreeMap::<(PackageStr, &Version), Vec<Option<&str>>>::new();
+                    let mut all_deps = BTreeMap::<(PackageStr, &Version, Option<&str>), Vec<Option<&str>>>::new();
                     for dep in &package.deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(None);
+                        all_deps.entry((dep.name, &dep.version, dep.git)).or_default().push(None);
                     }
                     for dep in &package.build_deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(Some("build"));
+                        all_deps.entry((dep.name, &dep.version, dep.git)).or_default().push(Some("build"));
                     }
                     for dep in &package.dev_deps {
-                        all_deps.entry((dep.name, &dep.version)).or_default().push(Some("dev"));
+                        all_deps.entry((dep.name, &dep.version, dep.git)).or_default().push(Some("dev"));
                     }
                     json!({
                         "id": self.pkgid(package),
-                        "dependencies": all_deps.keys().map(|(name, version)| self.pkgid_by(name, version)).collect::<Vec<_>>(),
-                        "deps": all_deps.iter().map(|((name, version), kinds)| json!({
+                        "dependencies": all_deps.keys().map(|(name, version, git)| self.pkgid_from(name, version, *git)).collect::<Vec<_>>(),
+                        "deps": all_deps.iter().map(|((name, version, git), kinds)| json!({
                             "name": name,
-                            "pkg": self.pkgid_by(name, version),
+                            "pkg": self.pkgid_from(name, version, *git),
                             "dep_kinds": kinds.iter().map(|kind| json!({
                                 "kind": kind,
                                 "target": null,
@@ -930,13 +1105,15 @@ fn files_inited(metadata: &Metadata) -> (ConfigFile, AuditsFile, ImportsFile) {
                         criteria: Some(vec![DEFAULT_CRIT.to_string().into()]),
                         dev_criteria: Some(vec![DEFAULT_CRIT.to_string().into()]),
                         dependency_criteria: DependencyCriteria::new(),
+                        dev_dependency_criteria: DependencyCriteria::new(),
+                        build_dependency_criteria: DependencyCriteria::new(),
                         notes: None,
                     },
                 );
             }
         }
     }
-    config.default_criteria = DEFAULT_CRIT.to_string();
+    config.default_criteria = DEFAULT_CRIT.to_string().into();
 
     // Rewrite the default used by init
     for exemption in &mut config.exemptions {
@@ -963,7 +1140,7 @@ fn files_full_audited(metadata: &Metadata) -> (ConfigFile, AuditsFile, ImportsFi
 
     let mut audited = SortedMap::<PackageName, Vec<AuditEntry>>::new();
     for package in &metadata.packages {
-        if package.is_third_party(&config.policy) {
+        if package.is_third_party(&config) {
             audited
                 .entry(package.name.clone())
                 .or_insert(vec![])
@@ -992,7 +1169,7 @@ fn builtin_files_full_audited(metadata: &Metadata) -> (ConfigFile, AuditsFile, I
 
     let mut audited = SortedMap::<PackageName, Vec<AuditEntry>>::new();
     for package in &metadata.packages {
-        if package.is_third_party(&config.policy) {
+        if package.is_third_party(&config) {
             audited
                 .entry(package.name.clone())
                 .or_insert(vec![])
@@ -1037,6 +1214,7 @@ where
     Config {
         metacfg: MetaConfig(vec![]),
         metadata: metadata.clone(),
+        feature_set_only_packages: SortedMap::new(),
         _rest: PartialConfig {
             cli,
             cache_dir: PathBuf::new(),
@@ -1059,6 +1237,24 @@ fn get_report(metadata: &Metadata, report: ResolveReport) -> String {
     output.to_string()
 }
 
+fn get_unused_exemptions(report: &ResolveReport, store: &Store) -> Vec<String> {
+    report
+        .unused_exemptions(&store.config.exemptions)
+        .into_iter()
+        .map(|(package, entry)| format!("{package}:{}", entry.version))
+        .collect()
+}
+
+fn get_report_json(metadata: &Metadata, report: ResolveReport) -> String {
+    let cfg = mock_cfg(metadata);
+    let output = BasicTestOutput::new();
+    let suggest = report.compute_suggest(&cfg, None, true).unwrap();
+    report
+        .print_json(&output.clone().as_dyn(), &cfg, suggest.as_ref())
+        .unwrap();
+    output.to_string()
+}
+
 struct BasicTestOutput {
     output: Mutex<Vec<u8>>,
     on_read_line: Option<Box<dyn Fn(&str) -> io::Result<String> + Send + Sync + 'static>>,
@@ -1104,6 +1300,11 @@ impl Out for BasicTestOutput {
         Ok(buf.len())
     }
 
+    fn is_term(&self) -> bool {
+        // Only pretend to be a terminal if there's a "user" to respond to prompts
+        self.on_read_line.is_some()
+    }
+
     fn clear_screen(&self) -> io::Result<()> {
         writeln!(self, "<<<CLEAR SCREEN>>>");
         Ok(())
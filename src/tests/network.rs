@@ -0,0 +1,733 @@
+use super::*;
+use crate::network::Network;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+};
+
+/// Serve `responses` to one request each over plain HTTP, returning the url
+/// being served and a handle which yields the requests that were received.
+fn serve<S: AsRef<str> + Send + 'static>(
+    responses: Vec<S>,
+) -> (reqwest::Url, std::thread::JoinHandle<Vec<String>>) {
+    serve_with(|_| responses)
+}
+
+/// Like `serve`, for responses which need to know the url being served.
+fn serve_with<S: AsRef<str> + Send + 'static>(
+    responses: impl FnOnce(&reqwest::Url) -> Vec<S>,
+) -> (reqwest::Url, std::thread::JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/audits.toml", listener.local_addr().unwrap());
+    let url = reqwest::Url::parse(&url).unwrap();
+    let responses = responses(&url);
+    let handle = std::thread::spawn(move || {
+        let mut requests = vec![];
+        for response in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = vec![];
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let len = stream.read(&mut buf).unwrap();
+                assert!(len > 0, "connection closed mid-request");
+                request.extend_from_slice(&buf[..len]);
+            }
+            requests.push(String::from_utf8(request).unwrap().to_lowercase());
+            stream.write_all(response.as_ref().as_bytes()).unwrap();
+        }
+        requests
+    });
+    (url, handle)
+}
+
+#[test]
+fn download_cached_revalidates() {
+    let _enter = TEST_RUNTIME.enter();
+    let cache_dir = tempfile::tempdir().unwrap();
+
+    let (url, server) = serve(vec![
+        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\nchanged",
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nfresh",
+    ]);
+    let network = Network::mock_with_http_cache(cache_dir.path().to_owned());
+    let download = || {
+        let body = tokio::runtime::Handle::current()
+            .block_on(network.download_cached(url.clone()))
+            .unwrap();
+        String::from_utf8(body).unwrap()
+    };
+
+    // Nothing cached yet, so a plain download which gets cached
+    assert_eq!(download(), "hello");
+    // Revalidated, and the server says our copy is still good
+    assert_eq!(download(), "hello");
+    // Revalidated, and the server sends something new without validators
+    assert_eq!(download(), "changed");
+    // Without validators there's nothing cached to revalidate
+    assert_eq!(download(), "fresh");
+
+    let requests = server.join().unwrap();
+    let conditional = requests
+        .iter()
+        .map(|request| request.contains("if-none-match: \"v1\""))
+        .collect::<Vec<_>>();
+    assert_eq!(conditional, [false, true, true, false]);
+}
+
+#[test]
+fn download_retries_server_errors() {
+    let _enter = TEST_RUNTIME.enter();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(cache_dir.path().to_owned());
+    let download = |url| {
+        tokio::runtime::Handle::current()
+            .block_on(network.download(url))
+            .ok()
+    };
+
+    // A server error is retried
+    let (url, server) = serve(vec![
+        "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+    ]);
+    assert_eq!(download(url).unwrap(), b"hello");
+    assert_eq!(server.join().unwrap().len(), 2);
+
+    // But a client error fails straight away, rather than retrying into the
+    // next response
+    let (url, server) = serve(vec![
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nagain",
+    ]);
+    assert!(download(url.clone()).is_none());
+    assert_eq!(download(url).unwrap(), b"again");
+    assert_eq!(server.join().unwrap().len(), 2);
+}
+
+#[test]
+fn download_retries_lost_bodies() {
+    // A connection which is lost partway through the body is retried too,
+    // both for plain and cached downloads.
+    let _enter = TEST_RUNTIME.enter();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(cache_dir.path().to_owned());
+    let truncated = "HTTP/1.1 200 OK\r\nContent-Length: 10\r\nConnection: close\r\n\r\nhel";
+    let complete = "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello";
+
+    let (url, server) = serve(vec![truncated, complete]);
+    let body = tokio::runtime::Handle::current()
+        .block_on(network.download(url))
+        .unwrap();
+    assert_eq!(body, b"hello");
+    assert_eq!(server.join().unwrap().len(), 2);
+
+    let (url, server) = serve(vec![truncated, complete]);
+    let body = tokio::runtime::Handle::current()
+        .block_on(network.download_cached(url))
+        .unwrap();
+    assert_eq!(body, b"hello");
+    assert_eq!(server.join().unwrap().len(), 2);
+}
+
+#[test]
+fn download_not_found() {
+    // Only a 404 or 410 means there's nothing to download (e.g. because a
+    // package was yanked), other failures shouldn't be mistaken for that.
+    let _enter = TEST_RUNTIME.enter();
+    let cache_dir = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(cache_dir.path().to_owned());
+    let is_not_found = |status: &str| {
+        let (url, server) = serve(vec![format!(
+            "HTTP/1.1 {status}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        )]);
+        let error = tokio::runtime::Handle::current()
+            .block_on(network.download(url))
+            .unwrap_err();
+        server.join().unwrap();
+        error.is_not_found()
+    };
+
+    assert!(is_not_found("404 Not Found"));
+    assert!(is_not_found("410 Gone"));
+    assert!(!is_not_found("403 Forbidden"));
+}
+
+#[test]
+fn network_timeout() {
+    let timeout = |config: &str, args: &[&str]| {
+        let store = Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+        let crate::cli::FakeCli::Vet(cli) =
+            clap::Parser::try_parse_from(["cargo", "vet"].iter().chain(args)).unwrap();
+        let cfg = PartialConfig {
+            cli,
+            cache_dir: PathBuf::new(),
+            mock_cache: true,
+        };
+        crate::network::request_timeout(&cfg, Some(&store.config)).as_secs()
+    };
+
+    assert_eq!(timeout("", &[]), 60);
+    assert_eq!(timeout("[network]\ntimeout = 120\n", &[]), 120);
+    // The command line takes precedence over config.toml
+    assert_eq!(
+        timeout("[network]\ntimeout = 120\n", &["--network-timeout", "5"]),
+        5
+    );
+}
+
+#[test]
+fn network_proxy() {
+    // Both downloads and git should go through `network.proxy`, rather than
+    // to the server directly.
+    let _enter = TEST_RUNTIME.enter();
+    let (proxy, server) = serve(vec![
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nhello",
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+    ]);
+    let config = format!(
+        "[network]\nproxy = \"{}\"\n",
+        proxy.origin().ascii_serialization()
+    );
+    let store = Store::mock_acquire(&config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+
+    let crate::cli::FakeCli::Vet(cli) = clap::Parser::try_parse_from(["cargo", "vet"]).unwrap();
+    let cfg = PartialConfig {
+        cli,
+        cache_dir: PathBuf::new(),
+        mock_cache: true,
+    };
+    let network = Network::acquire(&cfg, Some(&store.config)).unwrap();
+
+    let url = reqwest::Url::parse("http://example.invalid/audits.toml").unwrap();
+    let body = tokio::runtime::Handle::current()
+        .block_on(network.download(url))
+        .unwrap();
+    assert_eq!(body, b"hello");
+
+    let repo = reqwest::Url::parse("http://example.invalid/audits.git").unwrap();
+    tokio::runtime::Handle::current()
+        .block_on(network.fetch_git_files(&repo, crate::network::GitReference::DefaultBranch, &[]))
+        .unwrap_err();
+
+    let requests = server.join().unwrap();
+    assert!(
+        requests[0].starts_with("get http://example.invalid/audits.toml "),
+        "{}",
+        requests[0]
+    );
+    assert!(
+        requests[1].starts_with("get http://example.invalid/audits.git/info/refs"),
+        "{}",
+        requests[1]
+    );
+}
+
+#[test]
+fn fetch_all_suggest_deltas() {
+    // Fetching should cover what `suggest` would need as well as the packages
+    // in the graph, such as the audited version it would suggest a delta from,
+    // and cache the diffstats. Everything is in the --registry-src, so any
+    // attempt to fetch something else would fail for lack of a network.
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = files_no_exemptions(&metadata);
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![full_audit(ver(5), DEFAULT_CRIT)],
+    );
+    let store = Store::mock(config, audits, imports);
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    let registry_src = tempfile::tempdir().unwrap();
+    for (package, version, lines) in [
+        ("third-party1", "5.0.0", 3),
+        ("third-party1", "10.0.0", 4),
+        ("third-party2", "10.0.0", 1),
+        ("transitive-third-party1", "10.0.0", 2),
+    ] {
+        let dir = registry_src.path().join(format!("{package}-{version}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{package}\"\nversion = \"{version}\"\n"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("lib.rs"), "fn f() {}\n".repeat(lines)).unwrap();
+    }
+
+    let mut cfg = mock_cfg_args(
+        &metadata,
+        [
+            "cargo".as_ref(),
+            "vet".as_ref(),
+            "--registry-src".as_ref(),
+            registry_src.path().as_os_str(),
+        ],
+    );
+    cfg._rest.cache_dir = cache_dir.path().to_owned();
+    cfg._rest.mock_cache = false;
+
+    let output = BasicTestOutput::new();
+    crate::fetch_all(&output.clone().as_dyn(), &cfg, &store, None).unwrap();
+    assert_eq!(output.to_string(), "Fetched 3 packages\n");
+
+    let cache = crate::storage::Cache::acquire(&cfg).unwrap();
+    let diffed = cache
+        .diff_cache()
+        .into_iter()
+        .flat_map(|(package, diffs)| {
+            diffs.into_iter().map(move |(delta, diffstat)| {
+                let delta = format!("{} -> {}", delta.from, delta.to);
+                (package.clone(), delta, diffstat.count)
+            })
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        diffed,
+        [
+            ("third-party1".to_owned(), "5.0.0 -> 10.0.0".to_owned(), 3),
+            ("third-party2".to_owned(), "0.0.0 -> 10.0.0".to_owned(), 4),
+            (
+                "transitive-third-party1".to_owned(),
+                "0.0.0 -> 10.0.0".to_owned(),
+                5
+            ),
+        ]
+    );
+}
+
+#[test]
+fn imported_criteria_implies_changed() {
+    let _enter = TEST_RUNTIME.enter();
+    let http_cache = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+
+    let audits = |implies: &str| {
+        let body = format!(
+            "[criteria.fancy]\ndescription = \"fancy\"\nimplies = \"{implies}\"\n\n[audits]\n"
+        );
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    };
+    let (url, server) = serve(vec![audits("safe-to-deploy"), audits("safe-to-run")]);
+
+    let config = format!("[imports.peer]\nurl = \"{url}\"\ncriteria-map = []\n");
+    let imports = "[audits.peer.criteria.fancy]\ndescription = \"fancy\"\nimplies = \"safe-to-run\"\n\n[audits.peer.audits]\n";
+    // The changes to the criteria which a fetch finds
+    let fetch = || {
+        let mut store = Store::mock_acquire(&config, "[audits]\n", imports).unwrap();
+        match tokio::runtime::Handle::current()
+            .block_on(store.fetch_foreign_audits(&network, false))
+        {
+            Ok(()) => vec![],
+            Err(crate::errors::FetchAuditError::CriteriaChange(changes)) => changes
+                .errors
+                .into_iter()
+                .map(|change| (change.what, change.old, change.new))
+                .collect(),
+            Err(error) => panic!("fetch failed: {error:?}"),
+        }
+    };
+
+    // The criteria now implies something else, which changes what it means
+    assert_eq!(
+        fetch(),
+        [(
+            "implies",
+            "safe-to-run".to_owned(),
+            "safe-to-deploy".to_owned()
+        )]
+    );
+
+    // But the same definition is fine
+    assert_eq!(fetch(), []);
+    server.join().unwrap();
+}
+
+#[test]
+fn file_imports() {
+    // An import from a local file is read straight from disk, and isn't
+    // cached.
+    let _enter = TEST_RUNTIME.enter();
+    let http_cache = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+
+    let peer = tempfile::tempdir().unwrap();
+    let audits_path = peer.path().join("audits.toml");
+    std::fs::write(
+        &audits_path,
+        "[criteria.fancy]\ndescription = \"fancy\"\n\n[audits]\n",
+    )
+    .unwrap();
+    let url = reqwest::Url::from_file_path(&audits_path).unwrap();
+
+    let config = format!("[imports.peer]\nurl = \"{url}\"\ncriteria-map = []\n");
+    let mut store = Store::mock_acquire(&config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    tokio::runtime::Handle::current()
+        .block_on(store.fetch_foreign_audits(&network, false))
+        .unwrap();
+    assert!(store.imports.audits["peer"].criteria.contains_key("fancy"));
+    assert_eq!(std::fs::read_dir(http_cache.path()).unwrap().count(), 0);
+
+    // A missing file is reported like a missing download
+    std::fs::remove_file(&audits_path).unwrap();
+    let error = tokio::runtime::Handle::current()
+        .block_on(network.download_cached(url))
+        .unwrap_err();
+    assert!(error.is_not_found(), "{error:?}");
+}
+
+#[test]
+fn git_imports() {
+    let _enter = TEST_RUNTIME.enter();
+    let repo = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let out = std::process::Command::new("git")
+            .current_dir(repo.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8(out.stdout).unwrap().trim().to_owned()
+    };
+    let commit_audits = |package: &str| {
+        let audits = repo.path().join("supply-chain/audits.toml");
+        std::fs::create_dir_all(audits.parent().unwrap()).unwrap();
+        std::fs::write(
+            audits,
+            format!("[[audits.{package}]]\ncriteria = \"safe-to-deploy\"\nversion = \"1.0.0\"\n"),
+        )
+        .unwrap();
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", package]);
+        git(&["rev-parse", "HEAD"])
+    };
+    git(&["init", "--quiet", "--initial-branch=main"]);
+    let first = commit_audits("first");
+    let second = commit_audits("second");
+    let url = reqwest::Url::from_directory_path(repo.path()).unwrap();
+
+    let fetch = |extra: &str| {
+        let config = format!("[imports.peer]\ngit = \"{url}\"\n{extra}criteria-map = []\n");
+        let mut store = Store::mock_acquire(&config, "[audits]\n", "[audits]\n").unwrap();
+        let http_cache = tempfile::tempdir().unwrap();
+        let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+        tokio::runtime::Handle::current()
+            .block_on(store.fetch_foreign_audits(&network, false))
+            .unwrap();
+        let packages = store.imports.audits["peer"]
+            .audits
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        (store.imports.git_commits["peer"].clone(), packages)
+    };
+
+    assert_eq!(fetch(""), (second.clone(), vec!["second".to_owned()]));
+    assert_eq!(
+        fetch("branch = \"main\"\n"),
+        (second, vec!["second".to_owned()])
+    );
+    assert_eq!(
+        fetch(&format!("rev = \"{first}\"\n")),
+        (first, vec!["first".to_owned()])
+    );
+}
+
+#[test]
+fn git_cached_repo_refs() {
+    // With a shared repository cache, imports of different branches of the
+    // same repository shouldn't see each other's commits, even when fetched at
+    // the same time, and a tag should be fetched again in case it moved.
+    use crate::network::GitReference;
+
+    let _enter = TEST_RUNTIME.enter();
+    let repo = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let out = std::process::Command::new("git")
+            .current_dir(repo.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+        String::from_utf8(out.stdout).unwrap().trim().to_owned()
+    };
+    git(&["init", "--quiet", "--initial-branch=main"]);
+    git(&["commit", "--quiet", "--allow-empty", "-m", "main"]);
+    let main = git(&["rev-parse", "HEAD"]);
+    git(&["tag", "v1"]);
+    git(&["checkout", "--quiet", "-b", "other"]);
+    git(&["commit", "--quiet", "--allow-empty", "-m", "other"]);
+    let other = git(&["rev-parse", "HEAD"]);
+    git(&["checkout", "--quiet", "main"]);
+    let url = reqwest::Url::from_directory_path(repo.path()).unwrap();
+
+    let http_cache = tempfile::tempdir().unwrap();
+    let git_cache = tempfile::tempdir().unwrap();
+    let network =
+        Network::mock_with_git_cache(http_cache.path().to_owned(), git_cache.path().to_owned());
+    let (network, url) = (&network, &url);
+    let fetch = |reference| async move {
+        let (commit, _) = network.fetch_git_files(url, reference, &[]).await.unwrap();
+        commit
+    };
+
+    let (fetched_main, fetched_other, fetched_default) = tokio::runtime::Handle::current()
+        .block_on(async {
+            tokio::join!(
+                fetch(GitReference::Branch("main")),
+                fetch(GitReference::Branch("other")),
+                fetch(GitReference::DefaultBranch),
+            )
+        });
+    assert_eq!(fetched_main, main);
+    assert_eq!(fetched_other, other);
+    assert_eq!(fetched_default, main);
+
+    let fetch_tag = || tokio::runtime::Handle::current().block_on(fetch(GitReference::Rev("v1")));
+    assert_eq!(fetch_tag(), main);
+    git(&["tag", "--force", "v1", &other]);
+    assert_eq!(fetch_tag(), other);
+}
+
+#[test]
+fn git_import_sources() {
+    use crate::format::RemoteImport;
+    use crate::storage::check_import_source;
+
+    let git = |git: &str| RemoteImport {
+        git: Some(git.to_owned()),
+        ..Default::default()
+    };
+
+    assert!(check_import_source(&git("https://example.com/audits.git")).is_ok());
+    assert!(check_import_source(&git("ssh://git@example.com/audits.git")).is_ok());
+    assert!(check_import_source(&git("git@example.com:audits.git")).is_err());
+    assert!(check_import_source(&RemoteImport {
+        url: "https://example.com/audits.toml".to_owned(),
+        ..git("https://example.com/audits.git")
+    })
+    .is_err());
+    assert!(check_import_source(&RemoteImport {
+        rev: Some("v1".to_owned()),
+        branch: Some("main".to_owned()),
+        ..git("https://example.com/audits.git")
+    })
+    .is_err());
+    assert!(check_import_source(&RemoteImport {
+        url: "https://example.com/audits.toml".to_owned(),
+        rev: Some("v1".to_owned()),
+        ..Default::default()
+    })
+    .is_err());
+}
+
+#[test]
+fn signed_git_imports() {
+    let _enter = TEST_RUNTIME.enter();
+    let repo = tempfile::tempdir().unwrap();
+    let git = |args: &[&str]| {
+        let out = std::process::Command::new("git")
+            .current_dir(repo.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{out:?}");
+    };
+    let keys = tempfile::tempdir().unwrap();
+    let key = keys.path().join("key");
+    let out = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let public_key = std::fs::read_to_string(keys.path().join("key.pub")).unwrap();
+
+    let audits = repo.path().join("supply-chain/audits.toml");
+    std::fs::create_dir_all(audits.parent().unwrap()).unwrap();
+    let write_audits = |package: &str| {
+        std::fs::write(
+            &audits,
+            format!("[[audits.{package}]]\ncriteria = \"safe-to-deploy\"\nversion = \"1.0.0\"\n"),
+        )
+        .unwrap();
+    };
+    git(&["init", "--quiet", "--initial-branch=main"]);
+    write_audits("signed");
+    let signature = crate::signing::sign(&key, &audits).unwrap();
+    assert_eq!(signature, repo.path().join("supply-chain/audits.toml.sig"));
+    git(&["add", "."]);
+    git(&["commit", "--quiet", "-m", "signed"]);
+    let url = reqwest::Url::from_directory_path(repo.path()).unwrap();
+
+    let fetch = || {
+        let config = format!(
+            "[imports.peer]\ngit = \"{url}\"\npublic-key = \"{}\"\ncriteria-map = []\n",
+            public_key.trim()
+        );
+        let mut store = Store::mock_acquire(&config, "[audits]\n", "[audits]\n").unwrap();
+        let http_cache = tempfile::tempdir().unwrap();
+        let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+        tokio::runtime::Handle::current()
+            .block_on(store.fetch_foreign_audits(&network, false))
+            .map(|()| {
+                store.imports.audits["peer"]
+                    .audits
+                    .keys()
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .map_err(|error| {
+                assert!(
+                    matches!(error, crate::errors::FetchAuditError::BadSignature { .. }),
+                    "{error:?}"
+                );
+            })
+    };
+    assert_eq!(fetch().unwrap(), vec!["signed".to_owned()]);
+
+    // Changing audits.toml without signing it again is rejected.
+    write_audits("tampered");
+    git(&["commit", "--quiet", "-am", "tampered"]);
+    assert!(fetch().is_err());
+}
+
+#[test]
+fn signed_import_config() {
+    // With a `depth`, the imported config.toml decides which imports are
+    // followed, so it must be signed by the import's key too.
+    let _enter = TEST_RUNTIME.enter();
+    let keys = tempfile::tempdir().unwrap();
+    let key = keys.path().join("key");
+    let out = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+        .arg(&key)
+        .output()
+        .unwrap();
+    assert!(out.status.success(), "{out:?}");
+    let public_key = std::fs::read_to_string(keys.path().join("key.pub")).unwrap();
+
+    let sign = |name: &str, contents: &str| {
+        let path = keys.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        let signature = crate::signing::sign(&key, &path).unwrap();
+        std::fs::read_to_string(signature).unwrap()
+    };
+    let audits = "[audits]\n";
+    let audits_signature = sign("audits.toml", audits);
+    let config = "[imports]\n";
+    let config_signature = sign("config.toml", config);
+
+    let response = |body: &str| {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    };
+    let fetch = |served_config: &str| {
+        let (url, server) = serve(vec![
+            response(audits),
+            response(&audits_signature),
+            response(served_config),
+            response(&config_signature),
+        ]);
+        let config = format!(
+            "[imports.peer]\nurl = \"{url}\"\npublic-key = \"{}\"\ncriteria-map = []\ndepth = 1\n",
+            public_key.trim()
+        );
+        let mut store = Store::mock_acquire(&config, "[audits]\n", "[audits]\n").unwrap();
+        let http_cache = tempfile::tempdir().unwrap();
+        let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+        let result =
+            tokio::runtime::Handle::current().block_on(store.fetch_foreign_audits(&network, false));
+        server.join().unwrap();
+        result.map_err(|error| {
+            assert!(
+                matches!(error, crate::errors::FetchAuditError::BadSignature { .. }),
+                "{error:?}"
+            );
+        })
+    };
+    fetch(config).unwrap();
+
+    // A config.toml which names other imports than the one that was signed
+    // is rejected, rather than followed.
+    assert!(fetch("[imports.evil]\nurl = \"https://example.com/audits.toml\"\n").is_err());
+}
+
+#[test]
+fn import_cycle_to_root() {
+    // An import which leads back to the directly imported store is a cycle,
+    // even though that store isn't one of the transitive imports.
+    let _enter = TEST_RUNTIME.enter();
+    let response = |body: &str| {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    };
+    let (url, server) = serve_with(|url| {
+        let nested_url = url.join("nested/audits.toml").unwrap();
+        vec![
+            response("[audits]\n"),
+            response(&format!(
+                "[imports.nested]\nurl = \"{nested_url}\"\ncriteria-map = []\n"
+            )),
+            response("[audits]\n"),
+            response(&format!(
+                "[imports.back]\nurl = \"{url}\"\ncriteria-map = []\n"
+            )),
+        ]
+    });
+
+    let config = format!("[imports.peer]\nurl = \"{url}\"\ncriteria-map = []\ndepth = 2\n");
+    let mut store = Store::mock_acquire(&config, "[audits]\n", "[audits]\n").unwrap();
+    let http_cache = tempfile::tempdir().unwrap();
+    let network = Network::mock_with_http_cache(http_cache.path().to_owned());
+    tokio::runtime::Handle::current()
+        .block_on(store.fetch_foreign_audits(&network, false))
+        .unwrap();
+
+    // Following `back` would have been a fifth request.
+    assert_eq!(server.join().unwrap().len(), 4);
+    let transitive = &store.imports.transitive_imports["peer"];
+    assert_eq!(transitive.len(), 1);
+    assert_eq!(transitive[0].path, vec!["nested".to_owned()]);
+}
+
+#[test]
+fn minisign_signatures() {
+    use crate::signing::PublicKey;
+
+    // Made with a throwaway key, in the format `minisign -S` produces.
+    let public_key =
+        PublicKey::parse("RWQBAgMEBQYHCAOhB7/zzhC+HXDdGOdLwJln5NYwm6UNXx3chmQSVTG4").unwrap();
+    assert_eq!(public_key.signature_extension(), "minisig");
+    let signature = "untrusted comment: signature from minisign secret key
+RUQBAgMEBQYHCD0BhYBVk7OVn8AUQQXgC8nyaYeJ0YQOtS3DQiqUI+iVAtnfQoBF0uHF2fkCQRbygUkv8zvtyQToaHqKoH+drgI=
+trusted comment: cargo-vet test
+tz5tOCa+LdF8TMxH757Hx9Mqf7bNkxpfx2RXen0hSViwYKlWLI4Pyj8kz6/aOQyQ5ULQ/DCexdvjGc86bS0jAg==
+";
+    let verify = |data: &str| {
+        TEST_RUNTIME.block_on(public_key.verify(data.as_bytes(), signature.as_bytes()))
+    };
+    assert!(verify("[audits]\n").is_ok());
+    assert!(verify("[audits]\n\n").is_err());
+
+    assert!(PublicKey::parse("not a key").is_err());
+    assert!(Store::mock_acquire(
+        "[imports.peer]\nurl = \"https://example.com/audits.toml\"\npublic-key = \"nope\"\ncriteria-map = []\n",
+        "[audits]\n",
+        "[audits]\n",
+    )
+    .is_err());
+}
@@ -22,7 +22,7 @@ fn builtin_simple_exemptions_not_a_real_dep_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-not-a-real-dep-regenerate", exemptions);
@@ -48,12 +48,152 @@ fn builtin_simple_deps_exemptions_overbroad_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-unaudited-overbroad-regenerate", exemptions);
 }
 
+#[test]
+fn builtin_simple_deps_exemptions_prune() {
+    // (Pass) one exemption isn't needed at all, and another is overbroad.
+    // Should remove the first and weaken the second.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+
+    audits.audits.insert("dev".to_string(), vec![]);
+
+    config.exemptions.insert(
+        "dev".to_string(),
+        vec![exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+    );
+    config.exemptions.insert(
+        "fake-dep".to_string(),
+        vec![exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+    );
+
+    let mut store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg_args(&metadata, ["cargo", "vet", "prune", "--dry-run"]);
+    let sub_args = if let Some(crate::cli::Commands::Prune(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!();
+    };
+    let output = BasicTestOutput::new();
+    crate::do_cmd_prune(&output.clone().as_dyn(), &cfg, sub_args, &mut store, None).unwrap();
+
+    insta::assert_snapshot!(
+        "builtin-simple-deps-exemptions-prune",
+        format!("{output}\n{}", get_exemptions(&store))
+    );
+}
+
+#[test]
+fn builtin_simple_deps_exemptions_prune_no_suggest() {
+    // (Pass) an unneeded exemption has `suggest = false`. Unlike `regenerate
+    // exemptions`, prune should still remove it.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_full_audited(&metadata);
+
+    config.exemptions.insert(
+        "fake-dep".to_string(),
+        vec![ExemptedDependency {
+            suggest: false,
+            ..exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY)
+        }],
+    );
+
+    let mut store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg_args(&metadata, ["cargo", "vet", "prune"]);
+    let sub_args = if let Some(crate::cli::Commands::Prune(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!();
+    };
+    let output = BasicTestOutput::new();
+    crate::do_cmd_prune(&output.clone().as_dyn(), &cfg, sub_args, &mut store, None).unwrap();
+
+    assert_eq!(
+        output.to_string(),
+        "removed fake-dep:10.0.0 (safe-to-deploy)\n"
+    );
+    assert!(store.config.exemptions.is_empty());
+}
+
+#[test]
+fn builtin_simple_deps_exemptions_prune_failing() {
+    // (Fail) pruning shouldn't add the exemptions needed to make check pass.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_no_exemptions(&metadata);
+
+    let mut store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg_args(&metadata, ["cargo", "vet", "prune"]);
+    let sub_args = if let Some(crate::cli::Commands::Prune(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!();
+    };
+    let output = BasicTestOutput::new();
+    let result = crate::do_cmd_prune(&output.clone().as_dyn(), &cfg, sub_args, &mut store, None);
+
+    assert!(matches!(
+        result,
+        Err(crate::errors::PruneError::CheckFailing)
+    ));
+    assert!(store.config.exemptions.is_empty());
+}
+
+#[test]
+fn builtin_simple_deps_regenerate_from_scratch() {
+    // (Fail) nothing is audited, and there's a hand-written exemption which
+    // isn't needed. Should add notes to the exemptions it adds, keep the
+    // hand-written one, and do nothing the second time around.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_no_exemptions(&metadata);
+
+    let mut hand_written = exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY);
+    hand_written.suggest = false;
+    hand_written.notes = Some("we vendor this".to_owned());
+    config
+        .exemptions
+        .insert("fake-dep".to_string(), vec![hand_written]);
+
+    let mut store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg(&metadata);
+    let regenerate = |store: &mut Store| {
+        crate::regenerate_exemptions(
+            &cfg,
+            store,
+            None,
+            "{package}@{version} added on {date}",
+            "2022-07-01",
+        )
+        .unwrap();
+        get_exemptions(store)
+    };
+
+    let first = regenerate(&mut store);
+    let second = regenerate(&mut store);
+    assert_eq!(first, second);
+    insta::assert_snapshot!("builtin-simple-deps-regenerate-from-scratch", first);
+}
+
 #[test]
 fn builtin_complex_exemptions_twins_regenerate() {
     // (Pass) two versions of a crate exist and both are exemptions and they're needed
@@ -77,7 +217,7 @@ fn builtin_complex_exemptions_twins_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-unaudited-twins-regenerate", exemptions);
@@ -106,7 +246,7 @@ fn builtin_complex_exemptions_partial_twins_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -142,7 +282,7 @@ fn builtin_simple_exemptions_in_delta_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-unaudited-in-delta-regenerate", exemptions);
@@ -175,7 +315,7 @@ fn builtin_simple_exemptions_in_full_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-unaudited-in-full-regenerate", exemptions);
@@ -206,7 +346,7 @@ fn builtin_simple_deps_exemptions_adds_uneeded_criteria_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -242,7 +382,7 @@ fn builtin_dev_detection_exemptions_adds_uneeded_criteria_indirect_regenerate()
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -274,7 +414,7 @@ fn builtin_simple_exemptions_extra_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-unaudited-extra-regenerate", exemptions);
@@ -303,7 +443,7 @@ fn builtin_simple_exemptions_in_direct_full_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -364,7 +504,7 @@ fn builtin_simple_exemptions_nested_weaker_req_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -426,7 +566,7 @@ fn builtin_simple_exemptions_nested_stronger_req_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -455,7 +595,7 @@ fn builtin_simple_audit_as_default_root_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!(
@@ -488,8 +628,88 @@ fn builtin_simple_audit_as_weaker_root_regenerate() {
 
     let mut store = Store::mock(config, audits, imports);
     let cfg = mock_cfg(&metadata);
-    crate::minimize_exemptions(&cfg, &mut store, None).unwrap();
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
 
     let exemptions = get_exemptions(&store);
     insta::assert_snapshot!("builtin-simple-audit-as-weaker-root-regenerate", exemptions);
 }
+
+#[test]
+fn builtin_simple_init_exemption_note() {
+    // (Pass) `init --exemption-note` fills in a note for each new exemption.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_inited(&metadata);
+    crate::apply_exemption_note_template(
+        &mut config,
+        "{package}@{version}: exempted {date}, see TICKET-123",
+        "2022-07-01",
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let exemptions = get_exemptions(&store);
+    insta::assert_snapshot!("builtin-simple-init-exemption-note", exemptions);
+}
+
+#[test]
+fn test_utc_date() {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    let day = |days: u64| crate::utc_date(UNIX_EPOCH + Duration::from_secs(days * 86400 + 1));
+    assert_eq!(day(0), "1970-01-01");
+    assert_eq!(day(59), "1970-03-01");
+    assert_eq!(day(11016), "2000-02-29");
+    assert_eq!(day(19174), "2022-07-01");
+    assert_eq!(day(19357), "2022-12-31");
+}
+
+#[test]
+fn builtin_simple_init_with_import() {
+    // (Pass) `init --import` fetches the imports and then minimizes the initial
+    // exemptions, so packages covered by the import shouldn't get exemptions.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, mut imports) = builtin_files_inited(&metadata);
+
+    config.imports.insert(
+        "peer".to_owned(),
+        crate::format::RemoteImport {
+            url: "https://peer.example.com/audits.toml".to_owned(),
+            criteria_map: crate::builtin_criteria_map(),
+            depth: crate::format::DEFAULT_IMPORT_DEPTH,
+            transitive_sources: vec![],
+            ..Default::default()
+        },
+    );
+    imports.audits.insert(
+        "peer".to_owned(),
+        AuditsFile {
+            criteria: SortedMap::new(),
+            audits: [
+                (
+                    "third-party2".to_owned(),
+                    vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+                ),
+                (
+                    "transitive-third-party1".to_owned(),
+                    vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+                ),
+            ]
+            .into_iter()
+            .collect(),
+        },
+    );
+
+    let mut store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg(&metadata);
+    crate::minimize_exemptions(&cfg, &mut store, None, false).unwrap();
+
+    let exemptions = get_exemptions(&store);
+    insta::assert_snapshot!("builtin-simple-init-with-import", exemptions);
+}
@@ -1,6 +1,4 @@
-const EMPTY_CONFIG: &str = "\n";
-const EMPTY_AUDITS: &str = "[audits]\n";
-const EMPTY_IMPORTS: &str = "[audits]\n";
+use super::{EMPTY_AUDITS, EMPTY_CONFIG, EMPTY_IMPORTS};
 
 fn get_valid_store(config: &str, audits: &str, imports: &str) -> String {
     let res = crate::Store::mock_acquire(config, audits, imports);
@@ -22,6 +20,21 @@ fn test_all_min() {
     insta::assert_snapshot!(acquire_errors);
 }
 
+#[test]
+fn test_toml_syntax_error() {
+    let audits = r##"
+[criteria.good]
+description = "great"
+
+[[audits.serde]]
+version "1.0.0"
+criteria = "good"
+"##;
+
+    let acquire_errors = get_valid_store(EMPTY_CONFIG, audits, EMPTY_IMPORTS);
+    insta::assert_snapshot!(acquire_errors);
+}
+
 #[test]
 fn test_simple_bad_audit() {
     let audits = r##"
@@ -70,6 +83,32 @@ criteria = "no-good-bad-bad"
     insta::assert_snapshot!(acquire_errors);
 }
 
+#[test]
+fn test_implies_cycles() {
+    let audits = r##"
+[criteria.a]
+description = "a"
+implies = ["safe-to-deploy", "b"]
+
+[criteria.b]
+description = "b"
+implies = "c"
+
+[criteria.c]
+description = "c"
+implies = ["a", "undefined"]
+
+[criteria.narcissist]
+description = "implies itself"
+implies = "narcissist"
+
+[audits]
+"##;
+
+    let acquire_errors = get_valid_store(EMPTY_CONFIG, audits, EMPTY_IMPORTS);
+    insta::assert_snapshot!(acquire_errors);
+}
+
 #[test]
 fn test_many_bad_config() {
     let config = r##"
@@ -108,3 +147,635 @@ implies = ["safe-to-deploy"]
     let acquire_errors = get_valid_store(config, audits, EMPTY_IMPORTS);
     insta::assert_snapshot!(acquire_errors);
 }
+
+#[test]
+fn test_bad_default_criteria_and_criteria_map() {
+    let config = r##"
+default-criteria = "reviewd"
+
+[imports.peer]
+url = "https://peer.example.com/audits.toml"
+criteria-map = [
+    { theirs = "peer-reviewed", ours = "reviewd" },
+    { theirs = "peer-trusted", ours = "reviewed" },
+]
+"##;
+
+    let audits = r##"
+[criteria.reviewed]
+description = "reviewed"
+
+[audits]
+"##;
+
+    let acquire_errors = get_valid_store(config, audits, EMPTY_IMPORTS);
+    insta::assert_snapshot!(acquire_errors);
+}
+
+#[test]
+fn test_imports_lock_outdated() {
+    let config = r##"
+[imports.same]
+url = "https://same.example.com/audits.toml"
+criteria-map = []
+
+[imports.added]
+url = "https://added.example.com/audits.toml"
+criteria-map = []
+
+[imports.moved-to-git]
+git = "https://git.example.com/audits.git"
+criteria-map = []
+
+[imports.moved]
+url = "https://new.example.com/audits.toml"
+criteria-map = []
+"##;
+
+    let imports = r##"
+[audits.same.audits]
+
+[audits.moved-to-git.audits]
+
+[audits.moved.audits]
+
+[audits.removed.audits]
+
+[sources]
+same = "https://same.example.com/audits.toml"
+moved = "https://old.example.com/audits.toml"
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, imports).unwrap();
+    let error = store.check_imports_lock().unwrap_err();
+    insta::assert_snapshot!(format!("{:?}", miette::Report::new(error)));
+
+    // An up to date lock is fine
+    let config = r##"
+[imports.same]
+url = "https://same.example.com/audits.toml"
+criteria-map = []
+"##;
+
+    let imports = r##"
+[audits.same.audits]
+
+[sources]
+same = "https://same.example.com/audits.toml"
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, imports).unwrap();
+    store.check_imports_lock().unwrap();
+}
+
+#[test]
+fn test_allowed_import_sources() {
+    let config = r##"
+allowed-import-sources = ["gitlab.example.com", "https://raw.githubusercontent.com/example/", "https://example.org/foo"]
+
+[imports.internal]
+url = "https://gitlab.example.com/audits.toml"
+criteria-map = []
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    let allowed = &store.config.allowed_import_sources;
+    assert_eq!(allowed.len(), 3);
+
+    let check = |url: &str| {
+        crate::storage::import_source_allowed(allowed, &reqwest::Url::parse(url).unwrap())
+    };
+    assert!(check("https://gitlab.example.com/audits.toml"));
+    assert!(check("http://GitLab.Example.com/other/audits.toml"));
+    assert!(check(
+        "https://raw.githubusercontent.com/example/repo/main/supply-chain/audits.toml"
+    ));
+    assert!(!check("https://gitlab.example.com.evil.org/audits.toml"));
+    assert!(!check("https://evil.gitlab.example.com/audits.toml"));
+    assert!(!check(
+        "https://raw.githubusercontent.com/attacker/audits.toml"
+    ));
+    assert!(!check(
+        "http://raw.githubusercontent.com/example/audits.toml"
+    ));
+    assert!(check("https://example.org/foo/audits.toml"));
+    assert!(!check("https://example.org/foobar/audits.toml"));
+
+    // No allowlist means everything is allowed
+    assert!(crate::storage::import_source_allowed(
+        &[],
+        &reqwest::Url::parse("https://anywhere.org/audits.toml").unwrap()
+    ));
+}
+
+#[test]
+fn test_bad_import_urls() {
+    let config = r##"
+[imports.good]
+url = "https://example.com/supply-chain/audits.toml"
+criteria-map = []
+
+[imports.local]
+url = "file:///home/user/supply-chain/audits.toml"
+criteria-map = []
+
+[imports.bad-scheme]
+url = "htps://example.com/supply-chain/audits.toml"
+criteria-map = []
+
+[imports.whitespace]
+url = "https://example.com/supply-chain/audits.toml "
+criteria-map = []
+
+[imports.relative]
+url = "example.com/supply-chain/audits.toml"
+criteria-map = []
+
+[imports.ssh]
+url = "ssh://example.com/supply-chain/audits.toml"
+criteria-map = []
+
+[imports.ssh-git]
+git = "ssh://example.com/supply-chain.git"
+criteria-map = []
+"##;
+
+    let acquire_errors = get_valid_store(config, EMPTY_AUDITS, EMPTY_IMPORTS);
+    insta::assert_snapshot!(acquire_errors);
+}
+
+#[test]
+fn test_deprecated_unaudited() {
+    let config = r##"
+[[unaudited.clap]]
+version = "1.0.0"
+criteria = "safe-to-deploy"
+
+[[unaudited.clap_derive]]
+version = "1.0.0"
+criteria = "safe-to-deploy"
+
+[policy.unaudited]
+criteria = "safe-to-deploy"
+"##;
+
+    // The old name is still accepted...
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    assert_eq!(store.config.exemptions.len(), 2);
+
+    // ...but we warn about it (once)
+    let warnings = crate::storage::find_deprecated_fields(
+        &store.config_src,
+        config,
+        &[("unaudited", "exemptions")],
+    );
+    let output = warnings
+        .into_iter()
+        .map(|w| format!("{:?}", miette::Report::new(w)))
+        .collect::<Vec<_>>()
+        .join("\n");
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn test_deprecated_unaudited_key() {
+    let config = r##"
+unaudited = { clap = [{ version = "1.0.0", criteria = "safe-to-deploy" }] }
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    assert_eq!(store.config.exemptions.len(), 1);
+
+    let warnings = crate::storage::find_deprecated_fields(
+        &store.config_src,
+        config,
+        &[("unaudited", "exemptions")],
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].span.offset(), 1);
+}
+
+#[test]
+fn test_deprecated_unaudited_not_a_field() {
+    // Only an actual use of the top-level field counts, not something that
+    // looks like one inside a string, or a nested key of the same name.
+    let config = r##"
+[policy.unaudited]
+notes = """
+[unaudited.clap]
+"""
+
+[[unaudited.clap]]
+version = "1.0.0"
+criteria = "safe-to-deploy"
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    let warnings = crate::storage::find_deprecated_fields(
+        &store.config_src,
+        config,
+        &[("unaudited", "exemptions")],
+    );
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(
+        warnings[0].span.offset(),
+        config.rfind("unaudited.clap").unwrap()
+    );
+
+    let config = r##"
+[policy.unaudited]
+notes = """
+[unaudited.clap]
+"""
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    let warnings = crate::storage::find_deprecated_fields(
+        &store.config_src,
+        config,
+        &[("unaudited", "exemptions")],
+    );
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn test_source_hash() {
+    let audits = r##"
+[[audits.serde]]
+version = "1.0.0"
+criteria = "safe-to-deploy"
+source-hash = "sha256:0123456789abcdef"
+"##;
+
+    let store = crate::Store::mock_acquire(EMPTY_CONFIG, audits, EMPTY_IMPORTS).unwrap();
+    let entry = &store.audits.audits["serde"][0];
+    assert_eq!(
+        entry.source_hash.as_deref(),
+        Some("sha256:0123456789abcdef")
+    );
+
+    let formatted = crate::serialization::to_formatted_toml(&store.audits)
+        .unwrap()
+        .to_string();
+    assert!(formatted.contains("source-hash = \"sha256:0123456789abcdef\""));
+}
+
+#[test]
+fn test_source_hash_on_delta() {
+    let audits = r##"
+[[audits.serde]]
+delta = "1.0.0 -> 1.1.0"
+criteria = "safe-to-deploy"
+source-hash = "sha256:0123456789abcdef"
+"##;
+
+    let acquire_errors = get_valid_store(EMPTY_CONFIG, audits, EMPTY_IMPORTS);
+    assert!(acquire_errors.contains("'source-hash' is only supported for full audits"));
+}
+
+#[test]
+fn test_hash_package_source() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    std::fs::create_dir(root.join("src")).unwrap();
+    std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+    std::fs::write(root.join("src/lib.rs"), "pub fn demo() {}\n").unwrap();
+
+    let hash = crate::storage::hash_package_source(root).unwrap();
+    assert!(hash.starts_with("sha256:"));
+
+    // cargo's unpack marker isn't part of the source
+    std::fs::write(root.join(".cargo-ok"), "ok").unwrap();
+    assert_eq!(crate::storage::hash_package_source(root).unwrap(), hash);
+
+    // but any change to the source is
+    std::fs::write(root.join("src/lib.rs"), "pub fn demo() { }\n").unwrap();
+    assert_ne!(crate::storage::hash_package_source(root).unwrap(), hash);
+}
+
+#[test]
+fn test_package_notes() {
+    let config = r##"
+[notes]
+winapi = "upstream is unmaintained"
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    assert_eq!(store.config.notes["winapi"], "upstream is unmaintained");
+
+    let formatted = crate::serialization::to_formatted_toml(&store.config)
+        .unwrap()
+        .to_string();
+    assert!(formatted.contains("[notes]\nwinapi = \"upstream is unmaintained\""));
+}
+
+#[test]
+fn test_unpack_limits() {
+    use crate::errors::UnpackError;
+    use crate::storage::{unpack_package, UnpackLimits};
+
+    let dir = tempfile::tempdir().unwrap();
+    let tarball_path = dir.path().join("demo-1.0.0.crate");
+    {
+        let file = std::fs::File::create(&tarball_path).unwrap();
+        let gz = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        for (name, contents) in [
+            ("demo-1.0.0/Cargo.toml", &b"[package]\n"[..]),
+            ("demo-1.0.0/src/lib.rs", &[b'x'; 1000][..]),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, contents).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    let unpack = |max_size, max_files| {
+        let tarball = std::fs::File::open(&tarball_path).unwrap();
+        let unpack_dir = dir.path().join("demo-1.0.0");
+        let res = unpack_package(
+            &tarball,
+            &unpack_dir,
+            UnpackLimits {
+                max_size,
+                max_files,
+            },
+        );
+        (res, unpack_dir.exists())
+    };
+
+    let (res, exists) = unpack(100, 10);
+    assert!(matches!(res, Err(UnpackError::TooLarge { limit: 100 })));
+    assert!(!exists, "partial unpack should be cleaned up");
+
+    let (res, exists) = unpack(10_000, 1);
+    assert!(matches!(res, Err(UnpackError::TooManyFiles { limit: 1 })));
+    assert!(!exists, "partial unpack should be cleaned up");
+
+    let (res, exists) = unpack(10_000, 10);
+    res.unwrap();
+    assert!(exists);
+}
+
+#[test]
+fn test_merge_transitive_audits() {
+    use crate::format::AuditsFile;
+
+    // Their config.toml imports "nested" with this mapping
+    let config = r##"
+[imports.nested]
+url = "https://nested.example.com/audits.toml"
+criteria-map = [
+    { theirs = "reviewed", ours = "safe-to-deploy" },
+]
+"##;
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    let import = &store.config.imports["nested"];
+    assert_eq!(import.depth, 0);
+
+    let mut theirs: AuditsFile = toml::from_str(
+        r##"
+[[audits.third-party1]]
+criteria = "safe-to-deploy"
+version = "1.0.0"
+"##,
+    )
+    .unwrap();
+    let nested: AuditsFile = toml::from_str(
+        r##"
+[criteria.reviewed]
+description = "reviewed"
+
+[criteria.strongly-reviewed]
+description = "strongly reviewed"
+implies = "reviewed"
+
+[[audits.third-party1]]
+who = "Alice"
+criteria = "strongly-reviewed"
+delta = "1.0.0 -> 1.1.0"
+
+[[audits.third-party2]]
+criteria = "safe-to-run"
+version = "2.0.0"
+
+[[audits.third-party2]]
+criteria = "reviewed"
+version = "3.0.0"
+dependency-criteria = { third-party1 = "reviewed" }
+"##,
+    )
+    .unwrap();
+
+    crate::storage::merge_transitive_audits(&mut theirs, import, &nested);
+
+    // The implied criteria is mapped, the unmapped criteria and the entry with
+    // dependency-criteria are dropped.
+    assert_eq!(theirs.audits.len(), 1);
+    let entries = &theirs.audits["third-party1"];
+    assert_eq!(entries.len(), 2);
+    let merged = entries
+        .iter()
+        .find(|entry| entry.who.as_deref() == Some("Alice"))
+        .unwrap();
+    assert_eq!(merged.criteria.len(), 1);
+    assert_eq!(&*merged.criteria[0], "safe-to-deploy");
+}
+
+#[test]
+fn test_transitive_imports_lock() {
+    let imports = r##"
+[audits.peer]
+criteria = {}
+audits = {}
+
+[[transitive-imports.peer]]
+path = ["their-peer"]
+url = "https://their-peer.example.com/audits.toml"
+"##;
+    let store = crate::Store::mock_acquire(EMPTY_CONFIG, EMPTY_AUDITS, imports).unwrap();
+    let transitive = &store.imports.transitive_imports["peer"];
+    assert_eq!(transitive.len(), 1);
+    assert_eq!(transitive[0].path, vec!["their-peer".to_owned()]);
+
+    let formatted = crate::serialization::to_formatted_toml(&store.imports)
+        .unwrap()
+        .to_string();
+    assert!(formatted.contains("[[transitive-imports.peer]]"));
+}
+
+#[test]
+fn test_imports_lock_stable_order() {
+    // Audits in imports.lock should be written in a canonical order, so that
+    // reordering by the foreign source doesn't show up as a change.
+    let imports = r##"
+[[audits.peer.audits.serde]]
+criteria = "safe-to-deploy"
+delta = "1.0.0 -> 1.1.0"
+
+[[audits.peer.audits.serde]]
+criteria = "safe-to-run"
+version = "2.0.0"
+
+[[audits.peer.audits.serde]]
+criteria = "safe-to-deploy"
+version = "1.0.0"
+
+[[audits.peer.audits.anyhow]]
+criteria = "safe-to-deploy"
+version = "1.0.0"
+"##;
+    let store = crate::Store::mock_acquire(EMPTY_CONFIG, EMPTY_AUDITS, imports).unwrap();
+    let (_, _, formatted) = store.mock_commit();
+
+    // Writing back out what was written should be a no-op
+    let reloaded = crate::Store::mock_acquire(EMPTY_CONFIG, EMPTY_AUDITS, &formatted).unwrap();
+    let (_, _, reformatted) = reloaded.mock_commit();
+    assert_eq!(formatted, reformatted);
+
+    insta::assert_snapshot!(formatted);
+}
+
+#[test]
+fn test_is_yanked_or_missing() {
+    use crate::storage::is_yanked_or_missing;
+
+    let index_file = [
+        r#"{"name":"demo","vers":"1.0.0","deps":[],"cksum":"d867001db0e2b6e0496f9fac96930e2d42233ecd3ca0413e0753d4c7695d289c","features":{},"yanked":true}"#,
+        r#"{"name":"demo","vers":"1.1.0","deps":[],"cksum":"d867001db0e2b6e0496f9fac96930e2d42233ecd3ca0413e0753d4c7695d289c","features":{},"yanked":false}"#,
+    ]
+    .join("\n");
+    let krate = crates_index::Crate::from_slice(index_file.as_bytes()).unwrap();
+
+    let ver = |v: &str| cargo_metadata::Version::parse(v).unwrap();
+    assert!(is_yanked_or_missing(&krate, &ver("1.0.0")));
+    assert!(!is_yanked_or_missing(&krate, &ver("1.1.0")));
+    assert!(is_yanked_or_missing(&krate, &ver("2.0.0")));
+}
+
+#[test]
+fn test_imports_lock_differences() {
+    use crate::storage::imports_lock_differences;
+
+    let imports = |extra: &str| {
+        let imports = format!(
+            r##"
+[audits.peer.criteria.peer-reviewed]
+description = "reviewed by a peer"
+
+[[audits.peer.audits.third-party1]]
+criteria = "peer-reviewed"
+version = "1.0.0"
+{extra}
+"##
+        );
+        crate::Store::mock_acquire(EMPTY_CONFIG, EMPTY_AUDITS, &imports)
+            .unwrap()
+            .imports
+    };
+
+    let locked = imports("");
+    assert!(imports_lock_differences(&locked, &imports("")).is_empty());
+
+    let fetched = imports(
+        r##"
+[[audits.peer.audits.third-party1]]
+criteria = "peer-reviewed"
+version = "2.0.0"
+
+[audits.other]
+criteria = {}
+audits = {}
+"##,
+    );
+    assert_eq!(
+        imports_lock_differences(&locked, &fetched),
+        [
+            "other: missing from imports.lock",
+            "peer: audits of third-party1 differ (1 locked, 2 fetched)",
+        ]
+    );
+    assert_eq!(
+        imports_lock_differences(&fetched, &locked),
+        [
+            "other: no longer imported",
+            "peer: audits of third-party1 differ (2 locked, 1 fetched)",
+        ]
+    );
+}
+
+#[test]
+fn test_store_lock_timeout() {
+    use crate::errors::{FlockError, StoreAcquireError};
+    use std::time::Duration;
+
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join(crate::storage::CONFIG_TOML), EMPTY_CONFIG).unwrap();
+    std::fs::write(dir.path().join("audits.toml"), EMPTY_AUDITS).unwrap();
+    std::fs::write(dir.path().join("imports.lock"), EMPTY_IMPORTS).unwrap();
+    let root = crate::flock::Filesystem::new(dir.path().to_owned());
+
+    let held = crate::Store::acquire_path(&root, Duration::from_secs(1)).unwrap();
+    let res = crate::Store::acquire_path(&root, Duration::from_millis(200));
+    assert!(
+        matches!(
+            res,
+            Err(StoreAcquireError::CouldntLock(FlockError::Timeout { .. }))
+        ),
+        "expected a lock timeout, got {:?}",
+        res.map(|_| ())
+    );
+
+    // Once the first store is released, the lock can be taken again.
+    drop(held);
+    crate::Store::acquire_path(&root, Duration::from_millis(200)).unwrap();
+}
+
+#[test]
+fn test_network_proxy() {
+    let config = r##"
+[network]
+proxy = "http://proxy.example.com:8080"
+"##;
+
+    let store = crate::Store::mock_acquire(config, EMPTY_AUDITS, EMPTY_IMPORTS).unwrap();
+    assert_eq!(
+        store.config.network.proxy.as_deref(),
+        Some("http://proxy.example.com:8080")
+    );
+
+    let config = r##"
+[network]
+proxy = "http://[not a proxy"
+"##;
+
+    let acquire_errors = get_valid_store(config, EMPTY_AUDITS, EMPTY_IMPORTS);
+    insta::assert_snapshot!(acquire_errors);
+}
+
+#[test]
+fn test_bad_dev_dependency_criteria() {
+    let config = r##"
+[policy.clap]
+dev-dependency-criteria = { clap_derive = "safe-to-run", trybuild = "zilch" }
+"##;
+
+    let acquire_errors = get_valid_store(config, EMPTY_AUDITS, EMPTY_IMPORTS);
+    assert!(acquire_errors.contains("'zilch' is not a valid criteria name"));
+    assert!(!acquire_errors.contains("'safe-to-run' is not a valid criteria name"));
+}
+
+#[test]
+fn test_bad_build_dependency_criteria() {
+    let config = r##"
+[policy.clap]
+build-dependency-criteria = { cc = "safe-to-run", autocfg = "zilch" }
+"##;
+
+    let acquire_errors = get_valid_store(config, EMPTY_AUDITS, EMPTY_IMPORTS);
+    assert!(acquire_errors.contains("'zilch' is not a valid criteria name"));
+    assert!(!acquire_errors.contains("'safe-to-run' is not a valid criteria name"));
+}
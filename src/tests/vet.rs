@@ -1091,6 +1091,53 @@ fn mock_simple_policy_first_weaker() {
     insta::assert_snapshot!("simple-policy-first-weaker", output);
 }
 
+#[test]
+fn mock_simple_policy_first_pattern() {
+    // (Fail) First-party policy is too strong, applied by a pattern
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_full_audited(&metadata);
+
+    config
+        .policy
+        .insert("first*".to_string(), self_policy(["strong-reviewed"]));
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("simple-policy-first-pattern", output);
+}
+
+#[test]
+fn mock_simple_policy_first_pattern_specificity() {
+    // (Pass) Exact policies beat patterns, and longer patterns beat shorter ones
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+
+    for policies in [
+        [
+            ("first*", "strong-reviewed"),
+            ("first-party", "weak-reviewed"),
+        ],
+        [("f*", "strong-reviewed"), ("first-*", "weak-reviewed")],
+    ] {
+        let (mut config, audits, imports) = files_full_audited(&metadata);
+        for (pattern, criteria) in policies {
+            config
+                .policy
+                .insert(pattern.to_string(), self_policy([criteria]));
+        }
+
+        let store = Store::mock(config, audits, imports);
+        let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+        assert!(!report.has_errors(), "{:?}", policies);
+    }
+}
+
 #[test]
 fn mock_simple_policy_root_dep_weaker() {
     // (Pass) root->first-party policy weaker than necessary
@@ -1388,6 +1435,72 @@ fn builtin_simple_deps_minimal_audited() {
     insta::assert_snapshot!("builtin-simple-deps-minimal-audited", output);
 }
 
+#[test]
+fn builtin_simple_deps_proc_macro_criteria() {
+    // (Fail) Same as minimal audited, but proc-macros must be safe-to-deploy
+    // even when they're only used by dev-deps.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_minimal_audited(&metadata);
+    config.proc_macro_criteria = vec![SAFE_TO_DEPLOY.to_string().into()];
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-deps-proc-macro-criteria", output);
+}
+
+#[test]
+fn builtin_simple_deps_proc_macro_criteria_json() {
+    // (Fail) Same as above, but with the machine-readable output
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_minimal_audited(&metadata);
+    config.proc_macro_criteria = vec![SAFE_TO_DEPLOY.to_string().into()];
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report_json(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-deps-proc-macro-criteria-json", output);
+}
+
+#[test]
+fn builtin_simple_deps_build_dependency_criteria() {
+    // (Fail) `build` is only audited as safe-to-run, which is all `root` needs
+    // of it as a build-dependency. `build-dependency-criteria` takes priority
+    // over `dependency-criteria`, but only for build-dependency edges.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple_deps();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+    for package in ["build", "normal"] {
+        audits.audits.insert(
+            package.to_string(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+        );
+    }
+    let mut policy = dep_policy([("build", [SAFE_TO_DEPLOY]), ("normal", [SAFE_TO_DEPLOY])]);
+    policy.build_dependency_criteria =
+        dep_policy([("build", [SAFE_TO_RUN]), ("normal", [SAFE_TO_RUN])]).dependency_criteria;
+    config.policy.insert("root".to_string(), policy);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-deps-build-dependency-criteria", output);
+}
+
 #[test]
 fn builtin_no_deps() {
     // (Pass) No actual deps
@@ -1410,6 +1523,47 @@ fn builtin_no_deps() {
     insta::assert_snapshot!("builtin-no-deps", output);
 }
 
+#[test]
+fn builtin_native_library_packages() {
+    // Third-party packages which link native libraries are pointed out, and so
+    // is any native library which more than one package claims to link.
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::new(vec![
+        MockPackage {
+            name: "root-package",
+            is_workspace: true,
+            is_first_party: true,
+            deps: vec![dep("first-party-sys"), dep("libz-sys"), dep("zlib-sys")],
+            ..Default::default()
+        },
+        MockPackage {
+            name: "first-party-sys",
+            is_first_party: true,
+            links: Some("ourlib"),
+            ..Default::default()
+        },
+        MockPackage {
+            name: "libz-sys",
+            links: Some("z"),
+            ..Default::default()
+        },
+        MockPackage {
+            name: "zlib-sys",
+            links: Some("z"),
+            ..Default::default()
+        },
+    ]);
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_full_audited(&metadata);
+    let store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg(&metadata);
+
+    let output = BasicTestOutput::new();
+    crate::print_native_library_packages(&output.clone().as_dyn(), &cfg, &store);
+    insta::assert_snapshot!("builtin-native-library-packages", output.to_string());
+}
+
 #[test]
 fn builtin_only_first_deps() {
     // (Pass) No actual deps
@@ -1548,6 +1702,105 @@ fn builtin_dev_detection() {
     insta::assert_snapshot!("builtin-dev-detection", output);
 }
 
+#[test]
+fn builtin_dev_detection_dev_dependency_criteria() {
+    // (Fail) `both` is a normal and a dev dependency of `root`. As a normal
+    // dependency it only needs to be safe-to-run, but as a dev dependency
+    // (where it's used by tests which we run on CI with secrets) it must be
+    // safe-to-deploy, which it isn't.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+    audits.audits.insert(
+        "both".to_string(),
+        vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+    );
+    config
+        .policy
+        .insert("root".to_string(), dep_policy([("both", [SAFE_TO_RUN])]));
+
+    // Without a role-specific policy, the dev role inherits `dependency-criteria`.
+    let store = Store::mock(config.clone(), audits.clone(), imports.clone());
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(!report.has_errors());
+
+    config
+        .policy
+        .get_mut("root")
+        .unwrap()
+        .dev_dependency_criteria = dep_policy([("both", [SAFE_TO_DEPLOY])]).dependency_criteria;
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-dev-detection-dev-dependency-criteria", output);
+}
+
+#[test]
+fn builtin_dev_detection_dev_safe_to_run() {
+    // (Fail) root's tests demand safe-to-deploy of its dev-deps, but with
+    // dev-safe-to-run everything that's only used by dev-deps just needs to be
+    // safe-to-run, unless it has an explicit policy of its own.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_no_exemptions(&metadata);
+    for name in ["normal", "both"] {
+        audits.audits.insert(
+            name.to_string(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+        );
+    }
+    for name in [
+        "simple-dev",
+        "simple-dev-indirect",
+        "dev-cycle-direct",
+        "dev-cycle-indirect",
+    ] {
+        audits.audits.insert(
+            name.to_string(),
+            vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+        );
+    }
+    config.policy.insert(
+        "root".to_string(),
+        PolicyEntry {
+            dev_criteria: Some(vec![SAFE_TO_DEPLOY.to_string().into()]),
+            ..default_policy()
+        },
+    );
+
+    let store = Store::mock(config.clone(), audits.clone(), imports.clone());
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(report.has_errors());
+
+    config.dev_safe_to_run = true;
+    let store = Store::mock(config.clone(), audits.clone(), imports.clone());
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(!report.has_errors());
+    if let crate::resolver::Conclusion::Success(success) = &report.conclusion {
+        assert!(success.vetted_with_exemptions.is_empty());
+        assert!(success.vetted_partially.is_empty());
+    } else {
+        unreachable!("expected success");
+    }
+
+    config
+        .policy
+        .insert("simple-dev".to_string(), self_policy([SAFE_TO_DEPLOY]));
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-dev-detection-dev-safe-to-run", output);
+}
+
 #[test]
 fn builtin_dev_detection_empty() {
     // (Fail) same as above but without any audits to confirm expectations
@@ -1582,10 +1835,69 @@ fn builtin_dev_detection_empty_deeper() {
     insta::assert_snapshot!("builtin-dev-detection-empty-deeper", output);
 }
 
+#[test]
+fn builtin_dev_detection_init() {
+    // (Pass) A fresh `vet init` only exempts dev-only packages as safe-to-run,
+    // while anything on a normal dependency path (like `both`) stays
+    // safe-to-deploy.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_inited(&metadata);
+
+    let criteria = config
+        .exemptions
+        .iter()
+        .map(|(package, entries)| {
+            let criteria = entries
+                .iter()
+                .flat_map(|entry| entry.criteria.iter().map(|c| c.to_string()))
+                .collect::<Vec<_>>();
+            (package.as_str(), criteria)
+        })
+        .collect::<SortedMap<_, _>>();
+    for package in ["normal", "both"] {
+        assert_eq!(criteria[package], [SAFE_TO_DEPLOY], "{package}");
+    }
+    for package in [
+        "simple-dev",
+        "simple-dev-indirect",
+        "dev-cycle-direct",
+        "dev-cycle-indirect",
+    ] {
+        assert_eq!(criteria[package], [SAFE_TO_RUN], "{package}");
+    }
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+    assert!(!report.has_errors());
+}
+
+#[test]
+fn builtin_dev_detection_exclude_dev() {
+    // (Fail) same as above but with --exclude-dev, so only the packages used
+    // by normal builds (including `both`) are still required.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let filters = vec![crate::cli::GraphFilter::exclude_dev_only()];
+    let report = crate::resolver::resolve(&metadata, Some(&filters), &store, ResolveDepth::Deep);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-dev-detection-exclude-dev", output);
+}
+
 #[test]
 fn builtin_simple_exemptions_extra() {
     // (Pass) there's an extra unused exemptions entry, but the other is needed
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple();
@@ -1605,6 +1917,10 @@ fn builtin_simple_exemptions_extra() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(
+        get_unused_exemptions(&report, &store),
+        ["third-party1:5.0.0"]
+    );
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-extra", output);
@@ -1613,7 +1929,7 @@ fn builtin_simple_exemptions_extra() {
 #[test]
 fn builtin_simple_exemptions_not_a_real_dep() {
     // (Pass) there's an exemptions entry for a package that isn't in our tree at all.
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple();
@@ -1628,15 +1944,100 @@ fn builtin_simple_exemptions_not_a_real_dep() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(get_unused_exemptions(&report, &store), ["fake-dep:10.0.0"]);
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-not-a-real-dep", output);
 }
 
+#[test]
+fn builtin_simple_exemptions_not_a_real_dep_no_suggest() {
+    // (Pass) same as above, but the entry has `suggest = false`, which only
+    // affects `regenerate exemptions`, so it's still reported as unused.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_full_audited(&metadata);
+
+    config.exemptions.insert(
+        "fake-dep".to_string(),
+        vec![ExemptedDependency {
+            suggest: false,
+            ..exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY)
+        }],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(get_unused_exemptions(&report, &store), ["fake-dep:10.0.0"]);
+}
+
+#[test]
+fn check_unused_exemptions() {
+    // `cargo vet check` warns about unused exemptions, or fails with
+    // --deny-unused-exemptions.
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+    let metadata = mock.metadata();
+    let store_dir = tempfile::tempdir().unwrap();
+
+    let check = |args: &[&str]| {
+        let mut cfg = mock_cfg_args(
+            &metadata,
+            ["cargo", "vet", "check", "--locked", "--store-path"]
+                .iter()
+                .map(OsString::from)
+                .chain([store_dir.path().as_os_str().to_owned()])
+                .chain(args.iter().map(OsString::from)),
+        );
+        cfg.metacfg = crate::compute_metacfg(&metadata, cfg.cli.store_path.as_deref()).unwrap();
+        let sub_args = if let Some(crate::cli::Commands::Check(sub_args)) = &cfg.cli.command {
+            sub_args
+        } else {
+            unreachable!("didn't parse as a check command");
+        };
+        let output = BasicTestOutput::new();
+        let result = crate::cmd_check(&output.clone().as_dyn(), &cfg, sub_args);
+        (result, output.to_string())
+    };
+
+    let (mut config, audits, imports) = builtin_files_full_audited(&metadata);
+    config.exemptions.insert(
+        "fake-dep".to_string(),
+        vec![exemptions(ver(DEFAULT_VER), SAFE_TO_DEPLOY)],
+    );
+    let mut cfg = mock_cfg(&metadata);
+    cfg.metacfg = crate::compute_metacfg(&metadata, Some(store_dir.path())).unwrap();
+    let mut store = Store::create(&cfg).unwrap();
+    store.config = config;
+    store.audits = audits;
+    store.imports = imports;
+    store.commit().unwrap();
+
+    let (result, output) = check(&[]);
+    result.unwrap();
+    assert!(
+        output.ends_with(
+            "warning: these exemptions aren't needed, `cargo vet prune` can remove them:\n  \
+             fake-dep:10.0.0 (safe-to-deploy)\n"
+        ),
+        "{output}"
+    );
+
+    let (result, output) = check(&["--deny-unused-exemptions"]);
+    assert_eq!(
+        result.unwrap_err().to_string(),
+        "some exemptions aren't needed to pass `cargo vet check`\n  fake-dep:10.0.0"
+    );
+    assert!(!output.contains("warning"), "{output}");
+}
+
 #[test]
 fn builtin_simple_deps_exemptions_overbroad() {
     // (Pass) the exemptions entry is needed but it's overbroad
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (The exemptions entry is still needed, so it isn't reported as unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple_deps();
@@ -1653,6 +2054,7 @@ fn builtin_simple_deps_exemptions_overbroad() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(get_unused_exemptions(&report, &store).is_empty());
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-overbroad", output);
@@ -1715,7 +2117,7 @@ fn builtin_complex_exemptions_partial_twins() {
 #[test]
 fn builtin_simple_exemptions_in_delta() {
     // (Pass) An audited entry overlaps a delta and isn't needed
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple();
@@ -1739,6 +2141,10 @@ fn builtin_simple_exemptions_in_delta() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(
+        get_unused_exemptions(&report, &store),
+        ["third-party1:5.0.0"]
+    );
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-delta", output);
@@ -1747,7 +2153,7 @@ fn builtin_simple_exemptions_in_delta() {
 #[test]
 fn builtin_simple_exemptions_in_full() {
     // (Pass) An audited entry overlaps a full audit and isn't needed
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple();
@@ -1771,6 +2177,10 @@ fn builtin_simple_exemptions_in_full() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(
+        get_unused_exemptions(&report, &store),
+        ["third-party1:3.0.0"]
+    );
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-full", output);
@@ -1779,7 +2189,7 @@ fn builtin_simple_exemptions_in_full() {
 #[test]
 fn builtin_simple_exemptions_in_direct_full() {
     // (Pass) An audited entry overlaps a full audit which is the cur version and isn't needed
-    // (This test used to warn when we tried to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple();
@@ -1799,6 +2209,10 @@ fn builtin_simple_exemptions_in_direct_full() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(
+        get_unused_exemptions(&report, &store),
+        ["third-party1:10.0.0"]
+    );
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-unaudited-in-direct-full", output);
@@ -1977,7 +2391,9 @@ fn builtin_simple_exemptions_nested_stronger_req() {
 #[test]
 fn builtin_simple_deps_exemptions_adds_uneeded_criteria() {
     // (Pass) An audited entry overlaps a full audit which is the cur version and isn't needed
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (This test could warn if we try to detect "useless exemptions" eagerly,
+    // but the first-party dev-dependency search still counts the exemption as
+    // needed, so it isn't reported as unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::simple_deps();
@@ -1999,6 +2415,7 @@ fn builtin_simple_deps_exemptions_adds_uneeded_criteria() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(get_unused_exemptions(&report, &store).is_empty());
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
@@ -2010,7 +2427,7 @@ fn builtin_simple_deps_exemptions_adds_uneeded_criteria() {
 #[test]
 fn builtin_dev_detection_exemptions_adds_uneeded_criteria_indirect() {
     // (Pass) An audited entry overlaps a full audit which is the cur version and isn't needed
-    // (This test could warn if we try to detect "useless exemptions" eagerly)
+    // (`cargo vet` warns that the unneeded exemptions entry is unused)
 
     let _enter = TEST_RUNTIME.enter();
     let mock = MockMetadata::dev_detection();
@@ -2033,6 +2450,10 @@ fn builtin_dev_detection_exemptions_adds_uneeded_criteria_indirect() {
 
     let store = Store::mock(config, audits, imports);
     let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert_eq!(
+        get_unused_exemptions(&report, &store),
+        ["simple-dev-indirect:5.0.0"]
+    );
 
     let output = get_report(&metadata, report);
     insta::assert_snapshot!(
@@ -2072,6 +2493,35 @@ fn builtin_dev_detection_cursed_full() {
     insta::assert_snapshot!("builtin-dev-detection-cursed-full", output);
 }
 
+#[test]
+fn builtin_dev_detection_cursed_no_decompose() {
+    // (Fail): the same as the full cursed one, but with criteria decomposition
+    // disabled, so the safe-to-deploy audit of dev requires dev-indirect to be
+    // safe-to-deploy even when we only need safe-to-run.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_full_audited(&metadata);
+    config.decompose_criteria = false;
+
+    audits.audits.insert(
+        "simple-dev-indirect".to_string(),
+        vec![
+            full_audit(ver(5), SAFE_TO_RUN),
+            delta_audit(ver(5), ver(DEFAULT_VER), SAFE_TO_RUN),
+            delta_audit(ver(5), ver(DEFAULT_VER), SAFE_TO_DEPLOY),
+        ],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-dev-detection-cursed-no-decompose", output);
+}
+
 #[test]
 fn builtin_dev_detection_cursed_minimal() {
     // (Pass): the same as the full cursed one, but without the cursed part.
@@ -2489,3 +2939,885 @@ fn builtin_simple_audit_as_weaker_root() {
     let output = get_report(&metadata, report);
     insta::assert_snapshot!("builtin-simple-audit-as-weaker-root", output);
 }
+
+#[test]
+fn mock_simple_suggest_owners() {
+    // (Fail) Suggestions should be grouped by the owners in config.toml, and
+    // filtered with an assignee.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+    let cfg = mock_cfg(&metadata);
+    let owners = SortedMap::from_iter([
+        ("third-party*".to_owned(), "team-a".to_owned()),
+        ("third-party2".to_owned(), "team-b".to_owned()),
+    ]);
+
+    let mut output = String::new();
+    for assignee in [None, Some("team-b"), Some("team-c")] {
+        let mut suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+        suggest.assign_owners(&report, &owners, assignee);
+
+        let out = BasicTestOutput::new();
+        suggest.print_human(&out.clone().as_dyn(), &report).unwrap();
+        output += &format!("ASSIGNEE: {:?}\n{}\n", assignee, out);
+    }
+
+    insta::assert_snapshot!("mock-simple-suggest-owners", output);
+}
+
+#[test]
+fn mock_simple_suggest_budget() {
+    // (Fail) With a review budget, only the batch of suggestions which fits
+    // within it should be recommended, favouring high-impact small diffs.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+    let cfg = mock_cfg(&metadata);
+
+    let mut output = String::new();
+    for budget in [0, 100, 200, u64::MAX] {
+        let mut suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+        suggest.apply_budget(&report, budget);
+
+        let out = BasicTestOutput::new();
+        suggest.print_human(&out.clone().as_dyn(), &report).unwrap();
+        output += &format!("BUDGET: {}\n{}\n", budget, out);
+    }
+
+    insta::assert_snapshot!("mock-simple-suggest-budget", output);
+}
+
+#[test]
+fn mock_simple_suggest_max_lines() {
+    // (Fail) With a maximum number of lines, suggestions with larger diffs
+    // should be left out, and the rest still listed smallest diff first.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+    let cfg = mock_cfg(&metadata);
+
+    let mut output = String::new();
+    for max_lines in [99, 100] {
+        let mut suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+        suggest.apply_max_lines(max_lines);
+
+        let out = BasicTestOutput::new();
+        suggest.print_human(&out.clone().as_dyn(), &report).unwrap();
+        output += &format!("MAX LINES: {}\n{}\n", max_lines, out);
+    }
+
+    insta::assert_snapshot!("mock-simple-suggest-max-lines", output);
+}
+
+#[test]
+fn builtin_dev_detection_suggest_criteria() {
+    // (Fail) Suggestions are grouped by criteria with subtotals, and can be
+    // narrowed down to the ones which help satisfy a single criteria.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = builtin_files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+    let cfg = mock_cfg(&metadata);
+
+    let mut output = String::new();
+    for criteria in [None, Some(SAFE_TO_DEPLOY), Some(SAFE_TO_RUN)] {
+        let mut suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+        if let Some(criteria) = criteria {
+            suggest.apply_criteria(&report, criteria).unwrap();
+        }
+
+        let out = BasicTestOutput::new();
+        suggest.print_human(&out.clone().as_dyn(), &report).unwrap();
+        output += &format!("CRITERIA: {:?}\n{}\n", criteria, out);
+    }
+
+    let mut suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+    assert!(suggest.apply_criteria(&report, "nonexistent").is_err());
+
+    insta::assert_snapshot!("builtin-dev-detection-suggest-criteria", output);
+}
+
+#[test]
+fn mock_simple_suggest_failures() {
+    // (Fail) Packages which couldn't be fetched or diffed should be listed
+    // after the suggestions which could be made.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, audits, imports) = files_no_exemptions(&metadata);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+
+    // Use a real (empty) cache, with diffstats for all but one package already
+    // cached. Without a network, fetching that one for diffing has to fail.
+    let cache_dir = tempfile::tempdir().unwrap();
+    let mut diff_cache = crate::format::DiffCache::new();
+    for package in ["third-party2", "transitive-third-party1"] {
+        diff_cache.entry(package.to_owned()).or_default().insert(
+            Delta {
+                from: crate::resolver::ROOT_VERSION.clone(),
+                to: ver(DEFAULT_VER),
+            },
+            crate::format::DiffStat {
+                raw: "+100".to_owned(),
+                count: 100,
+            },
+        );
+    }
+    let diff_cache_path = cache_dir.path().join("diff-cache.toml");
+    crate::storage::store_diff_cache_file(&diff_cache_path, diff_cache).unwrap();
+
+    let mut cfg = mock_cfg(&metadata);
+    cfg._rest.mock_cache = false;
+    cfg._rest.cache_dir = cache_dir.path().to_owned();
+    cfg._rest.cli.diff_cache = Some(diff_cache_path);
+
+    let suggest = report.compute_suggest(&cfg, None, true).unwrap().unwrap();
+    assert_eq!(suggest.failures.len(), 1);
+    assert!(
+        suggest.failures[0].1.contains("--frozen"),
+        "{}",
+        suggest.failures[0].1
+    );
+
+    let out = BasicTestOutput::new();
+    suggest.print_human(&out.clone().as_dyn(), &report).unwrap();
+
+    insta::assert_snapshot!("mock-simple-suggest-failures", out.to_string());
+}
+
+#[test]
+fn mock_simple_suggest_prefer_audited_version() {
+    // (Fail) The preferred audited version should decide which delta gets
+    // suggested when several audited versions are available.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = files_no_exemptions(&metadata);
+
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![
+            full_audit(ver(3), DEFAULT_CRIT),
+            full_audit(ver(8), DEFAULT_CRIT),
+            full_audit(ver(12), DEFAULT_CRIT),
+        ],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    console::set_colors_enabled(false);
+    let cfg = mock_cfg(&metadata);
+
+    let mut output = String::new();
+    for prefer in [
+        crate::PreferAuditedVersion::SmallestDiff,
+        crate::PreferAuditedVersion::Highest,
+        crate::PreferAuditedVersion::Lowest,
+    ] {
+        let suggest = report
+            .compute_suggest_preferring(&cfg, None, true, prefer)
+            .unwrap()
+            .unwrap();
+        let item = suggest
+            .suggestions
+            .iter()
+            .find(|item| report.graph.nodes[item.package].name == "third-party1")
+            .unwrap();
+        output += &format!(
+            "{:?}: {} -> {} ({})\n",
+            prefer,
+            item.suggested_diff.from,
+            item.suggested_diff.to,
+            item.suggested_diff.diffstat.raw
+        );
+    }
+
+    insta::assert_snapshot!("mock-simple-suggest-prefer-audited-version", output);
+}
+
+#[test]
+fn mock_simple_suggest_include_exemptions() {
+    // (Fail) Exemptions marked suggest=false should only be suggested for
+    // replacement when all exemptions are included.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_inited(&metadata);
+
+    for exemption in config.exemptions.get_mut("third-party1").unwrap() {
+        exemption.suggest = false;
+    }
+
+    let store = Store::mock(config, audits, imports);
+
+    let mut output = String::new();
+    for include_all_exemptions in [false, true] {
+        let suggest_store = store.clone_for_suggest(include_all_exemptions);
+        let report = crate::resolver::resolve(&metadata, None, &suggest_store, ResolveDepth::Deep);
+        let cfg = mock_cfg(&metadata);
+        let suggest = report.compute_suggest(&cfg, None, true).unwrap();
+
+        console::set_colors_enabled(false);
+        let out = BasicTestOutput::new();
+        report
+            .print_suggest_human(&out.clone().as_dyn(), &cfg, suggest.as_ref())
+            .unwrap();
+        output += &format!(
+            "INCLUDE ALL EXEMPTIONS: {}\n{}\n",
+            include_all_exemptions, out
+        );
+    }
+
+    insta::assert_snapshot!("mock-simple-suggest-include-exemptions", output);
+}
+
+#[test]
+fn mock_simple_dump_resolution() {
+    // The resolution dump should include the search results, available
+    // audits and exemptions, and verdict for each third-party package.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = files_inited(&metadata);
+
+    config.exemptions.remove("third-party1");
+    audits.audits.insert(
+        "third-party2".to_owned(),
+        vec![full_audit(ver(5), DEFAULT_CRIT)],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let output = serde_json::to_string_pretty(&report.resolution_json(&store)).unwrap();
+    insta::assert_snapshot!("mock-simple-dump-resolution", output);
+}
+
+#[test]
+fn mock_simple_export_backlog() {
+    // The backlog export should list both exemptions and failing packages,
+    // along with their notes.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_inited(&metadata);
+
+    config.exemptions.remove("third-party1");
+    config
+        .exemptions
+        .get_mut("third-party2")
+        .unwrap()
+        .iter_mut()
+        .for_each(|exemption| exemption.notes = Some("vendored, see \"docs\", maybe".to_owned()));
+    config.notes.insert(
+        "third-party1".to_owned(),
+        "being replaced by an in-house crate".to_owned(),
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let mut output = String::new();
+    for format in ["csv", "json"] {
+        let cfg = mock_cfg_args(
+            &metadata,
+            ["cargo", "vet", "export-backlog", "--format", format],
+        );
+        let sub_args = if let Some(crate::cli::Commands::ExportBacklog(sub_args)) = &cfg.cli.command
+        {
+            sub_args
+        } else {
+            unreachable!();
+        };
+        let out = BasicTestOutput::new();
+        crate::do_cmd_export_backlog(&out.clone().as_dyn(), sub_args, &store, &report).unwrap();
+        output += &format!("FORMAT: {}\n{}\n", format, out);
+    }
+
+    insta::assert_snapshot!("mock-simple-export-backlog", output);
+}
+
+#[test]
+fn mock_simple_sarif() {
+    // (Fail) Each failure should be a SARIF result, pointing at the package's
+    // exemptions entry in config.toml if it has one.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_inited(&metadata);
+
+    config.exemptions.remove("third-party1");
+    config
+        .exemptions
+        .get_mut("third-party2")
+        .unwrap()
+        .iter_mut()
+        .for_each(|exemption| exemption.criteria = vec!["weak-reviewed".to_string().into()]);
+
+    let store = Store::mock(config, audits, imports);
+    let (config_text, _, _) = store.mock_commit();
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let out = BasicTestOutput::new();
+    report
+        .print_sarif(
+            &out.clone().as_dyn(),
+            "supply-chain/config.toml",
+            &config_text,
+        )
+        .unwrap();
+    let output = format!("CONFIG:\n{config_text}\nSARIF:\n{out}");
+    insta::assert_snapshot!("mock-simple-sarif", output);
+}
+
+#[test]
+fn mock_simple_package_notes() {
+    // (Fail) Per-package notes in config.toml should be shown alongside the
+    // package in both the failures and the suggestions.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_no_exemptions(&metadata);
+
+    config.notes.insert(
+        "third-party1".to_owned(),
+        "upstream is unmaintained, tracked in TICKET-123".to_owned(),
+    );
+    config.notes.insert(
+        "transitive-third-party1".to_owned(),
+        "only used by tests\nsee TICKET-456".to_owned(),
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("mock-simple-package-notes", output);
+}
+
+#[test]
+fn mock_simple_html_report() {
+    // (Fail) The HTML report should list every third-party package with its
+    // status, and the suggestions.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = files_inited(&metadata);
+
+    config.exemptions.remove("third-party2");
+    config.notes.insert(
+        "third-party1".to_owned(),
+        "kept until <upstream> & co. release".to_owned(),
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let cfg = mock_cfg(&metadata);
+    let suggest = report.compute_suggest(&cfg, None, true).unwrap();
+    let output = BasicTestOutput::new();
+    report
+        .print_html(&output.clone().as_dyn(), &cfg, suggest.as_ref())
+        .unwrap();
+
+    insta::assert_snapshot!("mock-simple-html-report", output.to_string());
+}
+
+#[test]
+fn mock_simple_feature_sets() {
+    // (Fail) A dependency which is only enabled by another feature set should
+    // be merged into the graph and require audits.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+    let mut metadata = mock.metadata();
+
+    let (config, audits, imports) = files_inited(&metadata);
+
+    let feature_set_mock = MockMetadata::new(vec![
+        MockPackage {
+            name: "root-package",
+            is_workspace: true,
+            is_first_party: true,
+            deps: vec![dep("first-party")],
+            ..Default::default()
+        },
+        MockPackage {
+            name: "first-party",
+            is_first_party: true,
+            deps: vec![dep("third-party2"), dep("feature-only")],
+            ..Default::default()
+        },
+        MockPackage {
+            name: "third-party2",
+            ..Default::default()
+        },
+        MockPackage {
+            name: "feature-only",
+            ..Default::default()
+        },
+    ]);
+    let new_packages = crate::merge_metadata(&mut metadata, feature_set_mock.metadata());
+    assert_eq!(
+        new_packages
+            .iter()
+            .map(|id| id.repr.as_str())
+            .collect::<Vec<_>>(),
+        [feature_set_mock.pkgid_by("feature-only", &ver(DEFAULT_VER))]
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("mock-simple-feature-sets", output);
+}
+
+#[test]
+fn mock_optional_deps_not_activated() {
+    // (Fail) An optional dependency that no feature activates shouldn't need
+    // to be audited, or get an exemption from `init`.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::optional_deps();
+
+    let metadata = mock.metadata();
+    let (config, _audits, _imports) = files_inited(&metadata);
+    assert!(config.exemptions.contains_key("third-party1"));
+    assert!(!config.exemptions.contains_key("third-party-optional"));
+
+    let (config, audits, imports) = files_no_exemptions(&metadata);
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("mock-optional-deps-not-activated", output);
+}
+
+#[test]
+fn mock_simple_coverage() {
+    // Coverage counts exemptions towards `vetted`, but not `audited`.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = files_full_audited(&metadata);
+
+    audits.audits.get_mut("third-party2").unwrap().clear();
+    config.exemptions.insert(
+        "third-party2".to_owned(),
+        vec![exemptions(ver(DEFAULT_VER), DEFAULT_CRIT)],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+    let coverage = report.compute_coverage();
+
+    let output = BasicTestOutput::new();
+    coverage.print_human(&output.clone().as_dyn()).unwrap();
+    let json = serde_json::to_string_pretty(&coverage).unwrap();
+    insta::assert_snapshot!(
+        "mock-simple-coverage",
+        format!("{}\n{json}", output.to_string())
+    );
+}
+
+#[test]
+fn builtin_long_delta_chain_stress() {
+    // (Pass, then Fail) A crate with hundreds of published versions and dense
+    // delta coverage (forwards and backwards) should still resolve, and suggest
+    // a fix when the chain is broken, without blowing up.
+
+    const VERSIONS: u64 = 500;
+    const GAP: u64 = VERSIONS / 2;
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::new(vec![
+        MockPackage {
+            name: "root-package",
+            is_workspace: true,
+            is_first_party: true,
+            deps: vec![dep("first-party")],
+            ..Default::default()
+        },
+        MockPackage {
+            name: "first-party",
+            is_first_party: true,
+            deps: vec![dep_ver("third-party1", VERSIONS)],
+            ..Default::default()
+        },
+        MockPackage {
+            name: "third-party1",
+            version: ver(VERSIONS),
+            ..Default::default()
+        },
+    ]);
+    let metadata = mock.metadata();
+
+    let dense_deltas = |skip_gap: bool| {
+        let mut entries = vec![full_audit(ver(1), SAFE_TO_DEPLOY)];
+        for from in 1..VERSIONS {
+            for to in (from + 1)..=(from + 3).min(VERSIONS) {
+                if skip_gap && from <= GAP && to > GAP {
+                    continue;
+                }
+                entries.push(delta_audit(ver(from), ver(to), SAFE_TO_DEPLOY));
+                entries.push(delta_audit(ver(to), ver(from), SAFE_TO_DEPLOY));
+            }
+        }
+        entries
+    };
+
+    let start = std::time::Instant::now();
+    for skip_gap in [false, true] {
+        let (config, mut audits, imports) = builtin_files_no_exemptions(&metadata);
+        audits
+            .audits
+            .insert("third-party1".to_owned(), dense_deltas(skip_gap));
+
+        let store = Store::mock(config, audits, imports);
+        let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+        assert_eq!(report.has_errors(), skip_gap);
+
+        let output = get_report(&metadata, report);
+        if skip_gap {
+            assert!(
+                output.contains(&format!("diff third-party1 {GAP}.0.0 {}.0.0", GAP + 1)),
+                "should suggest bridging the gap:\n{output}"
+            );
+        }
+    }
+    assert!(
+        start.elapsed() < std::time::Duration::from_secs(30),
+        "resolving a {VERSIONS}-version delta chain took {:?}",
+        start.elapsed()
+    );
+}
+
+#[test]
+fn builtin_simple_criteria_severity() {
+    // (Warn) Missing criteria can be selectively turned into warnings with
+    // --warn, or errors with --deny.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (config, mut audits, imports) = builtin_files_full_audited(&metadata);
+
+    // third-party1 is only safe-to-run, but needs to be safe-to-deploy
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![full_audit(ver(DEFAULT_VER), SAFE_TO_RUN)],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let severity = |deny: &[&str], warn: &[&str]| {
+        let mut report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+        let to_owned = |list: &[&str]| list.iter().map(|&s| s.to_owned()).collect::<Vec<_>>();
+        report
+            .set_criteria_severity(&to_owned(deny), &to_owned(warn))
+            .unwrap();
+        report
+    };
+
+    assert!(severity(&[], &[]).has_errors());
+    assert!(severity(&[SAFE_TO_DEPLOY], &[]).has_errors());
+    assert!(!severity(&[SAFE_TO_RUN], &[]).has_errors());
+    assert!(severity(&[SAFE_TO_DEPLOY], &[SAFE_TO_DEPLOY]).has_errors());
+
+    let report = severity(&[], &[SAFE_TO_DEPLOY]);
+    assert!(!report.has_errors());
+    assert!(report.has_warnings());
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("builtin-simple-criteria-severity", output);
+
+    let mut report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    assert!(report
+        .set_criteria_severity(&[], &["safe-to-jog".to_owned()])
+        .is_err());
+}
+
+#[test]
+fn cheapest_diff_chain_prefers_cheaper_chains() {
+    use crate::format::DiffStat;
+    use crate::resolver::{cheapest_diff_chain, DiffRecommendation};
+
+    let diff = |from: u64, to: u64, count: u64| DiffRecommendation {
+        from: ver(from),
+        to: ver(to),
+        diffstat: DiffStat {
+            raw: String::new(),
+            count,
+        },
+        steps: vec![],
+    };
+    let chain = |sources: &[u64], targets: &[u64], diffs: &[DiffRecommendation]| {
+        let sources = sources.iter().map(|&v| ver(v)).collect::<Vec<_>>();
+        let targets = targets.iter().map(|&v| ver(v)).collect::<Vec<_>>();
+        cheapest_diff_chain(&sources.iter().collect(), &targets.iter().collect(), diffs).map(
+            |chain| {
+                chain
+                    .iter()
+                    .map(|diff| (diff.from.major, diff.to.major))
+                    .collect::<Vec<_>>()
+            },
+        )
+    };
+
+    // The direct diff is a big rewrite, but it was done over two releases.
+    let diffs = [
+        diff(1, 4, 500),
+        diff(1, 2, 10),
+        diff(2, 3, 20),
+        diff(3, 4, 30),
+    ];
+    assert_eq!(
+        chain(&[1], &[4], &diffs),
+        Some(vec![(1, 2), (2, 3), (3, 4)])
+    );
+
+    // Equally expensive chains go to the shorter one.
+    let diffs = [
+        diff(1, 4, 60),
+        diff(1, 2, 10),
+        diff(2, 3, 20),
+        diff(3, 4, 30),
+    ];
+    assert_eq!(chain(&[1], &[4], &diffs), Some(vec![(1, 4)]));
+
+    // Any source and any target will do.
+    let diffs = [diff(1, 4, 60), diff(2, 3, 5), diff(3, 4, 30)];
+    assert_eq!(chain(&[1, 2], &[3, 4], &diffs), Some(vec![(2, 3)]));
+
+    // Diffs only go one way.
+    let diffs = [diff(4, 1, 60)];
+    assert_eq!(chain(&[1], &[4], &diffs), None);
+}
+
+#[test]
+fn source_hash_mismatches_use_local_source() {
+    use crate::resolver::source_hash_mismatches;
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    // Point third-party1 at a real unpacked source.
+    let src = tempfile::tempdir().unwrap();
+    std::fs::write(src.path().join("Cargo.toml"), "[package]\n").unwrap();
+    let mut metadata = mock.metadata();
+    for package in &mut metadata.packages {
+        if package.name == "third-party1" {
+            package.manifest_path = src.path().join("Cargo.toml").try_into().unwrap();
+        }
+    }
+    let actual = crate::storage::hash_package_source(src.path()).unwrap();
+
+    // `cargo vendor` adds a .cargo-checksum.json, which isn't part of the
+    // package's source.
+    std::fs::write(src.path().join(".cargo-checksum.json"), "{}").unwrap();
+    assert_eq!(
+        crate::storage::hash_package_source(src.path()).unwrap(),
+        actual
+    );
+
+    let (config, mut audits, imports) = files_full_audited(&metadata);
+    let set_hash = |audits: &mut AuditsFile, package: &str, hash: &str| {
+        for entry in audits.audits.get_mut(package).unwrap() {
+            entry.source_hash = Some(hash.to_owned());
+        }
+    };
+
+    // A matching hash is fine, and packages without a local source are skipped.
+    set_hash(&mut audits, "third-party1", &actual);
+    set_hash(&mut audits, "third-party2", "sha256:0123456789abcdef");
+    let store = Store::mock(config.clone(), audits.clone(), imports.clone());
+    assert_eq!(source_hash_mismatches(&metadata, &store), vec![]);
+
+    set_hash(&mut audits, "third-party1", "sha256:0123456789abcdef");
+    let store = Store::mock(config, audits, imports);
+    let mismatches = source_hash_mismatches(&metadata, &store);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].package, "third-party1");
+    assert_eq!(mismatches[0].audited, "sha256:0123456789abcdef");
+    assert_eq!(mismatches[0].actual, actual);
+}
+
+#[test]
+fn mock_dev_detection_dump_graph_criteria() {
+    // Policies are pushed down through normal and build deps, dev-deps get the
+    // dev policy, and dependency-criteria override both.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::dev_detection();
+
+    let metadata = mock.metadata();
+    let (mut config, audits, imports) = builtin_files_inited(&metadata);
+    config.policy.insert(
+        "root".to_owned(),
+        crate::format::PolicyEntry {
+            dependency_criteria: [("both".to_owned(), vec![SAFE_TO_RUN.to_owned().into()])]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        },
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let cfg = mock_cfg_args(
+        &metadata,
+        [
+            "cargo",
+            "vet",
+            "dump-graph",
+            "--depth=full",
+            "--graph-format=dot",
+            "--criteria",
+        ],
+    );
+    let sub_args = if let Some(crate::cli::Commands::DumpGraph(sub_args)) = &cfg.cli.command {
+        sub_args
+    } else {
+        unreachable!();
+    };
+
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+    let required = report.required_criteria(&store);
+    let output = BasicTestOutput::new();
+    report
+        .graph
+        .print_dot(&output.clone().as_dyn(), sub_args, Some(&required))
+        .unwrap();
+
+    insta::assert_snapshot!("mock-dev-detection-dump-graph-criteria", output.to_string());
+}
+
+#[test]
+fn mock_simple_explain() {
+    // `--explain` and the json report should show the chain of audits (or
+    // exemptions) which satisfied each criteria of a package.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::simple();
+
+    let metadata = mock.metadata();
+    let (mut config, mut audits, imports) = builtin_files_inited(&metadata);
+
+    config.exemptions.remove("third-party1");
+    audits.audits.insert(
+        "third-party1".to_owned(),
+        vec![
+            full_audit(ver(3), SAFE_TO_DEPLOY),
+            delta_audit(ver(3), ver(5), SAFE_TO_DEPLOY),
+            delta_audit(ver(5), ver(DEFAULT_VER), SAFE_TO_DEPLOY),
+        ],
+    );
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Deep);
+
+    let output = BasicTestOutput::new();
+    for package in [
+        "third-party1",
+        "third-party2",
+        "root-package",
+        "nonexistent",
+    ] {
+        report
+            .print_explanation(&output.clone().as_dyn(), package)
+            .unwrap();
+    }
+    insta::assert_snapshot!("mock-simple-explain", output.to_string());
+
+    // The json report has the same paths for every vetted package.
+    let output = get_report_json(&metadata, report);
+    insta::assert_snapshot!("mock-simple-explain-json", output);
+}
+
+#[test]
+fn mock_alternate_registry() {
+    // Packages from registries other than crates.io are first-party, unless
+    // their registry is listed in `third-party-registries`, as audits of the
+    // crates.io package of the same name would apply to them. As they can't
+    // be fetched, no audit is suggested for them.
+
+    let _enter = TEST_RUNTIME.enter();
+    let mock = MockMetadata::alternate_registry();
+
+    let metadata = mock.metadata();
+    let (config, _, _) = files_inited(&metadata);
+    assert!(!config.exemptions.contains_key("internal"));
+
+    let (mut config, audits, imports) = files_no_exemptions(&metadata);
+    config
+        .third_party_registries
+        .push(format!("sparse+{ALTERNATE_REGISTRY}/"));
+    let graph = crate::resolver::DepGraph::new(&metadata, None, Some(&config));
+    let internal = graph
+        .nodes
+        .iter()
+        .find(|node| node.name == "internal")
+        .unwrap();
+    assert!(internal.is_third_party);
+
+    let store = Store::mock(config, audits, imports);
+    let report = crate::resolver::resolve(&metadata, None, &store, ResolveDepth::Shallow);
+
+    let output = get_report(&metadata, report);
+    insta::assert_snapshot!("mock-alternate-registry", output);
+}
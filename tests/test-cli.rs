@@ -15,7 +15,7 @@
 // the env as `CARGO_BIN_EXE_<name>`.
 
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, Output, Stdio},
 };
 
@@ -108,6 +108,317 @@ fn test_markdown_help() {
     assert!(output.status.success(), "{}", output.status);
 }
 
+#[test]
+fn test_output_and_log_file_append() {
+    let dir = tempfile::tempdir().unwrap();
+    let output_path = dir.path().join("output.md");
+    let log_path = dir.path().join("vet.log");
+    std::fs::write(&log_path, "old log\n").unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let run = |extra_args: &[&str]| {
+        let output = Command::new(bin)
+            .arg("vet")
+            .arg("help-markdown")
+            .arg("--output-file")
+            .arg(&output_path)
+            .arg("--output-file-append")
+            .arg("--log-file")
+            .arg(&log_path)
+            .arg("--log-file-append")
+            .args(extra_args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", output.status);
+    };
+
+    // Appending keeps the old contents of both files
+    run(&[]);
+    let once = std::fs::read_to_string(&output_path).unwrap();
+    assert!(!once.is_empty());
+    run(&[]);
+    let twice = std::fs::read_to_string(&output_path).unwrap();
+    assert_eq!(twice, once.repeat(2));
+    assert!(std::fs::read_to_string(&log_path)
+        .unwrap()
+        .starts_with("old log\n"));
+
+    // Once the log is big enough it gets rotated
+    run(&["--log-file-max-size", "4"]);
+    let rotated = std::fs::read_to_string(dir.path().join("vet.log.1")).unwrap();
+    assert!(rotated.starts_with("old log\n"));
+    assert!(!std::fs::read_to_string(&log_path)
+        .unwrap()
+        .contains("old log"));
+}
+
+/// Make a package with a custom store path in a fresh temporary directory,
+/// returning the directory (which is removed when dropped) and its
+/// canonical path.
+fn make_print_config_project() -> (tempfile::TempDir, PathBuf) {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path().canonicalize().unwrap();
+    std::fs::create_dir_all(dir.join("src")).unwrap();
+    std::fs::write(dir.join("src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        r#"
+[package]
+name = "print-config-test"
+version = "0.1.0"
+
+[package.metadata.vet]
+store = { path = "custom-store" }
+"#,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("Cargo.lock"),
+        r#"
+version = 3
+
+[[package]]
+name = "print-config-test"
+version = "0.1.0"
+"#,
+    )
+    .unwrap();
+    (tempdir, dir)
+}
+
+fn print_config_from(dir: &Path) -> String {
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(dir)
+        .arg("vet")
+        .arg("print-config")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", format_outputs(&output));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_print_config_from_subdirectory() {
+    // A relative store.path is relative to the workspace root, not to the
+    // directory we were invoked from.
+    let (_tempdir, dir) = make_print_config_project();
+    let stdout = print_config_from(&dir.join("src"));
+    let expected = format!(
+        "store.path = {:?}  (from [package.metadata.vet])",
+        dir.join("custom-store")
+    );
+    assert!(stdout.contains(&expected), "{stdout}");
+}
+
+/// Make a workspace with a single `member` package, with the given
+/// `[*.metadata.vet]` tables (if any) in their Cargo.tomls, in a fresh
+/// temporary directory like `make_print_config_project`.
+fn make_workspace_print_config_project(
+    workspace_vet: &str,
+    member_vet: &str,
+) -> (tempfile::TempDir, PathBuf) {
+    let tempdir = tempfile::tempdir().unwrap();
+    let dir = tempdir.path().canonicalize().unwrap();
+    std::fs::create_dir_all(dir.join("member/src")).unwrap();
+    std::fs::write(dir.join("member/src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.join("Cargo.toml"),
+        format!(
+            r#"
+[workspace]
+members = ["member"]
+{workspace_vet}
+"#
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("member/Cargo.toml"),
+        format!(
+            r#"
+[package]
+name = "print-config-member"
+version = "0.1.0"
+{member_vet}
+"#
+        ),
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("Cargo.lock"),
+        r#"
+version = 3
+
+[[package]]
+name = "print-config-member"
+version = "0.1.0"
+"#,
+    )
+    .unwrap();
+    (tempdir, dir)
+}
+
+#[test]
+fn test_print_config_nested_package() {
+    // A relative store.path in [package.metadata.vet] is relative to that
+    // package's directory, even when it's nested inside a workspace.
+    let (_tempdir, dir) = make_workspace_print_config_project(
+        "",
+        "[package.metadata.vet]\nstore = { path = \"vet/store\" }",
+    );
+
+    let stdout = print_config_from(&dir.join("member/src"));
+    let expected = format!(
+        "store.path = {:?}  (from [package.metadata.vet])",
+        dir.join("member/vet/store")
+    );
+    assert!(stdout.contains(&expected), "{stdout}");
+}
+
+#[test]
+fn test_print_config_workspace_and_package() {
+    // When both the workspace and the package have a config, the package's
+    // takes priority, but only when vetting that package.
+    let (_tempdir, dir) = make_workspace_print_config_project(
+        "[workspace.metadata.vet]\nversion = 1\nstore = { path = \"ws-store\" }",
+        "[package.metadata.vet]\nstore = { path = \"member-store\" }",
+    );
+
+    let stdout = print_config_from(&dir.join("member"));
+    assert!(
+        stdout.contains("version = 1  (from [workspace.metadata.vet])"),
+        "{stdout}"
+    );
+    let expected = format!(
+        "store.path = {:?}  (from [package.metadata.vet])",
+        dir.join("member/member-store")
+    );
+    assert!(stdout.contains(&expected), "{stdout}");
+
+    let stdout = print_config_from(&dir);
+    let expected = format!(
+        "store.path = {:?}  (from [workspace.metadata.vet])",
+        dir.join("ws-store")
+    );
+    assert!(stdout.contains(&expected), "{stdout}");
+}
+
+#[test]
+fn test_print_config_conflicting_versions() {
+    let (_tempdir, dir) = make_workspace_print_config_project(
+        "[workspace.metadata.vet]\nversion = 1",
+        "[package.metadata.vet]\nversion = 2",
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-vet"))
+        .current_dir(dir.join("member"))
+        .arg("vet")
+        .arg("print-config")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(!output.status.success(), "{}", format_outputs(&output));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("[workspace.metadata.vet] has version = 1"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_missing_lockfile() {
+    // A project without a Cargo.lock can be read, but not resolved without
+    // writing one, which we refuse to do.
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"missing-lockfile-test\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .arg("vet")
+        .arg("--locked")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("Cargo.lock is missing"), "{stderr}");
+
+    // With a Cargo.lock, failures which have nothing to do with it aren't
+    // blamed on it.
+    std::fs::write(dir.path().join("Cargo.lock"), "version = 3\n").unwrap();
+    std::fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"missing-lockfile-test\"\nversion = \"0.1.0\"\n\n\
+         [dependencies]\nmissing = { path = \"missing\" }\n",
+    )
+    .unwrap();
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .arg("vet")
+        .arg("--locked")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(!stderr.contains("Cargo.lock is missing"), "{stderr}");
+    assert!(
+        stderr.contains("`cargo metadata` exited with an error"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_doctor() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("doctor")
+        .arg("--locked")
+        .arg("--frozen")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    // Whether everything passes depends on the machine we're run on, but the
+    // project itself should always be found.
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    for check in ["git:", "$CARGO:", "CARGO_HOME:", "temporary directory:"] {
+        assert!(stdout.contains(check), "{}", format_outputs(&output));
+    }
+    assert!(
+        stdout.contains(&format!("ok  cargo metadata: {}", project.display())),
+        "{}",
+        format_outputs(&output)
+    );
+    assert!(
+        stdout.contains("ok  store: "),
+        "{}",
+        format_outputs(&output)
+    );
+}
+
 #[test]
 fn test_project() {
     let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -130,6 +441,263 @@ fn test_project() {
     assert!(output.status.success(), "{}", output.status);
 }
 
+#[test]
+fn test_check_self() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("check-self")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    insta::assert_snapshot!("test-check-self", format_outputs(&output));
+    assert!(output.status.success(), "{}", output.status);
+}
+
+#[test]
+fn test_explain_diff_cache() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("explain-diff-cache")
+        .arg("--diff-cache")
+        .arg("../diff-cache.toml")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{}", output.status);
+    let stdout = std::str::from_utf8(&output.stdout).unwrap();
+    assert!(
+        stdout.contains(
+            "alsa:\n  0.0.0 -> 0.4.3: 23 files changed, 12197 insertions(+) (12197 lines)\n"
+        ),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_export_import_diff_cache() {
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let dir = tempfile::tempdir().unwrap();
+    let exported = dir.path().join("exported.toml");
+    let ours = dir.path().join("ours.toml");
+
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let run = |args: &[&std::ffi::OsStr]| {
+        let output = Command::new(bin)
+            .current_dir(&project)
+            .arg("vet")
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", format_outputs(&output));
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    run(&[
+        "export-diff-cache".as_ref(),
+        exported.as_os_str(),
+        "--diff-cache".as_ref(),
+        "../diff-cache.toml".as_ref(),
+    ]);
+
+    // Importing into an empty diff-cache adds everything, and importing again
+    // adds nothing.
+    let import = || {
+        run(&[
+            "import-diff-cache".as_ref(),
+            exported.as_os_str(),
+            "--diff-cache".as_ref(),
+            ours.as_os_str(),
+        ])
+    };
+    let stdout = import();
+    assert!(!stdout.starts_with("imported 0 "), "{stdout}");
+    let stdout = import();
+    assert!(stdout.starts_with("imported 0 new diffstats"), "{stdout}");
+
+    let stdout = run(&[
+        "explain-diff-cache".as_ref(),
+        "--diff-cache".as_ref(),
+        ours.as_os_str(),
+    ]);
+    assert!(
+        stdout.contains(
+            "alsa:\n  0.0.0 -> 0.4.3: 23 files changed, 12197 insertions(+) (12197 lines)\n"
+        ),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn test_diff_outside_project() {
+    // `diff` doesn't need a project, so outside of one it should only fail
+    // because (with --frozen) it can't fetch the package.
+    let dir = tempfile::tempdir().unwrap();
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .arg("vet")
+        .arg("diff")
+        .arg("no-such-crate")
+        .arg("1.0.0")
+        .arg("1.0.1")
+        .arg("--mode=local")
+        .arg("--locked")
+        .arg("--frozen")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("Running as --frozen but needed to fetch no-such-crate:1.0."),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_diff_broken_manifest() {
+    // Inside a project whose manifest is broken, `diff` should report the
+    // broken manifest rather than quietly running without the project.
+    // There's a Cargo.lock, so the failure isn't put down to a missing one.
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Cargo.toml"), "[package\n").unwrap();
+    std::fs::write(dir.path().join("Cargo.lock"), "").unwrap();
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(dir.path())
+        .arg("vet")
+        .arg("diff")
+        .arg("no-such-crate")
+        .arg("1.0.0")
+        .arg("1.0.1")
+        .arg("--mode=local")
+        .arg("--locked")
+        .arg("--frozen")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("`cargo metadata` exited with an error"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_fetch_frozen() {
+    // `fetch` only exists to use the network, so it should refuse to run
+    // without it instead of quietly doing nothing.
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("fetch")
+        .arg("--manifest-path")
+        .arg("Cargo.toml")
+        .arg("--locked")
+        .arg("--frozen")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("fetch can't be run with --frozen"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_prune_exclude_dev() {
+    // Pruning with dev-only packages filtered out would remove their
+    // exemptions, so it should be refused.
+    let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("test-project");
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    let output = Command::new(bin)
+        .current_dir(&project)
+        .arg("vet")
+        .arg("prune")
+        .arg("--manifest-path")
+        .arg("Cargo.toml")
+        .arg("--exclude-dev")
+        .arg("--locked")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    let stderr = std::str::from_utf8(&output.stderr).unwrap();
+    assert!(
+        stderr.contains("--exclude-dev can't be used with prune"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn test_bad_version() {
+    // A typo'd version should be a clean usage error, not a panic.
+    let bin = env!("CARGO_BIN_EXE_cargo-vet");
+    for args in [
+        &["inspect", "some-crate", "1.x"][..],
+        &["diff", "some-crate", "1.0.0", "1.0"],
+        &["certify", "some-crate", "one"],
+    ] {
+        let output = Command::new(bin)
+            .arg("vet")
+            .args(args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .unwrap();
+
+        assert_eq!(output.status.code(), Some(2), "{}", format_outputs(&output));
+        let stderr = std::str::from_utf8(&output.stderr).unwrap();
+        let bad = args.last().unwrap();
+        assert!(
+            stderr.contains(&format!("'{bad}' is not a valid semver version")),
+            "{}",
+            stderr
+        );
+        assert!(!stderr.contains("panicked"), "{}", stderr);
+    }
+}
+
 #[test]
 fn test_project_json() {
     let project = PathBuf::from(env!("CARGO_MANIFEST_DIR"))